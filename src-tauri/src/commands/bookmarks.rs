@@ -0,0 +1,162 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+use tauri::{AppHandle, State};
+
+use crate::commands::player::{self, VlcManager};
+use crate::commands::store;
+use crate::error::AppError;
+
+/// Setting holding every bookmark, by item id (`hash_remote_path` of the file),
+/// so they survive re-scans and travel with the other settings
+const BOOKMARKS_SETTING: &str = "bookmarks";
+
+/// Serializes load-modify-save cycles on the bookmarks setting
+static BOOKMARKS_LOCK: Mutex<()> = Mutex::new(());
+
+/// A saved position in one media item.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Bookmark {
+    pub id: String,
+    pub item_id: String,
+    pub time_ms: i64,
+    pub label: String,
+    /// Unix seconds
+    pub created_at: u64,
+}
+
+/// Where `jump_to_bookmark` went: `seeked` is false when the item isn't loaded
+/// and the frontend has to open it at `time_ms` itself.
+#[derive(Debug, Serialize, Clone)]
+pub struct BookmarkJump {
+    pub item_id: String,
+    pub time_ms: i64,
+    pub seeked: bool,
+}
+
+fn load_bookmarks(app: &AppHandle) -> HashMap<String, Vec<Bookmark>> {
+    store::get_setting(app, BOOKMARKS_SETTING).unwrap_or_default()
+}
+
+fn save_bookmarks(app: &AppHandle, bookmarks: &HashMap<String, Vec<Bookmark>>) -> Result<(), String> {
+    store::set_setting(app, BOOKMARKS_SETTING, serde_json::json!(bookmarks))
+}
+
+fn find_bookmark(app: &AppHandle, bookmark_id: &str) -> Result<Bookmark, AppError> {
+    load_bookmarks(app)
+        .into_values()
+        .flatten()
+        .find(|b| b.id == bookmark_id)
+        .ok_or_else(|| AppError::invalid(format!("No bookmark with id {}", bookmark_id)))
+}
+
+/// Bookmark `time_ms` in `item_id`. A blank label is stored as the timestamp.
+#[tauri::command]
+pub async fn add_bookmark(
+    app: AppHandle,
+    item_id: String,
+    time_ms: i64,
+    label: String,
+) -> Result<Bookmark, AppError> {
+    if item_id.trim().is_empty() {
+        return Err(AppError::invalid("Bookmark item id must not be empty"));
+    }
+    let time_ms = time_ms.max(0);
+    let label = match label.trim() {
+        "" => format_timestamp(time_ms),
+        label => label.to_string(),
+    };
+    let bookmark = Bookmark {
+        id: format!("{:016x}", rand::random::<u64>()),
+        item_id: item_id.clone(),
+        time_ms,
+        label,
+        created_at: SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or(0),
+    };
+
+    let _guard = BOOKMARKS_LOCK.lock().unwrap();
+    let mut bookmarks = load_bookmarks(&app);
+    let list = bookmarks.entry(item_id).or_default();
+    list.push(bookmark.clone());
+    list.sort_by_key(|b| b.time_ms);
+    save_bookmarks(&app, &bookmarks)?;
+    Ok(bookmark)
+}
+
+/// Bookmarks of `item_id`, in playback order.
+#[tauri::command]
+pub async fn list_bookmarks(app: AppHandle, item_id: String) -> Result<Vec<Bookmark>, AppError> {
+    Ok(load_bookmarks(&app).remove(&item_id).unwrap_or_default())
+}
+
+#[tauri::command]
+pub async fn delete_bookmark(app: AppHandle, bookmark_id: String) -> Result<(), AppError> {
+    let _guard = BOOKMARKS_LOCK.lock().unwrap();
+    let mut bookmarks = load_bookmarks(&app);
+    let before: usize = bookmarks.values().map(Vec::len).sum();
+    bookmarks.retain(|_, list| {
+        list.retain(|b| b.id != bookmark_id);
+        !list.is_empty()
+    });
+    if bookmarks.values().map(Vec::len).sum::<usize>() == before {
+        return Err(AppError::invalid(format!("No bookmark with id {}", bookmark_id)));
+    }
+    save_bookmarks(&app, &bookmarks)?;
+    Ok(())
+}
+
+/// Seek to a bookmark if its item is the one playing; otherwise return where
+/// it is so the frontend can open the item there.
+#[tauri::command]
+pub async fn jump_to_bookmark(
+    app: AppHandle,
+    vlc: State<'_, VlcManager>,
+    bookmark_id: String,
+) -> Result<BookmarkJump, AppError> {
+    let bookmark = find_bookmark(&app, &bookmark_id)?;
+    let seeked = player::seek_if_loaded(&vlc, &bookmark.item_id, bookmark.time_ms).await?;
+    Ok(BookmarkJump {
+        item_id: bookmark.item_id,
+        time_ms: bookmark.time_ms,
+        seeked,
+    })
+}
+
+/// Move bookmarks from old item ids to new ones (old id -> new id), for
+/// `progress::migrate_item_ids`.
+pub(crate) fn rekey(app: &AppHandle, ids: &HashMap<String, String>) -> Result<(), String> {
+    let _guard = BOOKMARKS_LOCK.lock().unwrap();
+    let mut bookmarks = load_bookmarks(app);
+    let mut changed = false;
+    for (old_id, new_id) in ids {
+        let Some(mut moved) = bookmarks.remove(old_id) else {
+            continue;
+        };
+        for bookmark in &mut moved {
+            bookmark.item_id = new_id.clone();
+        }
+        let list = bookmarks.entry(new_id.clone()).or_default();
+        list.extend(moved);
+        list.sort_by_key(|b| b.time_ms);
+        changed = true;
+    }
+    if changed {
+        save_bookmarks(app, &bookmarks)?;
+    }
+    Ok(())
+}
+
+/// "1:02:03" or "2:03"
+fn format_timestamp(ms: i64) -> String {
+    let secs = ms / 1000;
+    let (h, m, s) = (secs / 3600, secs / 60 % 60, secs % 60);
+    if h > 0 {
+        format!("{}:{:02}:{:02}", h, m, s)
+    } else {
+        format!("{}:{:02}", m, s)
+    }
+}
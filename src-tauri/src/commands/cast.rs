@@ -0,0 +1,223 @@
+//! Chromecast (and other renderer) discovery through libVLC's renderer
+//! discoverer API, which vlc-rs doesn't wrap.
+//!
+//! The discovery lives on the VLC thread. libVLC reports renderers from its own
+//! threads, so the item table is behind a mutex and events are emitted straight
+//! from the callback.
+//!
+//! While a renderer is set, `player_set_renderer` moves the video's
+//! `rclone serve http` process from loopback to the LAN address, behind basic
+//! auth, so the device can reach the stream; it moves back on returning to
+//! local output.
+
+use serde::Serialize;
+use std::collections::HashMap;
+use std::ffi::{c_char, c_int, c_void, CStr, CString};
+use std::sync::Mutex;
+use tauri::{AppHandle, Emitter};
+
+#[allow(non_camel_case_types)]
+type libvlc_renderer_discoverer_t = c_void;
+#[allow(non_camel_case_types)]
+type libvlc_renderer_item_t = c_void;
+#[allow(non_camel_case_types)]
+type libvlc_event_manager_t = c_void;
+
+/// The head of `libvlc_event_t` as seen by renderer discoverer events: the
+/// union's first member is `{ libvlc_renderer_item_t *item; }`.
+#[repr(C)]
+struct RendererEvent {
+    kind: c_int,
+    obj: *mut c_void,
+    item: *mut libvlc_renderer_item_t,
+}
+
+type EventCallback = unsafe extern "C" fn(event: *const RendererEvent, data: *mut c_void);
+
+const EVENT_ITEM_ADDED: c_int = 0x502;
+const EVENT_ITEM_DELETED: c_int = 0x503;
+
+/// mDNS discovery; finds Chromecasts on the local network
+const DISCOVERER_NAME: &str = "microdns_renderer";
+
+extern "C" {
+    fn libvlc_renderer_discoverer_new(
+        instance: *mut vlc::sys::libvlc_instance_t,
+        name: *const c_char,
+    ) -> *mut libvlc_renderer_discoverer_t;
+    fn libvlc_renderer_discoverer_release(rd: *mut libvlc_renderer_discoverer_t);
+    fn libvlc_renderer_discoverer_start(rd: *mut libvlc_renderer_discoverer_t) -> c_int;
+    fn libvlc_renderer_discoverer_stop(rd: *mut libvlc_renderer_discoverer_t);
+    fn libvlc_renderer_discoverer_event_manager(
+        rd: *mut libvlc_renderer_discoverer_t,
+    ) -> *mut libvlc_event_manager_t;
+    fn libvlc_event_attach(
+        em: *mut libvlc_event_manager_t,
+        event_type: c_int,
+        callback: EventCallback,
+        data: *mut c_void,
+    ) -> c_int;
+    fn libvlc_renderer_item_hold(item: *mut libvlc_renderer_item_t) -> *mut libvlc_renderer_item_t;
+    fn libvlc_renderer_item_release(item: *mut libvlc_renderer_item_t);
+    fn libvlc_renderer_item_name(item: *const libvlc_renderer_item_t) -> *const c_char;
+    fn libvlc_renderer_item_type(item: *const libvlc_renderer_item_t) -> *const c_char;
+    fn libvlc_media_player_set_renderer(
+        player: *mut vlc::sys::libvlc_media_player_t,
+        item: *mut libvlc_renderer_item_t,
+    ) -> c_int;
+}
+
+/// Payload of `player:renderer-found`
+#[derive(Debug, Serialize, Clone)]
+pub struct RendererInfo {
+    pub id: String,
+    pub name: String,
+    /// e.g. "chromecast"
+    #[serde(rename = "type")]
+    pub kind: String,
+}
+
+struct HeldItem {
+    ptr: *mut libvlc_renderer_item_t,
+    info: RendererInfo,
+}
+
+#[derive(Default)]
+struct Items {
+    by_id: HashMap<String, HeldItem>,
+    next_id: u64,
+}
+
+/// State shared with the libVLC callback; boxed so its address stays put.
+struct Shared {
+    app: AppHandle,
+    items: Mutex<Items>,
+}
+
+// The raw item pointers are reference-counted by libVLC and only used under the mutex
+unsafe impl Send for Shared {}
+unsafe impl Sync for Shared {}
+
+pub(crate) struct RendererDiscovery {
+    discoverer: *mut libvlc_renderer_discoverer_t,
+    shared: Box<Shared>,
+}
+
+fn c_string(ptr: *const c_char) -> String {
+    if ptr.is_null() {
+        return String::new();
+    }
+    unsafe { CStr::from_ptr(ptr) }.to_string_lossy().into_owned()
+}
+
+unsafe extern "C" fn on_event(event: *const RendererEvent, data: *mut c_void) {
+    let (Some(event), Some(shared)) = (event.as_ref(), (data as *const Shared).as_ref()) else {
+        return;
+    };
+    let Ok(mut items) = shared.items.lock() else {
+        return;
+    };
+    match event.kind {
+        EVENT_ITEM_ADDED => {
+            items.next_id += 1;
+            let info = RendererInfo {
+                id: items.next_id.to_string(),
+                name: c_string(libvlc_renderer_item_name(event.item)),
+                kind: c_string(libvlc_renderer_item_type(event.item)),
+            };
+            let ptr = libvlc_renderer_item_hold(event.item);
+            let _ = shared.app.emit("player:renderer-found", &info);
+            items.by_id.insert(info.id.clone(), HeldItem { ptr, info });
+        }
+        EVENT_ITEM_DELETED => {
+            let id = items
+                .by_id
+                .iter()
+                .find(|(_, held)| held.ptr == event.item)
+                .map(|(id, _)| id.clone());
+            if let Some(held) = id.and_then(|id| items.by_id.remove(&id)) {
+                let _ = shared
+                    .app
+                    .emit("player:renderer-lost", serde_json::json!({ "id": held.info.id }));
+                libvlc_renderer_item_release(held.ptr);
+            }
+        }
+        _ => {}
+    }
+}
+
+impl RendererDiscovery {
+    /// Start mDNS renderer discovery. Fails when the libVLC build has no
+    /// microdns plugin.
+    pub(crate) fn start(app: &AppHandle, instance: &vlc::Instance) -> Result<Self, String> {
+        let name = CString::new(DISCOVERER_NAME).unwrap();
+        let discoverer = unsafe { libvlc_renderer_discoverer_new(instance.raw(), name.as_ptr()) };
+        if discoverer.is_null() {
+            return Err("Renderer discovery is not available in this VLC build".to_string());
+        }
+        let discovery = RendererDiscovery {
+            discoverer,
+            shared: Box::new(Shared {
+                app: app.clone(),
+                items: Mutex::new(Items::default()),
+            }),
+        };
+        unsafe {
+            let em = libvlc_renderer_discoverer_event_manager(discoverer);
+            let data = &*discovery.shared as *const Shared as *mut c_void;
+            libvlc_event_attach(em, EVENT_ITEM_ADDED, on_event, data);
+            libvlc_event_attach(em, EVENT_ITEM_DELETED, on_event, data);
+            if libvlc_renderer_discoverer_start(discoverer) != 0 {
+                // Drop releases the discoverer
+                return Err("Failed to start renderer discovery".to_string());
+            }
+        }
+        Ok(discovery)
+    }
+
+    /// Renderers found so far, e.g. to answer a repeated discovery request.
+    pub(crate) fn renderers(&self) -> Vec<RendererInfo> {
+        self.shared
+            .items
+            .lock()
+            .map(|items| items.by_id.values().map(|h| h.info.clone()).collect())
+            .unwrap_or_default()
+    }
+
+    /// Route the player's output to renderer `id`, or back to local output with
+    /// None. Takes effect the next time playback starts.
+    pub(crate) fn apply(&self, player: &vlc::MediaPlayer, id: Option<&str>) -> Result<(), String> {
+        let items = self
+            .shared
+            .items
+            .lock()
+            .map_err(|_| "Renderer list is unavailable".to_string())?;
+        let ptr = match id {
+            Some(id) => items
+                .by_id
+                .get(id)
+                .map(|held| held.ptr)
+                .ok_or_else(|| format!("Unknown renderer: {}", id))?,
+            None => std::ptr::null_mut(),
+        };
+        if unsafe { libvlc_media_player_set_renderer(player.raw(), ptr) } != 0 {
+            return Err("VLC rejected the renderer".to_string());
+        }
+        Ok(())
+    }
+}
+
+impl Drop for RendererDiscovery {
+    fn drop(&mut self) {
+        unsafe {
+            libvlc_renderer_discoverer_stop(self.discoverer);
+            libvlc_renderer_discoverer_release(self.discoverer);
+        }
+        // No more callbacks after release, so the held items can go
+        if let Ok(mut items) = self.shared.items.lock() {
+            for (_, held) in items.by_id.drain() {
+                unsafe { libvlc_renderer_item_release(held.ptr) };
+            }
+        }
+    }
+}
@@ -0,0 +1,324 @@
+//! Discord Rich Presence: "Watching The Matrix — 0:42:10 / 2:16:00".
+//!
+//! Opt-in through the `discord.enabled` setting; the Discord application id
+//! comes from the `discord.client_id` setting. Presence is fed from the
+//! `vlc:state`, `vlc:time` and `vlc:meta` events plus the title passed to
+//! `open_media`, and is sent from a worker thread over Discord's local IPC
+//! socket. Nothing is reported and no error surfaces when Discord isn't running.
+
+use std::io::{Read, Write};
+use std::sync::{mpsc, Mutex};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use tauri::{AppHandle, Listener};
+
+use crate::commands::store;
+use crate::error::AppError;
+
+/// Setting (see `store::get_setting`) turning presence on
+const ENABLED_SETTING: &str = "discord.enabled";
+/// Setting holding the Discord application (client) id
+const CLIENT_ID_SETTING: &str = "discord.client_id";
+
+/// Discord rate-limits activity updates to 5 per 20 s; progress only needs
+/// refreshing occasionally, while state changes go out right away
+const PROGRESS_INTERVAL: Duration = Duration::from_secs(15);
+/// How long to wait before trying to reach Discord again after a failure
+const RECONNECT_INTERVAL: Duration = Duration::from_secs(30);
+const IPC_TIMEOUT: Duration = Duration::from_secs(2);
+
+const OP_HANDSHAKE: u32 = 0;
+const OP_FRAME: u32 = 1;
+
+#[derive(Default)]
+struct Presence {
+    /// Title passed to `open_media`, if any
+    title: Option<String>,
+    /// Title VLC read from the file's tags (or its name), used as a fallback
+    meta_title: String,
+    audio_only: bool,
+    /// None when nothing is playing; Some(paused) otherwise
+    paused: Option<bool>,
+    time_ms: i64,
+    duration_ms: i64,
+}
+
+static PRESENCE: Mutex<Option<Presence>> = Mutex::new(None);
+static WAKE: Mutex<Option<mpsc::Sender<bool>>> = Mutex::new(None);
+
+fn update(f: impl FnOnce(&mut Presence), urgent: bool) {
+    if let Ok(mut presence) = PRESENCE.lock() {
+        f(presence.get_or_insert_with(Presence::default));
+    }
+    if let Some(tx) = WAKE.lock().ok().and_then(|w| w.clone()) {
+        let _ = tx.send(urgent);
+    }
+}
+
+/// Record what `open_media` (or the queue) is starting.
+pub(crate) fn now_playing(title: Option<String>, audio_only: bool) {
+    update(
+        |p| {
+            *p = Presence {
+                title: title.filter(|t| !t.trim().is_empty()),
+                audio_only,
+                paused: Some(false),
+                ..Presence::default()
+            }
+        },
+        true,
+    );
+}
+
+/// Playback stopped: clear the presence.
+pub(crate) fn stopped() {
+    update(|p| *p = Presence::default(), true);
+}
+
+/// Start the presence worker and follow player events.
+pub fn init(app: &AppHandle) {
+    let (tx, rx) = mpsc::channel();
+    *WAKE.lock().unwrap() = Some(tx);
+
+    app.listen_any("vlc:state", |event| {
+        let Ok(payload) = serde_json::from_str::<serde_json::Value>(event.payload()) else {
+            return;
+        };
+        let flag = |key: &str| payload[key].as_bool().unwrap_or(false);
+        if flag("ended") {
+            stopped();
+        } else if !flag("buffering") {
+            let playing = flag("playing");
+            update(|p| if p.paused.is_some() { p.paused = Some(!playing) }, true);
+        }
+    });
+    app.listen_any("vlc:time", |event| {
+        let Ok(payload) = serde_json::from_str::<serde_json::Value>(event.payload()) else {
+            return;
+        };
+        update(
+            |p| {
+                p.time_ms = payload["time_ms"].as_i64().unwrap_or(0);
+                p.duration_ms = payload["duration_ms"].as_i64().unwrap_or(0);
+            },
+            false,
+        );
+    });
+    app.listen_any("vlc:meta", |event| {
+        let Ok(payload) = serde_json::from_str::<serde_json::Value>(event.payload()) else {
+            return;
+        };
+        let title = payload["title"].as_str().unwrap_or_default().to_string();
+        update(|p| p.meta_title = title, true);
+    });
+
+    let app = app.clone();
+    std::thread::spawn(move || worker(app, rx));
+}
+
+/// Sends the current presence when woken urgently or every `PROGRESS_INTERVAL`.
+fn worker(app: AppHandle, rx: mpsc::Receiver<bool>) {
+    let mut ipc: Option<Ipc> = None;
+    let mut last_failure: Option<Instant> = None;
+    let mut last_sent: Option<Instant> = None;
+    let mut showing = false;
+
+    loop {
+        let urgent = match rx.recv_timeout(PROGRESS_INTERVAL) {
+            Ok(urgent) => urgent,
+            Err(mpsc::RecvTimeoutError::Timeout) => false,
+            Err(mpsc::RecvTimeoutError::Disconnected) => return,
+        };
+        if !urgent && last_sent.is_some_and(|t| t.elapsed() < PROGRESS_INTERVAL) {
+            continue;
+        }
+
+        let enabled = store::get_setting::<bool>(&app, ENABLED_SETTING).unwrap_or(false);
+        let activity = if enabled {
+            PRESENCE.lock().ok().and_then(|p| p.as_ref().and_then(activity))
+        } else {
+            None
+        };
+        if activity.is_none() && !showing {
+            // Disabled while nothing is shown: let go of the socket
+            ipc = None;
+            continue;
+        }
+
+        if ipc.is_none() {
+            if last_failure.is_some_and(|t| t.elapsed() < RECONNECT_INTERVAL) {
+                continue;
+            }
+            let Some(client_id) = store::get_setting::<String>(&app, CLIENT_ID_SETTING)
+                .filter(|id| !id.trim().is_empty())
+            else {
+                continue;
+            };
+            ipc = Ipc::connect(client_id.trim());
+            if ipc.is_none() {
+                last_failure = Some(Instant::now());
+                continue;
+            }
+        }
+
+        let sent = ipc
+            .as_mut()
+            .is_some_and(|ipc| ipc.set_activity(activity.as_ref()).is_ok());
+        if sent {
+            showing = activity.is_some();
+            last_sent = Some(Instant::now());
+        } else {
+            // Discord went away; the presence dies with the connection
+            ipc = None;
+            showing = false;
+            last_failure = Some(Instant::now());
+        }
+    }
+}
+
+/// Activity payload for Discord, or None when nothing is playing.
+fn activity(p: &Presence) -> Option<serde_json::Value> {
+    let paused = p.paused?;
+    let title = p
+        .title
+        .clone()
+        .or_else(|| Some(p.meta_title.clone()).filter(|t| !t.is_empty()))?;
+    let verb = if p.audio_only { "Listening to" } else { "Watching" };
+    let mut state = if p.duration_ms > 0 {
+        format!("{} / {}", format_time(p.time_ms), format_time(p.duration_ms))
+    } else {
+        format_time(p.time_ms)
+    };
+    if paused {
+        state = format!("Paused — {}", state);
+    }
+    let mut activity = serde_json::json!({
+        "details": format!("{} {}", verb, title),
+        "state": state,
+    });
+    // Discord counts up from `start` itself, so the elapsed time stays live
+    // between progress updates
+    if !paused {
+        let now_ms = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_millis() as i64)
+            .unwrap_or(0);
+        activity["timestamps"] = serde_json::json!({ "start": (now_ms - p.time_ms) / 1000 });
+    }
+    Some(activity)
+}
+
+/// `h:mm:ss`
+fn format_time(ms: i64) -> String {
+    let secs = ms.max(0) / 1000;
+    format!("{}:{:02}:{:02}", secs / 3600, secs / 60 % 60, secs % 60)
+}
+
+trait Stream: Read + Write + Send {}
+impl<T: Read + Write + Send> Stream for T {}
+
+/// Connection to the local Discord client.
+struct Ipc {
+    stream: Box<dyn Stream>,
+    nonce: u64,
+}
+
+impl Ipc {
+    fn connect(client_id: &str) -> Option<Ipc> {
+        let stream = (0..10).find_map(open_socket)?;
+        let mut ipc = Ipc { stream, nonce: 0 };
+        ipc.send(OP_HANDSHAKE, &serde_json::json!({ "v": 1, "client_id": client_id }))
+            .ok()?;
+        Some(ipc)
+    }
+
+    fn set_activity(&mut self, activity: Option<&serde_json::Value>) -> std::io::Result<()> {
+        self.nonce += 1;
+        let payload = serde_json::json!({
+            "cmd": "SET_ACTIVITY",
+            "args": { "pid": std::process::id(), "activity": activity },
+            "nonce": self.nonce.to_string(),
+        });
+        self.send(OP_FRAME, &payload)
+    }
+
+    /// Write one frame and read Discord's reply to it.
+    fn send(&mut self, op: u32, payload: &serde_json::Value) -> std::io::Result<()> {
+        let body = payload.to_string().into_bytes();
+        let mut frame = Vec::with_capacity(8 + body.len());
+        frame.extend_from_slice(&op.to_le_bytes());
+        frame.extend_from_slice(&(body.len() as u32).to_le_bytes());
+        frame.extend_from_slice(&body);
+        self.stream.write_all(&frame)?;
+
+        let mut header = [0u8; 8];
+        self.stream.read_exact(&mut header)?;
+        let reply_op = u32::from_le_bytes([header[0], header[1], header[2], header[3]]);
+        let len = u32::from_le_bytes([header[4], header[5], header[6], header[7]]) as usize;
+        let mut reply = vec![0u8; len];
+        self.stream.read_exact(&mut reply)?;
+        // Op 2 is Discord closing the connection (e.g. an unknown client id)
+        if reply_op == 2 {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::ConnectionAborted,
+                String::from_utf8_lossy(&reply).into_owned(),
+            ));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(unix)]
+fn open_socket(index: u32) -> Option<Box<dyn Stream>> {
+    use std::os::unix::net::UnixStream;
+    use std::path::PathBuf;
+
+    let name = format!("discord-ipc-{}", index);
+    let dirs = ["XDG_RUNTIME_DIR", "TMPDIR", "TMP", "TEMP"]
+        .iter()
+        .filter_map(std::env::var_os)
+        .map(PathBuf::from)
+        .chain(std::iter::once(PathBuf::from("/tmp")));
+    for dir in dirs {
+        // Flatpak and Snap builds of Discord put the socket in a subdirectory
+        for sub in ["", "app/com.discordapp.Discord", "snap.discord"] {
+            if let Ok(stream) = UnixStream::connect(dir.join(sub).join(&name)) {
+                let _ = stream.set_read_timeout(Some(IPC_TIMEOUT));
+                let _ = stream.set_write_timeout(Some(IPC_TIMEOUT));
+                return Some(Box::new(stream));
+            }
+        }
+    }
+    None
+}
+
+#[cfg(windows)]
+fn open_socket(index: u32) -> Option<Box<dyn Stream>> {
+    std::fs::OpenOptions::new()
+        .read(true)
+        .write(true)
+        .open(format!(r"\\.\pipe\discord-ipc-{}", index))
+        .ok()
+        .map(|pipe| Box::new(pipe) as Box<dyn Stream>)
+}
+
+#[cfg(not(any(unix, windows)))]
+fn open_socket(_index: u32) -> Option<Box<dyn Stream>> {
+    None
+}
+
+/// Turn Discord presence on or off and remember the choice. `client_id` (the
+/// Discord application id) is stored when given.
+#[tauri::command]
+pub async fn set_discord_presence_enabled(
+    app: AppHandle,
+    enabled: bool,
+    client_id: Option<String>,
+) -> Result<(), AppError> {
+    if let Some(id) = client_id {
+        store::set_setting(&app, CLIENT_ID_SETTING, serde_json::json!(id.trim()))?;
+    }
+    store::set_setting(&app, ENABLED_SETTING, serde_json::json!(enabled))?;
+    // Wake the worker so the presence appears or clears right away
+    update(|_| {}, true);
+    Ok(())
+}
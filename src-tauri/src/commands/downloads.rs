@@ -0,0 +1,784 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+use tauri::{AppHandle, Emitter, Manager};
+use tauri_plugin_store::StoreExt;
+use tokio::process::Command;
+use tokio::sync::oneshot;
+
+use crate::commands::{player, progress, rclone};
+use crate::error::AppError;
+
+/// Persisted download queue, keyed by download id
+const DOWNLOADS_STORE_PATH: &str = "rcloneflix-downloads.json";
+const DOWNLOADS_KEY: &str = "downloads";
+/// Most frequent progress update from `resumable_download`
+const PROGRESS_INTERVAL: std::time::Duration = std::time::Duration::from_millis(500);
+
+/// Serializes read-modify-write cycles on the queue
+static QUEUE_LOCK: Mutex<()> = Mutex::new(());
+/// The download being transferred, with the sender that stops it. Downloads
+/// run one at a time so a queue of episodes doesn't split the bandwidth.
+static ACTIVE: Mutex<Option<(String, Option<oneshot::Sender<()>>)>> = Mutex::new(None);
+/// (id, bytes, total) of the active download. Progress ticks only update this;
+/// the store is written when the download changes state.
+static PROGRESS: Mutex<Option<(String, u64, u64)>> = Mutex::new(None);
+/// Cancel senders for downloads that run outside the queue (`download_remote_file`
+/// and book downloads), by id
+static TRANSFERS: Mutex<Option<HashMap<String, oneshot::Sender<()>>>> = Mutex::new(None);
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum DownloadState {
+    Queued,
+    Downloading,
+    Paused,
+    Completed,
+    Failed,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Download {
+    /// Library item id of the file, so the UI can match downloads to items
+    pub id: String,
+    pub config_path: String,
+    pub remote_root: String,
+    pub file_path: String,
+    /// Local file the download is written to
+    pub local_path: String,
+    pub state: DownloadState,
+    pub bytes: u64,
+    pub total: u64,
+    pub error: Option<String>,
+    /// Unix seconds; the queue runs in this order
+    pub queued_at: u64,
+}
+
+/// One progress update of a download
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct TransferStats {
+    pub bytes: u64,
+    pub total: u64,
+    pub percent: u32,
+    /// Bytes per second
+    pub speed: u64,
+    /// ETA in rclone's style, e.g. "4m30s"; None while unknown
+    pub eta: Option<String>,
+}
+
+fn now_secs() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+/// Register a download running outside the queue under `id`. Fails if one
+/// with that id is already running.
+pub(crate) fn start_transfer(id: &str) -> Result<oneshot::Receiver<()>, AppError> {
+    let (cancel_tx, cancel_rx) = oneshot::channel();
+    let mut transfers = TRANSFERS.lock().unwrap();
+    let transfers = transfers.get_or_insert_with(HashMap::new);
+    if transfers.contains_key(id) {
+        return Err(format!("Download {} is already running", id).into());
+    }
+    transfers.insert(id.to_string(), cancel_tx);
+    Ok(cancel_rx)
+}
+
+/// Forget a finished `start_transfer` download.
+pub(crate) fn end_transfer(id: &str) {
+    if let Some(transfers) = TRANSFERS.lock().unwrap().as_mut() {
+        transfers.remove(id);
+    }
+}
+
+/// Stop the `start_transfer` download `id`, if it's still running.
+pub(crate) fn cancel_transfer(id: &str) {
+    let sender = TRANSFERS
+        .lock()
+        .unwrap()
+        .as_mut()
+        .and_then(|transfers| transfers.remove(id));
+    if let Some(sender) = sender {
+        let _ = sender.send(());
+    }
+}
+
+/// Size and hash of a remote file
+#[derive(Debug, Clone)]
+pub(crate) struct RemoteStat {
+    /// None when the backend doesn't report sizes
+    pub size: Option<u64>,
+    /// Only checked when the backend provides it
+    pub sha256: Option<String>,
+}
+
+/// Stat a remote file with `rclone lsjson --stat --hash`. Fails with a clear
+/// error when the file doesn't exist, before any download starts.
+pub(crate) async fn stat_remote(app: &AppHandle, config_path: &str, remote_path: &str) -> Result<RemoteStat, String> {
+    let output = Command::new(player::rclone_binary(app))
+        .args(["lsjson", "--stat", "--hash", "--hash-type", "sha256", "--config", config_path, remote_path])
+        .envs(rclone::config_env(config_path))
+        .output()
+        .await
+        .map_err(|e| format!("Failed to run rclone: {}", e))?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        // rclone exits with 3 when a directory or file isn't found
+        if output.status.code() == Some(3) || stderr.contains("not found") {
+            return Err(format!("File not found on remote: {}", remote_path));
+        }
+        return Err(format!("rclone lsjson error: {}", stderr));
+    }
+    let stat: serde_json::Value = serde_json::from_slice(&output.stdout)
+        .map_err(|e| format!("Failed to parse rclone output: {}", e))?;
+    if stat["IsDir"].as_bool().unwrap_or(false) {
+        return Err(format!("Not a file: {}", remote_path));
+    }
+    Ok(RemoteStat {
+        // rclone reports -1 for an unknown size
+        size: stat["Size"].as_i64().and_then(|size| u64::try_from(size).ok()),
+        sha256: stat["Hashes"]["sha256"]
+            .as_str()
+            .filter(|h| !h.is_empty())
+            .map(|h| h.to_lowercase()),
+    })
+}
+
+/// Where an unfinished download of `local_path` is kept between attempts
+pub(crate) fn partial_path(local_path: &Path) -> PathBuf {
+    let mut name = local_path.file_name().unwrap_or_default().to_os_string();
+    name.push(".partial");
+    local_path.with_file_name(name)
+}
+
+/// "4m30s" style duration, as rclone prints ETAs
+fn format_eta(secs: u64) -> String {
+    match (secs / 3600, secs / 60 % 60, secs % 60) {
+        (0, 0, s) => format!("{}s", s),
+        (0, m, s) => format!("{}m{}s", m, s),
+        (h, m, s) => format!("{}h{}m{}s", h, m, s),
+    }
+}
+
+fn sha256_file(path: &Path) -> Result<String, String> {
+    use sha2::{Digest, Sha256};
+    let mut file = std::fs::File::open(path)
+        .map_err(|e| format!("Failed to open {}: {}", path.display(), e))?;
+    let mut hasher = Sha256::new();
+    std::io::copy(&mut file, &mut hasher)
+        .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+    Ok(hasher
+        .finalize()
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect())
+}
+
+/// Check a finished partial against the remote and move it into place. A
+/// partial that doesn't match is discarded so the next attempt starts over.
+/// Each check is skipped when the remote doesn't report that value.
+async fn finish_partial(partial: &Path, local_path: &Path, stat: &RemoteStat) -> Result<(), String> {
+    let size = std::fs::metadata(partial).map(|m| m.len()).unwrap_or(0);
+    if let Some(expected) = stat.size.filter(|&expected| expected != size) {
+        eprintln!(
+            "Warning: discarding {} ({} bytes, expected {})",
+            partial.display(),
+            size,
+            expected
+        );
+        let _ = std::fs::remove_file(partial);
+        return Err(format!("Downloaded size mismatch for {}; it will restart from the beginning", local_path.display()));
+    }
+    if let Some(expected) = &stat.sha256 {
+        let path = partial.to_path_buf();
+        let actual = tokio::task::spawn_blocking(move || sha256_file(&path))
+            .await
+            .map_err(|e| format!("Hashing failed: {}", e))??;
+        if &actual != expected {
+            eprintln!("Warning: discarding {} (SHA-256 mismatch)", partial.display());
+            let _ = std::fs::remove_file(partial);
+            return Err(format!("Downloaded file for {} is corrupt; it will restart from the beginning", local_path.display()));
+        }
+    }
+    std::fs::rename(partial, local_path)
+        .map_err(|e| format!("Failed to move {} into place: {}", local_path.display(), e))
+}
+
+/// Download `remote_path` to `local_path` through a shared rclone serve
+/// started with `options`, writing to a `.partial` file and resuming it with an HTTP
+/// Range request if an earlier attempt left one. The result is checked against
+/// `stat` before it's renamed into place. Returns Ok(false) when cancelled,
+/// leaving the partial for the next attempt.
+pub(crate) async fn resumable_download(
+    app: &AppHandle,
+    config_path: &str,
+    remote_path: &str,
+    stat: &RemoteStat,
+    local_path: &Path,
+    options: &player::ServeOptions,
+    mut cancel: oneshot::Receiver<()>,
+    mut on_stats: impl FnMut(TransferStats),
+) -> Result<bool, String> {
+    if let Some(parent) = local_path.parent() {
+        std::fs::create_dir_all(parent)
+            .map_err(|e| format!("Failed to create {}: {}", parent.display(), e))?;
+    }
+    let partial = partial_path(local_path);
+    let mut offset = std::fs::metadata(&partial).map(|m| m.len()).unwrap_or(0);
+    if stat.size.is_some_and(|size| offset > size) {
+        eprintln!(
+            "Warning: discarding {} ({} bytes, larger than the remote file)",
+            partial.display(),
+            offset
+        );
+        let _ = std::fs::remove_file(&partial);
+        offset = 0;
+    }
+
+    // Without a known size the partial can't be known complete; the server
+    // answers the range request with 416 if it is
+    if stat.size.is_none_or(|size| offset < size) {
+        let serve_session = format!("download:{}", local_path.display());
+        let url = shared_file_url(app, config_path, remote_path, options, &serve_session).await?;
+        let result = fetch_range(&url, &partial, offset, None, stat.size, &mut cancel, &mut on_stats).await;
+        player::release_shared_session(&app.state::<player::VlcManager>(), &serve_session);
+        if !result? {
+            return Ok(false);
+        }
+    }
+
+    finish_partial(&partial, local_path, stat).await?;
+    let size = std::fs::metadata(local_path).map(|m| m.len()).unwrap_or(0);
+    on_stats(TransferStats {
+        bytes: size,
+        total: size,
+        percent: 100,
+        speed: 0,
+        eta: None,
+    });
+    Ok(true)
+}
+
+/// Fetch the first `len` bytes of `remote_path` into `head_path`, resuming a
+/// shorter file left by an earlier attempt. Returns Ok(false) when cancelled.
+pub(crate) async fn download_head(
+    app: &AppHandle,
+    config_path: &str,
+    remote_path: &str,
+    head_path: &Path,
+    len: u64,
+    options: &player::ServeOptions,
+    mut cancel: oneshot::Receiver<()>,
+) -> Result<bool, String> {
+    if let Some(parent) = head_path.parent() {
+        std::fs::create_dir_all(parent)
+            .map_err(|e| format!("Failed to create {}: {}", parent.display(), e))?;
+    }
+    let offset = std::fs::metadata(head_path).map(|m| m.len()).unwrap_or(0);
+    if len == 0 || offset >= len {
+        return Ok(true);
+    }
+    let serve_session = format!("download:{}", head_path.display());
+    let url = shared_file_url(app, config_path, remote_path, options, &serve_session).await?;
+    let result = fetch_range(&url, head_path, offset, Some(len - 1), Some(len), &mut cancel, &mut |_| {}).await;
+    player::release_shared_session(&app.state::<player::VlcManager>(), &serve_session);
+    result
+}
+
+/// URL of `remote_path` on a shared rclone serve of its parent folder, held
+/// for `serve_session` until `player::release_shared_session`.
+async fn shared_file_url(
+    app: &AppHandle,
+    config_path: &str,
+    remote_path: &str,
+    options: &player::ServeOptions,
+    serve_session: &str,
+) -> Result<String, String> {
+    // Serve the parent folder; the file name is the URL path
+    let colon = remote_path
+        .find(':')
+        .ok_or_else(|| format!("Not a remote path: {}", remote_path))?;
+    let split = remote_path.rfind('/').filter(|&pos| pos > colon).unwrap_or(colon);
+    let remote_root = &remote_path[..split.max(colon + 1)];
+    let file_name = &remote_path[split + 1..];
+
+    let vlc = app.state::<player::VlcManager>();
+    Ok(player::shared_serve_url(app, &vlc, config_path, remote_root, file_name, serve_session.to_string(), options).await?)
+}
+
+/// GET `url` into `partial` from byte `offset` up to byte `end` (inclusive;
+/// the end of the file when None), out of `total` bytes if known.
+/// Returns Ok(false) when cancelled.
+async fn fetch_range(
+    url: &str,
+    partial: &Path,
+    mut offset: u64,
+    end: Option<u64>,
+    total: Option<u64>,
+    cancel: &mut oneshot::Receiver<()>,
+    on_stats: &mut impl FnMut(TransferStats),
+) -> Result<bool, String> {
+    use std::io::Write;
+
+    let mut request = tauri_plugin_http::reqwest::Client::new().get(url);
+    if offset > 0 {
+        eprintln!("Resuming {} at byte {}", partial.display(), offset);
+    }
+    if let Some(end) = end {
+        request = request.header("Range", format!("bytes={}-{}", offset, end));
+    } else if offset > 0 {
+        request = request.header("Range", format!("bytes={}-", offset));
+    }
+    let mut resp = request
+        .send()
+        .await
+        .map_err(|e| format!("Download request failed: {}", e))?;
+    let status = resp.status().as_u16();
+    // A server that ignores the range sends the whole file
+    let append = match status {
+        206 => true,
+        200 => {
+            offset = 0;
+            false
+        }
+        // Nothing past `offset`: the partial already has the whole file
+        416 if offset > 0 && total.is_none() => return Ok(true),
+        _ => return Err(format!("Download failed (HTTP {})", status)),
+    };
+    let mut file = std::fs::OpenOptions::new()
+        .create(true)
+        .write(true)
+        .append(append)
+        .truncate(!append)
+        .open(partial)
+        .map_err(|e| format!("Failed to open {}: {}", partial.display(), e))?;
+
+    let started = std::time::Instant::now();
+    let start_offset = offset;
+    let mut last_emit = started;
+    loop {
+        tokio::select! {
+            chunk = resp.chunk() => match chunk.map_err(|e| format!("Download interrupted: {}", e))? {
+                Some(chunk) => {
+                    // A server that ignores the range end sends past it
+                    let take = end.map_or(chunk.len(), |end| chunk.len().min((end + 1).saturating_sub(offset) as usize));
+                    file.write_all(&chunk[..take])
+                        .map_err(|e| format!("Failed to write {}: {}", partial.display(), e))?;
+                    offset += take as u64;
+                    if end.is_some_and(|end| offset > end) {
+                        break;
+                    }
+                    if last_emit.elapsed() >= PROGRESS_INTERVAL {
+                        last_emit = std::time::Instant::now();
+                        let elapsed = started.elapsed().as_secs_f64().max(0.001);
+                        let speed = ((offset - start_offset) as f64 / elapsed) as u64;
+                        on_stats(TransferStats {
+                            bytes: offset,
+                            total: total.unwrap_or(0),
+                            percent: total.and_then(|total| (offset * 100).checked_div(total)).unwrap_or(0) as u32,
+                            speed,
+                            eta: total
+                                .filter(|_| speed > 0)
+                                .map(|total| format_eta(total.saturating_sub(offset) / speed)),
+                        });
+                    }
+                }
+                None => break,
+            },
+            _ = &mut *cancel => {
+                let _ = file.flush();
+                return Ok(false);
+            }
+        }
+    }
+    file.flush()
+        .map_err(|e| format!("Failed to write {}: {}", partial.display(), e))?;
+    Ok(true)
+}
+
+fn load(app: &AppHandle) -> HashMap<String, Download> {
+    app.store(DOWNLOADS_STORE_PATH)
+        .ok()
+        .and_then(|s| s.get(DOWNLOADS_KEY))
+        .and_then(|v| serde_json::from_value(v).ok())
+        .unwrap_or_default()
+}
+
+fn save(app: &AppHandle, downloads: &HashMap<String, Download>) -> Result<(), String> {
+    let store = app
+        .store(DOWNLOADS_STORE_PATH)
+        .map_err(|e| format!("Failed to open downloads store: {}", e))?;
+    store.set(DOWNLOADS_KEY, serde_json::json!(downloads));
+    store
+        .save()
+        .map_err(|e| format!("Failed to save downloads store: {}", e))
+}
+
+/// Apply `change` to download `id` and persist it. Returns the updated download,
+/// or None if it was removed meanwhile.
+fn update(app: &AppHandle, id: &str, change: impl FnOnce(&mut Download)) -> Option<Download> {
+    let _guard = QUEUE_LOCK.lock().unwrap();
+    let mut downloads = load(app);
+    let download = downloads.get_mut(id)?;
+    change(download);
+    let updated = download.clone();
+    if let Err(e) = save(app, &downloads) {
+        eprintln!("{}", e);
+    }
+    Some(updated)
+}
+
+/// Ask the running transfer of `id`, if any, to stop.
+fn stop_active(id: &str) {
+    if let Some((active_id, sender)) = ACTIVE.lock().unwrap().as_mut() {
+        if active_id == id {
+            if let Some(sender) = sender.take() {
+                let _ = sender.send(());
+            }
+        }
+    }
+}
+
+/// Start the next queued download if nothing is transferring.
+fn pump(app: &AppHandle) {
+    let mut active = ACTIVE.lock().unwrap();
+    if active.is_some() {
+        return;
+    }
+    let next = {
+        let _guard = QUEUE_LOCK.lock().unwrap();
+        let mut downloads = load(app);
+        let next = downloads
+            .values_mut()
+            .filter(|d| d.state == DownloadState::Queued)
+            .min_by_key(|d| d.queued_at);
+        let Some(next) = next else {
+            return;
+        };
+        next.state = DownloadState::Downloading;
+        next.error = None;
+        let next = next.clone();
+        if let Err(e) = save(app, &downloads) {
+            eprintln!("{}", e);
+        }
+        next
+    };
+
+    let (cancel_tx, cancel_rx) = oneshot::channel();
+    *active = Some((next.id.clone(), Some(cancel_tx)));
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        run(&app, next, cancel_rx).await;
+        *ACTIVE.lock().unwrap() = None;
+        pump(&app);
+    });
+}
+
+async fn run(app: &AppHandle, download: Download, cancel: oneshot::Receiver<()>) {
+    let id = download.id.clone();
+    let remote_path = format!(
+        "{}/{}",
+        download.remote_root.trim_end_matches('/'),
+        download.file_path.trim_start_matches('/')
+    );
+
+    let stat = match stat_remote(app, &download.config_path, &remote_path).await {
+        Ok(stat) => stat,
+        Err(e) => {
+            update(app, &id, |d| {
+                d.state = DownloadState::Failed;
+                d.error = Some(e.clone());
+            });
+            let _ = app.emit("downloads:error", serde_json::json!({ "id": id, "error": e }));
+            return;
+        }
+    };
+
+    let result = resumable_download(
+        app,
+        &download.config_path,
+        &remote_path,
+        &stat,
+        Path::new(&download.local_path),
+        &player::ServeOptions::default(),
+        cancel,
+        |stats| {
+            *PROGRESS.lock().unwrap() = Some((id.clone(), stats.bytes, stats.total));
+            let _ = app.emit("downloads:progress", serde_json::json!({
+                "id": id,
+                "bytes": stats.bytes,
+                "total": stats.total,
+                "percent": stats.percent,
+                "speed": stats.speed,
+                "eta": stats.eta,
+            }));
+        },
+    )
+    .await;
+    let progress = PROGRESS.lock().unwrap().take().map(|(_, bytes, total)| (bytes, total));
+    let keep_progress = |d: &mut Download| {
+        if let Some((bytes, total)) = progress {
+            d.bytes = bytes;
+            d.total = total;
+        }
+    };
+
+    match result {
+        // Paused or removed; the command already updated the state
+        Ok(false) => {
+            update(app, &id, keep_progress);
+        }
+        Ok(true) => {
+            let size = std::fs::metadata(&download.local_path).map(|m| m.len()).unwrap_or(0);
+            update(app, &id, |d| {
+                d.state = DownloadState::Completed;
+                d.bytes = size;
+                d.total = size;
+            });
+            let _ = app.emit("downloads:complete", serde_json::json!({
+                "id": id,
+                "path": download.local_path,
+            }));
+        }
+        Err(e) => {
+            eprintln!("Download {} failed: {}", id, e);
+            update(app, &id, |d| {
+                keep_progress(d);
+                d.state = DownloadState::Failed;
+                d.error = Some(e.clone());
+            });
+            let _ = app.emit("downloads:error", serde_json::json!({ "id": id, "error": e }));
+        }
+    }
+}
+
+/// Requeue downloads interrupted by the app closing and start the queue.
+/// Called once at startup. Downloads queued under an older item id scheme are
+/// moved to their current id.
+pub fn restore(app: &AppHandle) {
+    {
+        let _guard = QUEUE_LOCK.lock().unwrap();
+        let mut downloads = load(app);
+        let mut changed = false;
+        let stale: Vec<String> = downloads
+            .iter()
+            .filter(|(id, d)| **id != progress::item_id_for(&d.remote_root, &d.file_path))
+            .map(|(id, _)| id.clone())
+            .collect();
+        for old_id in stale {
+            if let Some(mut download) = downloads.remove(&old_id) {
+                download.id = progress::item_id_for(&download.remote_root, &download.file_path);
+                downloads.insert(download.id.clone(), download);
+                changed = true;
+            }
+        }
+        for download in downloads.values_mut() {
+            if download.state == DownloadState::Downloading {
+                download.state = DownloadState::Queued;
+                changed = true;
+            }
+        }
+        if changed {
+            if let Err(e) = save(app, &downloads) {
+                eprintln!("{}", e);
+            }
+        }
+    }
+    pump(app);
+}
+
+/// Local copy of a file if its download has completed and is still on disk.
+pub(crate) fn completed_path(app: &AppHandle, remote_root: &str, file_path: &str) -> Option<PathBuf> {
+    let id = progress::item_id_for(remote_root, file_path);
+    let _guard = QUEUE_LOCK.lock().unwrap();
+    load(app)
+        .remove(&id)
+        .filter(|d| d.state == DownloadState::Completed)
+        .map(|d| PathBuf::from(d.local_path))
+        .filter(|p| p.is_file())
+}
+
+/// Where `file_path` is downloaded under `dest`, keeping its folders so files
+/// with the same name in different seasons don't overwrite each other.
+/// None if `file_path` has no file name.
+fn local_path_for(dest: &Path, file_path: &str) -> Option<PathBuf> {
+    let segments: Vec<&str> = file_path
+        .split('/')
+        .filter(|s| !s.is_empty() && *s != "." && *s != "..")
+        .collect();
+    if segments.is_empty() {
+        return None;
+    }
+    Some(segments.iter().fold(dest.to_path_buf(), |path, segment| path.join(segment)))
+}
+
+/// Queue `file_path` under `remote_root` for download into the directory `dest`,
+/// in the same folders it has under `remote_root`. The download id is the file's library item id; queueing a file again
+/// restarts a paused or failed download.
+#[tauri::command]
+pub fn queue_download(
+    app: AppHandle,
+    config_path: String,
+    remote_root: String,
+    file_path: String,
+    dest: String,
+) -> Result<Download, AppError> {
+    let local_path = local_path_for(Path::new(&dest), &file_path)
+        .ok_or_else(|| format!("Not a file path: {}", file_path))?;
+    let id = progress::item_id_for(&remote_root, &file_path);
+    let download = {
+        let _guard = QUEUE_LOCK.lock().unwrap();
+        let mut downloads = load(&app);
+        if let Some(existing) = downloads.get(&id) {
+            if matches!(existing.state, DownloadState::Downloading | DownloadState::Completed) {
+                return Ok(existing.clone());
+            }
+        }
+        let download = Download {
+            id: id.clone(),
+            config_path,
+            remote_root,
+            file_path: file_path.clone(),
+            local_path: local_path.to_string_lossy().into_owned(),
+            state: DownloadState::Queued,
+            bytes: 0,
+            total: 0,
+            error: None,
+            queued_at: now_secs(),
+        };
+        downloads.insert(id, download.clone());
+        save(&app, &downloads)?;
+        download
+    };
+    pump(&app);
+    Ok(download)
+}
+
+/// All downloads in queue order.
+#[tauri::command]
+pub fn list_downloads(app: AppHandle) -> Result<Vec<Download>, AppError> {
+    let _guard = QUEUE_LOCK.lock().unwrap();
+    let mut downloads: Vec<Download> = load(&app).into_values().collect();
+    if let Some((id, bytes, total)) = PROGRESS.lock().unwrap().clone() {
+        if let Some(download) = downloads.iter_mut().find(|d| d.id == id) {
+            download.bytes = bytes;
+            download.total = total;
+        }
+    }
+    downloads.sort_by_key(|d| d.queued_at);
+    Ok(downloads)
+}
+
+#[tauri::command]
+pub fn pause_download(app: AppHandle, id: String) -> Result<(), AppError> {
+    let updated = update(&app, &id, |d| {
+        if matches!(d.state, DownloadState::Queued | DownloadState::Downloading) {
+            d.state = DownloadState::Paused;
+        }
+    });
+    if updated.is_none() {
+        return Err(AppError::invalid(format!("No download with id {}", id)));
+    }
+    stop_active(&id);
+    Ok(())
+}
+
+/// Requeue a paused or failed download.
+#[tauri::command]
+pub fn resume_download(app: AppHandle, id: String) -> Result<(), AppError> {
+    let updated = update(&app, &id, |d| {
+        if matches!(d.state, DownloadState::Paused | DownloadState::Failed) {
+            d.state = DownloadState::Queued;
+            d.error = None;
+        }
+    });
+    if updated.is_none() {
+        return Err(AppError::invalid(format!("No download with id {}", id)));
+    }
+    pump(&app);
+    Ok(())
+}
+
+/// Stop and forget a download, deleting the downloaded file if `delete_file`.
+#[tauri::command]
+pub fn remove_download(app: AppHandle, id: String, delete_file: bool) -> Result<(), AppError> {
+    let removed = {
+        let _guard = QUEUE_LOCK.lock().unwrap();
+        let mut downloads = load(&app);
+        let removed = downloads
+            .remove(&id)
+            .ok_or_else(|| format!("No download with id {}", id))?;
+        save(&app, &downloads)?;
+        removed
+    };
+    stop_active(&id);
+    let local_path = Path::new(&removed.local_path);
+    // An unfinished download is never playable, so its partial file always goes
+    let _ = std::fs::remove_file(partial_path(local_path));
+    if delete_file && local_path.is_file() {
+        std::fs::remove_file(local_path)
+            .map_err(|e| format!("Failed to delete {}: {}", local_path.display(), e))?;
+    }
+    Ok(())
+}
+
+/// Download a single remote file to `dest_path` outside the queue, emitting
+/// `download-progress` { sessionId, bytes, total, percent, speed, eta } and then
+/// `download-complete` { sessionId, path }. `cancel_download(session_id)` stops
+/// it and keeps the partial file, so calling again with the same `dest_path`
+/// resumes where it left off.
+#[tauri::command]
+pub async fn download_remote_file(
+    app: AppHandle,
+    config_path: String,
+    remote_path: String, // full path, e.g. "gdrive:/Movies/film.mkv"
+    dest_path: String,
+    session_id: String,
+) -> Result<String, AppError> {
+    let cancel = start_transfer(&session_id)?;
+    let result = async {
+        let stat = stat_remote(&app, &config_path, &remote_path).await?;
+        resumable_download(
+            &app,
+            &config_path,
+            &remote_path,
+            &stat,
+            Path::new(&dest_path),
+            &player::ServeOptions::default(),
+            cancel,
+            |stats| {
+                let _ = app.emit("download-progress", serde_json::json!({
+                    "sessionId": session_id,
+                    "bytes": stats.bytes,
+                    "total": stats.total,
+                    "percent": stats.percent,
+                    "speed": stats.speed,
+                    "eta": stats.eta,
+                }));
+            },
+        )
+        .await
+    }
+    .await;
+    end_transfer(&session_id);
+
+    if !result? {
+        return Err(AppError::cancelled("Download cancelled"));
+    }
+    let _ = app.emit("download-complete", serde_json::json!({
+        "sessionId": session_id,
+        "path": dest_path,
+    }));
+    Ok(dest_path)
+}
+
+/// Stop a running `download_remote_file`. Does nothing if it already finished.
+#[tauri::command]
+pub fn cancel_download(session_id: String) -> Result<(), AppError> {
+    cancel_transfer(&session_id);
+    Ok(())
+}
@@ -1,10 +1,23 @@
 use serde::{Deserialize, Serialize};
-use std::process::Command;
-use tauri::{AppHandle, Emitter};
+use tauri::AppHandle;
 use tauri_plugin_store::StoreExt;
-use std::time::Duration;
+
+use crate::commands::oauth;
+use crate::secrets;
+use crate::error::AppError;
 
 const STORE_PATH: &str = "rcloneflix-keys.json";
+const TOKEN_ENDPOINT: &str = "https://oauth2.googleapis.com/token";
+const USERINFO_ENDPOINT: &str = "https://www.googleapis.com/oauth2/v3/userinfo";
+
+/// Error returned by `refresh_google_tokens` when Google no longer accepts the
+/// refresh token (revoked, expired or password changed). The stored tokens are
+/// cleared and the user has to sign in again.
+pub const REFRESH_TOKEN_REVOKED: &str = "google_refresh_token_revoked";
+
+/// Error returned by `fetch_google_profile` on a 401: refresh the access token
+/// with `refresh_google_tokens` and retry.
+pub const ACCESS_TOKEN_EXPIRED: &str = "google_access_token_expired";
 
 #[derive(Debug, Serialize, Deserialize, Clone, Default)]
 pub struct GoogleTokens {
@@ -15,146 +28,41 @@ pub struct GoogleTokens {
     pub display_name: String,
 }
 
-/// Open the Google OAuth URL in the system browser and start a local
-/// HTTP server on the given port to capture the redirect callback.
-/// Emits "oauth-callback" event with the code when it arrives.
-/// Server times out after 5 minutes to prevent indefinite resource usage.
+/// The signed-in user's profile from Google's userinfo endpoint
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct GoogleProfile {
+    pub email: String,
+    pub display_name: String,
+    pub picture_url: Option<String>,
+}
+
+/// Open the Google OAuth URL in the system browser and capture the redirect on
+/// a local server on `port`; see `oauth::run_loopback_flow`. Returns the PKCE
+/// code verifier for the token exchange.
 #[tauri::command]
 pub async fn start_google_oauth(
     app: AppHandle,
     auth_url: String,
     port: u16,
-) -> Result<(), String> {
-    // Open browser
-    #[cfg(target_os = "linux")]
-    Command::new("xdg-open").arg(&auth_url).spawn()
-        .map_err(|e| format!("Failed to open browser: {}", e))?;
-    #[cfg(target_os = "macos")]
-    Command::new("open").arg(&auth_url).spawn()
-        .map_err(|e| format!("Failed to open browser: {}", e))?;
-    #[cfg(target_os = "windows")]
-    Command::new("cmd").args(["/C", "start", &auth_url]).spawn()
-        .map_err(|e| format!("Failed to open browser: {}", e))?;
-
-    // Spawn local HTTP server to capture callback
-    let app_clone = app.clone();
-    tokio::spawn(async move {
-        let addr = format!("127.0.0.1:{}", port);
-        let listener = match tokio::net::TcpListener::bind(&addr).await {
-            Ok(l) => l,
-            Err(e) => {
-                eprintln!("Failed to bind OAuth callback server: {}", e);
-                let _ = app_clone.emit("oauth-error", serde_json::json!({ 
-                    "error": format!("Failed to bind callback server: {}", e) 
-                }));
-                return;
-            }
-        };
-
-        // Accept one connection with timeout (5 minutes)
-        let accept_future = listener.accept();
-        let timeout = Duration::from_secs(300);
-        
-        match tokio::time::timeout(timeout, accept_future).await {
-            Ok(Ok((mut stream, _))) => {
-                use tokio::io::{AsyncReadExt, AsyncWriteExt};
-                let mut buf = vec![0u8; 4096];
-                if let Ok(n) = stream.read(&mut buf).await {
-                    let request = String::from_utf8_lossy(&buf[..n]);
-                    // Parse ?code=xxx from GET /oauth/callback?code=xxx
-                    if let Some(code) = extract_code(&request) {
-                        // Send success response to browser
-                        let response = "HTTP/1.1 200 OK\r\nContent-Type: text/html\r\n\r\
-                            <html><body style='font-family:sans-serif;text-align:center;padding:60px'>\
-                            <h2>✓ Signed in successfully</h2>\
-                            <p>You can close this tab and return to RcloneFlix.</p>\
-                            </body></html>";
-                        let _ = stream.write_all(response.as_bytes()).await;
-
-                        // Emit event to frontend
-                        let _ = app_clone.emit("oauth-callback", serde_json::json!({ "code": code }));
-                    } else if request.contains("error=") {
-                        // Handle OAuth errors
-                        let error = extract_error(&request).unwrap_or_else(|| "Unknown OAuth error".to_string());
-                        let response = format!("HTTP/1.1 400 Bad Request\r\n\r\nOAuth error: {}", error);
-                        let _ = stream.write_all(response.as_bytes()).await;
-                        let _ = app_clone.emit("oauth-error", serde_json::json!({ "error": error }));
-                    } else {
-                        let response = "HTTP/1.1 400 Bad Request\r\n\r\nMissing code parameter";
-                        let _ = stream.write_all(response.as_bytes()).await;
-                        let _ = app_clone.emit("oauth-error", serde_json::json!({ 
-                            "error": "Authorization code not received" 
-                        }));
-                    }
-                }
-            }
-            Ok(Err(e)) => {
-                eprintln!("OAuth callback accept error: {}", e);
-                let _ = app_clone.emit("oauth-error", serde_json::json!({ 
-                    "error": format!("Connection error: {}", e) 
-                }));
-            }
-            Err(_) => {
-                // Timeout
-                let _ = app_clone.emit("oauth-error", serde_json::json!({ 
-                    "error": "OAuth timeout - please try again" 
-                }));
-            }
-        }
-    });
-
-    Ok(())
-}
-
-fn extract_code(request: &str) -> Option<String> {
-    // GET /oauth/callback?code=xxxx&... HTTP/1.1
-    let line = request.lines().next()?;
-    let path = line.split_whitespace().nth(1)?;
-    let query = path.split('?').nth(1)?;
-    for param in query.split('&') {
-        let mut parts = param.splitn(2, '=');
-        if parts.next() == Some("code") {
-            return parts.next().map(|s| s.to_string());
-        }
-    }
-    None
+) -> Result<String, AppError> {
+    Ok(oauth::run_loopback_flow(&app, "Google", &auth_url, port).await?)
 }
 
-/// Extract OAuth error from request query string
-fn extract_error(request: &str) -> Option<String> {
-    let line = request.lines().next()?;
-    let path = line.split_whitespace().nth(1)?;
-    let query = path.split('?').nth(1)?;
-    for param in query.split('&') {
-        let mut parts = param.splitn(2, '=');
-        if parts.next() == Some("error") {
-            return parts.next().map(|s| s.replace('+', " "));
-        }
-    }
-    None
-}
-
-/// Save Google tokens to encrypted store
-#[tauri::command]
-pub async fn save_google_tokens(
-    app: AppHandle,
-    tokens: GoogleTokens,
-) -> Result<(), String> {
+fn write_tokens(app: &AppHandle, tokens: &GoogleTokens) -> Result<(), String> {
     let store = app.store(STORE_PATH)
         .map_err(|e| format!("Store error: {}", e))?;
-    store.set("google_tokens", serde_json::to_value(&tokens).unwrap());
+    let sealed = secrets::seal(app, &serde_json::to_value(tokens).unwrap())?;
+    store.set("google_tokens", sealed);
     store.save().map_err(|e| format!("Save error: {}", e))?;
     Ok(())
 }
 
-/// Load Google tokens from store
-#[tauri::command]
-pub async fn load_google_tokens(app: AppHandle) -> Result<Option<GoogleTokens>, String> {
+fn read_tokens(app: &AppHandle) -> Result<Option<GoogleTokens>, String> {
     let store = app.store(STORE_PATH)
         .map_err(|e| format!("Store error: {}", e))?;
     match store.get("google_tokens") {
         Some(v) => {
-            let tokens: GoogleTokens = serde_json::from_value(v)
+            let tokens: GoogleTokens = serde_json::from_value(secrets::open(app, v)?)
                 .map_err(|e| format!("Parse error: {}", e))?;
             Ok(Some(tokens))
         }
@@ -162,12 +70,117 @@ pub async fn load_google_tokens(app: AppHandle) -> Result<Option<GoogleTokens>,
     }
 }
 
+/// Save Google tokens to the store, encrypted
+#[tauri::command]
+pub async fn save_google_tokens(
+    app: AppHandle,
+    tokens: GoogleTokens,
+) -> Result<(), AppError> {
+    Ok(write_tokens(&app, &tokens)?)
+}
+
+/// Load Google tokens from store
+#[tauri::command]
+pub async fn load_google_tokens(app: AppHandle) -> Result<Option<GoogleTokens>, AppError> {
+    Ok(read_tokens(&app)?)
+}
+
 /// Clear stored Google tokens (sign out)
 #[tauri::command]
-pub async fn clear_google_tokens(app: AppHandle) -> Result<(), String> {
+pub async fn clear_google_tokens(app: AppHandle) -> Result<(), AppError> {
     let store = app.store(STORE_PATH)
         .map_err(|e| format!("Store error: {}", e))?;
     store.delete("google_tokens");
     store.save().map_err(|e| format!("Save error: {}", e))?;
     Ok(())
 }
+
+/// Exchange the stored refresh token for a new access token, update
+/// `access_token`/`expires_at` (ms since epoch) in the store and return the tokens.
+/// `client_secret` is only needed for non-PKCE clients.
+/// Fails with `REFRESH_TOKEN_REVOKED` when Google rejects the refresh token.
+#[tauri::command]
+pub async fn refresh_google_tokens(
+    app: AppHandle,
+    client_id: String,
+    client_secret: Option<String>,
+) -> Result<GoogleTokens, AppError> {
+    let mut tokens = read_tokens(&app)?
+        .ok_or_else(|| "Not signed in to Google".to_string())?;
+    if tokens.refresh_token.is_empty() {
+        return Err("No refresh token stored".into());
+    }
+
+    let mut form = vec![
+        ("grant_type", "refresh_token".to_string()),
+        ("refresh_token", tokens.refresh_token.clone()),
+        ("client_id", client_id),
+    ];
+    if let Some(secret) = client_secret.filter(|s| !s.is_empty()) {
+        form.push(("client_secret", secret));
+    }
+
+    let (status, body) = oauth::post_token_request(TOKEN_ENDPOINT, &form).await?;
+
+    if !(200..300).contains(&status) {
+        let error = body["error"].as_str().unwrap_or("unknown_error");
+        if error == "invalid_grant" {
+            let store = app.store(STORE_PATH)
+                .map_err(|e| format!("Store error: {}", e))?;
+            store.delete("google_tokens");
+            store.save().map_err(|e| format!("Save error: {}", e))?;
+            return Err(REFRESH_TOKEN_REVOKED.into());
+        }
+        let description = body["error_description"].as_str().unwrap_or("");
+        return Err(format!("Token refresh failed ({}): {} {}", status, error, description)
+            .trim_end()
+            .into());
+    }
+
+    let access_token = body["access_token"]
+        .as_str()
+        .ok_or_else(|| "Token response missing access_token".to_string())?;
+    let expires_in = body["expires_in"].as_u64().unwrap_or(3600);
+
+    tokens.access_token = access_token.to_string();
+    tokens.expires_at = oauth::now_ms() + expires_in * 1000;
+    // Google may rotate the refresh token
+    if let Some(refresh) = body["refresh_token"].as_str() {
+        tokens.refresh_token = refresh.to_string();
+    }
+
+    write_tokens(&app, &tokens)?;
+    Ok(tokens)
+}
+
+/// Fetch the user's email, name and picture for an access token, to fill the
+/// `email`/`display_name` fields of `GoogleTokens` after sign-in.
+/// Fails with `ACCESS_TOKEN_EXPIRED` when Google answers 401.
+#[tauri::command]
+pub async fn fetch_google_profile(access_token: String) -> Result<GoogleProfile, AppError> {
+    let resp = tauri_plugin_http::reqwest::Client::new()
+        .get(USERINFO_ENDPOINT)
+        .bearer_auth(&access_token)
+        .timeout(std::time::Duration::from_secs(30))
+        .send()
+        .await
+        .map_err(|e| format!("Profile request failed: {}", e))?;
+    let status = resp.status().as_u16();
+    if status == 401 {
+        return Err(ACCESS_TOKEN_EXPIRED.into());
+    }
+    let text = resp
+        .text()
+        .await
+        .map_err(|e| format!("Failed to read profile: {}", e))?;
+    if !(200..300).contains(&status) {
+        return Err(format!("Profile request failed ({}): {}", status, text).into());
+    }
+    let body: serde_json::Value =
+        serde_json::from_str(&text).map_err(|e| format!("Parse error: {}", e))?;
+    Ok(GoogleProfile {
+        email: body["email"].as_str().unwrap_or_default().to_string(),
+        display_name: body["name"].as_str().unwrap_or_default().to_string(),
+        picture_url: body["picture"].as_str().map(|s| s.to_string()),
+    })
+}
@@ -0,0 +1,107 @@
+use tauri::plugin::TauriPlugin;
+use tauri::{AppHandle, Emitter, Manager, Wry};
+use tauri_plugin_global_shortcut::{Code, GlobalShortcutExt, Shortcut, ShortcutState};
+use tauri_plugin_store::StoreExt;
+
+use crate::commands::player::{self, VlcManager};
+use crate::error::AppError;
+
+const STORE_PATH: &str = "rcloneflix-keys.json";
+const ENABLED_KEY: &str = "media_keys_enabled";
+
+const MEDIA_KEYS: [Code; 4] = [
+    Code::MediaPlayPause,
+    Code::MediaStop,
+    Code::MediaTrackNext,
+    Code::MediaTrackPrevious,
+];
+
+/// Global-shortcut plugin with the media key handler attached. Keys are only
+/// registered once the user opts in (see `set_media_keys_enabled`).
+pub fn plugin() -> TauriPlugin<Wry> {
+    tauri_plugin_global_shortcut::Builder::new()
+        .with_handler(|app, shortcut, event| {
+            if event.state() == ShortcutState::Pressed {
+                handle_key(app, shortcut.key);
+            }
+        })
+        .build()
+}
+
+/// Register the media keys at startup if the stored setting enables them.
+/// Failures are reported as a `media-keys:error` event rather than aborting setup.
+pub fn init(app: &AppHandle) {
+    let enabled = app
+        .store(STORE_PATH)
+        .ok()
+        .and_then(|s| s.get(ENABLED_KEY))
+        .and_then(|v| v.as_bool())
+        .unwrap_or(false);
+    if enabled {
+        if let Err(e) = register_all(app) {
+            let _ = app.emit("media-keys:error", serde_json::json!({ "error": e }));
+        }
+    }
+}
+
+fn handle_key(app: &AppHandle, key: Code) {
+    let Some(vlc) = app.try_state::<VlcManager>() else {
+        return;
+    };
+    match key {
+        Code::MediaPlayPause => player::toggle_pause(&vlc),
+        Code::MediaStop => player::stop_playback(app, &vlc),
+        Code::MediaTrackNext | Code::MediaTrackPrevious => {
+            let step = if key == Code::MediaTrackNext { 1 } else { -1 };
+            let app = app.clone();
+            tauri::async_runtime::spawn(async move {
+                let Some(vlc) = app.try_state::<VlcManager>() else {
+                    return;
+                };
+                // No-op when there is no queue to move through
+                if let Err(e) = player::play_queue_step(&app, &vlc, step).await {
+                    let _ = app.emit("vlc:error", serde_json::json!({ "message": e.to_string() }));
+                }
+            });
+        }
+        _ => {}
+    }
+}
+
+fn register_all(app: &AppHandle) -> Result<(), String> {
+    for key in MEDIA_KEYS {
+        if let Err(e) = app.global_shortcut().register(Shortcut::new(None, key)) {
+            unregister_all(app);
+            return Err(format!(
+                "Could not register media keys (another application may be using them): {}",
+                e
+            ));
+        }
+    }
+    Ok(())
+}
+
+fn unregister_all(app: &AppHandle) {
+    for key in MEDIA_KEYS {
+        let _ = app.global_shortcut().unregister(Shortcut::new(None, key));
+    }
+}
+
+/// Enable or disable global media keys and remember the choice.
+/// Returns an error if the keys are held by another application.
+#[tauri::command]
+pub async fn set_media_keys_enabled(app: AppHandle, enabled: bool) -> Result<(), AppError> {
+    unregister_all(&app);
+    if enabled {
+        register_all(&app)?;
+    }
+
+    let store = app
+        .store(STORE_PATH)
+        .map_err(|e| format!("Failed to open store: {}", e))?;
+    store.set(ENABLED_KEY, serde_json::json!(enabled));
+    store
+        .save()
+        .map_err(|e| format!("Failed to save store: {}", e))?;
+    Ok(())
+}
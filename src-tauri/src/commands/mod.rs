@@ -4,3 +4,21 @@ pub mod rclone;
 pub mod store;
 pub mod scan;
 pub mod google;
+pub mod oauth;
+pub mod progress;
+pub mod bookmarks;
+pub mod media_keys;
+#[cfg(target_os = "linux")]
+pub mod mpris;
+pub mod tracks;
+pub mod segments;
+pub mod subtitles;
+pub mod cast;
+pub mod tmdb;
+pub mod opensubtitles;
+pub mod storyboard;
+pub mod temp_cache;
+pub mod prefetch;
+pub mod downloads;
+pub mod transcode;
+pub mod discord;
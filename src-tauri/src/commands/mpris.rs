@@ -0,0 +1,411 @@
+//! MPRIS D-Bus server (Linux) so the GNOME/KDE media widget and keyboard media
+//! keys control playback.
+//!
+//! Methods map onto the same player helpers the global media keys use. What the
+//! widget shows is mirrored from the `vlc:state`, `vlc:time` and `vlc:meta`
+//! events the VLC thread already emits, so nothing here talks to VLC directly.
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+use tauri::{AppHandle, Emitter, Listener, Manager};
+use tokio::sync::mpsc;
+use zbus::zvariant::{ObjectPath, Value};
+use zbus::{fdo, interface, SignalContext};
+
+use crate::commands::player::{self, VlcManager};
+
+const BUS_NAME: &str = "org.mpris.MediaPlayer2.rcloneflix";
+const OBJECT_PATH: &str = "/org/mpris/MediaPlayer2";
+/// Only one item plays at a time, so a fixed track id is enough
+const TRACK_ID: &str = "/org/rcloneflix/track/current";
+const NO_TRACK: &str = "/org/mpris/MediaPlayer2/TrackList/NoTrack";
+/// A time jump larger than this (µs) between `vlc:time` events is announced as `Seeked`
+const SEEK_JUMP_US: i64 = 3_000_000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Status {
+    Stopped,
+    Playing,
+    Paused,
+}
+
+impl Status {
+    fn as_str(self) -> &'static str {
+        match self {
+            Status::Stopped => "Stopped",
+            Status::Playing => "Playing",
+            Status::Paused => "Paused",
+        }
+    }
+}
+
+/// What the desktop widget shows, kept up to date from player events.
+struct NowPlaying {
+    status: Status,
+    title: String,
+    artist: String,
+    album: String,
+    art_url: String,
+    length_us: i64,
+    position_us: i64,
+    /// 0.0-1.0; only changes through MPRIS, as the player emits no volume event
+    volume: f64,
+}
+
+impl Default for NowPlaying {
+    fn default() -> Self {
+        NowPlaying {
+            status: Status::Stopped,
+            title: String::new(),
+            artist: String::new(),
+            album: String::new(),
+            art_url: String::new(),
+            length_us: 0,
+            position_us: 0,
+            volume: 1.0,
+        }
+    }
+}
+
+type Shared = Arc<Mutex<NowPlaying>>;
+
+/// Property change or signal the bus task should announce
+enum Change {
+    Status,
+    Metadata,
+    Seeked(i64),
+}
+
+/// Start the MPRIS server and mirror player events into it. A missing session
+/// bus (e.g. a headless session) only disables the integration.
+pub fn init(app: &AppHandle) {
+    let now: Shared = Arc::new(Mutex::new(NowPlaying::default()));
+    let (changes, rx) = mpsc::unbounded_channel();
+    listen(app, &now, &changes);
+
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        if let Err(e) = serve(app, now, changes, rx).await {
+            eprintln!("MPRIS server failed: {}", e);
+        }
+    });
+}
+
+async fn serve(
+    app: AppHandle,
+    now: Shared,
+    changes: mpsc::UnboundedSender<Change>,
+    mut rx: mpsc::UnboundedReceiver<Change>,
+) -> zbus::Result<()> {
+    let conn = zbus::connection::Builder::session()?
+        .name(BUS_NAME)?
+        .serve_at(OBJECT_PATH, Root { app: app.clone() })?
+        .serve_at(OBJECT_PATH, MprisPlayer { app, now, changes })?
+        .build()
+        .await?;
+    let iface = conn
+        .object_server()
+        .interface::<_, MprisPlayer>(OBJECT_PATH)
+        .await?;
+
+    while let Some(change) = rx.recv().await {
+        let ctxt = iface.signal_context();
+        let player = iface.get().await;
+        let result = match change {
+            Change::Status => player.playback_status_changed(ctxt).await,
+            Change::Metadata => player.metadata_changed(ctxt).await,
+            Change::Seeked(position) => MprisPlayer::seeked(ctxt, position).await,
+        };
+        if let Err(e) = result {
+            eprintln!("MPRIS signal failed: {}", e);
+        }
+    }
+    Ok(())
+}
+
+fn listen(app: &AppHandle, now: &Shared, changes: &mpsc::UnboundedSender<Change>) {
+    let (state_now, tx) = (now.clone(), changes.clone());
+    app.listen_any("vlc:state", move |event| {
+        let Ok(payload) = serde_json::from_str::<serde_json::Value>(event.payload()) else {
+            return;
+        };
+        let flag = |key: &str| payload[key].as_bool().unwrap_or(false);
+        let status = if flag("ended") {
+            Status::Stopped
+        } else if flag("playing") {
+            Status::Playing
+        } else if flag("buffering") {
+            // Buffering keeps whatever the widget showed before
+            return;
+        } else {
+            Status::Paused
+        };
+        let mut now = state_now.lock().unwrap();
+        if now.status != status {
+            now.status = status;
+            let _ = tx.send(Change::Status);
+        }
+    });
+
+    let (time_now, tx) = (now.clone(), changes.clone());
+    app.listen_any("vlc:time", move |event| {
+        let Ok(payload) = serde_json::from_str::<serde_json::Value>(event.payload()) else {
+            return;
+        };
+        let position_us = payload["time_ms"].as_i64().unwrap_or(0) * 1000;
+        let length_us = payload["duration_ms"].as_i64().unwrap_or(0) * 1000;
+        let mut now = time_now.lock().unwrap();
+        if (position_us - now.position_us).abs() > SEEK_JUMP_US {
+            let _ = tx.send(Change::Seeked(position_us));
+        }
+        now.position_us = position_us;
+        if now.length_us != length_us {
+            now.length_us = length_us;
+            let _ = tx.send(Change::Metadata);
+        }
+    });
+
+    let (meta_now, tx) = (now.clone(), changes.clone());
+    app.listen_any("vlc:meta", move |event| {
+        let Ok(payload) = serde_json::from_str::<serde_json::Value>(event.payload()) else {
+            return;
+        };
+        let text = |key: &str| payload[key].as_str().unwrap_or_default().to_string();
+        let mut now = meta_now.lock().unwrap();
+        now.title = text("title");
+        now.artist = text("artist");
+        now.album = text("album");
+        now.art_url = text("artwork_url");
+        let _ = tx.send(Change::Metadata);
+    });
+}
+
+/// `org.mpris.MediaPlayer2`: the application itself
+struct Root {
+    app: AppHandle,
+}
+
+#[interface(name = "org.mpris.MediaPlayer2")]
+impl Root {
+    fn raise(&self) {
+        if let Some(window) = self.app.get_webview_window("main") {
+            let _ = window.unminimize();
+            let _ = window.set_focus();
+        }
+    }
+
+    fn quit(&self) {
+        self.app.exit(0);
+    }
+
+    #[zbus(property)]
+    fn can_quit(&self) -> bool {
+        true
+    }
+
+    #[zbus(property)]
+    fn can_raise(&self) -> bool {
+        true
+    }
+
+    #[zbus(property)]
+    fn has_track_list(&self) -> bool {
+        false
+    }
+
+    #[zbus(property)]
+    fn identity(&self) -> String {
+        "RcloneFlix".to_string()
+    }
+
+    #[zbus(property)]
+    fn supported_uri_schemes(&self) -> Vec<String> {
+        Vec::new()
+    }
+
+    #[zbus(property)]
+    fn supported_mime_types(&self) -> Vec<String> {
+        Vec::new()
+    }
+}
+
+/// `org.mpris.MediaPlayer2.Player`: transport controls and now-playing info
+struct MprisPlayer {
+    app: AppHandle,
+    now: Shared,
+    changes: mpsc::UnboundedSender<Change>,
+}
+
+impl MprisPlayer {
+    fn with_vlc(&self, f: impl FnOnce(&VlcManager)) {
+        if let Some(vlc) = self.app.try_state::<VlcManager>() {
+            f(&vlc);
+        }
+    }
+
+    fn step(&self, step: isize) {
+        let app = self.app.clone();
+        tauri::async_runtime::spawn(async move {
+            let Some(vlc) = app.try_state::<VlcManager>() else {
+                return;
+            };
+            // No-op when there is no queue to move through
+            if let Err(e) = player::play_queue_step(&app, &vlc, step).await {
+                let _ = app.emit("vlc:error", serde_json::json!({ "message": e.to_string() }));
+            }
+        });
+    }
+}
+
+#[interface(name = "org.mpris.MediaPlayer2.Player")]
+impl MprisPlayer {
+    fn play(&self) {
+        self.with_vlc(player::play);
+    }
+
+    fn pause(&self) {
+        self.with_vlc(player::pause);
+    }
+
+    fn play_pause(&self) {
+        self.with_vlc(player::toggle_pause);
+    }
+
+    fn stop(&self) {
+        self.with_vlc(|vlc| player::stop_playback(&self.app, vlc));
+        // A stopped player reports the same `vlc:state` as a paused one
+        self.now.lock().unwrap().status = Status::Stopped;
+        let _ = self.changes.send(Change::Status);
+    }
+
+    fn next(&self) {
+        self.step(1);
+    }
+
+    fn previous(&self) {
+        self.step(-1);
+    }
+
+    /// `offset` is in microseconds
+    fn seek(&self, offset: i64) {
+        self.with_vlc(|vlc| player::seek_relative(vlc, offset / 1000));
+    }
+
+    /// Ignored for a stale track id or a position past the end, per the spec
+    fn set_position(&self, track_id: ObjectPath<'_>, position: i64) {
+        let length_us = self.now.lock().unwrap().length_us;
+        if track_id.as_str() != TRACK_ID || position < 0 || (length_us > 0 && position > length_us) {
+            return;
+        }
+        self.with_vlc(|vlc| player::seek(vlc, position / 1000));
+    }
+
+    fn open_uri(&self, _uri: String) -> fdo::Result<()> {
+        Err(fdo::Error::NotSupported("Opening URIs is not supported".to_string()))
+    }
+
+    #[zbus(signal)]
+    async fn seeked(ctxt: &SignalContext<'_>, position: i64) -> zbus::Result<()>;
+
+    #[zbus(property)]
+    fn playback_status(&self) -> String {
+        self.now.lock().unwrap().status.as_str().to_string()
+    }
+
+    #[zbus(property)]
+    fn rate(&self) -> f64 {
+        1.0
+    }
+
+    #[zbus(property)]
+    fn minimum_rate(&self) -> f64 {
+        1.0
+    }
+
+    #[zbus(property)]
+    fn maximum_rate(&self) -> f64 {
+        1.0
+    }
+
+    #[zbus(property)]
+    fn metadata(&self) -> HashMap<String, Value<'static>> {
+        let now = self.now.lock().unwrap();
+        let mut metadata = HashMap::new();
+        if now.status == Status::Stopped && now.title.is_empty() {
+            metadata.insert(
+                "mpris:trackid".to_string(),
+                Value::from(ObjectPath::from_static_str_unchecked(NO_TRACK)),
+            );
+            return metadata;
+        }
+        metadata.insert(
+            "mpris:trackid".to_string(),
+            Value::from(ObjectPath::from_static_str_unchecked(TRACK_ID)),
+        );
+        if now.length_us > 0 {
+            metadata.insert("mpris:length".to_string(), Value::from(now.length_us));
+        }
+        if !now.title.is_empty() {
+            metadata.insert("xesam:title".to_string(), Value::from(now.title.clone()));
+        }
+        if !now.artist.is_empty() {
+            metadata.insert("xesam:artist".to_string(), Value::from(vec![now.artist.clone()]));
+        }
+        if !now.album.is_empty() {
+            metadata.insert("xesam:album".to_string(), Value::from(now.album.clone()));
+        }
+        if !now.art_url.is_empty() {
+            metadata.insert("mpris:artUrl".to_string(), Value::from(now.art_url.clone()));
+        }
+        metadata
+    }
+
+    #[zbus(property)]
+    fn volume(&self) -> f64 {
+        self.now.lock().unwrap().volume
+    }
+
+    #[zbus(property)]
+    fn set_volume(&mut self, volume: f64) {
+        let volume = volume.clamp(0.0, 1.0);
+        self.now.lock().unwrap().volume = volume;
+        self.with_vlc(|vlc| player::set_volume(vlc, (volume * 100.0).round() as i32));
+    }
+
+    /// Clients poll this; jumps are announced through `Seeked` instead
+    #[zbus(property(emits_changed_signal = "false"))]
+    fn position(&self) -> i64 {
+        self.now.lock().unwrap().position_us
+    }
+
+    #[zbus(property)]
+    fn can_go_next(&self) -> bool {
+        true
+    }
+
+    #[zbus(property)]
+    fn can_go_previous(&self) -> bool {
+        true
+    }
+
+    #[zbus(property)]
+    fn can_play(&self) -> bool {
+        true
+    }
+
+    #[zbus(property)]
+    fn can_pause(&self) -> bool {
+        true
+    }
+
+    #[zbus(property)]
+    fn can_seek(&self) -> bool {
+        true
+    }
+
+    #[zbus(property(emits_changed_signal = "const"))]
+    fn can_control(&self) -> bool {
+        true
+    }
+}
@@ -0,0 +1,406 @@
+//! Provider-agnostic OAuth 2.0 for installed apps: authorization code flow with
+//! PKCE and `state`, captured by a one-shot loopback HTTP server.
+
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use rand::RngCore;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::process::Command;
+use std::time::Duration;
+use tauri::{AppHandle, Emitter};
+use crate::error::AppError;
+
+/// Endpoints and client settings for one OAuth provider (Google, Dropbox,
+/// OneDrive, ...).
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct OAuthProvider {
+    /// Display name for sign-in error pages, e.g. "Dropbox"
+    #[serde(default)]
+    pub name: Option<String>,
+    pub auth_url: String,
+    pub token_url: String,
+    pub client_id: String,
+    /// Only for providers that require one even with PKCE
+    #[serde(default)]
+    pub client_secret: Option<String>,
+    #[serde(default)]
+    pub scopes: Vec<String>,
+    /// Loopback port for the redirect (`http://localhost:{port}/oauth/callback`)
+    pub port: u16,
+    /// Provider-specific auth URL parameters, e.g. `access_type=offline` for
+    /// Google or `token_access_type=offline` for Dropbox
+    #[serde(default)]
+    pub extra_params: HashMap<String, String>,
+}
+
+impl OAuthProvider {
+    fn redirect_uri(&self) -> String {
+        format!("http://localhost:{}/oauth/callback", self.port)
+    }
+}
+
+/// Tokens from a code exchange. `expires_at` is ms since epoch (0 if unknown).
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct OAuthTokens {
+    pub access_token: String,
+    pub refresh_token: String,
+    pub expires_at: u64,
+}
+
+/// PKCE code verifier: 32 random bytes, base64url without padding (43 chars).
+fn generate_code_verifier() -> String {
+    let mut bytes = [0u8; 32];
+    rand::rngs::OsRng.fill_bytes(&mut bytes);
+    URL_SAFE_NO_PAD.encode(bytes)
+}
+
+/// Random `state` value tying the callback to this sign-in attempt (login CSRF).
+fn generate_state() -> String {
+    let mut bytes = [0u8; 16];
+    rand::rngs::OsRng.fill_bytes(&mut bytes);
+    URL_SAFE_NO_PAD.encode(bytes)
+}
+
+/// S256 challenge for a verifier: base64url(SHA-256(verifier)).
+fn code_challenge(verifier: &str) -> String {
+    URL_SAFE_NO_PAD.encode(Sha256::digest(verifier.as_bytes()))
+}
+
+/// Replace (or add) a query parameter on a URL. `value` must already be URL-safe.
+fn set_query_param(url: &str, key: &str, value: &str) -> String {
+    let (base, query) = url.split_once('?').unwrap_or((url, ""));
+    let mut params: Vec<&str> = query
+        .split('&')
+        .filter(|p| !p.is_empty() && p.split('=').next() != Some(key))
+        .collect();
+    let param = format!("{}={}", key, value);
+    params.push(&param);
+    format!("{}?{}", base, params.join("&"))
+}
+
+/// Percent-encode a query value (RFC 3986 unreserved characters pass through).
+fn encode_component(value: &str) -> String {
+    let mut out = String::with_capacity(value.len());
+    for b in value.bytes() {
+        if b.is_ascii_alphanumeric() || matches!(b, b'-' | b'.' | b'_' | b'~') {
+            out.push(b as char);
+        } else {
+            out.push_str(&format!("%{:02X}", b));
+        }
+    }
+    out
+}
+
+/// Add PKCE and `state` to `auth_url`, open it in the system browser and start
+/// a local HTTP server on `port` to capture the redirect callback. Returns the
+/// PKCE code verifier for the token exchange.
+/// Callbacks that don't echo `state` back are rejected with a 400 and an
+/// "oauth-error" event; a valid one emits "oauth-callback" with the code.
+/// `provider_name` names the provider on the error page of a failed sign-in.
+/// The server times out after 5 minutes to prevent indefinite resource usage.
+pub(crate) async fn run_loopback_flow(
+    app: &AppHandle,
+    provider_name: &str,
+    auth_url: &str,
+    port: u16,
+) -> Result<String, String> {
+    let verifier = generate_code_verifier();
+    let auth_url = set_query_param(auth_url, "code_challenge", &code_challenge(&verifier));
+    let auth_url = set_query_param(&auth_url, "code_challenge_method", "S256");
+    let state = generate_state();
+    let auth_url = set_query_param(&auth_url, "state", &state);
+
+    // Open browser
+    #[cfg(target_os = "linux")]
+    Command::new("xdg-open").arg(&auth_url).spawn()
+        .map_err(|e| format!("Failed to open browser: {}", e))?;
+    #[cfg(target_os = "macos")]
+    Command::new("open").arg(&auth_url).spawn()
+        .map_err(|e| format!("Failed to open browser: {}", e))?;
+    #[cfg(target_os = "windows")]
+    Command::new("cmd").args(["/C", "start", &auth_url]).spawn()
+        .map_err(|e| format!("Failed to open browser: {}", e))?;
+
+    // Spawn local HTTP server to capture callback
+    let app_clone = app.clone();
+    let provider_name = provider_name.to_string();
+    tokio::spawn(async move {
+        let addr = format!("127.0.0.1:{}", port);
+        let listener = match tokio::net::TcpListener::bind(&addr).await {
+            Ok(l) => l,
+            Err(e) => {
+                eprintln!("Failed to bind OAuth callback server: {}", e);
+                let _ = app_clone.emit("oauth-error", serde_json::json!({ 
+                    "error": format!("Failed to bind callback server: {}", e) 
+                }));
+                return;
+            }
+        };
+
+        // Accept one connection with timeout (5 minutes)
+        let accept_future = listener.accept();
+        let timeout = Duration::from_secs(300);
+        
+        match tokio::time::timeout(timeout, accept_future).await {
+            Ok(Ok((mut stream, _))) => {
+                use tokio::io::{AsyncReadExt, AsyncWriteExt};
+                let mut buf = vec![0u8; 4096];
+                if let Ok(n) = stream.read(&mut buf).await {
+                    let request = String::from_utf8_lossy(&buf[..n]);
+                    // Parse ?code=xxx from GET /oauth/callback?code=xxx
+                    if extract_param(&request, "state").as_deref() != Some(state.as_str()) {
+                        let response = "HTTP/1.1 400 Bad Request\r\n\r\nInvalid state parameter";
+                        let _ = stream.write_all(response.as_bytes()).await;
+                        let _ = app_clone.emit("oauth-error", serde_json::json!({
+                            "error": "OAuth state mismatch - please try signing in again"
+                        }));
+                    } else if let Some(code) = extract_code(&request) {
+                        // Send success response to browser
+                        let response = "HTTP/1.1 200 OK\r\nContent-Type: text/html\r\n\r\
+                            <html><body style='font-family:sans-serif;text-align:center;padding:60px'>\
+                            <h2>✓ Signed in successfully</h2>\
+                            <p>You can close this tab and return to RcloneFlix.</p>\
+                            </body></html>";
+                        let _ = stream.write_all(response.as_bytes()).await;
+
+                        // Emit event to frontend
+                        let _ = app_clone.emit("oauth-callback", serde_json::json!({ "code": code }));
+                    } else if let Some(error) = extract_error(&request) {
+                        // Providers redirect with ?error=access_denied when the user cancels
+                        let response = error_page(&friendly_error(&provider_name, &error));
+                        let _ = stream.write_all(response.as_bytes()).await;
+                        let _ = app_clone.emit("oauth-error", serde_json::json!({ "error": error }));
+                    } else {
+                        let response = "HTTP/1.1 400 Bad Request\r\n\r\nMissing code parameter";
+                        let _ = stream.write_all(response.as_bytes()).await;
+                        let _ = app_clone.emit("oauth-error", serde_json::json!({ 
+                            "error": "Authorization code not received" 
+                        }));
+                    }
+                }
+            }
+            Ok(Err(e)) => {
+                eprintln!("OAuth callback accept error: {}", e);
+                let _ = app_clone.emit("oauth-error", serde_json::json!({ 
+                    "error": format!("Connection error: {}", e) 
+                }));
+            }
+            Err(_) => {
+                // Timeout
+                let _ = app_clone.emit("oauth-error", serde_json::json!({ 
+                    "error": "OAuth timeout - please try again" 
+                }));
+            }
+        }
+    });
+
+    Ok(verifier)
+}
+
+/// Human-readable text for the standard OAuth error codes from `provider_name`
+fn friendly_error(provider_name: &str, error: &str) -> String {
+    match error {
+        "access_denied" => "Sign-in was cancelled.".to_string(),
+        "admin_policy_enforced" => "Your organisation doesn't allow signing in to this app.".to_string(),
+        "invalid_scope" | "unauthorized_client" | "invalid_request" => {
+            format!("The sign-in request was rejected by {}.", provider_name)
+        }
+        _ => format!("{} couldn't complete the sign-in.", provider_name),
+    }
+}
+
+/// Browser response for a failed sign-in
+fn error_page(message: &str) -> String {
+    format!(
+        "HTTP/1.1 400 Bad Request\r\nContent-Type: text/html; charset=utf-8\r\n\r\n\
+         <html><body style='font-family:sans-serif;text-align:center;padding:60px'>\
+         <h2>Sign-in failed</h2>\
+         <p>{}</p>\
+         <p>You can close this tab and try again from RcloneFlix.</p>\
+         </body></html>",
+        message
+    )
+}
+
+/// Decode a query-string value: "%XX" escapes and '+' for space. Malformed
+/// escapes are kept as-is.
+fn percent_decode(value: &str) -> String {
+    let bytes = value.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'+' => out.push(b' '),
+            b'%' if i + 2 < bytes.len()
+                && bytes[i + 1].is_ascii_hexdigit()
+                && bytes[i + 2].is_ascii_hexdigit() =>
+            {
+                let hex = std::str::from_utf8(&bytes[i + 1..i + 3]).unwrap();
+                out.push(u8::from_str_radix(hex, 16).unwrap());
+                i += 3;
+                continue;
+            }
+            b => out.push(b),
+        }
+        i += 1;
+    }
+    String::from_utf8_lossy(&out).into_owned()
+}
+
+/// Decoded value of a query parameter in the callback request line
+fn extract_param(request: &str, name: &str) -> Option<String> {
+    // GET /oauth/callback?code=xxxx&state=yyyy HTTP/1.1
+    let line = request.lines().next()?;
+    let path = line.split_whitespace().nth(1)?;
+    let query = path.split('?').nth(1)?;
+    for param in query.split('&') {
+        let mut parts = param.splitn(2, '=');
+        if parts.next() == Some(name) {
+            return parts.next().map(percent_decode);
+        }
+    }
+    None
+}
+
+fn extract_code(request: &str) -> Option<String> {
+    extract_param(request, "code")
+}
+
+/// Extract OAuth error from request query string
+fn extract_error(request: &str) -> Option<String> {
+    extract_param(request, "error")
+}
+
+/// Current time in ms since epoch, for `expires_at` fields
+pub(crate) fn now_ms() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(0)
+}
+
+/// POST a form to a token endpoint. Returns the HTTP status and the JSON body
+/// (Null when the body isn't JSON) so callers can inspect `error` codes.
+pub(crate) async fn post_token_request(
+    token_url: &str,
+    form: &[(&str, String)],
+) -> Result<(u16, serde_json::Value), String> {
+    let resp = tauri_plugin_http::reqwest::Client::new()
+        .post(token_url)
+        .form(form)
+        .timeout(Duration::from_secs(30))
+        .send()
+        .await
+        .map_err(|e| format!("Token request failed: {}", e))?;
+    let status = resp.status().as_u16();
+    let body = resp
+        .text()
+        .await
+        .ok()
+        .and_then(|t| serde_json::from_str(&t).ok())
+        .unwrap_or_default();
+    Ok((status, body))
+}
+
+/// Start sign-in with any OAuth provider: builds the authorization URL from the
+/// provider config and runs the loopback flow. Returns the PKCE code verifier
+/// to pass to `exchange_oauth_code`.
+#[tauri::command]
+pub async fn start_oauth(app: AppHandle, provider: OAuthProvider) -> Result<String, AppError> {
+    let mut params = vec![
+        ("client_id".to_string(), provider.client_id.clone()),
+        ("redirect_uri".to_string(), provider.redirect_uri()),
+        ("response_type".to_string(), "code".to_string()),
+    ];
+    if !provider.scopes.is_empty() {
+        params.push(("scope".to_string(), provider.scopes.join(" ")));
+    }
+    params.extend(provider.extra_params.iter().map(|(k, v)| (k.clone(), v.clone())));
+    let query = params
+        .iter()
+        .map(|(k, v)| format!("{}={}", encode_component(k), encode_component(v)))
+        .collect::<Vec<_>>()
+        .join("&");
+    let separator = if provider.auth_url.contains('?') { '&' } else { '?' };
+    let auth_url = format!("{}{}{}", provider.auth_url, separator, query);
+
+    let provider_name = provider.name.as_deref().unwrap_or("The provider");
+    Ok(run_loopback_flow(&app, provider_name, &auth_url, provider.port).await?)
+}
+
+/// Exchange the code from "oauth-callback" for tokens at the provider's token URL.
+#[tauri::command]
+pub async fn exchange_oauth_code(
+    provider: OAuthProvider,
+    code: String,
+    code_verifier: String,
+) -> Result<OAuthTokens, AppError> {
+    let mut form = vec![
+        ("grant_type", "authorization_code".to_string()),
+        ("code", code),
+        ("code_verifier", code_verifier),
+        ("client_id", provider.client_id.clone()),
+        ("redirect_uri", provider.redirect_uri()),
+    ];
+    if let Some(secret) = provider.client_secret.clone().filter(|s| !s.is_empty()) {
+        form.push(("client_secret", secret));
+    }
+
+    let (status, body) = post_token_request(&provider.token_url, &form).await?;
+    if !(200..300).contains(&status) {
+        let error = body["error"].as_str().unwrap_or("unknown_error");
+        return Err(format!("Token exchange failed ({}): {}", status, error).into());
+    }
+    let access_token = body["access_token"]
+        .as_str()
+        .ok_or_else(|| "Token response missing access_token".to_string())?;
+    Ok(OAuthTokens {
+        access_token: access_token.to_string(),
+        refresh_token: body["refresh_token"].as_str().unwrap_or_default().to_string(),
+        expires_at: body["expires_in"]
+            .as_u64()
+            .map(|secs| now_ms() + secs * 1000)
+            .unwrap_or(0),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn percent_decode_handles_escapes_and_plus() {
+        assert_eq!(percent_decode("4%2F0AX4XfWh"), "4/0AX4XfWh");
+        assert_eq!(percent_decode("a+b%20c"), "a b c");
+        assert_eq!(percent_decode("caf%C3%A9"), "café");
+        // Malformed escapes are kept
+        assert_eq!(percent_decode("100%"), "100%");
+        assert_eq!(percent_decode("%zz%2"), "%zz%2");
+    }
+
+    #[test]
+    fn callback_code_is_decoded() {
+        let request = "GET /oauth/callback?state=abc&code=4%2F0AX4Xf+Wh%2Bq&scope=drive HTTP/1.1\r\nHost: localhost\r\n\r\n";
+        assert_eq!(extract_code(request).as_deref(), Some("4/0AX4Xf Wh+q"));
+        assert_eq!(extract_param(request, "state").as_deref(), Some("abc"));
+        assert_eq!(extract_param(request, "scope").as_deref(), Some("drive"));
+    }
+
+    #[test]
+    fn missing_parameter_is_none() {
+        let request = "GET /oauth/callback?error=access_denied&state=abc HTTP/1.1\r\n\r\n";
+        assert_eq!(extract_code(request), None);
+        assert_eq!(extract_error(request).as_deref(), Some("access_denied"));
+        assert_eq!(extract_code("GET /oauth/callback HTTP/1.1\r\n\r\n"), None);
+        assert_eq!(extract_code(""), None);
+    }
+
+    #[test]
+    fn friendly_error_names_the_provider() {
+        assert_eq!(friendly_error("Dropbox", "invalid_scope"), "The sign-in request was rejected by Dropbox.");
+        assert_eq!(friendly_error("OneDrive", "server_error"), "OneDrive couldn't complete the sign-in.");
+        assert_eq!(friendly_error("Dropbox", "access_denied"), "Sign-in was cancelled.");
+    }
+}
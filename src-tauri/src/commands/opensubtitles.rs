@@ -0,0 +1,184 @@
+use serde::Serialize;
+use std::path::PathBuf;
+use std::time::Duration;
+use tauri::AppHandle;
+
+use crate::commands::store;
+use crate::error::AppError;
+
+const API_BASE: &str = "https://api.opensubtitles.com/api/v1";
+/// OpenSubtitles rejects requests without an identifying User-Agent
+const USER_AGENT: &str = concat!("rcloneflix v", env!("CARGO_PKG_VERSION"));
+
+/// One subtitle file from `opensubtitles_search`
+#[derive(Debug, Serialize, Clone)]
+pub struct SubtitleSearchResult {
+    /// Pass to `opensubtitles_download`
+    pub file_id: u64,
+    pub file_name: String,
+    /// ISO 639-1 code, e.g. "en"
+    pub language: String,
+    /// Release name the subtitle was timed against
+    pub release: String,
+    pub title: String,
+    pub year: Option<u32>,
+    pub download_count: u64,
+    pub hearing_impaired: bool,
+    pub rating: f64,
+}
+
+fn client_request(
+    method: tauri_plugin_http::reqwest::Method,
+    url: &str,
+    key: &str,
+) -> tauri_plugin_http::reqwest::RequestBuilder {
+    tauri_plugin_http::reqwest::Client::new()
+        .request(method, url)
+        .header("Api-Key", key)
+        .header("User-Agent", USER_AGENT)
+        .header("Accept", "application/json")
+        .timeout(Duration::from_secs(30))
+}
+
+fn api_key(app: &AppHandle) -> Result<String, String> {
+    store::api_key(app, "opensubtitles_key")?
+        .ok_or_else(|| "No OpenSubtitles API key configured".to_string())
+}
+
+async fn read_json(
+    resp: tauri_plugin_http::reqwest::Response,
+) -> Result<(u16, serde_json::Value, String), String> {
+    let status = resp.status().as_u16();
+    let text = resp
+        .text()
+        .await
+        .map_err(|e| format!("Failed to read OpenSubtitles response: {}", e))?;
+    let body = serde_json::from_str(&text).unwrap_or_default();
+    Ok((status, body, text))
+}
+
+/// Search subtitles by free-text `query` (usually the title) and/or `imdb_id`
+/// ("tt0133093" or "0133093"). `language` is a comma-separated list of ISO
+/// 639-1 codes, e.g. "en,de".
+#[tauri::command]
+pub async fn opensubtitles_search(
+    app: AppHandle,
+    query: Option<String>,
+    language: Option<String>,
+    imdb_id: Option<String>,
+) -> Result<Vec<SubtitleSearchResult>, AppError> {
+    let key = api_key(&app)?;
+    let mut params = Vec::new();
+    if let Some(query) = query.map(|q| q.trim().to_string()).filter(|q| !q.is_empty()) {
+        params.push(("query", query));
+    }
+    if let Some(imdb) = imdb_id {
+        let digits = imdb.trim().trim_start_matches("tt").trim_start_matches('0').to_string();
+        if !digits.is_empty() {
+            params.push(("imdb_id", digits));
+        }
+    }
+    if params.is_empty() {
+        return Err(AppError::invalid("Search needs a query or an IMDb id"));
+    }
+    if let Some(language) = language.map(|l| l.trim().to_lowercase()).filter(|l| !l.is_empty()) {
+        params.push(("languages", language));
+    }
+
+    let resp = client_request(
+        tauri_plugin_http::reqwest::Method::GET,
+        &format!("{}/subtitles", API_BASE),
+        &key,
+    )
+    .query(&params)
+    .send()
+    .await
+    .map_err(|e| format!("OpenSubtitles search failed: {}", e))?;
+    let (status, body, text) = read_json(resp).await?;
+    if !(200..300).contains(&status) {
+        let message = body["message"].as_str().unwrap_or(text.as_str());
+        return Err(format!("OpenSubtitles search failed ({}): {}", status, message).into());
+    }
+
+    let mut results = Vec::new();
+    for entry in body["data"].as_array().into_iter().flatten() {
+        let attrs = &entry["attributes"];
+        let feature = &attrs["feature_details"];
+        // One result per file; multi-CD subtitles list several
+        for file in attrs["files"].as_array().into_iter().flatten() {
+            let Some(file_id) = file["file_id"].as_u64() else {
+                continue;
+            };
+            results.push(SubtitleSearchResult {
+                file_id,
+                file_name: file["file_name"].as_str().unwrap_or_default().to_string(),
+                language: attrs["language"].as_str().unwrap_or_default().to_string(),
+                release: attrs["release"].as_str().unwrap_or_default().to_string(),
+                title: feature["title"].as_str().unwrap_or_default().to_string(),
+                year: feature["year"].as_u64().map(|y| y as u32),
+                download_count: attrs["download_count"].as_u64().unwrap_or_default(),
+                hearing_impaired: attrs["hearing_impaired"].as_bool().unwrap_or_default(),
+                rating: attrs["ratings"].as_f64().unwrap_or_default(),
+            });
+        }
+    }
+    Ok(results)
+}
+
+/// Download subtitle file `file_id` as .srt into the temp directory and return
+/// its path, ready for `player_add_subtitle`. Each download counts against the
+/// account's daily quota.
+#[tauri::command]
+pub async fn opensubtitles_download(app: AppHandle, file_id: u64) -> Result<String, AppError> {
+    let key = api_key(&app)?;
+    let resp = client_request(
+        tauri_plugin_http::reqwest::Method::POST,
+        &format!("{}/download", API_BASE),
+        &key,
+    )
+    .header("Content-Type", "application/json")
+    .body(serde_json::json!({ "file_id": file_id, "sub_format": "srt" }).to_string())
+    .send()
+    .await
+    .map_err(|e| format!("OpenSubtitles download failed: {}", e))?;
+    let (status, body, text) = read_json(resp).await?;
+
+    // Quota exhausted: 406 with the reset time (older responses used 429)
+    if status == 406 || status == 429 || body["remaining"].as_i64().is_some_and(|r| r < 0) {
+        let reset = body["reset_time"].as_str().unwrap_or("in 24 hours");
+        return Err(format!(
+            "OpenSubtitles download quota reached; it resets {}",
+            reset
+        ).into());
+    }
+    if !(200..300).contains(&status) {
+        let message = body["message"].as_str().unwrap_or(text.as_str());
+        return Err(format!("OpenSubtitles download failed ({}): {}", status, message).into());
+    }
+    let link = body["link"]
+        .as_str()
+        .ok_or_else(|| "OpenSubtitles response missing download link".to_string())?;
+
+    let resp = tauri_plugin_http::reqwest::Client::new()
+        .get(link)
+        .header("User-Agent", USER_AGENT)
+        .timeout(Duration::from_secs(60))
+        .send()
+        .await
+        .map_err(|e| format!("Subtitle download failed: {}", e))?;
+    if !resp.status().is_success() {
+        return Err(format!("Subtitle download failed ({})", resp.status().as_u16()).into());
+    }
+    let bytes = resp
+        .bytes()
+        .await
+        .map_err(|e| format!("Subtitle download failed: {}", e))?;
+
+    let dir: PathBuf = std::env::temp_dir().join("rcloneflix-subtitles");
+    std::fs::create_dir_all(&dir)
+        .map_err(|e| format!("Failed to create {}: {}", dir.display(), e))?;
+    let path = dir.join(format!("{}.srt", file_id));
+    std::fs::write(&path, &bytes)
+        .map_err(|e| format!("Failed to write {}: {}", path.display(), e))?;
+    Ok(path.to_string_lossy().into_owned())
+}
@@ -1,54 +1,987 @@
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{HashMap, VecDeque};
 use std::path::PathBuf;
 use std::process::{Child, Command};
-use std::sync::{mpsc, Mutex};
+use std::sync::atomic::{AtomicU8, Ordering};
+use std::sync::{mpsc, Arc, Mutex};
 use std::thread;
-use std::time::Duration;
+use std::time::{Duration, Instant};
 use tokio::process::Command as TokioCommand;
+use tokio::sync::{oneshot, watch};
 use tauri::{AppHandle, Emitter, Manager, State};
+use tauri_plugin_store::StoreExt;
 use vlc::MediaPlayerAudioEx;
 
+use crate::commands::cast::{RendererDiscovery, RendererInfo};
+use crate::commands::segments::{self, SegmentKind, SkipSegment};
+use crate::commands::{discord, downloads, prefetch, progress, rclone, scan, store, storyboard, subtitles, temp_cache, tracks, transcode};
+use crate::power::{self, InhibitKind};
+use crate::error::AppError;
+
 // ── VLC thread command ────────────────────────────────────────────────────────
 
 enum VlcCmd {
-    Open { url: String, start_ms: i64 },
+    /// `item_id` keys the watch-progress store; None skips progress tracking.
+    /// `series_key` groups files whose track choices should carry over.
+    /// `audio_only` opens the media without video output.
+    Open {
+        url: String,
+        start_ms: i64,
+        item_id: Option<String>,
+        series_key: Option<String>,
+        audio_only: bool,
+    },
     Play,
     Pause,
+    /// Pause if playing, otherwise play (decided on the VLC thread from real state)
+    TogglePause,
     Stop,
     Seek(i64),      // ms
+    SeekRelative(i64), // ms, applied to VLC's current time
+    /// Seek to a fraction (0-1) of the media; works before the duration is known
+    SeekFraction(f32),
     SetVolume(i32), // 0-100
-    #[cfg(target_os = "linux")]
-    SetWindow(u32), // X11 drawable XID
+    SetMute(bool),
+    /// Video scale factor; 0 fits the window (libVLC's convention)
+    SetScale(f32),
+    /// Crop geometry "WxH+X+Y" to zoom into a region; None shows the whole picture
+    SetZoomRect(Option<String>),
+    /// The stored subtitle encoding changed: load the current media's subtitles
+    /// again so their decoders pick it up
+    ReloadSubtitles,
+    SetWindow(NativeWindow),
+    /// Pre-create the next queue item's media so it can be swapped in at end of track
+    Preload {
+        url: String,
+        index: usize,
+        item_id: String,
+        series_key: Option<String>,
+        audio_only: bool,
+    },
+    /// Select tracks by VLC track id (-1 disables subtitles) and remember the
+    /// chosen language for the current series
+    SetAudioTrack(i32),
+    SetSubtitleTrack(i32),
+    /// Load an external subtitle file into the current media and select it
+    AddSubtitle(String),
+    /// Whether audio-only playback also keeps the screen on (default: suspend only)
+    SetAudioKeepsScreenOn(bool),
+    /// Snapshot the player state and send it back on the oneshot channel
+    GetStatus(oneshot::Sender<PlayerStatus>),
+    /// Reply whether nothing is loaded on the main player and neither a
+    /// crossfade nor a preview is playing
+    IsIdle(oneshot::Sender<bool>),
+    /// Send back the buffered libVLC log lines, oldest first
+    GetLogs(oneshot::Sender<Vec<LogLine>>),
+    /// Start renderer discovery (if not running) and reply with the renderers known so far
+    DiscoverRenderers(oneshot::Sender<Result<Vec<RendererInfo>, String>>),
+    /// Cast to a discovered renderer, or back to local output with None.
+    /// `url` replaces the current media's source when its serve process was
+    /// rebound for the change.
+    SetRenderer {
+        id: Option<String>,
+        url: Option<String>,
+        reply: oneshot::Sender<Result<(), String>>,
+    },
+    /// Forget the preloaded next item (the queue order it was chosen from changed)
+    DropPreload,
+    /// Overlap between consecutive audio-only queue items; zero is a hard cut
+    SetCrossfade(Duration),
+    /// Fraction of the duration (0-1] after which `player:watched` fires
+    SetWatchedThreshold(f64),
+    /// Pause after `after` (None disarms), or with `finish_chapter` at the end
+    /// of the chapter playing by then
+    SetSleepTimer { after: Option<Duration>, finish_chapter: bool },
+    GetSleepTimer(oneshot::Sender<Option<SleepTimerStatus>>),
+    /// The stored skip segments changed: look them up again for the loaded media
+    ReloadSkipSegments,
+    /// Segment kinds skipped without asking
+    SetAutoSkip(Vec<SegmentKind>),
+    /// Seek past the skip segment playback is in; replies with it, or None
+    SkipSegment(oneshot::Sender<Option<SkipSegment>>),
+    /// Minimum libVLC log level kept in the buffer (see `LogBuffer`)
+    SetLogLevel(u8),
+    /// Play `url` on the preview player, audio only, from `start_ms`
+    PreviewOpen { url: String, start_ms: i64 },
+    PreviewStop,
+    PreviewSetVolume(i32), // 0-100
+    /// Sent from libVLC's event callbacks: the player changed state or time, so
+    /// re-read it
+    PlayerEvent,
+    /// How long playback time may stand still before the stream counts as
+    /// stalled; zero disables stall detection
+    SetStallTimeout(Duration),
+    /// Reopen the current media at the last known position after a stall.
+    /// `manual` (from `player_retry_stream`) resets the automatic retry count.
+    Reconnect { manual: bool },
     Shutdown,
 }
 
+// ── libVLC log capture ────────────────────────────────────────────────────────
+
+/// How many libVLC log lines are kept for `get_player_logs`
+const LOG_CAPACITY: usize = 500;
+/// How many recent error lines are attached to `vlc:error`
+const ERROR_LOG_LINES: usize = 5;
+
+const LOG_DEBUG: u8 = 0;
+const LOG_NOTICE: u8 = 1;
+const LOG_WARNING: u8 = 2;
+const LOG_ERROR: u8 = 3;
+
+#[derive(Debug, Serialize, Clone)]
+pub struct LogLine {
+    /// "debug", "notice", "warning" or "error"
+    pub level: &'static str,
+    pub message: String,
+}
+
+/// Bounded ring of recent libVLC log lines. The log callback runs on libVLC's
+/// own threads, so everything here is behind a lock or atomic.
+struct LogBuffer {
+    lines: Mutex<VecDeque<(u8, String)>>,
+    min_level: AtomicU8,
+}
+
+impl LogBuffer {
+    fn new(min_level: u8) -> Self {
+        LogBuffer {
+            lines: Mutex::new(VecDeque::with_capacity(LOG_CAPACITY)),
+            min_level: AtomicU8::new(min_level),
+        }
+    }
+
+    fn push(&self, level: u8, message: String) {
+        if level < self.min_level.load(Ordering::Relaxed) {
+            return;
+        }
+        if let Ok(mut lines) = self.lines.lock() {
+            if lines.len() == LOG_CAPACITY {
+                lines.pop_front();
+            }
+            lines.push_back((level, message));
+        }
+    }
+
+    fn snapshot(&self) -> Vec<LogLine> {
+        let Ok(lines) = self.lines.lock() else {
+            return Vec::new();
+        };
+        lines
+            .iter()
+            .map(|(level, message)| LogLine {
+                level: log_level_name(*level),
+                message: message.clone(),
+            })
+            .collect()
+    }
+
+    /// The last few error-level messages, oldest first
+    fn recent_errors(&self) -> Vec<String> {
+        let Ok(lines) = self.lines.lock() else {
+            return Vec::new();
+        };
+        let mut errors: Vec<String> = lines
+            .iter()
+            .rev()
+            .filter(|(level, _)| *level == LOG_ERROR)
+            .take(ERROR_LOG_LINES)
+            .map(|(_, message)| message.clone())
+            .collect();
+        errors.reverse();
+        errors
+    }
+}
+
+fn log_level_name(level: u8) -> &'static str {
+    match level {
+        LOG_DEBUG => "debug",
+        LOG_NOTICE => "notice",
+        LOG_WARNING => "warning",
+        _ => "error",
+    }
+}
+
+fn parse_log_level(name: &str) -> Option<u8> {
+    match name.to_lowercase().as_str() {
+        "debug" => Some(LOG_DEBUG),
+        "notice" | "info" => Some(LOG_NOTICE),
+        "warning" | "warn" => Some(LOG_WARNING),
+        "error" => Some(LOG_ERROR),
+        _ => None,
+    }
+}
+
+/// `vlc:error` payload with the most recent libVLC error lines attached, so the
+/// UI can show e.g. "no suitable decoder module for fourcc hevc".
+fn error_payload(logs: &LogBuffer, message: &str) -> serde_json::Value {
+    serde_json::json!({ "message": message, "logs": logs.recent_errors() })
+}
+
+/// Synchronous snapshot of the player, returned by `player_get_status`.
+#[derive(Debug, Serialize, Clone)]
+pub struct PlayerStatus {
+    /// "idle" when nothing has been opened, otherwise VLC's state in lowercase
+    /// ("opening", "buffering", "playing", "paused", "stopped", "ended", "error")
+    pub state: String,
+    pub time_ms: i64,
+    pub duration_ms: i64,
+    pub volume: i32,
+    pub muted: bool,
+    pub rate: f32,
+    /// Selected track ids; None when disabled or not yet known
+    pub audio_track: Option<i32>,
+    pub subtitle_track: Option<i32>,
+    pub repeat: RepeatMode,
+    /// Crossfade between audio-only queue items; 0 when off
+    pub crossfade_ms: u64,
+    /// Watch-progress id of the loaded media, when it has one
+    pub item_id: Option<String>,
+}
+
+impl PlayerStatus {
+    fn idle() -> Self {
+        PlayerStatus {
+            state: "idle".into(),
+            time_ms: 0,
+            duration_ms: 0,
+            volume: DEFAULT_VOLUME,
+            muted: false,
+            rate: 1.0,
+            audio_track: None,
+            subtitle_track: None,
+            repeat: RepeatMode::Off,
+            crossfade_ms: 0,
+            item_id: None,
+        }
+    }
+}
+
+/// Player options live in the app settings store
+const OPTIONS_STORE_PATH: &str = "rcloneflix-keys.json";
+/// Store keys overriding the caching defaults below (milliseconds)
+const NETWORK_CACHING_KEY: &str = "player_network_caching_ms";
+const FILE_CACHING_KEY: &str = "player_file_caching_ms";
+/// HTTP streams need a deep buffer to ride out seeks and slow remotes; FUSE paths
+/// are already cached by the mount, so a short buffer keeps seeking snappy.
+const DEFAULT_NETWORK_CACHING_MS: u64 = 3000;
+const DEFAULT_FILE_CACHING_MS: u64 = 300;
+
+/// A resume seek that hasn't been applied by then (stream never reached Playing)
+/// is dropped with `player:resume-failed`
+const RESUME_SEEK_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Resume seek waiting for playback to start. Tied to the media generation it
+/// was made for, so it can never land on media opened afterwards.
+struct PendingSeek {
+    ms: i64,
+    generation: u64,
+    since: Instant,
+}
+
+/// What to do with a `PendingSeek` on a pass of the VLC loop
+#[derive(Debug, PartialEq)]
+enum ResumeStep {
+    /// Not playing yet; keep it
+    Wait,
+    /// Playing short of the resume point: seek there
+    Seek(i64),
+    /// Already there (`:start-time` worked) or made for older media: drop it
+    Done,
+    /// Never started playing: drop it and report the failed resume
+    TimedOut,
+}
+
+impl PendingSeek {
+    fn new(ms: i64, generation: u64) -> Self {
+        PendingSeek { ms, generation, since: Instant::now() }
+    }
+
+    /// `playing_at` is the playback time once VLC reports Playing.
+    fn step(&self, generation: u64, playing_at: Option<i64>) -> ResumeStep {
+        if self.generation != generation {
+            return ResumeStep::Done;
+        }
+        match playing_at {
+            Some(time_ms) if time_ms < self.ms - 2000 => ResumeStep::Seek(self.ms),
+            Some(_) => ResumeStep::Done,
+            None if self.since.elapsed() > RESUME_SEEK_TIMEOUT => ResumeStep::TimedOut,
+            None => ResumeStep::Wait,
+        }
+    }
+}
+
+/// How often `vlc:time` is emitted while playing. VLC's own time events are much
+/// more frequent; 1 Hz keeps WebKitGTK repaints (which cause flicker) down.
+const TIME_EMIT_INTERVAL: Duration = Duration::from_secs(1);
+/// Longest the VLC thread sleeps without a command or player event, so the 1 Hz
+/// work, stall detection and the resume-seek timeout still run. Shorter while
+/// something is playing or loading.
+const ACTIVE_WAKE_INTERVAL: Duration = Duration::from_millis(250);
+const IDLE_WAKE_INTERVAL: Duration = Duration::from_secs(1);
+
+/// How close to the end of a track the next gapless item is resolved and preloaded.
+const GAPLESS_PRELOAD_MS: i64 = 10_000;
+/// Settings holding the last volume (0-100) and mute state, restored on launch
+const VOLUME_SETTING: &str = "player.volume";
+const MUTED_SETTING: &str = "player.muted";
+/// Volume before the user first sets one
+const DEFAULT_VOLUME: i32 = 80;
+/// How long volume and mute must stay unchanged before they're saved
+const AUDIO_SAVE_DELAY: Duration = Duration::from_secs(1);
+/// Setting holding the crossfade duration in ms
+const CROSSFADE_SETTING: &str = "player.crossfade_ms";
+/// Longest crossfade; leaves time to preload the next item before the fade starts
+const MAX_CROSSFADE_MS: u64 = 8_000;
+/// How often the crossfade volume ramps are updated
+const CROSSFADE_STEP_MS: i64 = 50;
+
+/// Setting: whether opening other media keeps an armed sleep timer (default true)
+const SLEEP_TIMER_KEEP_ON_OPEN_SETTING: &str = "player.sleep_timer_keep_on_open";
+
+/// Seconds playback time may stand still while playing or buffering before the
+/// stream counts as stalled
+const DEFAULT_STALL_TIMEOUT_SECS: u64 = 20;
+/// Setting (see `store::get_setting`) overriding `DEFAULT_STALL_TIMEOUT_SECS`
+const STALL_TIMEOUT_SETTING: &str = "player.stall_timeout_secs";
+/// Automatic reconnects after a stall before giving up with `vlc:error`
+const MAX_STALL_RETRIES: u32 = 3;
+
+/// Setting holding the `Normalization` the VLC instance is created with
+const NORMALIZATION_SETTING: &str = "player.normalization";
+/// Loudness `normvol` is tuned for at its default `--norm-max-level` of 2.0
+const DEFAULT_TARGET_LUFS: f32 = -16.0;
+/// Normalization the running VLC instance was created with
+static INSTANCE_NORMALIZATION: Mutex<Option<Normalization>> = Mutex::new(None);
+
+/// Restarts of a VLC thread that died before giving up with `player:fatal`
+const MAX_THREAD_RESTARTS: u32 = 5;
+/// Wait before the first automatic restart; doubles with each further one
+const THREAD_RESTART_BACKOFF: Duration = Duration::from_millis(500);
+/// A thread that stayed up this long since its restart resets the count
+const THREAD_RESTART_RESET_AFTER: Duration = Duration::from_secs(60);
+/// Item id and position last saved by the VLC thread, reported in
+/// `player:restarted` so the frontend can reopen where playback died
+static LAST_POSITION: Mutex<Option<(String, i64)>> = Mutex::new(None);
+/// How long `open_media` waits for a starting VLC thread before giving up
+const VLC_READY_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Whether a VLC thread created its instance and player: None while it's
+/// starting, the reason when it couldn't
+type VlcReadiness = Option<Result<(), String>>;
+
+/// Session id under which the preloaded next queue item holds its serve process.
+const QUEUE_PRELOAD_SESSION: &str = "queue-preload";
+/// Session id under which the preview player holds its serve process.
+const PREVIEW_SESSION: &str = "preview";
+
 // ── Managed state ─────────────────────────────────────────────────────────────
 
 pub struct VlcManager {
     cmd_tx: Mutex<mpsc::SyncSender<VlcCmd>>,
     /// rclone serve http child process for video (VLC path)
     serve_child: Mutex<Option<Child>>,
-    /// rclone serve http processes for epub/pdf readers, keyed by session id
-    book_sessions: Mutex<HashMap<String, Child>>,
+    /// What `serve_child` serves, so stall recovery can restart it on the same port
+    serve_source: Mutex<Option<ServeSource>>,
+    /// Set while casting: `serve_child` listens on the LAN with these
+    /// credentials so the renderer can reach it
+    cast_serve: Mutex<Option<LanServe>>,
+    /// Shared rclone serve http processes for epub/pdf readers, one per remote root
+    book_sessions: Mutex<ServeProcesses>,
+    /// Playlist for binge-watching / album playback
+    queue: Mutex<PlayQueue>,
+    app: AppHandle,
+    restarts: Mutex<ThreadRestarts>,
+    /// Startup outcome of the current VLC thread; the channel closes when it exits
+    ready: Mutex<watch::Receiver<VlcReadiness>>,
+}
+
+/// Automatic restarts of a VLC thread that panicked or failed to start
+#[derive(Default)]
+struct ThreadRestarts {
+    /// Since the thread last stayed up for `THREAD_RESTART_RESET_AFTER`
+    count: u32,
+    last: Option<Instant>,
+    /// Hit `MAX_THREAD_RESTARTS`; `player:fatal` was emitted and the player
+    /// stays down until the app restarts
+    given_up: bool,
+}
+
+#[derive(Debug, Clone)]
+struct ServeSource {
+    config_path: String,
+    remote_root: String,
+    /// The file being played, relative to `remote_root`
+    file_path: String,
+    options: ServeOptions,
+    port: u16,
+}
+
+/// Where a serve process listens while casting: on this machine's LAN address,
+/// behind HTTP basic auth, so the renderer can fetch from it but nobody else can
+#[derive(Debug, Clone)]
+struct LanServe {
+    host: std::net::IpAddr,
+    user: String,
+    pass: String,
+}
+
+impl LanServe {
+    /// Pick the LAN address and fresh credentials.
+    fn new() -> Result<Self, AppError> {
+        use rand::RngCore;
+
+        // Connecting a UDP socket sends nothing; it only picks the interface
+        // that routes off this machine
+        let host = std::net::UdpSocket::bind("0.0.0.0:0")
+            .and_then(|socket| {
+                socket.connect("8.8.8.8:80")?;
+                socket.local_addr()
+            })
+            .map(|addr| addr.ip())
+            .ok()
+            .filter(|ip| !ip.is_loopback() && !ip.is_unspecified())
+            .ok_or("No network interface to cast from; is this machine on the LAN?")?;
+        let mut bytes = [0u8; 16];
+        rand::rngs::OsRng.fill_bytes(&mut bytes);
+        Ok(LanServe {
+            host,
+            user: "rcloneflix".to_string(),
+            pass: bytes.iter().map(|b| format!("{:02x}", b)).collect(),
+        })
+    }
+}
+
+/// Address a serve process on `port` listens on: the LAN address while casting,
+/// loopback otherwise.
+fn serve_addr(port: u16, lan: Option<&LanServe>) -> std::net::SocketAddr {
+    let host = lan.map_or(std::net::IpAddr::from([127, 0, 0, 1]), |lan| lan.host);
+    std::net::SocketAddr::new(host, port)
+}
+
+/// URL of `file_path` on a serve process started with `spawn_serve(.., port, .., lan)`.
+fn serve_url(port: u16, lan: Option<&LanServe>, file_path: &str) -> String {
+    let encoded = percent_encode_path(file_path.trim_start_matches('/'));
+    match lan {
+        Some(lan) => format!("http://{}:{}@{}/{}", lan.user, lan.pass, serve_addr(port, Some(lan)), encoded),
+        None => format!("http://{}/{}", serve_addr(port, None), encoded),
+    }
+}
+
+/// rclone VFS cache modes accepted for `--vfs-cache-mode`
+const VFS_CACHE_MODES: &[&str] = &["off", "minimal", "writes", "full"];
+
+/// Per-session flags for an rclone serve process. Sessions with different
+/// options get separate servers.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub(crate) struct ServeOptions {
+    /// `--bwlimit`, e.g. "5M"
+    pub bwlimit: Option<String>,
+    /// `--vfs-cache-mode` other than "off"
+    pub vfs_cache_mode: Option<String>,
+    /// `--vfs-cache-max-size`, e.g. "20G"
+    pub vfs_cache_max_size: Option<String>,
+}
+
+impl ServeOptions {
+    /// Check command parameters; empty values and cache mode "off" count as unset.
+    pub(crate) fn new(
+        bwlimit: Option<&str>,
+        vfs_cache_mode: Option<&str>,
+        vfs_cache_max_size: Option<&str>,
+    ) -> Result<Self, AppError> {
+        let bwlimit = validate_bwlimit(bwlimit)?.map(|b| b.to_string());
+        let vfs_cache_mode = match vfs_cache_mode.map(|m| m.trim().to_lowercase()).filter(|m| !m.is_empty()) {
+            Some(mode) if !VFS_CACHE_MODES.contains(&mode.as_str()) => {
+                return Err(AppError::invalid(format!(
+                    "Invalid VFS cache mode: {} (expected one of {})",
+                    mode,
+                    VFS_CACHE_MODES.join(", ")
+                )));
+            }
+            Some(mode) if mode == "off" => None,
+            mode => mode,
+        };
+        let vfs_cache_max_size = match vfs_cache_max_size.map(str::trim).filter(|s| !s.is_empty()) {
+            Some(size) if size.starts_with('-') => {
+                return Err(AppError::invalid(format!("Invalid VFS cache size: {}", size)));
+            }
+            Some(_) if vfs_cache_mode.is_none() => {
+                return Err(AppError::invalid("A VFS cache size needs a VFS cache mode"));
+            }
+            size => size.map(|s| s.to_string()),
+        };
+        Ok(Self { bwlimit, vfs_cache_mode, vfs_cache_max_size })
+    }
+}
+
+/// One entry in the playback queue; same source fields as `open_media`.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct QueueItem {
+    pub remote_root: String,
+    pub file_path: String,
+    /// None resumes from the stored watch progress
+    #[serde(default)]
+    pub start_ms: Option<i64>,
+    /// Series this file belongs to, for remembering audio/subtitle choices
+    #[serde(default)]
+    pub series_key: Option<String>,
+    /// Watch-progress key; defaults to the id the library scanner assigns the file
+    #[serde(default)]
+    pub media_id: Option<String>,
+    /// rclone `--bwlimit` for the serve process, e.g. "5M"; None is unlimited
+    #[serde(default)]
+    pub bwlimit: Option<String>,
+    /// rclone `--vfs-cache-mode` for the serve process; None caches nothing
+    #[serde(default)]
+    pub vfs_cache_mode: Option<String>,
+    /// rclone `--vfs-cache-max-size`, e.g. "20G"
+    #[serde(default)]
+    pub vfs_cache_max_size: Option<String>,
+    /// Play without any video output (music), leaving the UI uncovered
+    #[serde(default)]
+    pub audio_only: bool,
+    /// Display title for desktop integrations (Discord presence)
+    #[serde(default)]
+    pub title: Option<String>,
+    /// `file_path` is a DVD/Blu-ray folder (VIDEO_TS or BDMV inside) or disc image
+    #[serde(default)]
+    pub disc: bool,
+}
+
+impl QueueItem {
+    fn media_id(&self) -> String {
+        self.media_id
+            .clone()
+            .unwrap_or_else(|| progress::item_id_for(&self.remote_root, &self.file_path))
+    }
+
+    fn serve_options(&self) -> Result<ServeOptions, AppError> {
+        ServeOptions::new(
+            self.bwlimit.as_deref(),
+            self.vfs_cache_mode.as_deref(),
+            self.vfs_cache_max_size.as_deref(),
+        )
+    }
+}
+
+/// Next gapless queue item, created ahead of time on the VLC thread.
+struct PreloadedItem {
+    index: usize,
+    media: vlc::Media,
+    item_id: String,
+    series_key: Option<String>,
+    audio_only: bool,
+}
+
+/// Crossfade in progress: the next item plays on the fader player while the
+/// volumes ramp, then the two players trade places.
+struct Crossfade {
+    started: Instant,
+    duration: Duration,
+    /// Volume both ramps work towards; follows `SetVolume` during the fade
+    volume: i32,
+    index: usize,
+    item_id: String,
+    series_key: Option<String>,
+}
+
+impl Crossfade {
+    /// 0 at the start of the fade, 1 once it's over
+    fn progress(&self) -> f32 {
+        (self.started.elapsed().as_secs_f32() / self.duration.as_secs_f32()).min(1.0)
+    }
+}
+
+/// Sleep timer armed by `player_set_sleep_timer`.
+struct SleepTimer {
+    deadline: Instant,
+    finish_chapter: bool,
+    /// Set once the deadline passed with `finish_chapter`: the item being
+    /// finished and the time its current chapter ends
+    chapter_end: Option<(Option<String>, i64)>,
+}
+
+impl SleepTimer {
+    fn status(&self, player: &vlc::MediaPlayer) -> SleepTimerStatus {
+        let remaining_ms = match &self.chapter_end {
+            Some((_, end_ms)) => (end_ms - player.get_time().unwrap_or(0)).max(0) as u64,
+            None => self.deadline.saturating_duration_since(Instant::now()).as_millis() as u64,
+        };
+        SleepTimerStatus {
+            remaining_ms,
+            finish_chapter: self.finish_chapter,
+            waiting_for_chapter_end: self.chapter_end.is_some(),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct SleepTimerStatus {
+    /// Until playback pauses; once waiting for the chapter end, until that end
+    pub remaining_ms: u64,
+    pub finish_chapter: bool,
+    /// The time is up and playback continues to the end of the chapter
+    pub waiting_for_chapter_end: bool,
+}
+
+/// Where the chapter playing at `time_ms` ends, from the chapters
+/// `get_media_chapters` probed for `item_id`. Media without chapters (a music
+/// track) counts as one chapter.
+fn chapter_end_ms(item_id: Option<&str>, time_ms: i64, duration_ms: i64) -> i64 {
+    let chapter_end = item_id.and_then(|id| {
+        CHAPTER_CACHE
+            .lock()
+            .unwrap()
+            .as_ref()
+            .and_then(|c| c.get(id))
+            .and_then(|chapters| {
+                chapters
+                    .iter()
+                    .find(|c| c.start_ms <= time_ms && time_ms < c.end_ms)
+                    .map(|c| c.end_ms)
+            })
+    });
+    chapter_end.unwrap_or(if duration_ms > 0 { duration_ms } else { time_ms })
+}
+
+fn crossfade_setting(app: &AppHandle) -> u64 {
+    store::get_setting::<u64>(app, CROSSFADE_SETTING)
+        .unwrap_or(0)
+        .min(MAX_CROSSFADE_MS)
+}
+
+/// Wake the VLC thread loop on `player`'s state and time changes.
+fn attach_player_events(player: &vlc::MediaPlayer, events_tx: &mpsc::SyncSender<VlcCmd>) {
+    let em = player.event_manager();
+    for event in [
+        vlc::EventType::MediaPlayerOpening,
+        vlc::EventType::MediaPlayerBuffering,
+        vlc::EventType::MediaPlayerPlaying,
+        vlc::EventType::MediaPlayerPaused,
+        vlc::EventType::MediaPlayerStopped,
+        vlc::EventType::MediaPlayerEndReached,
+        vlc::EventType::MediaPlayerEncounteredError,
+        vlc::EventType::MediaPlayerTimeChanged,
+    ] {
+        let tx = events_tx.clone();
+        // try_send: never block libVLC; a full channel already has a wake-up queued
+        let _ = em.attach(event, move |_, _| {
+            let _ = tx.try_send(VlcCmd::PlayerEvent);
+        });
+    }
+}
+
+/// Stop a crossfade early (the user opened, stopped or seeked), silencing the
+/// incoming item and putting the current one back at full volume.
+fn cancel_crossfade(fade: &mut Option<Crossfade>, fader: Option<&vlc::MediaPlayer>, player: &vlc::MediaPlayer) {
+    if let Some(f) = fade.take() {
+        if let Some(fader) = fader {
+            fader.stop();
+        }
+        let _ = player.set_volume(f.volume);
+    }
+}
+
+#[derive(Default)]
+struct PlayQueue {
+    config_path: String,
+    items: Vec<QueueItem>,
+    /// Index of the item currently loaded into VLC
+    index: Option<usize>,
+    /// Preload the next item near the end of each track and swap it in without
+    /// tearing down the stream (for album playback)
+    gapless: bool,
+    /// Kept when the queue is replaced or cleared
+    repeat: RepeatMode,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum RepeatMode {
+    #[default]
+    Off,
+    /// Restart the current item when it ends
+    One,
+    /// Wrap from the last queue item back to the first
+    All,
+}
+
+impl PlayQueue {
+    fn snapshot(&self) -> serde_json::Value {
+        serde_json::json!({
+            "items": self.items,
+            "index": self.index,
+            "gapless": self.gapless,
+            "repeat": self.repeat,
+        })
+    }
+
+    /// Index `step` items from the current one; wraps around in repeat-all.
+    fn step_index(&self, step: isize) -> Option<usize> {
+        let current = self.index?;
+        let len = self.items.len();
+        if self.repeat == RepeatMode::All && len > 0 {
+            return Some((current as isize + step).rem_euclid(len as isize) as usize);
+        }
+        current.checked_add_signed(step).filter(|&t| t < len)
+    }
+
+    /// Whether the item that just ended should play again from the start: repeat-one,
+    /// or repeat-all with nothing queued (a single `open_media` item).
+    fn restarts_current(&self) -> bool {
+        match self.repeat {
+            RepeatMode::One => true,
+            RepeatMode::All => self.items.is_empty(),
+            RepeatMode::Off => false,
+        }
+    }
+}
+
+/// One `rclone serve http` process shared by every session under the same remote root.
+struct SharedServe {
+    child: Child,
+    port: u16,
+    sessions: usize,
+}
+
+/// Key for a shared serve process: (config_path, remote_root, options).
+type ServeKey = (String, String, ServeOptions);
+
+/// rclone serve http processes keyed by remote root, with reference-counted sessions
+/// so opening the next file from the same folder reuses the running server.
+#[derive(Default)]
+pub struct ServeProcesses {
+    servers: HashMap<ServeKey, SharedServe>,
+    /// session id → server the session is attached to
+    sessions: HashMap<String, ServeKey>,
+}
+
+impl ServeProcesses {
+    /// Port of a running server for this key. Servers whose process has exited are
+    /// dropped so the caller spawns a fresh one.
+    fn live_port(&mut self, key: &ServeKey) -> Option<u16> {
+        let alive = match self.servers.get_mut(key) {
+            Some(s) => matches!(s.child.try_wait(), Ok(None)),
+            None => return None,
+        };
+        if alive {
+            return self.servers.get(key).map(|s| s.port);
+        }
+        self.servers.remove(key);
+        self.sessions.retain(|_, k| k != key);
+        None
+    }
+
+    /// Attach `session_id` to a running server for `key` and return its port,
+    /// or None when one has to be started.
+    fn reuse(&mut self, session_id: String, key: ServeKey) -> Option<u16> {
+        let port = self.live_port(&key)?;
+        self.attach(session_id, key);
+        Some(port)
+    }
+
+    fn insert(&mut self, key: ServeKey, child: Child, port: u16) {
+        self.servers.insert(key, SharedServe { child, port, sessions: 0 });
+    }
+
+    /// Attach a session to the server for `key`, releasing whatever it held before.
+    /// Re-attaching to the server the session already holds changes nothing.
+    fn attach(&mut self, session_id: String, key: ServeKey) {
+        if self.sessions.get(&session_id) == Some(&key) {
+            return;
+        }
+        self.release(&session_id);
+        if let Some(server) = self.servers.get_mut(&key) {
+            server.sessions += 1;
+            self.sessions.insert(session_id, key);
+        }
+    }
+
+    /// Detach a session; the server is killed when its last session goes away.
+    fn release(&mut self, session_id: &str) {
+        let Some(key) = self.sessions.remove(session_id) else {
+            return;
+        };
+        let last = match self.servers.get_mut(&key) {
+            Some(server) => {
+                server.sessions = server.sessions.saturating_sub(1);
+                server.sessions == 0
+            }
+            None => false,
+        };
+        if last {
+            if let Some(mut server) = self.servers.remove(&key) {
+                let _ = server.child.kill();
+            }
+        }
+    }
+
+    fn kill_all(&mut self) {
+        self.sessions.clear();
+        for (_, mut server) in self.servers.drain() {
+            let _ = server.child.kill();
+        }
+    }
+}
+
+/// Start a VLC thread with a fresh instance and return its command channel
+/// and its startup outcome.
+fn spawn_vlc_thread(app: AppHandle) -> (mpsc::SyncSender<VlcCmd>, watch::Receiver<VlcReadiness>) {
+    let (tx, rx) = mpsc::sync_channel::<VlcCmd>(64);
+    let (ready_tx, ready_rx) = watch::channel(None);
+    let events_tx = tx.clone();
+    thread::spawn(move || {
+        let panic_app = app.clone();
+        // The receiver drops with the thread either way, which is what `send`
+        // notices; catching the panic only lets the UI hear about it right away
+        if std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| vlc_thread(rx, events_tx, ready_tx, app))).is_err() {
+            let _ = panic_app.emit(
+                "vlc:error",
+                serde_json::json!({ "message": "The video player crashed and will restart" }),
+            );
+        }
+    });
+    (tx, ready_rx)
+}
+
+/// A VLC thread that couldn't create its player or has exited
+fn thread_dead(ready: &watch::Receiver<VlcReadiness>) -> bool {
+    ready.has_changed().is_err() || matches!(*ready.borrow(), Some(Err(_)))
 }
 
 impl VlcManager {
     pub fn new(app: AppHandle) -> Self {
-        let (tx, rx) = mpsc::sync_channel::<VlcCmd>(64);
-        thread::spawn(move || vlc_thread(rx, app));
+        let (cmd_tx, ready) = spawn_vlc_thread(app.clone());
         VlcManager {
-            cmd_tx: Mutex::new(tx),
+            cmd_tx: Mutex::new(cmd_tx),
             serve_child: Mutex::new(None),
-            book_sessions: Mutex::new(HashMap::new()),
+            serve_source: Mutex::new(None),
+            cast_serve: Mutex::new(None),
+            book_sessions: Mutex::new(ServeProcesses::default()),
+            queue: Mutex::new(PlayQueue::default()),
+            app,
+            restarts: Mutex::new(ThreadRestarts::default()),
+            ready: Mutex::new(ready),
+        }
+    }
+
+    /// Error for a command the VLC thread can't take, with the reason libVLC
+    /// failed to start when the thread reported one.
+    fn unavailable(&self) -> AppError {
+        let reason = self.ready.lock().unwrap().borrow().clone().and_then(Result::err);
+        AppError::VlcInit { reason }
+    }
+
+    /// Wait up to `VLC_READY_TIMEOUT` for the VLC thread to have its instance
+    /// and player. A thread that failed to start or has died is replaced
+    /// first, subject to the restart backoff.
+    async fn wait_ready(&self) -> Result<(), AppError> {
+        let mut ready = self.ready.lock().unwrap().clone();
+        if thread_dead(&ready) {
+            let mut tx = self.cmd_tx.lock().unwrap();
+            // Another caller may have replaced it while we waited for the lock
+            ready = self.ready.lock().unwrap().clone();
+            if thread_dead(&ready) {
+                if !self.respawn_dead_thread(&mut tx) {
+                    return Err(self.unavailable());
+                }
+                ready = self.ready.lock().unwrap().clone();
+            }
+        }
+        match tokio::time::timeout(VLC_READY_TIMEOUT, ready.wait_for(Option::is_some)).await {
+            Ok(Ok(state)) => match state.clone() {
+                Some(Err(reason)) => Err(AppError::VlcInit { reason: Some(reason) }),
+                _ => Ok(()),
+            },
+            Ok(Err(_)) => Err(AppError::VlcInit {
+                reason: Some("the VLC thread exited while starting".to_string()),
+            }),
+            Err(_) => Err(AppError::timeout("Timed out waiting for libVLC to start")),
         }
     }
 
+    /// Hand a command to the VLC thread. If the thread has died, a new one is
+    /// started (see `respawn_dead_thread`) and gets the command instead.
     fn send(&self, cmd: VlcCmd) -> bool {
-        self.cmd_tx
-            .lock()
-            .map(|tx| tx.send(cmd).is_ok())
-            .unwrap_or(false)
+        let Ok(mut tx) = self.cmd_tx.lock() else {
+            return false;
+        };
+        let cmd = match tx.send(cmd) {
+            Ok(()) => return true,
+            Err(mpsc::SendError(cmd)) => cmd,
+        };
+        self.respawn_dead_thread(&mut tx) && tx.send(cmd).is_ok()
+    }
+
+    /// Start a replacement for a VLC thread that panicked or couldn't create
+    /// its instance, and emit `player:restarted` { restarts, item_id,
+    /// position_ms } so the frontend can reopen the last item. The new thread
+    /// re-reads persisted player settings; nothing was loaded in it yet.
+    ///
+    /// Restarts back off exponentially: commands sent before the next restart
+    /// is due fail as if VLC were unavailable. After `MAX_THREAD_RESTARTS` in
+    /// a row this emits `player:fatal` and stops trying. Returns whether
+    /// `tx` now leads to a new thread.
+    fn respawn_dead_thread(&self, tx: &mut mpsc::SyncSender<VlcCmd>) -> bool {
+        let mut restarts = self.restarts.lock().unwrap();
+        if restarts.given_up {
+            return false;
+        }
+        let now = Instant::now();
+        if restarts
+            .last
+            .is_some_and(|t| now.duration_since(t) >= THREAD_RESTART_RESET_AFTER)
+        {
+            restarts.count = 0;
+        }
+        if restarts.count >= MAX_THREAD_RESTARTS {
+            restarts.given_up = true;
+            let _ = self.app.emit(
+                "player:fatal",
+                serde_json::json!({
+                    "message": format!(
+                        "The video player stopped {} times in a row; restart the app to try again",
+                        MAX_THREAD_RESTARTS
+                    )
+                }),
+            );
+            return false;
+        }
+        let backoff = THREAD_RESTART_BACKOFF * 2u32.pow(restarts.count);
+        if restarts.last.is_some_and(|t| now.duration_since(t) < backoff) {
+            return false;
+        }
+        restarts.count += 1;
+        restarts.last = Some(now);
+        eprintln!("VLC thread is gone; starting a new one (restart {})", restarts.count);
+
+        let (new_tx, ready) = spawn_vlc_thread(self.app.clone());
+        *tx = new_tx;
+        *self.ready.lock().unwrap() = ready;
+        // The new player plays locally
+        *self.cast_serve.lock().unwrap() = None;
+        let (item_id, position_ms) = LAST_POSITION.lock().unwrap().clone().unzip();
+        let _ = self.app.emit(
+            "player:restarted",
+            serde_json::json!({
+                "restarts": restarts.count,
+                "item_id": item_id,
+                "position_ms": position_ms,
+            }),
+        );
+        true
+    }
+
+    /// Replace the VLC thread with a new one, so instance options are re-read.
+    /// Only safe while `VlcCmd::IsIdle` holds: the main, fader and preview
+    /// players are all torn down, and runtime-only settings (log level, watched threshold) go back to defaults.
+    fn restart_thread(&self, app: &AppHandle) {
+        let (tx, ready) = spawn_vlc_thread(app.clone());
+        let old = std::mem::replace(&mut *self.cmd_tx.lock().unwrap(), tx);
+        *self.ready.lock().unwrap() = ready;
+        *self.cast_serve.lock().unwrap() = None;
+        let _ = old.send(VlcCmd::Shutdown);
     }
 }
 
@@ -62,22 +995,36 @@ impl Drop for VlcManager {
                 let _ = c.kill();
             }
         }
-        if let Ok(mut map) = self.book_sessions.lock() {
-            for (_, mut c) in map.drain() {
-                let _ = c.kill();
-            }
+        transcode::kill_all(self);
+        if let Ok(mut servers) = self.book_sessions.lock() {
+            servers.kill_all();
         }
     }
 }
 
 // ── VLC background thread ─────────────────────────────────────────────────────
 
-fn vlc_thread(rx: mpsc::Receiver<VlcCmd>, app: AppHandle) {
-    let instance = match vlc::Instance::new() {
+/// `ready` reports whether the instance and player could be created, before
+/// any command is handled.
+fn vlc_thread(
+    rx: mpsc::Receiver<VlcCmd>,
+    events_tx: mpsc::SyncSender<VlcCmd>,
+    ready: watch::Sender<VlcReadiness>,
+    app: AppHandle,
+) {
+    // Subtitle renderer styling and audio filters can only be set on the instance
+    let normalization = load_normalization(&app);
+    *INSTANCE_NORMALIZATION.lock().unwrap() = Some(normalization);
+    let mut args = subtitles::instance_args(&app);
+    args.extend(normalization.instance_args());
+    let instance = match vlc::Instance::with_args(Some(args)) {
         Some(i) => i,
         None => {
             let plugin_path = std::env::var("VLC_PLUGIN_PATH")
                 .unwrap_or_else(|_| "<not set>".into());
+            ready.send_replace(Some(Err(format!(
+                "failed to initialize libvlc (VLC_PLUGIN_PATH={plugin_path})"
+            ))));
             let _ = app.emit(
                 "vlc:error",
                 serde_json::json!({
@@ -91,89 +1038,624 @@ fn vlc_thread(rx: mpsc::Receiver<VlcCmd>, app: AppHandle) {
         }
     };
 
-    let player = match vlc::MediaPlayer::new(&instance) {
+    // Notice and above by default; `player_set_log_level` can enable debug at runtime
+    let logs = Arc::new(LogBuffer::new(LOG_NOTICE));
+    {
+        let logs = logs.clone();
+        instance.set_log(move |level, _log, message| {
+            let level = match level {
+                vlc::LogLevel::Debug => LOG_DEBUG,
+                vlc::LogLevel::Notice => LOG_NOTICE,
+                vlc::LogLevel::Warning => LOG_WARNING,
+                vlc::LogLevel::Error => LOG_ERROR,
+            };
+            logs.push(level, message.into_owned());
+        });
+    }
+
+    let mut player = match vlc::MediaPlayer::new(&instance) {
         Some(p) => p,
         None => {
+            ready.send_replace(Some(Err("failed to create the media player".to_string())));
             let _ = app.emit(
                 "vlc:error",
-                serde_json::json!({ "message": "Failed to create VLC media player" }),
+                error_payload(&logs, "Failed to create VLC media player"),
             );
             return;
         }
     };
+    ready.send_replace(Some(Ok(())));
+
+    // Wake the loop on player events instead of polling the state. The callbacks
+    // run on libVLC threads and only nudge the loop; state is still read here, so
+    // events and commands are handled in the order they arrive on one channel.
+    attach_player_events(&player, &events_tx);
+
+    // Volume and mute carry over between launches
+    let mut volume = store::get_setting::<i32>(&app, VOLUME_SETTING)
+        .unwrap_or(DEFAULT_VOLUME)
+        .clamp(0, 100);
+    let mut muted = store::get_setting::<bool>(&app, MUTED_SETTING).unwrap_or(false);
+    let _ = player.set_volume(volume);
+    player.set_mute(muted);
+    // What the store holds, and when the user last changed either
+    let mut saved_audio = (volume, muted);
+    let mut audio_changed = Instant::now();
+
+    // Second player the next audio item fades in on; created on the first
+    // crossfade, then swapped with `player` each time one completes
+    let mut fader: Option<vlc::MediaPlayer> = None;
+    let mut fade: Option<Crossfade> = None;
+    let mut crossfade = Duration::from_millis(crossfade_setting(&app));
+    let mut current_audio_only = false;
+    // Set while casting; the fader would play locally, so crossfades are skipped
+    let mut casting = false;
 
-    let mut pending_seek_ms: Option<i64> = None;
+    // Hover previews; created on first use and never given the video window
+    let mut preview: Option<vlc::MediaPlayer> = None;
+    let mut preview_last_playing = false;
+
+    // Started on the first `player_discover_renderers`; dropped before the player
+    let mut renderers: Option<RendererDiscovery> = None;
+
+    let mut pending_seek: Option<PendingSeek> = None;
+    // Bumped whenever the loaded media changes or stops
+    let mut media_generation: u64 = 0;
     let mut last_emitted_playing = false;
     let mut last_emitted_buffering = false;
-    // Emit time updates at ~1 Hz to minimise WebKitGTK repaints (which cause flicker).
-    // The poll loop itself stays at 100 ms so commands feel responsive.
-    let mut time_tick: u8 = 0;
+    let mut last_ended = false;
+    let mut last_error = false;
+    // Last track list sent as `vlc:tracks`; reset on Open so the new media always emits.
+    let mut last_tracks: Option<serde_json::Value> = None;
+    // Whether `vlc:meta` has been sent for the current media.
+    let mut meta_emitted = false;
+    // Next gapless queue item, ready to swap in when the current one ends
+    let mut preloaded: Option<PreloadedItem> = None;
+    let mut preload_requested = false;
+    // Media generation the next queue item's prefetch was requested for
+    let mut prefetch_generation: Option<u64> = None;
+    // Watch-progress key and series of the loaded media
+    let mut current_item: Option<String> = None;
+    let mut current_series: Option<String> = None;
+    // (audio, subtitle) track ids to restore instead of applying preferences, after
+    // the current media was reloaded in place
+    let mut restore_tracks: Option<(i32, i32)> = None;
+    // Subtitle files added to the media of this generation
+    let mut external_subtitles: (u64, Vec<String>) = (0, Vec::new());
+    // Progress is written every tick but only flushed to disk every 10 s
+    let mut progress_tick: u8 = 0;
+    // `player:watched` fires once per opened item when it passes the threshold
+    let mut watched_threshold = progress::watched_fraction(&app);
+    let mut watched_emitted = false;
+    let mut sleep_timer: Option<SleepTimer> = None;
+    // Skip segments of the loaded media, looked up on the 1 Hz tick until known
+    // (chapter names only become available once the frontend probed them)
+    let mut skip_segments: Option<Vec<SkipSegment>> = None;
+    let mut skip_segments_generation: u64 = 0;
+    let mut current_segment: Option<SkipSegment> = None;
+    let mut auto_skip = segments::auto_skip_kinds(&app);
+    // Screensaver/sleep inhibit held while playing; dropped (released) on
+    // pause/stop/end and when this thread exits
+    let mut inhibit: Option<power::InhibitGuard> = None;
+    let mut audio_keeps_screen_on = false;
+    // Stall detection: last playback time seen and since when it hasn't moved
+    let mut stall_timeout = Duration::from_secs(
+        store::get_setting(&app, STALL_TIMEOUT_SETTING).unwrap_or(DEFAULT_STALL_TIMEOUT_SECS),
+    );
+    let mut stall_last_ms: i64 = -1;
+    let mut stall_since = Instant::now();
+    let mut stall_reported = false;
+    let mut stall_retries: u32 = 0;
+    // Where a reconnect resumes: the last time playback actually reached
+    let mut last_known_ms: i64 = 0;
+    // Time updates go out at TIME_EMIT_INTERVAL while playing
+    let mut last_time_emit = Instant::now();
+    let mut active = false;
 
     loop {
-        match rx.recv_timeout(Duration::from_millis(100)) {
-            Ok(VlcCmd::Open { url, start_ms }) => {
+        let wake = if fade.is_some() {
+            Duration::from_millis(CROSSFADE_STEP_MS as u64)
+        } else if active {
+            ACTIVE_WAKE_INTERVAL
+        } else {
+            IDLE_WAKE_INTERVAL
+        };
+        match rx.recv_timeout(wake) {
+            Ok(VlcCmd::Open { url, start_ms, item_id, series_key, audio_only }) => {
+                if pending_seek.is_none() {
+                    save_position(&app, &player, current_item.as_deref(), watched_threshold, true);
+                    check_watched(&app, &player, current_item.as_deref(), watched_threshold, &mut watched_emitted);
+                }
+                watched_emitted = false;
+                // The preview never plays over newly opened main media
+                stop_preview(&app, preview.as_ref(), "main-open");
+                preview_last_playing = false;
+                cancel_crossfade(&mut fade, fader.as_ref(), &player);
+                if sleep_timer.is_some()
+                    && !store::get_setting::<bool>(&app, SLEEP_TIMER_KEEP_ON_OPEN_SETTING).unwrap_or(true)
+                {
+                    sleep_timer = None;
+                }
+                // A chapter being finished belonged to the old media
+                if let Some(timer) = &mut sleep_timer {
+                    timer.chapter_end = None;
+                }
+                current_item = item_id;
+                current_series = series_key;
+                current_audio_only = audio_only;
+                restore_tracks = None;
+                last_known_ms = start_ms;
+                stall_retries = 0;
                 player.stop();
-                pending_seek_ms = None;
-
-                let media = if url.starts_with("http://") || url.starts_with("https://") {
-                    vlc::Media::new_location(&instance, &url)
-                } else {
-                    vlc::Media::new_path(&instance, &url)
-                };
+                media_generation += 1;
+                pending_seek = None;
+                last_tracks = None;
+                meta_emitted = false;
+                preloaded = None;
+                preload_requested = false;
 
-                match media {
+                match new_media(&app, &instance, &url) {
                     Some(m) => {
+                        if audio_only {
+                            add_media_option(&m, ":no-video");
+                        }
+                        // Start the input at the resume point so slow streams don't
+                        // have to buffer from 0 first; the pending seek is a fallback
+                        if start_ms > 5000 {
+                            add_media_option(&m, &format!(":start-time={:.3}", start_ms as f64 / 1000.0));
+                            pending_seek = Some(PendingSeek::new(start_ms, media_generation));
+                        }
                         player.set_media(&m);
+                        reset_video_geometry(&player);
                         if let Err(_) = player.play() {
                             let _ = app.emit(
                                 "vlc:error",
-                                serde_json::json!({ "message": "Failed to start playback" }),
+                                error_payload(&logs, "Failed to start playback"),
                             );
+                            release_serve_for(&app, &url);
                         }
-                        if start_ms > 5000 {
-                            pending_seek_ms = Some(start_ms);
-                        }
+                        // libVLC can reset the volume for new media
+                        let _ = player.set_volume(volume);
+                        player.set_mute(muted);
                     }
                     None => {
                         let _ = app.emit(
                             "vlc:error",
-                            serde_json::json!({ "message": "Failed to open media source" }),
+                            error_payload(&logs, "Failed to open media source"),
                         );
+                        release_serve_for(&app, &url);
                     }
                 }
             }
 
             Ok(VlcCmd::Play) => {
                 let _ = player.play();
+                if let Some(f) = fader.as_ref().filter(|_| fade.is_some()) {
+                    let _ = f.play();
+                }
             }
             Ok(VlcCmd::Pause) => {
                 player.set_pause(true);
+                if let Some(f) = fader.as_ref().filter(|_| fade.is_some()) {
+                    f.set_pause(true);
+                }
+            }
+            Ok(VlcCmd::TogglePause) => {
+                let pause = player.state() == vlc::State::Playing;
+                if pause {
+                    player.set_pause(true);
+                } else {
+                    let _ = player.play();
+                }
+                // Both halves of a crossfade pause and resume together
+                if let Some(f) = fader.as_ref().filter(|_| fade.is_some()) {
+                    if pause {
+                        f.set_pause(true);
+                    } else {
+                        let _ = f.play();
+                    }
+                }
             }
             Ok(VlcCmd::Stop) => {
+                // Skip while a resume seek is pending, or we'd overwrite it with ~0
+                if pending_seek.is_none() {
+                    save_position(&app, &player, current_item.as_deref(), watched_threshold, true);
+                    check_watched(&app, &player, current_item.as_deref(), watched_threshold, &mut watched_emitted);
+                }
+                current_item = None;
+                cancel_crossfade(&mut fade, fader.as_ref(), &player);
                 player.stop();
-                pending_seek_ms = None;
+                media_generation += 1;
+                pending_seek = None;
+                preloaded = None;
             }
             Ok(VlcCmd::Seek(ms)) => {
+                // Seeking away from the end abandons a fade; the next item is
+                // preloaded again when the end comes round
+                if fade.is_some() {
+                    cancel_crossfade(&mut fade, fader.as_ref(), &player);
+                    preload_requested = false;
+                }
                 player.set_time(ms);
             }
-            Ok(VlcCmd::SetVolume(vol)) => {
-                let _ = player.set_volume(vol);
+            Ok(VlcCmd::SeekRelative(delta_ms)) => {
+                if fade.is_some() {
+                    cancel_crossfade(&mut fade, fader.as_ref(), &player);
+                    preload_requested = false;
+                }
+                let duration_ms = player
+                    .get_media()
+                    .and_then(|m| m.duration())
+                    .unwrap_or(0);
+                let current_ms = player.get_time().unwrap_or(0);
+                let max_ms = if duration_ms > 0 { duration_ms } else { i64::MAX };
+                let target_ms = current_ms.saturating_add(delta_ms).clamp(0, max_ms);
+                player.set_time(target_ms);
+                // Report the new position now rather than on the next 1 Hz tick,
+                // so the scrubber doesn't briefly jump back to the old time
+                let position = if duration_ms > 0 {
+                    target_ms as f64 / duration_ms as f64
+                } else {
+                    player.get_position().unwrap_or(0.0) as f64
+                };
+                let _ = app.emit(
+                    "vlc:time",
+                    serde_json::json!({
+                        "time_ms": target_ms,
+                        "duration_ms": duration_ms,
+                        "position": position,
+                    }),
+                );
+                last_time_emit = Instant::now();
             }
-
-            #[cfg(target_os = "linux")]
-            Ok(VlcCmd::SetWindow(xid)) => {
-                player.set_xwindow(xid);
+            Ok(VlcCmd::SeekFraction(position)) => {
+                if fade.is_some() {
+                    cancel_crossfade(&mut fade, fader.as_ref(), &player);
+                    preload_requested = false;
+                }
+                player.set_position(position);
             }
-
-            Ok(VlcCmd::Shutdown) | Err(mpsc::RecvTimeoutError::Disconnected) => break,
-            Err(mpsc::RecvTimeoutError::Timeout) => {}
-        }
-
-        // Seek to resume position once VLC has started playing
-        if let Some(seek_ms) = pending_seek_ms {
-            if player.state() == vlc::State::Playing {
-                player.set_time(seek_ms);
-                pending_seek_ms = None;
+            Ok(VlcCmd::SetVolume(vol)) => {
+                match &mut fade {
+                    // The ramps pick up the new level on the next pass
+                    Some(f) => f.volume = vol,
+                    None => {
+                        let _ = player.set_volume(vol);
+                    }
+                }
+                volume = vol;
+                audio_changed = Instant::now();
+            }
+            Ok(VlcCmd::SetMute(mute)) => {
+                player.set_mute(mute);
+                if let Some(f) = fader.as_ref().filter(|_| fade.is_some()) {
+                    f.set_mute(mute);
+                }
+                muted = mute;
+                audio_changed = Instant::now();
+            }
+            Ok(VlcCmd::SetScale(factor)) => {
+                unsafe { vlc::sys::libvlc_video_set_scale(player.raw(), factor) };
+            }
+            Ok(VlcCmd::SetZoomRect(geometry)) => {
+                set_crop_geometry(&player, geometry.as_deref());
+            }
+            Ok(VlcCmd::ReloadSubtitles) => {
+                // The encoding is read when a subtitle decoder is created: put it
+                // on the playing media, then load its subtitles again so new
+                // decoders pick it up while the video keeps playing
+                let active = matches!(
+                    player.state(),
+                    vlc::State::Playing | vlc::State::Paused | vlc::State::Buffering
+                );
+                if let Some(media) = player.get_media().filter(|_| active) {
+                    for opt in subtitles::media_options(&app) {
+                        add_media_option(&media, &opt);
+                    }
+                    let files: &[String] = match &external_subtitles {
+                        (generation, files) if *generation == media_generation => files,
+                        _ => &[],
+                    };
+                    if files.is_empty() {
+                        // An embedded text track gets a new decoder when reselected
+                        let spu = unsafe { vlc::sys::libvlc_video_get_spu(player.raw()) };
+                        if spu >= 0 {
+                            unsafe {
+                                vlc::sys::libvlc_video_set_spu(player.raw(), -1);
+                                vlc::sys::libvlc_video_set_spu(player.raw(), spu);
+                            }
+                        }
+                    }
+                    for (i, path) in files.iter().enumerate() {
+                        if !add_subtitle_slave(&player, path, i + 1 == files.len()) {
+                            let _ = app.emit("vlc:error", error_payload(&logs, "Failed to reload subtitle file"));
+                        }
+                    }
+                    last_tracks = None;
+                }
+            }
+
+            Ok(VlcCmd::SetWindow(window)) => match window {
+                NativeWindow::Xid(xid) => player.set_xwindow(xid),
+                NativeWindow::Hwnd(hwnd) => player.set_hwnd(hwnd as *mut std::ffi::c_void),
+                NativeWindow::NsView(view) => player.set_nsobject(view as *mut std::ffi::c_void),
+            },
+
+            Ok(VlcCmd::Preload { url, index, item_id, series_key, audio_only }) => {
+                if let Some(media) = new_media(&app, &instance, &url) {
+                    if audio_only {
+                        add_media_option(&media, ":no-video");
+                    }
+                    // Parse ahead of time so the swap at end of track starts immediately
+                    media.parse_async();
+                    preloaded = Some(PreloadedItem { index, media, item_id, series_key, audio_only });
+                }
+            }
+
+            Ok(VlcCmd::SetAudioTrack(id)) => {
+                unsafe { vlc::sys::libvlc_audio_set_track(player.raw(), id) };
+                if let (Some(series), Some(media)) = (&current_series, player.get_media()) {
+                    tracks::remember_audio(&app, series, &media, id);
+                }
+            }
+            Ok(VlcCmd::SetSubtitleTrack(id)) => {
+                unsafe { vlc::sys::libvlc_video_set_spu(player.raw(), id) };
+                if let (Some(series), Some(media)) = (&current_series, player.get_media()) {
+                    tracks::remember_subtitle(&app, series, &media, id);
+                }
+            }
+
+            Ok(VlcCmd::AddSubtitle(path)) => {
+                let added = std::ffi::CString::new(path.as_str())
+                    .map(|p| unsafe { vlc::sys::libvlc_video_set_subtitle_file(player.raw(), p.as_ptr()) } != 0)
+                    .unwrap_or(false);
+                if !added {
+                    let _ = app.emit("vlc:error", error_payload(&logs, "Failed to load subtitle file"));
+                } else {
+                    // Kept so an encoding change can load it again
+                    if external_subtitles.0 != media_generation {
+                        external_subtitles = (media_generation, Vec::new());
+                    }
+                    external_subtitles.1.push(path);
+                }
+                // The new track shows up in the next `vlc:tracks`
+            }
+
+            Ok(VlcCmd::SetAudioKeepsScreenOn(enabled)) => {
+                audio_keeps_screen_on = enabled;
+            }
+
+            Ok(VlcCmd::GetStatus(reply)) => {
+                let mut status = player_status(&player);
+                status.crossfade_ms = crossfade.as_millis() as u64;
+                status.item_id = current_item.clone();
+                // libVLC reports -1 until audio output exists, so use what was set
+                status.volume = fade.as_ref().map_or(volume, |f| f.volume);
+                status.muted = muted;
+                let _ = reply.send(status);
+            }
+            Ok(VlcCmd::IsIdle(reply)) => {
+                let main_idle = matches!(
+                    player_status(&player).state.as_str(),
+                    "idle" | "stopped" | "ended" | "error"
+                );
+                let preview_idle = preview.as_ref().is_none_or(|p| {
+                    !matches!(
+                        p.state(),
+                        vlc::State::Opening | vlc::State::Buffering | vlc::State::Playing | vlc::State::Paused
+                    )
+                });
+                let _ = reply.send(main_idle && fade.is_none() && preview_idle);
+            }
+            Ok(VlcCmd::GetLogs(reply)) => {
+                let _ = reply.send(logs.snapshot());
+            }
+            Ok(VlcCmd::SetLogLevel(level)) => {
+                logs.min_level.store(level, Ordering::Relaxed);
+            }
+            Ok(VlcCmd::SetStallTimeout(timeout)) => {
+                stall_timeout = timeout;
+            }
+            Ok(VlcCmd::Reconnect { manual }) => {
+                if manual {
+                    stall_retries = 0;
+                }
+                // Nothing to reconnect once playback was stopped or finished
+                let active = !matches!(
+                    player.state(),
+                    vlc::State::NothingSpecial | vlc::State::Stopped | vlc::State::Ended
+                );
+                let reopened = active
+                    .then(|| reopen_media(&app, &instance, &player, current_audio_only))
+                    .flatten();
+                if let Some(media) = reopened {
+                    // Keep the selected tracks if they were known before the stall
+                    if last_tracks.is_some() {
+                        restore_tracks = Some(unsafe {
+                            (
+                                vlc::sys::libvlc_audio_get_track(player.raw()),
+                                vlc::sys::libvlc_video_get_spu(player.raw()),
+                            )
+                        });
+                    }
+                    player.stop();
+                    media_generation += 1;
+                    pending_seek = None;
+                    if last_known_ms > 5000 {
+                        add_media_option(&media, &format!(":start-time={:.3}", last_known_ms as f64 / 1000.0));
+                        pending_seek = Some(PendingSeek::new(last_known_ms, media_generation));
+                    }
+                    player.set_media(&media);
+                    let _ = player.play();
+                    last_tracks = None;
+                    stall_last_ms = -1;
+                    stall_since = Instant::now();
+                    stall_reported = false;
+                }
+            }
+
+            Ok(VlcCmd::DiscoverRenderers(reply)) => {
+                let result = match &renderers {
+                    Some(d) => Ok(d.renderers()),
+                    None => RendererDiscovery::start(&app, &instance).map(|d| {
+                        let found = d.renderers();
+                        renderers = Some(d);
+                        found
+                    }),
+                };
+                let _ = reply.send(result);
+            }
+            Ok(VlcCmd::SetRenderer { id, url, reply }) => {
+                let result = match (&renderers, id.as_deref()) {
+                    (Some(d), id) => d.apply(&player, id),
+                    // Nothing can be casting without a discovery
+                    (None, None) => Ok(()),
+                    (None, Some(_)) => Err("Renderer discovery has not been started".to_string()),
+                };
+                // The renderer is picked up when playback starts, so restart the
+                // current media in place on the new output, from its new source
+                // if the serve process moved
+                let active = matches!(
+                    player.state(),
+                    vlc::State::Playing | vlc::State::Paused | vlc::State::Buffering
+                );
+                let moved = url
+                    .filter(|_| result.is_ok() && player.get_media().is_some())
+                    .and_then(|url| new_media(&app, &instance, &url));
+                if let Some(media) = &moved {
+                    if current_audio_only {
+                        add_media_option(media, ":no-video");
+                    }
+                }
+                if result.is_ok() && active {
+                    let time = player.get_time().unwrap_or(0);
+                    player.stop();
+                    if let Some(media) = &moved {
+                        player.set_media(media);
+                    }
+                    let _ = player.play();
+                    media_generation += 1;
+                    pending_seek = Some(PendingSeek::new(time, media_generation));
+                } else if let Some(media) = &moved {
+                    // Stopped: the next play starts from the new source
+                    player.set_media(media);
+                }
+                if result.is_ok() {
+                    casting = id.is_some();
+                    let _ = app.emit("player:renderer-changed", serde_json::json!({ "id": id }));
+                }
+                let _ = reply.send(result);
+            }
+
+            Ok(VlcCmd::DropPreload) => {
+                preloaded = None;
+                preload_requested = false;
+                cancel_crossfade(&mut fade, fader.as_ref(), &player);
+            }
+            Ok(VlcCmd::SetCrossfade(duration)) => {
+                crossfade = duration;
+            }
+            Ok(VlcCmd::SetWatchedThreshold(threshold)) => {
+                watched_threshold = threshold;
+            }
+            Ok(VlcCmd::SetSleepTimer { after, finish_chapter }) => {
+                sleep_timer = after.map(|after| SleepTimer {
+                    deadline: Instant::now() + after,
+                    finish_chapter,
+                    chapter_end: None,
+                });
+            }
+            Ok(VlcCmd::GetSleepTimer(reply)) => {
+                let _ = reply.send(sleep_timer.as_ref().map(|t| t.status(&player)));
+            }
+            Ok(VlcCmd::ReloadSkipSegments) => {
+                skip_segments = None;
+            }
+            Ok(VlcCmd::SetAutoSkip(kinds)) => {
+                auto_skip = kinds;
+            }
+            Ok(VlcCmd::SkipSegment(reply)) => {
+                let time_ms = player.get_time().unwrap_or(0);
+                let segment = skip_segments
+                    .iter()
+                    .flatten()
+                    .find(|s| s.contains(time_ms))
+                    .cloned();
+                if let Some(s) = &segment {
+                    player.set_time(s.end_ms);
+                }
+                let _ = reply.send(segment);
+            }
+
+            Ok(VlcCmd::PreviewOpen { url, start_ms }) => {
+                if preview.is_none() {
+                    preview = vlc::MediaPlayer::new(&instance);
+                }
+                let media = if url.contains("://") {
+                    vlc::Media::new_location(&instance, &url)
+                } else {
+                    vlc::Media::new_path(&instance, &url)
+                };
+                match (&preview, media) {
+                    (Some(p), Some(media)) => {
+                        // No drawable to render into: without this libVLC would
+                        // open a window of its own
+                        add_media_option(&media, ":no-video");
+                        if start_ms > 0 {
+                            add_media_option(&media, &format!(":start-time={:.3}", start_ms as f64 / 1000.0));
+                        }
+                        p.set_media(&media);
+                        if p.play().is_err() {
+                            let _ = app.emit("preview:error", serde_json::json!({ "message": "Failed to start preview" }));
+                        }
+                    }
+                    _ => {
+                        let _ = app.emit("preview:error", serde_json::json!({ "message": "Failed to open preview" }));
+                    }
+                }
+            }
+            Ok(VlcCmd::PreviewStop) => {
+                stop_preview(&app, preview.as_ref(), "stopped");
+                preview_last_playing = false;
+            }
+            Ok(VlcCmd::PreviewSetVolume(vol)) => {
+                if let Some(p) = &preview {
+                    let _ = p.set_volume(vol);
+                }
+            }
+
+            Ok(VlcCmd::PlayerEvent) => {}
+
+            Ok(VlcCmd::Shutdown) | Err(mpsc::RecvTimeoutError::Disconnected) => break,
+            Err(mpsc::RecvTimeoutError::Timeout) => {}
+        }
+
+        // Persist volume and mute once they've settled, off this thread, so a
+        // slider drag isn't a store write per step
+        if (volume, muted) != saved_audio && audio_changed.elapsed() >= AUDIO_SAVE_DELAY {
+            saved_audio = (volume, muted);
+            let app = app.clone();
+            tauri::async_runtime::spawn_blocking(move || save_audio(&app, saved_audio));
+        }
+
+        // Seek to resume position once VLC has started playing, unless :start-time
+        // already got it there
+        if let Some(seek) = &pending_seek {
+            let playing_at = (player.state() == vlc::State::Playing).then(|| player.get_time().unwrap_or(0));
+            match seek.step(media_generation, playing_at) {
+                ResumeStep::Wait => {}
+                ResumeStep::Seek(ms) => {
+                    player.set_time(ms);
+                    pending_seek = None;
+                }
+                ResumeStep::Done => pending_seek = None,
+                ResumeStep::TimedOut => {
+                    let _ = app.emit(
+                        "player:resume-failed",
+                        serde_json::json!({ "item_id": current_item, "position_ms": seek.ms }),
+                    );
+                    pending_seek = None;
+                }
             }
         }
 
@@ -183,13 +1665,255 @@ fn vlc_thread(rx: mpsc::Receiver<VlcCmd>, app: AppHandle) {
         let is_buffering = matches!(state, vlc::State::Opening | vlc::State::Buffering);
         let is_ended = state == vlc::State::Ended;
         let is_error = state == vlc::State::Error;
+        // The preview has no event hookup, so it is polled while it plays
+        let preview_state = preview.as_ref().map(|p| p.state());
+        let preview_playing = preview_state == Some(vlc::State::Playing);
+        if preview_playing != preview_last_playing {
+            let _ = app.emit("preview:state", serde_json::json!({ "playing": preview_playing }));
+            preview_last_playing = preview_playing;
+        }
+        if matches!(preview_state, Some(vlc::State::Ended | vlc::State::Error)) {
+            let reason = if preview_state == Some(vlc::State::Ended) { "ended" } else { "error" };
+            stop_preview(&app, preview.as_ref(), reason);
+        }
+        active = is_playing
+            || is_buffering
+            || pending_seek.is_some()
+            || matches!(preview_state, Some(vlc::State::Opening | vlc::State::Buffering | vlc::State::Playing));
+        // Once a second while playing: time, progress, track changes
+        let on_tick = is_playing && last_time_emit.elapsed() >= TIME_EMIT_INTERVAL;
 
-        if is_error {
-            let _ = app.emit(
-                "vlc:error",
-                serde_json::json!({ "message": "VLC playback error" }),
-            );
+        // The media failed for good, so its serve process has nothing left to do
+        if is_error && !last_error {
+            let _ = app.emit("vlc:error", error_payload(&logs, "VLC playback error"));
+            if let Some(mrl) = player.get_media().and_then(|m| m.mrl()) {
+                release_serve_for(&app, &mrl);
+            }
+        }
+        last_error = is_error;
+
+        // Sleep timer: pause once the time is up, or with `finish_chapter` once
+        // the chapter playing at that point is over (or another item started)
+        if let Some(timer) = &mut sleep_timer {
+            if timer.deadline <= Instant::now() {
+                let time_ms = player.get_time().unwrap_or(0);
+                let fire = if timer.finish_chapter && (is_playing || is_buffering) {
+                    let (item, end_ms) = timer.chapter_end.get_or_insert_with(|| {
+                        let duration_ms = player.get_media().and_then(|m| m.duration()).unwrap_or(0);
+                        let end_ms = chapter_end_ms(current_item.as_deref(), time_ms, duration_ms);
+                        (current_item.clone(), end_ms)
+                    });
+                    // Up to one wake interval early rather than into the next chapter
+                    *item != current_item || time_ms >= *end_ms - ACTIVE_WAKE_INTERVAL.as_millis() as i64
+                } else {
+                    true
+                };
+                if fire {
+                    let finished_chapter = timer.chapter_end.is_some();
+                    sleep_timer = None;
+                    player.set_pause(true);
+                    if let Some(f) = fader.as_ref().filter(|_| fade.is_some()) {
+                        f.set_pause(true);
+                    }
+                    if pending_seek.is_none() {
+                        save_position(&app, &player, current_item.as_deref(), watched_threshold, true);
+                    }
+                    let _ = app.emit(
+                        "player:sleep-timer-fired",
+                        serde_json::json!({
+                            "item_id": current_item,
+                            "position_ms": time_ms,
+                            "finished_chapter": finished_chapter,
+                        }),
+                    );
+                }
+            }
+        }
+
+        // Skip segments: tell the UI when playback enters or leaves one, or skip
+        // it straight away when its kind is set to auto-skip
+        if skip_segments_generation != media_generation {
+            skip_segments_generation = media_generation;
+            skip_segments = None;
+            current_segment = None;
+        }
+        if skip_segments.is_none() && on_tick {
+            skip_segments = load_skip_segments(&app, current_series.as_deref(), current_item.as_deref());
+        }
+        if is_playing && pending_seek.is_none() && fade.is_none() {
+            let time_ms = player.get_time().unwrap_or(0);
+            let inside = skip_segments
+                .iter()
+                .flatten()
+                .find(|s| s.contains(time_ms))
+                .cloned();
+            if inside != current_segment {
+                match &inside {
+                    Some(segment) if auto_skip.contains(&segment.kind) => {
+                        player.set_time(segment.end_ms);
+                        let _ = app.emit("player:segment-skipped", segment);
+                    }
+                    Some(segment) => {
+                        let _ = app.emit("player:segment-entered", segment);
+                    }
+                    None => {
+                        let _ = app.emit("player:segment-left", &current_segment);
+                    }
+                }
+                current_segment = inside;
+            }
+        }
+
+        // Crossfade: start the preloaded next item on the fader once the current
+        // one is within the fade window of its end. Audio only; video hard-cuts.
+        if fade.is_none()
+            && !crossfade.is_zero()
+            && is_playing
+            && current_audio_only
+            && !casting
+            && preloaded.as_ref().is_some_and(|next| next.audio_only)
+        {
+            let duration_ms = player.get_media().and_then(|m| m.duration()).unwrap_or(0);
+            let remaining_ms = duration_ms - player.get_time().unwrap_or(0);
+            if duration_ms > 0 && remaining_ms <= crossfade.as_millis() as i64 {
+                if fader.is_none() {
+                    fader = vlc::MediaPlayer::new(&instance);
+                    if let Some(f) = &fader {
+                        attach_player_events(f, &events_tx);
+                    }
+                }
+                if let (Some(f), Some(next)) = (&fader, preloaded.take()) {
+                    let _ = f.set_volume(0);
+                    f.set_mute(muted);
+                    f.set_media(&next.media);
+                    if f.play().is_ok() {
+                        fade = Some(Crossfade {
+                            started: Instant::now(),
+                            duration: Duration::from_millis(remaining_ms.max(CROSSFADE_STEP_MS) as u64),
+                            volume,
+                            index: next.index,
+                            item_id: next.item_id,
+                            series_key: next.series_key,
+                        });
+                    } else {
+                        // Fall back to the gapless swap at end of track
+                        preloaded = Some(next);
+                    }
+                }
+            }
+        }
+
+        // Ramp the volumes, and hand over to the fader once the fade is done or
+        // the outgoing item ran out early
+        if let Some(f) = &fade {
+            let progress = f.progress();
+            if progress >= 1.0 || is_ended || is_error {
+                let f = fade.take().unwrap();
+                if !watched_emitted {
+                    emit_watched(&app, current_item.as_deref());
+                }
+                save_finished(&app, &player, current_item.as_deref());
+                player.stop();
+                if let Some(incoming) = fader.take() {
+                    fader = Some(std::mem::replace(&mut player, incoming));
+                }
+                let _ = player.set_volume(f.volume);
+                current_item = Some(f.item_id);
+                current_series = f.series_key;
+                current_audio_only = true;
+                watched_emitted = false;
+                media_generation += 1;
+                pending_seek = None;
+                last_tracks = None;
+                meta_emitted = false;
+                preload_requested = false;
+                last_ended = false;
+                let index = f.index;
+                let app = app.clone();
+                tauri::async_runtime::spawn(async move {
+                    if let Some(vlc) = app.try_state::<VlcManager>() {
+                        mark_queue_advanced(&app, &vlc, index);
+                    }
+                });
+                continue;
+            }
+            let level = |share: f32| (f.volume as f32 * share).round() as i32;
+            let _ = player.set_volume(level(1.0 - progress));
+            if let Some(incoming) = &fader {
+                let _ = incoming.set_volume(level(progress));
+            }
+        }
+
+        // Reaching the end always counts as watched (VLC's time is unreliable once Ended)
+        if is_ended && !last_ended && !watched_emitted {
+            watched_emitted = true;
+            emit_watched(&app, current_item.as_deref());
+        }
+
+        // Repeat: restart the same media from 0 without an "ended" state event
+        if is_ended && !last_ended {
+            let restart = app
+                .try_state::<VlcManager>()
+                .is_some_and(|vlc| vlc.queue.lock().unwrap().restarts_current());
+            if restart {
+                save_finished(&app, &player, current_item.as_deref());
+                player.stop();
+                // A fresh Media, so the `:start-time` resume option of the
+                // original open doesn't send every repeat back to that point
+                if let Some(m) = reopen_media(&app, &instance, &player, current_audio_only) {
+                    player.set_media(&m);
+                }
+                let _ = player.play();
+                media_generation += 1;
+                preload_requested = false;
+                watched_emitted = false;
+                last_ended = true;
+                continue;
+            }
+        }
+
+        // Gapless: swap in the preloaded next item the moment the current one ends,
+        // skipping the "ended" state event so the UI goes straight to the next track
+        if is_ended && !last_ended {
+            if let Some(next) = preloaded.take() {
+                save_finished(&app, &player, current_item.as_deref());
+                current_item = Some(next.item_id);
+                current_series = next.series_key;
+                current_audio_only = next.audio_only;
+                watched_emitted = false;
+                let index = next.index;
+                player.set_media(&next.media);
+                reset_video_geometry(&player);
+                let _ = player.play();
+                media_generation += 1;
+                last_tracks = None;
+                meta_emitted = false;
+                preload_requested = false;
+                last_ended = true;
+                let app = app.clone();
+                tauri::async_runtime::spawn(async move {
+                    if let Some(vlc) = app.try_state::<VlcManager>() {
+                        mark_queue_advanced(&app, &vlc, index);
+                    }
+                });
+                continue;
+            }
+        }
+
+        // Auto-advance the queue when the current item finishes
+        if is_ended && !last_ended {
+            save_finished(&app, &player, current_item.as_deref());
+            let app = app.clone();
+            tauri::async_runtime::spawn(async move {
+                let Some(vlc) = app.try_state::<VlcManager>() else {
+                    return;
+                };
+                if let Err(e) = play_queue_step(&app, &vlc, 1).await {
+                    let _ = app.emit("vlc:error", serde_json::json!({ "message": e.to_string() }));
+                }
+            });
         }
+        last_ended = is_ended;
 
         // Only emit state when it actually changes — avoids redundant repaints
         if is_ended {
@@ -212,150 +1936,807 @@ fn vlc_thread(rx: mpsc::Receiver<VlcCmd>, app: AppHandle) {
             last_emitted_buffering = is_buffering;
         }
 
-        // Emit time once per second (every 10th poll at 100 ms cadence).
-        // Keeping this at 1 Hz prevents constant WebKitGTK repaints that cause video flicker.
+        // Emit now-playing metadata once per media, after VLC has read the tags
+        if is_playing && !meta_emitted {
+            if let Some(media) = player.get_media() {
+                let _ = app.emit("vlc:meta", collect_meta(&media));
+                meta_emitted = true;
+            }
+        }
+
+        // Emit the track list as soon as playback starts, then again whenever it
+        // changes (checked on the 1 Hz tick below to keep the poll loop cheap).
+        if is_playing && (last_tracks.is_none() || on_tick) {
+            if let Some(media) = player.get_media() {
+                if let Some(track_list) = collect_tracks(&media) {
+                    // First time tracks are known for this media: apply language preferences,
+                    // or put back the previous selection after an in-place reload
+                    if last_tracks.is_none() {
+                        match restore_tracks.take() {
+                            Some((audio, spu)) => unsafe {
+                                vlc::sys::libvlc_audio_set_track(player.raw(), audio);
+                                vlc::sys::libvlc_video_set_spu(player.raw(), spu);
+                            },
+                            None => tracks::apply_preferences(
+                                &app,
+                                &player,
+                                &media,
+                                current_series.as_deref(),
+                            ),
+                        }
+                    }
+                    if last_tracks.as_ref() != Some(&track_list) {
+                        let _ = app.emit("vlc:tracks", &track_list);
+                        last_tracks = Some(track_list);
+                    }
+                }
+            }
+        }
+
+        // Keep the machine awake while playing. Until tracks are known assume video.
         if is_playing {
-            time_tick = time_tick.wrapping_add(1);
-            if time_tick >= 10 {
-                time_tick = 0;
-                let time_ms = player.get_time().unwrap_or(0);
-                let duration_ms = player
-                    .get_media()
-                    .and_then(|m| m.duration())
-                    .unwrap_or(0);
+            let audio_only = last_tracks
+                .as_ref()
+                .and_then(|t| t["video"].as_array())
+                .is_some_and(|v| v.is_empty());
+            let kind = if audio_only && !audio_keeps_screen_on {
+                InhibitKind::Sleep
+            } else {
+                InhibitKind::Display
+            };
+            if inhibit.as_ref().map(|g| g.kind()) != Some(kind) {
+                inhibit = Some(power::acquire(kind));
+            }
+        } else if !is_buffering {
+            inhibit = None;
+        }
+
+        // Stall detection: time standing still while playing or buffering usually
+        // means the serve stream died (e.g. a VPN hiccup) and VLC would wait forever
+        if is_playing || is_buffering {
+            let time_ms = player.get_time().unwrap_or(-1);
+            if time_ms != stall_last_ms {
+                if is_playing && pending_seek.is_none() && time_ms > 0 {
+                    // Playing normally again after a reconnect
+                    if stall_last_ms >= 0 && time_ms > stall_last_ms {
+                        stall_retries = 0;
+                    }
+                    last_known_ms = time_ms;
+                }
+                stall_last_ms = time_ms;
+                stall_since = Instant::now();
+                stall_reported = false;
+            } else if !stall_reported
+                && !stall_timeout.is_zero()
+                && stall_since.elapsed() >= stall_timeout
+            {
+                stall_reported = true;
+                let will_retry = stall_retries < MAX_STALL_RETRIES;
                 let _ = app.emit(
-                    "vlc:time",
-                    serde_json::json!({ "time_ms": time_ms, "duration_ms": duration_ms }),
+                    "player:stalled",
+                    serde_json::json!({
+                        "item_id": current_item,
+                        "position_ms": last_known_ms,
+                        "attempt": stall_retries + 1,
+                        "will_retry": will_retry,
+                    }),
                 );
+                if will_retry {
+                    stall_retries += 1;
+                    let app = app.clone();
+                    tauri::async_runtime::spawn(async move {
+                        let Some(vlc) = app.try_state::<VlcManager>() else {
+                            return;
+                        };
+                        if let Err(e) = recover_stream(&app, &vlc, false).await {
+                            let _ = app.emit("vlc:error", serde_json::json!({ "message": e.to_string() }));
+                        }
+                    });
+                } else {
+                    let _ = app.emit(
+                        "vlc:error",
+                        error_payload(&logs, "The stream stalled and could not be reconnected"),
+                    );
+                }
             }
         } else {
-            time_tick = 0;
+            stall_last_ms = -1;
+            stall_since = Instant::now();
+            stall_reported = false;
         }
-    }
-}
 
-// ── Helpers ───────────────────────────────────────────────────────────────────
+        // Emit time once per second. Keeping this at 1 Hz prevents constant
+        // WebKitGTK repaints that cause video flicker.
+        if on_tick {
+            last_time_emit = Instant::now();
+            let time_ms = player.get_time().unwrap_or(0);
+            let duration_ms = player
+                .get_media()
+                .and_then(|m| m.duration())
+                .unwrap_or(0);
+            // `position` (0-1) is known even while HTTP streams report no duration
+            let _ = app.emit(
+                "vlc:time",
+                serde_json::json!({
+                    "time_ms": time_ms,
+                    "duration_ms": duration_ms,
+                    "position": player.get_position().unwrap_or(0.0),
+                }),
+            );
 
-pub fn rclone_binary(app: &AppHandle) -> PathBuf {
-    let resource_dir = app
-        .path()
-        .resource_dir()
-        .unwrap_or_else(|_| PathBuf::from("."));
-    let s = resource_dir.join("rclone");
-    if s.exists() { s } else { PathBuf::from("rclone") }
-}
+            if pending_seek.is_none() {
+                progress_tick = progress_tick.wrapping_add(1);
+                let flush = progress_tick % 10 == 0;
+                save_position(&app, &player, current_item.as_deref(), watched_threshold, flush);
+                check_watched(&app, &player, current_item.as_deref(), watched_threshold, &mut watched_emitted);
+            }
 
-/// Parse "remote:/sub/path" into ("remote", "/sub/path").
-pub fn parse_remote_root(remote_root: &str) -> (&str, &str) {
-    if let Some(pos) = remote_root.find(':') {
-        let name = &remote_root[..pos];
-        let path = &remote_root[pos + 1..];
-        (name, if path.is_empty() { "/" } else { path })
-    } else {
-        (remote_root, "/")
-    }
-}
+            // Halfway through, start copying the next queue item into the temp cache
+            if prefetch_generation != Some(media_generation)
+                && duration_ms > 0
+                && time_ms * 2 >= duration_ms
+            {
+                prefetch_generation = Some(media_generation);
+                let app = app.clone();
+                tauri::async_runtime::spawn(async move {
+                    if let Some(vlc) = app.try_state::<VlcManager>() {
+                        prefetch_next_in_queue(&app, &vlc);
+                    }
+                });
+            }
 
-/// Percent-encode a relative file path, encoding each segment but preserving '/'.
-/// Properly handles Unicode characters using UTF-8 encoding.
-pub fn percent_encode_path(path: &str) -> String {
-    const UNRESERVED: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789-_.~";
-    
-    path.split('/')
-        .map(|seg| {
-            let mut out = String::with_capacity(seg.len() * 3);
-            for ch in seg.chars() {
-                if ch.is_ascii() && UNRESERVED.contains(&(ch as u8)) {
-                    out.push(ch);
-                } else {
-                    // Encode UTF-8 bytes
-                    for byte in ch.encode_utf8(&mut [0; 4]).as_bytes() {
-                        out.push_str(&format!("%{:02X}", byte));
+            // Near the end of the track, ask the queue to resolve the next item
+            if !preload_requested
+                && duration_ms > 0
+                && duration_ms - time_ms < GAPLESS_PRELOAD_MS
+            {
+                preload_requested = true;
+                let app = app.clone();
+                tauri::async_runtime::spawn(async move {
+                    if let Some(vlc) = app.try_state::<VlcManager>() {
+                        preload_next_in_queue(&app, &vlc).await;
                     }
-                }
+                });
             }
-            out
-        })
-        .collect::<Vec<_>>()
-        .join("/")
+        } else if !is_playing {
+            last_time_emit = Instant::now();
+        }
+    }
+    if (volume, muted) != saved_audio {
+        save_audio(&app, (volume, muted));
+    }
 }
 
-/// Check /proc/mounts for an active rclone FUSE mount matching the remote name.
-/// If found and the file exists locally, returns the local path.
-#[cfg(target_os = "linux")]
-fn find_fuse_local_path(remote_name: &str, relative_path: &str) -> Option<PathBuf> {
-    let mounts = std::fs::read_to_string("/proc/mounts").ok()?;
-    for line in mounts.lines() {
-        let mut parts = line.split_whitespace();
-        let device = parts.next()?;
-        let mount_point = parts.next()?;
-        let fs_type = parts.next()?;
-
-        // rclone FUSE mounts appear as fuse.rclone (or just fuse on older kernels)
-        if !fs_type.contains("fuse") {
-            continue;
-        }
+fn save_audio(app: &AppHandle, (volume, muted): (i32, bool)) {
+    if let Err(e) = store::set_setting(app, VOLUME_SETTING, serde_json::json!(volume)) {
+        eprintln!("Failed to save volume: {}", e);
+    }
+    if let Err(e) = store::set_setting(app, MUTED_SETTING, serde_json::json!(muted)) {
+        eprintln!("Failed to save mute state: {}", e);
+    }
+}
 
-        // Device field is typically "remote_name:" for rclone mounts
-        let device_name = device.trim_end_matches(':');
-        if !device_name.eq_ignore_ascii_case(remote_name) {
-            continue;
-        }
+/// Stop the preview player if it has media and release its serve process.
+fn stop_preview(app: &AppHandle, preview: Option<&vlc::MediaPlayer>, reason: &str) {
+    let Some(p) = preview else {
+        return;
+    };
+    if matches!(p.state(), vlc::State::NothingSpecial | vlc::State::Stopped) {
+        return;
+    }
+    p.stop();
+    if let Some(vlc) = app.try_state::<VlcManager>() {
+        vlc.book_sessions.lock().unwrap().release(PREVIEW_SESSION);
+    }
+    let _ = app.emit("preview:stopped", serde_json::json!({ "reason": reason }));
+}
 
-        let local = PathBuf::from(mount_point).join(relative_path.trim_start_matches('/'));
-        if local.exists() {
-            return Some(local);
-        }
+/// Kill the video's serve process if it is the one serving `url`, after playback
+/// of `url` failed. A serve process started since for other media is left alone.
+/// The serve source is kept so a retry can respawn it; stop or a new open clears it.
+fn release_serve_for(app: &AppHandle, url: &str) {
+    let Some(vlc) = app.try_state::<VlcManager>() else {
+        return;
+    };
+    let source = vlc.serve_source.lock().unwrap();
+    // A prefetched head is played through a proxy in front of the serve
+    let proxied = prefetch::stop_proxy_for(url);
+    let serves_url = source
+        .as_ref()
+        .is_some_and(|s| url.starts_with(&format!("http://127.0.0.1:{}/", s.port)));
+    if !serves_url && !proxied {
+        return;
+    }
+    if let Some(mut c) = vlc.serve_child.lock().unwrap().take() {
+        let _ = c.kill();
     }
-    None
 }
 
-#[cfg(not(target_os = "linux"))]
-fn find_fuse_local_path(_remote_name: &str, _relative_path: &str) -> Option<PathBuf> {
-    None
+/// Record the current position of `item_id` in the watch-progress store, as
+/// finished past `watched_fraction` of it.
+fn save_position(
+    app: &AppHandle,
+    player: &vlc::MediaPlayer,
+    item_id: Option<&str>,
+    watched_fraction: f64,
+    flush: bool,
+) {
+    let Some(id) = item_id else {
+        return;
+    };
+    let Some(time_ms) = player.get_time().filter(|&t| t >= 0) else {
+        return;
+    };
+    let duration_ms = player.get_media().and_then(|m| m.duration()).unwrap_or(0);
+    *LAST_POSITION.lock().unwrap() = Some((id.to_string(), time_ms));
+    if let Err(e) = progress::record_progress(app, id, time_ms, duration_ms, watched_fraction, flush) {
+        eprintln!("Failed to save watch progress: {}", e);
+    }
 }
 
-/// Poll until the TCP port is accepting connections (rclone serve http is ready).
-pub async fn wait_for_port(port: u16) -> Result<(), String> {
-    let deadline = std::time::Instant::now() + Duration::from_secs(10);
-    while std::time::Instant::now() < deadline {
-        if std::net::TcpStream::connect(format!("127.0.0.1:{}", port)).is_ok() {
-            return Ok(());
-        }
-        tokio::time::sleep(Duration::from_millis(100)).await;
+/// Emit `player:watched` for the current item once it has played past `threshold`
+/// of its duration. `emitted` keeps it to once per open.
+fn check_watched(
+    app: &AppHandle,
+    player: &vlc::MediaPlayer,
+    item_id: Option<&str>,
+    threshold: f64,
+    emitted: &mut bool,
+) {
+    if *emitted || item_id.is_none() {
+        return;
+    }
+    let time_ms = player.get_time().unwrap_or(0);
+    let duration_ms = player.get_media().and_then(|m| m.duration()).unwrap_or(0);
+    if progress::watched_at(time_ms, duration_ms, threshold) {
+        *emitted = true;
+        emit_watched(app, item_id);
     }
-    Err(format!(
-        "Timed out waiting for rclone serve on port {}",
-        port
-    ))
 }
 
-/// Extract the X11 window XID from the Tauri main window (Linux only).
-#[cfg(target_os = "linux")]
-fn get_window_xid(app: &AppHandle) -> Option<u32> {
-    use raw_window_handle::{HasWindowHandle, RawWindowHandle};
-    let window = app.get_webview_window("main")?;
-    let handle = window.window_handle().ok()?;
-    match handle.as_raw() {
-        RawWindowHandle::Xlib(h) => Some(h.window as u32),
-        RawWindowHandle::Xcb(h) => Some(h.window.get()),
-        _ => None,
+fn emit_watched(app: &AppHandle, item_id: Option<&str>) {
+    if let Some(id) = item_id {
+        let _ = app.emit("player:watched", serde_json::json!({ "item_id": id }));
     }
 }
 
-// ── Tauri commands ────────────────────────────────────────────────────────────
+/// Mark `item_id` as played to the end (VLC's time is unreliable once Ended).
+fn save_finished(app: &AppHandle, player: &vlc::MediaPlayer, item_id: Option<&str>) {
+    let Some(id) = item_id else {
+        return;
+    };
+    let duration_ms = player.get_media().and_then(|m| m.duration()).unwrap_or(0);
+    if duration_ms <= 0 {
+        return;
+    }
+    if let Err(e) = progress::record_progress(app, id, duration_ms, duration_ms, progress::watched_fraction(app), true) {
+        eprintln!("Failed to save watch progress: {}", e);
+    }
+}
 
-/// Resolve stream source (FUSE mount → local path, or rclone serve http → URL)
-/// then start VLC playback.
+fn player_status(player: &vlc::MediaPlayer) -> PlayerStatus {
+    let Some(media) = player.get_media() else {
+        return PlayerStatus::idle();
+    };
+    let state = match player.state() {
+        vlc::State::NothingSpecial => "idle",
+        vlc::State::Opening => "opening",
+        vlc::State::Buffering => "buffering",
+        vlc::State::Playing => "playing",
+        vlc::State::Paused => "paused",
+        vlc::State::Stopped => "stopped",
+        vlc::State::Ended => "ended",
+        vlc::State::Error => "error",
+    };
+    // vlc-rs doesn't wrap the track getters; -1 means disabled/none
+    let (audio_track, subtitle_track) = unsafe {
+        (
+            vlc::sys::libvlc_audio_get_track(player.raw()),
+            vlc::sys::libvlc_video_get_spu(player.raw()),
+        )
+    };
+    PlayerStatus {
+        state: state.into(),
+        time_ms: player.get_time().unwrap_or(0),
+        duration_ms: media.duration().unwrap_or(0),
+        volume: player.get_volume(),
+        muted: player.get_mute().unwrap_or(false),
+        rate: player.get_rate(),
+        audio_track: Some(audio_track).filter(|&id| id >= 0),
+        subtitle_track: Some(subtitle_track).filter(|&id| id >= 0),
+        // Lives in the queue; filled in by `player_get_status`
+        repeat: RepeatMode::Off,
+        // Live on the VLC thread; filled in when answering `GetStatus`
+        crossfade_ms: 0,
+        item_id: None,
+    }
+}
+
+/// Crop to "WxH+X+Y" (None clears the crop). vlc-rs doesn't wrap this.
+fn set_crop_geometry(player: &vlc::MediaPlayer, geometry: Option<&str>) {
+    let geometry = geometry.and_then(|g| std::ffi::CString::new(g).ok());
+    let ptr = geometry.as_ref().map_or(std::ptr::null(), |g| g.as_ptr());
+    unsafe { vlc::sys::libvlc_video_set_crop_geometry(player.raw(), ptr) };
+}
+
+/// Scale and zoom are per-item: new media starts fitted to the window, uncropped.
+fn reset_video_geometry(player: &vlc::MediaPlayer) {
+    unsafe { vlc::sys::libvlc_video_set_scale(player.raw(), 0.0) };
+    set_crop_geometry(player, None);
+}
+
+fn add_media_option(media: &vlc::Media, option: &str) {
+    if let Ok(opt) = std::ffi::CString::new(option) {
+        // vlc-rs doesn't wrap per-media options
+        unsafe { vlc::sys::libvlc_media_add_option(media.raw(), opt.as_ptr()) };
+    }
+}
+
+// Not wrapped by vlc-rs
+extern "C" {
+    fn libvlc_media_player_add_slave(
+        p_mi: *mut vlc::sys::libvlc_media_player_t,
+        i_type: std::os::raw::c_int,
+        psz_uri: *const std::os::raw::c_char,
+        b_select: bool,
+    ) -> std::os::raw::c_int;
+}
+
+/// Load the subtitle file at `path` into what `player` is playing, without
+/// reopening it. Returns whether libVLC accepted it.
+fn add_subtitle_slave(player: &vlc::MediaPlayer, path: &str, select: bool) -> bool {
+    const SLAVE_TYPE_SUBTITLE: std::os::raw::c_int = 0;
+    let Ok(uri) = std::ffi::CString::new(file_uri(path)) else {
+        return false;
+    };
+    unsafe { libvlc_media_player_add_slave(player.raw(), SLAVE_TYPE_SUBTITLE, uri.as_ptr(), select) == 0 }
+}
+
+/// "/dir/a b.srt" -> "file:///dir/a%20b.srt"; Windows paths get the extra slash.
+fn file_uri(path: &str) -> String {
+    let path = path.replace('\\', "/");
+    let encoded = percent_encode_path(&path);
+    if path.starts_with('/') {
+        format!("file://{}", encoded)
+    } else {
+        format!("file:///{}", encoded)
+    }
+}
+
+/// A fresh media for what `player` has loaded, for reopening it in place.
+/// Per-open options aren't carried over except `:no-video` for audio-only
+/// items, so an audio item doesn't pop up a video window.
+fn reopen_media(
+    app: &AppHandle,
+    instance: &vlc::Instance,
+    player: &vlc::MediaPlayer,
+    audio_only: bool,
+) -> Option<vlc::Media> {
+    let media = new_media(app, instance, &player.get_media()?.mrl()?)?;
+    if audio_only {
+        add_media_option(&media, ":no-video");
+    }
+    Some(media)
+}
+
+/// Create the media for a URL or local path, with a caching profile to match:
+/// `:network-caching` for http(s) streams, `:file-caching` for FUSE paths.
+/// The applied value is reported on `vlc:debug`.
+fn new_media(app: &AppHandle, instance: &vlc::Instance, url: &str) -> Option<vlc::Media> {
+    let is_network = url.starts_with("http://") || url.starts_with("https://");
+    // Reloads pass the media's MRL back in, so any URL scheme is a location
+    let media = if url.contains("://") {
+        vlc::Media::new_location(instance, url)
+    } else {
+        vlc::Media::new_path(instance, url)
+    }?;
+
+    let (option, key, default) = if is_network {
+        ("network-caching", NETWORK_CACHING_KEY, DEFAULT_NETWORK_CACHING_MS)
+    } else {
+        ("file-caching", FILE_CACHING_KEY, DEFAULT_FILE_CACHING_MS)
+    };
+    let caching_ms = app
+        .store(OPTIONS_STORE_PATH)
+        .ok()
+        .and_then(|s| s.get(key))
+        .and_then(|v| v.as_u64())
+        .unwrap_or(default);
+    add_media_option(&media, &format!(":{}={}", option, caching_ms));
+    for opt in subtitles::media_options(app) {
+        add_media_option(&media, &opt);
+    }
+    let _ = app.emit("vlc:debug", serde_json::json!({
+        "message": format!("Applied {} profile: {}={}ms", if is_network { "http" } else { "fuse" }, option, caching_ms),
+        "option": option,
+        "cachingMs": caching_ms,
+    }));
+
+    Some(media)
+}
+
+/// Build the `vlc:tracks` payload from the media's elementary streams.
+/// Returns None until VLC has parsed the media and reported at least one track.
+fn collect_tracks(media: &vlc::Media) -> Option<serde_json::Value> {
+    let tracks = media.tracks()?;
+    if tracks.is_empty() {
+        return None;
+    }
+
+    let mut audio = Vec::new();
+    let mut subtitle = Vec::new();
+    let mut video = Vec::new();
+
+    for t in &tracks {
+        let codec = fourcc_to_string(t.codec);
+        match &t.type_specific_data {
+            vlc::MediaTrackUnion::Audio(a) => audio.push(serde_json::json!({
+                "id": t.id,
+                "codec": codec,
+                "language": t.language,
+                "description": t.description,
+                "channels": a.channels,
+                "rate": a.rate,
+            })),
+            vlc::MediaTrackUnion::Subtitle(s) => subtitle.push(serde_json::json!({
+                "id": t.id,
+                "codec": codec,
+                "language": t.language,
+                "description": t.description,
+                "encoding": s.encoding,
+            })),
+            vlc::MediaTrackUnion::Video(v) => video.push(serde_json::json!({
+                "id": t.id,
+                "codec": codec,
+                "language": t.language,
+                "description": t.description,
+                "width": v.width,
+                "height": v.height,
+            })),
+            vlc::MediaTrackUnion::None => {}
+        }
+    }
+
+    Some(serde_json::json!({ "audio": audio, "subtitle": subtitle, "video": video }))
+}
+
+/// Build the `vlc:meta` payload from the tags VLC read (ID3, Vorbis comments, MP4 atoms).
+/// Missing fields are empty strings so video files still produce a well-formed event.
+fn collect_meta(media: &vlc::Media) -> serde_json::Value {
+    let get = |meta: vlc::Meta| media.get_meta(meta).unwrap_or_default();
+    serde_json::json!({
+        "title": get(vlc::Meta::Title),
+        "artist": get(vlc::Meta::Artist),
+        "album": get(vlc::Meta::Album),
+        "track_number": get(vlc::Meta::TrackNumber),
+        "genre": get(vlc::Meta::Genre),
+        "artwork_url": get(vlc::Meta::ArtworkURL),
+    })
+}
+
+/// VLC reports codecs as little-endian FourCCs, e.g. 0x34363268 → "h264".
+fn fourcc_to_string(fourcc: u32) -> String {
+    fourcc
+        .to_le_bytes()
+        .iter()
+        .map(|&b| if b.is_ascii_graphic() { b as char } else { ' ' })
+        .collect::<String>()
+        .trim()
+        .to_string()
+}
+
+// ── Helpers ───────────────────────────────────────────────────────────────────
+
+pub fn rclone_binary(app: &AppHandle) -> PathBuf {
+    let resource_dir = app
+        .path()
+        .resource_dir()
+        .unwrap_or_else(|_| PathBuf::from("."));
+    let s = resource_dir.join("rclone");
+    if s.exists() { s } else { PathBuf::from("rclone") }
+}
+
+/// Parse "remote:/sub/path" into ("remote", "/sub/path").
+pub fn parse_remote_root(remote_root: &str) -> (&str, &str) {
+    if let Some(pos) = remote_root.find(':') {
+        let name = &remote_root[..pos];
+        let path = &remote_root[pos + 1..];
+        (name, if path.is_empty() { "/" } else { path })
+    } else {
+        (remote_root, "/")
+    }
+}
+
+/// Percent-encode a relative file path, encoding each segment but preserving '/'.
+///
+/// The input must be the raw, decoded path exactly as rclone lists it (e.g. from
+/// `lsjson`), never a path that has already been URL-encoded. Every byte outside
+/// the RFC 3986 unreserved set is encoded, including '%' itself, so
+/// `50% Off.mkv` becomes `50%25%20Off.mkv` and rclone serve decodes it back to the
+/// real filename. Treating an existing `%XX` as pre-encoded would make a file
+/// literally named `100%25.mkv` unreachable, so the function deliberately does not
+/// try to be idempotent. `#`, `?` and `&` are encoded too, so they can't be
+/// mistaken for a fragment or query string; non-ASCII names (e.g. Japanese) are
+/// encoded as their UTF-8 bytes.
+pub fn percent_encode_path(path: &str) -> String {
+    const UNRESERVED: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789-_.~";
+
+    path.split('/')
+        .map(|seg| {
+            let mut out = String::with_capacity(seg.len() * 3);
+            for &byte in seg.as_bytes() {
+                if UNRESERVED.contains(&byte) {
+                    out.push(byte as char);
+                } else {
+                    out.push_str(&format!("%{:02X}", byte));
+                }
+            }
+            out
+        })
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+/// Undo the octal escapes /proc/mounts uses for whitespace and backslashes in
+/// its fields, e.g. `My\040Drive` -> `My Drive`.
+#[cfg(target_os = "linux")]
+fn unescape_mount_field(field: &str) -> String {
+    let bytes = field.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let digits = bytes.get(i + 1..i + 4).filter(|d| d.iter().all(|b| (b'0'..=b'7').contains(b)));
+        match digits {
+            Some(d) if bytes[i] == b'\\' => {
+                out.push(d.iter().fold(0u8, |acc, b| acc.wrapping_mul(8).wrapping_add(b - b'0')));
+                i += 4;
+            }
+            _ => {
+                out.push(bytes[i]);
+                i += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&out).into_owned()
+}
+
+/// A FUSE mount as listed in /proc/mounts.
+#[derive(Debug, Serialize, Clone)]
+pub struct FuseMount {
+    pub device: String,
+    pub mount_point: String,
+    pub fs_type: String,
+}
+
+impl FuseMount {
+    /// Mounts made by `rclone mount` report fuse.rclone; older kernels only
+    /// report fuse, in which case the "remote:" device gives it away.
+    fn is_rclone(&self) -> bool {
+        self.fs_type == "fuse.rclone" || (self.fs_type == "fuse" && self.device.contains(':'))
+    }
+}
+
+/// Parse the FUSE entries out of /proc/mounts.
+#[cfg(target_os = "linux")]
+fn read_fuse_mounts() -> Vec<FuseMount> {
+    std::fs::read_to_string("/proc/mounts")
+        .map(|mounts| parse_fuse_mounts(&mounts))
+        .unwrap_or_default()
+}
+
+#[cfg(target_os = "linux")]
+fn parse_fuse_mounts(mounts: &str) -> Vec<FuseMount> {
+    mounts
+        .lines()
+        .filter_map(|line| {
+            let mut parts = line.split_whitespace();
+            let device = unescape_mount_field(parts.next()?);
+            let mount_point = unescape_mount_field(parts.next()?);
+            let fs_type = parts.next()?;
+            fs_type.contains("fuse").then(|| FuseMount {
+                device,
+                mount_point,
+                fs_type: fs_type.to_string(),
+            })
+        })
+        .collect()
+}
+
+#[cfg(not(target_os = "linux"))]
+fn read_fuse_mounts() -> Vec<FuseMount> {
+    Vec::new()
+}
+
+/// `dvd://` or `bluray://` MRL for a local disc folder: the folder holding
+/// VIDEO_TS or BDMV, or one of those folders itself. None for anything else,
+/// including .iso images, which VLC opens as plain files.
+fn disc_mrl(path: &std::path::Path) -> Option<String> {
+    if !path.is_dir() {
+        return None;
+    }
+    let named = |p: &std::path::Path, name: &str| {
+        p.file_name()
+            .is_some_and(|n| n.to_string_lossy().eq_ignore_ascii_case(name))
+    };
+    let has_subdir = |name: &str| {
+        std::fs::read_dir(path)
+            .map(|entries| entries.flatten().any(|e| named(&e.path(), name) && e.path().is_dir()))
+            .unwrap_or(false)
+    };
+    let (scheme, root) = if has_subdir("VIDEO_TS") {
+        ("dvd", path)
+    } else if has_subdir("BDMV") {
+        ("bluray", path)
+    } else if named(path, "VIDEO_TS") {
+        ("dvd", path.parent()?)
+    } else if named(path, "BDMV") {
+        ("bluray", path.parent()?)
+    } else {
+        return None;
+    };
+    let root = root.to_string_lossy().replace('\\', "/");
+    Some(format!("{}:///{}", scheme, percent_encode_path(root.trim_start_matches('/'))))
+}
+
+/// Check the FUSE mounts for an active rclone mount of the remote. If found
+/// and the file exists locally, returns the local path. Mounts of other
+/// remotes never count, even if they hold a file at the same relative path.
+fn find_fuse_local_path(remote_name: &str, relative_path: &str) -> Option<PathBuf> {
+    let relative_path = relative_path.trim_start_matches('/');
+    read_fuse_mounts().into_iter().find_map(|mount| {
+        // Device field is "remote_name:" for rclone mounts, or "remote_name:sub/path"
+        // when only part of the remote is mounted
+        let (device_name, device_path) = mount.device.split_once(':')?;
+        if !device_name.eq_ignore_ascii_case(remote_name) {
+            return None;
+        }
+        let local = PathBuf::from(&mount.mount_point).join(path_within_mount(device_path, relative_path)?);
+        local.exists().then_some(local)
+    })
+}
+
+/// Where `relative_path` (from the remote's root) sits inside a mount of the
+/// remote's `device_path` folder, or None if the mount doesn't contain it.
+fn path_within_mount<'a>(device_path: &str, relative_path: &'a str) -> Option<&'a str> {
+    let device_path = device_path.trim_matches('/');
+    if device_path.is_empty() || relative_path == device_path {
+        Some(relative_path.strip_prefix(device_path).unwrap_or(relative_path))
+    } else {
+        relative_path
+            .strip_prefix(device_path)
+            .and_then(|rest| rest.strip_prefix('/'))
+    }
+}
+
+/// List active rclone FUSE mounts so the UI can show which libraries play
+/// from a local mount rather than on-demand HTTP. Always empty off Linux.
+#[tauri::command]
+pub fn list_rclone_mounts() -> Vec<FuseMount> {
+    read_fuse_mounts().into_iter().filter(FuseMount::is_rclone).collect()
+}
+
+/// Poll until a serve process that was just spawned accepts connections on
+/// `port` (rclone serve http is ready). A process that exits first (bad
+/// config, port taken, broken binary) fails right away rather than after the
+/// timeout; on any failure the process is killed and reaped.
+async fn wait_for_serve(child: &mut Child, port: u16) -> Result<(), AppError> {
+    wait_for_serve_on(child, serve_addr(port, None)).await
+}
+
+/// `wait_for_serve` for a process listening on `addr`.
+async fn wait_for_serve_on(child: &mut Child, addr: std::net::SocketAddr) -> Result<(), AppError> {
+    let port = addr.port();
+    let deadline = std::time::Instant::now() + Duration::from_secs(10);
+    while std::time::Instant::now() < deadline {
+        if std::net::TcpStream::connect(addr).is_ok() {
+            return Ok(());
+        }
+        if let Ok(Some(status)) = child.try_wait() {
+            return Err(AppError::rclone_failed(format!(
+                "rclone serve exited before it was ready ({})",
+                status
+            )));
+        }
+        tokio::time::sleep(Duration::from_millis(100)).await;
+    }
+    let _ = child.kill();
+    let _ = child.wait();
+    Err(AppError::timeout(format!(
+        "Timed out waiting for rclone serve on port {}",
+        port
+    )))
+}
+
+/// Native surface VLC renders video into. Pointers are kept as integers so the
+/// command can be sent to the VLC thread.
+#[derive(Debug, Clone, Copy)]
+enum NativeWindow {
+    /// X11 drawable (Linux)
+    Xid(u32),
+    /// Win32 HWND (Windows)
+    Hwnd(usize),
+    /// NSView (macOS)
+    NsView(usize),
+}
+
+/// Native handle of the Tauri main window for VLC to render into. None on
+/// Wayland, where libVLC can't embed, so VLC opens its own window.
+fn get_window_handle(app: &AppHandle) -> Option<NativeWindow> {
+    use raw_window_handle::{HasWindowHandle, RawWindowHandle};
+    let window = app.get_webview_window("main")?;
+    let handle = window.window_handle().ok()?;
+    match handle.as_raw() {
+        RawWindowHandle::Xlib(h) => Some(NativeWindow::Xid(h.window as u32)),
+        RawWindowHandle::Xcb(h) => Some(NativeWindow::Xid(h.window.get())),
+        RawWindowHandle::Win32(h) => Some(NativeWindow::Hwnd(h.hwnd.get() as usize)),
+        RawWindowHandle::AppKit(h) => Some(NativeWindow::NsView(h.ns_view.as_ptr() as usize)),
+        _ => None,
+    }
+}
+
+// ── Tauri commands ────────────────────────────────────────────────────────────
+
+/// Resolve stream source (offline download or FUSE mount → local path, or rclone
+/// serve http → URL)
+/// then start VLC playback. Opening a single file replaces any active queue.
+/// Playback position is saved to the watch-progress store under `media_id` as it
+/// plays (flushed to disk every 10 s) and marked finished when it ends.
+/// `bwlimit` caps the serve process's throughput for metered connections; a cap
+/// below the file's bitrate means rebuffering, so it trades smoothness for the cap.
+/// `vfs_cache_mode` ("minimal", "writes", "full") turns on rclone's VFS cache for
+/// the serve process, capped at `vfs_cache_max_size`; with "full", seeking back
+/// within already-fetched parts of a large file is served from disk.
 #[tauri::command]
 pub async fn open_media(
     app: AppHandle,
     vlc: State<'_, VlcManager>,
     config_path: String,
-    remote_root: String, // e.g. "gdrive:/Movies"
-    file_path: String,   // relative path within remote_root
-    start_ms: i64,       // resume position in milliseconds
-) -> Result<(), String> {
+    remote_root: String,   // e.g. "gdrive:/Movies"
+    file_path: String,     // relative path within remote_root
+    start_ms: Option<i64>, // resume position in ms; None uses the stored progress
+    series_key: Option<String>, // remembers track choices across a show's episodes
+    media_id: Option<String>,   // watch-progress key; None uses the library item id
+    bwlimit: Option<String>,    // rclone --bwlimit for the serve process, e.g. "5M"
+    audio_only: Option<bool>,   // no video output; the window isn't claimed
+    vfs_cache_mode: Option<String>,     // rclone --vfs-cache-mode; None caches nothing
+    vfs_cache_max_size: Option<String>, // rclone --vfs-cache-max-size, e.g. "20G"
+    title: Option<String>,      // shown in Discord presence; None uses the file's tags
+    disc: Option<bool>,         // DVD/Blu-ray folder or disc image (`DiscoveredFile::disc`)
+) -> Result<(), AppError> {
+    clear_queue(&app, &vlc);
+    let item = QueueItem {
+        remote_root,
+        file_path,
+        start_ms,
+        series_key,
+        media_id,
+        bwlimit,
+        vfs_cache_mode,
+        vfs_cache_max_size,
+        audio_only: audio_only.unwrap_or(false),
+        title,
+        disc: disc.unwrap_or(false),
+    };
+    open_source(&app, &vlc, &config_path, &item).await
+}
+
+/// Shared by `open_media` and the queue: resolve the source and send it to VLC.
+async fn open_source(
+    app: &AppHandle,
+    vlc: &VlcManager,
+    config_path: &str,
+    item: &QueueItem,
+) -> Result<(), AppError> {
+    let remote_root = item.remote_root.as_str();
+    let file_path = item.file_path.as_str();
+    let options = item.serve_options()?;
+    let item_id = item.media_id();
+    let start_ms = item
+        .start_ms
+        .unwrap_or_else(|| progress::resume_position(app, &item_id));
+    // No serve process for a player that can't exist
+    vlc.wait_ready().await?;
+
     // Kill any existing rclone serve process first
     {
         let mut guard = vlc.serve_child.lock().unwrap();
@@ -363,13 +2744,13 @@ pub async fn open_media(
             let _ = c.kill();
         }
     }
+    *vlc.serve_source.lock().unwrap() = None;
 
-    // Get window XID before any async work (borrows are short-lived)
-    #[cfg(target_os = "linux")]
-    let xid = get_window_xid(&app);
+    // Get the window handle before any async work (borrows are short-lived)
+    let window = get_window_handle(app);
 
     // Build the full relative path from remote root + file path
-    let (remote_name, root_sub_path) = parse_remote_root(&remote_root);
+    let (remote_name, root_sub_path) = parse_remote_root(remote_root);
     let full_relative = format!(
         "{}/{}",
         root_sub_path.trim_matches('/'),
@@ -377,107 +2758,1033 @@ pub async fn open_media(
     );
     let full_relative = full_relative.trim_start_matches('/').to_string();
 
-    // 1. Try FUSE mount (zero-overhead, full seeking support)
-    let url = if let Some(local_path) = find_fuse_local_path(remote_name, &full_relative) {
-        local_path.to_string_lossy().into_owned()
+    // Whatever was prefetched for the previous item is done with
+    prefetch::cancel();
+    prefetch::stop_proxy();
+
+    // 1. Prefer a completed offline download (works without any connectivity)
+    // 2. Then a FUSE mount (zero-overhead, full seeking support)
+    // 3. Then a copy prefetched while the previous queue item played
+    let local = downloads::completed_path(app, remote_root, file_path)
+        .or_else(|| find_fuse_local_path(remote_name, &full_relative))
+        .or_else(|| prefetch::cached(app, remote_root, file_path));
+    let disc = item.disc || file_path.to_lowercase().ends_with(".iso");
+    let url = if let Some(local_path) = local {
+        disc_mrl(&local_path).unwrap_or_else(|| local_path.to_string_lossy().into_owned())
+    } else if disc {
+        // VLC reads discs with random access across many files, which a single
+        // HTTP stream can't provide
+        return Err(AppError::invalid(
+            "DVD/Blu-ray folders and disc images only play from a mounted remote (rclone mount) or a download",
+        ));
     } else {
-        // 2. Fall back to rclone serve http
+        // 4. Fall back to rclone serve http
         let port = portpicker::pick_unused_port().ok_or("No available port")?;
-        let rclone = rclone_binary(&app);
 
         let _ = app.emit(
             "rclone:status",
             serde_json::json!({ "state": "starting", "message": "Connecting to remote…" }),
         );
 
-        let child = Command::new(&rclone)
-            .args([
-                "serve",
-                "http",
-                "--config",
-                &config_path,
-                "--addr",
-                &format!("127.0.0.1:{}", port),
-                "--read-only",
-                "--no-checksum",
-                "--allow-origin",
-                "*",
-                &remote_root,
-            ])
-            .spawn()
-            .map_err(|e| format!("Failed to start rclone serve: {}", e))?;
+        // While casting, the renderer fetches the stream over the LAN
+        let lan = vlc.cast_serve.lock().unwrap().clone();
+        let mut child = spawn_serve(app, config_path, remote_root, port, &options, lan.as_ref())?;
+
+        // Wait until rclone's HTTP server is accepting connections
+        wait_for_serve_on(&mut child, serve_addr(port, lan.as_ref())).await?;
+
+        let _ = app.emit(
+            "rclone:status",
+            serde_json::json!({ "state": "ready", "message": "Stream ready" }),
+        );
+
+        {
+            let mut guard = vlc.serve_child.lock().unwrap();
+            *guard = Some(child);
+        }
+        *vlc.serve_source.lock().unwrap() = Some(ServeSource {
+            config_path: config_path.to_string(),
+            remote_root: remote_root.to_string(),
+            file_path: file_path.to_string(),
+            options,
+            port,
+        });
+
+        // rclone serve http uses remote_root as its root, so the URL path
+        // is just file_path (relative to remote_root), not full_relative.
+        let direct = serve_url(port, lan.as_ref(), file_path);
+
+        // 5. Start from a head prefetched while the previous item played, with
+        // the rest streamed from the serve behind it
+        match prefetch::warm_head(app, remote_root, file_path).filter(|_| lan.is_none()) {
+            Some(head) => match prefetch::serve_head(head, &direct, file_path).await {
+                Ok(proxied) => proxied,
+                Err(e) => {
+                    eprintln!("Warning: streaming without the prefetched head: {}", e);
+                    direct
+                }
+            },
+            None => direct,
+        }
+    };
+
+    // Tell VLC which window to render into (must be sent before Open).
+    // Audio-only items leave the window alone; the next video item sets it again.
+    if let Some(window) = window.filter(|_| !item.audio_only) {
+        let _ = vlc.send(VlcCmd::SetWindow(window));
+    }
+
+    let open = VlcCmd::Open {
+        url,
+        start_ms,
+        item_id: Some(item_id),
+        series_key: item.series_key.clone(),
+        audio_only: item.audio_only,
+    };
+    if !vlc.send(open) {
+        prefetch::stop_proxy();
+        *vlc.serve_source.lock().unwrap() = None;
+        if let Some(mut c) = vlc.serve_child.lock().unwrap().take() {
+            let _ = c.kill();
+        }
+        return Err(vlc.unavailable());
+    }
+    discord::now_playing(item.title.clone(), item.audio_only);
+    Ok(())
+}
+
+/// Check an optional rclone `--bwlimit` value; empty counts as unlimited.
+fn validate_bwlimit(bwlimit: Option<&str>) -> Result<Option<&str>, AppError> {
+    match bwlimit.map(str::trim).filter(|b| !b.is_empty()) {
+        Some(b) if b.starts_with('-') => Err(AppError::invalid(format!("Invalid bandwidth limit: {}", b))),
+        other => Ok(other),
+    }
+}
+
+/// Start `rclone serve http` for `remote_root` on `port` with `options`, on
+/// loopback, or with `lan` on the LAN address behind basic auth.
+fn spawn_serve(
+    app: &AppHandle,
+    config_path: &str,
+    remote_root: &str,
+    port: u16,
+    options: &ServeOptions,
+    lan: Option<&LanServe>,
+) -> Result<Child, AppError> {
+    let mut command = Command::new(rclone_binary(app));
+    command.envs(rclone::config_env(config_path));
+    command.args([
+        "serve",
+        "http",
+        "--config",
+        config_path,
+        "--addr",
+        &serve_addr(port, lan).to_string(),
+        "--read-only",
+        "--no-checksum",
+        "--allow-origin",
+        "*",
+    ]);
+    if let Some(bwlimit) = &options.bwlimit {
+        command.args(["--bwlimit", bwlimit]);
+    }
+    if let Some(mode) = &options.vfs_cache_mode {
+        command.args(["--vfs-cache-mode", mode]);
+    }
+    if let Some(size) = &options.vfs_cache_max_size {
+        command.args(["--vfs-cache-max-size", size]);
+    }
+    if let Some(lan) = lan {
+        // Through the environment so the password isn't in the process list
+        command.args(["--user", &lan.user]).env("RCLONE_PASS", &lan.pass);
+    }
+    command.arg(remote_root).spawn().map_err(AppError::rclone_spawn)
+}
+
+/// Restart the video's serve process, if there is one, on its port: on the LAN
+/// with `lan`'s credentials, or on loopback with None. Returns the URL the
+/// current media is now reachable at.
+async fn rebind_serve(app: &AppHandle, vlc: &VlcManager, lan: Option<&LanServe>) -> Result<Option<String>, AppError> {
+    let Some(source) = vlc.serve_source.lock().unwrap().clone() else {
+        return Ok(None);
+    };
+    // The port has to be free before the new process can bind it
+    if let Some(mut old) = vlc.serve_child.lock().unwrap().take() {
+        let _ = old.kill();
+        let _ = old.wait();
+    }
+    let mut child = spawn_serve(
+        app,
+        &source.config_path,
+        &source.remote_root,
+        source.port,
+        &source.options,
+        lan,
+    )?;
+    wait_for_serve_on(&mut child, serve_addr(source.port, lan)).await?;
+    *vlc.serve_child.lock().unwrap() = Some(child);
+    Ok(Some(serve_url(source.port, lan, &source.file_path)))
+}
+
+/// Recover a stalled stream: restart the video's serve process if it died (on
+/// its old port, so the media URL stays valid), then have the VLC thread reopen
+/// the media at the last known position.
+async fn recover_stream(app: &AppHandle, vlc: &VlcManager, manual: bool) -> Result<(), AppError> {
+    let source = vlc.serve_source.lock().unwrap().clone();
+    if let Some(source) = source {
+        let alive = vlc
+            .serve_child
+            .lock()
+            .unwrap()
+            .as_mut()
+            .is_some_and(|c| matches!(c.try_wait(), Ok(None)));
+        if !alive {
+            let _ = app.emit(
+                "rclone:status",
+                serde_json::json!({ "state": "starting", "message": "Reconnecting to remote…" }),
+            );
+            let lan = vlc.cast_serve.lock().unwrap().clone();
+            let mut child = spawn_serve(
+                app,
+                &source.config_path,
+                &source.remote_root,
+                source.port,
+                &source.options,
+                lan.as_ref(),
+            )?;
+            if let Some(mut old) = vlc.serve_child.lock().unwrap().take() {
+                let _ = old.kill();
+            }
+            wait_for_serve_on(&mut child, serve_addr(source.port, lan.as_ref())).await?;
+            *vlc.serve_child.lock().unwrap() = Some(child);
+            let _ = app.emit(
+                "rclone:status",
+                serde_json::json!({ "state": "ready", "message": "Stream ready" }),
+            );
+        }
+    }
+    if !vlc.send(VlcCmd::Reconnect { manual }) {
+        return Err(vlc.unavailable());
+    }
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn player_play(vlc: State<'_, VlcManager>) -> Result<(), AppError> {
+    let _ = vlc.send(VlcCmd::Play);
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn player_pause(vlc: State<'_, VlcManager>) -> Result<(), AppError> {
+    let _ = vlc.send(VlcCmd::Pause);
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn player_seek(vlc: State<'_, VlcManager>, ms: i64) -> Result<(), AppError> {
+    let _ = vlc.send(VlcCmd::Seek(ms));
+    Ok(())
+}
+
+/// Skip forward/backward by `delta_ms` from VLC's authoritative current time,
+/// clamped to [0, duration]. Used for ±10 s / ±30 s skip buttons.
+#[tauri::command]
+pub async fn player_seek_relative(vlc: State<'_, VlcManager>, delta_ms: i64) -> Result<(), AppError> {
+    let _ = vlc.send(VlcCmd::SeekRelative(delta_ms));
+    Ok(())
+}
+
+/// Seek to `pos` (0.0-1.0) of the media. Unlike `player_seek` this works while
+/// the duration is still unknown, as on HTTP streams that haven't been probed.
+#[tauri::command]
+pub async fn player_seek_fraction(vlc: State<'_, VlcManager>, pos: f32) -> Result<(), AppError> {
+    if pos.is_nan() {
+        return Err(AppError::invalid("Seek position must be a number"));
+    }
+    let _ = vlc.send(VlcCmd::SeekFraction(pos.clamp(0.0, 1.0)));
+    Ok(())
+}
+
+/// Ask the VLC thread for its current state, time, volume and selected tracks.
+/// Lets the UI restore itself after a reload without waiting for the next event.
+#[tauri::command]
+pub async fn player_get_status(vlc: State<'_, VlcManager>) -> Result<PlayerStatus, AppError> {
+    let mut status = ask_vlc_thread(&vlc, VlcCmd::GetStatus).await?;
+    status.repeat = vlc.queue.lock().unwrap().repeat;
+    Ok(status)
+}
+
+/// Have the VLC thread look up the loaded media's skip segments again.
+pub(crate) fn reload_skip_segments(vlc: &VlcManager) {
+    let _ = vlc.send(VlcCmd::ReloadSkipSegments);
+}
+
+/// Seek to the end of the skip segment playback is in (after
+/// `player:segment-entered`). Returns that segment, or None when not in one.
+#[tauri::command]
+pub async fn player_skip_current_segment(vlc: State<'_, VlcManager>) -> Result<Option<SkipSegment>, AppError> {
+    ask_vlc_thread(&vlc, VlcCmd::SkipSegment).await
+}
+
+/// Choose whether segments of `kind` are skipped without asking
+/// (`player:segment-skipped`) instead of offered (`player:segment-entered`).
+/// Persisted; returns the kinds now auto-skipped.
+#[tauri::command]
+pub async fn player_set_segment_auto_skip(
+    app: AppHandle,
+    vlc: State<'_, VlcManager>,
+    kind: SegmentKind,
+    enabled: bool,
+) -> Result<Vec<SegmentKind>, AppError> {
+    let mut kinds = segments::auto_skip_kinds(&app);
+    kinds.retain(|&k| k != kind);
+    if enabled {
+        kinds.push(kind);
+    }
+    store::set_setting(&app, segments::AUTO_SKIP_SETTING, serde_json::json!(kinds))?;
+    let _ = vlc.send(VlcCmd::SetAutoSkip(kinds.clone()));
+    Ok(kinds)
+}
+
+/// Seek to `ms` if `item_id` is the loaded media and hasn't stopped or ended.
+/// Returns whether it seeked.
+pub(crate) async fn seek_if_loaded(vlc: &VlcManager, item_id: &str, ms: i64) -> Result<bool, AppError> {
+    let status = ask_vlc_thread(vlc, VlcCmd::GetStatus).await?;
+    let loaded = status.item_id.as_deref() == Some(item_id)
+        && matches!(status.state.as_str(), "opening" | "buffering" | "playing" | "paused");
+    if loaded {
+        let _ = vlc.send(VlcCmd::Seek(ms));
+    }
+    Ok(loaded)
+}
+
+/// Send a command carrying a reply channel and wait (briefly) for the answer.
+async fn ask_vlc_thread<T>(
+    vlc: &VlcManager,
+    cmd: impl FnOnce(oneshot::Sender<T>) -> VlcCmd,
+) -> Result<T, AppError> {
+    let (tx, rx) = oneshot::channel();
+    if !vlc.send(cmd(tx)) {
+        return Err(vlc.unavailable());
+    }
+    match tokio::time::timeout(Duration::from_millis(500), rx).await {
+        Ok(Ok(value)) => Ok(value),
+        Ok(Err(_)) => Err("VLC thread exited before replying".into()),
+        Err(_) => Err(AppError::timeout("Timed out waiting for the VLC thread")),
+    }
+}
+
+/// Start looking for Chromecasts and other renderers on the network. Returns the
+/// renderers already known; later ones arrive as `player:renderer-found` and
+/// disappear with `player:renderer-lost`.
+#[tauri::command]
+pub async fn player_discover_renderers(
+    vlc: State<'_, VlcManager>,
+) -> Result<Vec<RendererInfo>, AppError> {
+    Ok(ask_vlc_thread(&vlc, VlcCmd::DiscoverRenderers).await??)
+}
+
+/// Cast the current and following media to a renderer from `player_discover_renderers`.
+/// None returns to local output. Current playback restarts on the new output at
+/// the same position.
+///
+/// The renderer fetches `rclone serve http` streams itself, so while casting the
+/// video's serve process listens on the LAN address with basic auth; returning
+/// to local output moves it back to loopback.
+#[tauri::command]
+pub async fn player_set_renderer(
+    app: AppHandle,
+    vlc: State<'_, VlcManager>,
+    renderer_id: Option<String>,
+) -> Result<(), AppError> {
+    let previous = vlc.cast_serve.lock().unwrap().clone();
+    let lan = match (&renderer_id, &previous) {
+        (None, _) => None,
+        // Switching between renderers keeps the running server
+        (Some(_), Some(lan)) => Some(lan.clone()),
+        (Some(_), None) => Some(LanServe::new()?),
+    };
+    let rebind = lan.is_some() != previous.is_some();
+    let url = if rebind {
+        match rebind_serve(&app, &vlc, lan.as_ref()).await {
+            Ok(url) => url,
+            Err(e) => {
+                let _ = rebind_serve(&app, &vlc, previous.as_ref()).await;
+                return Err(e);
+            }
+        }
+    } else {
+        None
+    };
+    *vlc.cast_serve.lock().unwrap() = lan;
+
+    let result = ask_vlc_thread(&vlc, |reply| VlcCmd::SetRenderer { id: renderer_id, url, reply })
+        .await
+        .and_then(|applied| applied.map_err(AppError::from));
+    if result.is_err() && rebind {
+        // The player still reads from the old address
+        *vlc.cast_serve.lock().unwrap() = previous.clone();
+        let _ = rebind_serve(&app, &vlc, previous.as_ref()).await;
+    }
+    result
+}
+
+/// The last 500 libVLC log lines (at or above the current log level), oldest first.
+#[tauri::command]
+pub async fn get_player_logs(vlc: State<'_, VlcManager>) -> Result<Vec<LogLine>, AppError> {
+    ask_vlc_thread(&vlc, VlcCmd::GetLogs).await
+}
+
+/// Set the minimum libVLC log level captured: "debug", "notice", "warning" or "error".
+/// Takes effect immediately for new log lines.
+#[tauri::command]
+pub async fn player_set_log_level(vlc: State<'_, VlcManager>, level: String) -> Result<(), AppError> {
+    let level = parse_log_level(&level).ok_or_else(|| AppError::invalid(format!("Unknown log level: {}", level)))?;
+    let _ = vlc.send(VlcCmd::SetLogLevel(level));
+    Ok(())
+}
+
+/// Set the fraction of an item (0-1], default 0.9) after which `player:watched`
+/// is emitted for it and its progress is stored as finished. Persisted.
+#[tauri::command]
+pub async fn player_set_watched_threshold(
+    app: AppHandle,
+    vlc: State<'_, VlcManager>,
+    threshold: f64,
+) -> Result<(), AppError> {
+    if !(threshold > 0.0 && threshold <= 1.0) {
+        return Err(AppError::invalid(format!("Watched threshold must be in (0, 1], got {}", threshold)));
+    }
+    store::set_setting(&app, progress::WATCHED_THRESHOLD_SETTING, serde_json::json!(threshold))?;
+    let _ = vlc.send(VlcCmd::SetWatchedThreshold(threshold));
+    Ok(())
+}
+
+/// Load a local subtitle file (e.g. from `opensubtitles_download`) into the
+/// current media and select it.
+#[tauri::command]
+pub async fn player_add_subtitle(vlc: State<'_, VlcManager>, path: String) -> Result<(), AppError> {
+    if !std::path::Path::new(&path).is_file() {
+        return Err(AppError::invalid(format!("Subtitle file not found: {}", path)));
+    }
+    let _ = vlc.send(VlcCmd::AddSubtitle(path));
+    Ok(())
+}
+
+/// Play a short audio preview of a file (e.g. on hover) on a second player,
+/// independent of the main one: it reports through `preview:state`,
+/// `preview:stopped` and `preview:error`, and is stopped whenever the main player
+/// opens media. Video is disabled because the preview has no window to draw in.
+#[tauri::command]
+pub async fn preview_open(
+    app: AppHandle,
+    vlc: State<'_, VlcManager>,
+    config_path: String,
+    remote_root: String,
+    file_path: String,
+    start_ms: Option<i64>,
+) -> Result<(), AppError> {
+    let url = resolve_shared_source(
+        &app,
+        &vlc,
+        &config_path,
+        &remote_root,
+        &file_path,
+        PREVIEW_SESSION,
+        &ServeOptions::default(),
+    )
+    .await?;
+    if !vlc.send(VlcCmd::PreviewOpen { url, start_ms: start_ms.unwrap_or(0) }) {
+        return Err(vlc.unavailable());
+    }
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn preview_stop(vlc: State<'_, VlcManager>) -> Result<(), AppError> {
+    let _ = vlc.send(VlcCmd::PreviewStop);
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn preview_set_volume(vlc: State<'_, VlcManager>, vol: i32) -> Result<(), AppError> {
+    let _ = vlc.send(VlcCmd::PreviewSetVolume(vol.clamp(0, 100)));
+    Ok(())
+}
+
+/// Reconnect the current stream at its last position, e.g. after automatic stall
+/// recovery gave up. Resets the automatic retry count.
+#[tauri::command]
+pub async fn player_retry_stream(app: AppHandle, vlc: State<'_, VlcManager>) -> Result<(), AppError> {
+    recover_stream(&app, &vlc, true).await
+}
+
+/// Seconds without playback progress before `player:stalled` fires and a
+/// reconnect is attempted (default 20); 0 disables stall detection. Persisted.
+#[tauri::command]
+pub async fn player_set_stall_timeout(
+    app: AppHandle,
+    vlc: State<'_, VlcManager>,
+    secs: u64,
+) -> Result<(), AppError> {
+    store::set_setting(&app, STALL_TIMEOUT_SETTING, serde_json::json!(secs))?;
+    let _ = vlc.send(VlcCmd::SetStallTimeout(Duration::from_secs(secs)));
+    Ok(())
+}
+
+/// Loudness normalization, applied through libVLC's `normvol` audio filter.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+pub struct Normalization {
+    pub enabled: bool,
+    /// Approximate target loudness; `normvol` has no LUFS meter, so this only
+    /// scales its maximum level relative to `DEFAULT_TARGET_LUFS`
+    pub target_lufs: f32,
+}
+
+impl Default for Normalization {
+    fn default() -> Self {
+        Normalization { enabled: false, target_lufs: DEFAULT_TARGET_LUFS }
+    }
+}
+
+impl Normalization {
+    fn instance_args(&self) -> Vec<String> {
+        if !self.enabled {
+            return Vec::new();
+        }
+        let max_level = 2.0 * 10f32.powf((self.target_lufs - DEFAULT_TARGET_LUFS) / 20.0);
+        vec![
+            "--audio-filter=normvol".to_string(),
+            format!("--norm-max-level={:.2}", max_level.clamp(0.5, 10.0)),
+        ]
+    }
+}
+
+fn load_normalization(app: &AppHandle) -> Normalization {
+    store::get_setting(app, NORMALIZATION_SETTING).unwrap_or_default()
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct NormalizationApplied {
+    pub normalization: Normalization,
+    /// The player was restarted to apply it
+    pub restarted: bool,
+    /// Still pending because something is playing; applied by a later
+    /// `player_set_normalization` call while idle, or on the next launch
+    pub requires_restart: bool,
+}
+
+/// Turn loudness normalization on or off; `target_lufs` (default -16) sets how
+/// loud it aims. Persisted. Audio filters are fixed when the VLC instance is
+/// created, so the player is restarted to apply a change, but only while
+/// nothing is loaded and no crossfade or preview is playing; otherwise the
+/// result reports `requires_restart`.
+#[tauri::command]
+pub async fn player_set_normalization(
+    app: AppHandle,
+    vlc: State<'_, VlcManager>,
+    enabled: bool,
+    target_lufs: Option<f32>,
+) -> Result<NormalizationApplied, AppError> {
+    let target_lufs = target_lufs.unwrap_or(DEFAULT_TARGET_LUFS);
+    if !(-40.0..=0.0).contains(&target_lufs) {
+        return Err(AppError::invalid(format!(
+            "Target loudness must be between -40 and 0 LUFS, got {}",
+            target_lufs
+        )));
+    }
+    let normalization = Normalization { enabled, target_lufs };
+    store::set_setting(&app, NORMALIZATION_SETTING, serde_json::json!(normalization))?;
+
+    let running = INSTANCE_NORMALIZATION.lock().unwrap().unwrap_or_default();
+    let changed = running.instance_args() != normalization.instance_args();
+    // A thread that failed to start counts as idle, so the restart retries it
+    let idle = ask_vlc_thread(&vlc, VlcCmd::IsIdle).await.unwrap_or(true);
+    let restarted = changed && idle;
+    if restarted {
+        vlc.restart_thread(&app);
+    }
+    Ok(NormalizationApplied {
+        normalization,
+        restarted,
+        requires_restart: changed && !restarted,
+    })
+}
+
+/// Pause playback after `minutes`; None or 0 disarms the timer. With
+/// `finish_chapter`, playback continues to the end of the chapter (or track)
+/// playing when the time is up. `player:sleep-timer-fired` is emitted on pause.
+#[tauri::command]
+pub async fn player_set_sleep_timer(
+    vlc: State<'_, VlcManager>,
+    minutes: Option<u32>,
+    finish_chapter: bool,
+) -> Result<(), AppError> {
+    let after = minutes
+        .filter(|&m| m > 0)
+        .map(|m| Duration::from_secs(u64::from(m) * 60));
+    if !vlc.send(VlcCmd::SetSleepTimer { after, finish_chapter }) {
+        return Err(vlc.unavailable());
+    }
+    Ok(())
+}
+
+/// The armed sleep timer and how long until it pauses playback, or None.
+#[tauri::command]
+pub async fn player_get_sleep_timer(
+    vlc: State<'_, VlcManager>,
+) -> Result<Option<SleepTimerStatus>, AppError> {
+    ask_vlc_thread(&vlc, VlcCmd::GetSleepTimer).await
+}
+
+/// Choose whether opening other media keeps an armed sleep timer (the
+/// default) or disarms it. Persisted.
+#[tauri::command]
+pub async fn player_set_sleep_timer_keep_on_open(app: AppHandle, enabled: bool) -> Result<(), AppError> {
+    store::set_setting(&app, SLEEP_TIMER_KEEP_ON_OPEN_SETTING, serde_json::json!(enabled))?;
+    Ok(())
+}
+
+/// Overlap consecutive audio-only queue items by `ms` (at most 8000), fading the
+/// outgoing track out and the next one in; 0 turns it off. Video items always
+/// cut. Persisted.
+#[tauri::command]
+pub async fn player_set_crossfade(
+    app: AppHandle,
+    vlc: State<'_, VlcManager>,
+    ms: u64,
+) -> Result<(), AppError> {
+    if ms > MAX_CROSSFADE_MS {
+        return Err(AppError::invalid(format!(
+            "Crossfade must be at most {} ms, got {}",
+            MAX_CROSSFADE_MS, ms
+        )));
+    }
+    store::set_setting(&app, CROSSFADE_SETTING, serde_json::json!(ms))?;
+    let _ = vlc.send(VlcCmd::SetCrossfade(Duration::from_millis(ms)));
+    Ok(())
+}
+
+/// Choose whether audio-only playback keeps the screen on. By default it only
+/// prevents system suspend and lets the screen blank; video always keeps it on.
+#[tauri::command]
+pub async fn player_set_audio_keeps_screen_on(
+    vlc: State<'_, VlcManager>,
+    enabled: bool,
+) -> Result<(), AppError> {
+    let _ = vlc.send(VlcCmd::SetAudioKeepsScreenOn(enabled));
+    Ok(())
+}
+
+/// Switch audio track by VLC track id (from `vlc:tracks`). The track's language
+/// is remembered for the current series.
+#[tauri::command]
+pub async fn player_set_audio_track(vlc: State<'_, VlcManager>, id: i32) -> Result<(), AppError> {
+    let _ = vlc.send(VlcCmd::SetAudioTrack(id));
+    Ok(())
+}
+
+/// Switch subtitle track by VLC track id; -1 turns subtitles off. The choice is
+/// remembered for the current series.
+#[tauri::command]
+pub async fn player_set_subtitle_track(vlc: State<'_, VlcManager>, id: i32) -> Result<(), AppError> {
+    let _ = vlc.send(VlcCmd::SetSubtitleTrack(id));
+    Ok(())
+}
+
+/// vol is 0-100 (maps to VLC's 0-100 normal range). Persisted and restored on
+/// the next launch, as is the mute state.
+#[tauri::command]
+pub async fn player_set_volume(vlc: State<'_, VlcManager>, vol: i32) -> Result<(), AppError> {
+    if !vlc.send(VlcCmd::SetVolume(vol.clamp(0, 100))) {
+        return Err(vlc.unavailable());
+    }
+    Ok(())
+}
+
+/// Mute or unmute without losing the volume level. Persisted.
+#[tauri::command]
+pub async fn player_set_mute(vlc: State<'_, VlcManager>, muted: bool) -> Result<(), AppError> {
+    if !vlc.send(VlcCmd::SetMute(muted)) {
+        return Err(vlc.unavailable());
+    }
+    Ok(())
+}
+
+/// Scale the video by `factor` (1.0 = 1:1 pixel mapping, 2.0 = 2x zoom).
+/// None fits the video to the window again.
+#[tauri::command]
+pub async fn player_set_scale(vlc: State<'_, VlcManager>, factor: Option<f32>) -> Result<(), AppError> {
+    let factor = match factor {
+        Some(f) if !(f.is_finite() && f > 0.0) => {
+            return Err(AppError::invalid(format!("Scale factor must be positive, got {}", f)));
+        }
+        Some(f) => f,
+        None => 0.0,
+    };
+    let _ = vlc.send(VlcCmd::SetScale(factor));
+    Ok(())
+}
+
+/// Zoom into a region of the video given in source pixels. Pass no rect to
+/// show the whole picture again.
+#[tauri::command]
+pub async fn player_set_zoom_rect(
+    vlc: State<'_, VlcManager>,
+    x: Option<u32>,
+    y: Option<u32>,
+    width: Option<u32>,
+    height: Option<u32>,
+) -> Result<(), AppError> {
+    let geometry = match (width, height) {
+        (Some(0), _) | (_, Some(0)) => {
+            return Err(AppError::invalid("Zoom rectangle must have a positive width and height"));
+        }
+        (Some(w), Some(h)) => Some(format!("{}x{}+{}+{}", w, h, x.unwrap_or(0), y.unwrap_or(0))),
+        (None, None) => None,
+        _ => return Err(AppError::invalid("Zoom rectangle needs both width and height")),
+    };
+    let _ = vlc.send(VlcCmd::SetZoomRect(geometry));
+    Ok(())
+}
+
+/// Set the subtitle text encoding (e.g. "Windows-1251"); None auto-detects.
+/// Stored for future media, and the current media's subtitles are loaded again
+/// so they're decoded with it; the video keeps playing.
+#[tauri::command]
+pub async fn player_set_subtitle_encoding(
+    app: AppHandle,
+    vlc: State<'_, VlcManager>,
+    encoding: Option<String>,
+) -> Result<(), AppError> {
+    subtitles::save_encoding(&app, encoding)?;
+    let _ = vlc.send(VlcCmd::ReloadSubtitles);
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn player_stop(app: AppHandle, vlc: State<'_, VlcManager>) -> Result<(), AppError> {
+    stop_playback(&app, &vlc);
+    Ok(())
+}
+
+/// Stop VLC, clear the queue and kill the video's rclone serve process.
+pub(crate) fn stop_playback(app: &AppHandle, vlc: &VlcManager) {
+    let _ = vlc.send(VlcCmd::Stop);
+    prefetch::cancel();
+    prefetch::stop_proxy();
+    discord::stopped();
+    clear_queue(app, vlc);
+    *vlc.serve_source.lock().unwrap() = None;
+    let mut guard = vlc.serve_child.lock().unwrap();
+    if let Some(mut c) = guard.take() {
+        let _ = c.kill();
+    }
+}
+
+/// Toggle play/pause based on the player's actual state (used by media keys).
+pub(crate) fn toggle_pause(vlc: &VlcManager) {
+    let _ = vlc.send(VlcCmd::TogglePause);
+}
+
+/// Transport controls for desktop integrations (MPRIS) that have no command
+/// handle to call through.
+pub(crate) fn play(vlc: &VlcManager) {
+    let _ = vlc.send(VlcCmd::Play);
+}
+
+pub(crate) fn pause(vlc: &VlcManager) {
+    let _ = vlc.send(VlcCmd::Pause);
+}
+
+pub(crate) fn seek(vlc: &VlcManager, ms: i64) {
+    let _ = vlc.send(VlcCmd::Seek(ms.max(0)));
+}
+
+pub(crate) fn seek_relative(vlc: &VlcManager, delta_ms: i64) {
+    let _ = vlc.send(VlcCmd::SeekRelative(delta_ms));
+}
+
+pub(crate) fn set_volume(vlc: &VlcManager, vol: i32) {
+    let _ = vlc.send(VlcCmd::SetVolume(vol.clamp(0, 100)));
+}
+
+// ── Queue ─────────────────────────────────────────────────────────────────────
+
+fn emit_queue_changed(app: &AppHandle, queue: &PlayQueue) {
+    let _ = app.emit("player:queue-changed", queue.snapshot());
+}
+
+fn clear_queue(app: &AppHandle, vlc: &VlcManager) {
+    vlc.book_sessions.lock().unwrap().release(QUEUE_PRELOAD_SESSION);
+    let mut queue = vlc.queue.lock().unwrap();
+    if queue.items.is_empty() && queue.index.is_none() {
+        return;
+    }
+    *queue = PlayQueue {
+        repeat: queue.repeat,
+        ..PlayQueue::default()
+    };
+    emit_queue_changed(app, &queue);
+}
+
+/// Resolve a file for a secondary use (gapless preload, preview): a FUSE mount
+/// path if there is one, otherwise a URL on the shared serve process for its
+/// remote root, attached under `session_id`.
+pub(crate) async fn resolve_shared_source(
+    app: &AppHandle,
+    vlc: &VlcManager,
+    config_path: &str,
+    remote_root: &str,
+    file_path: &str,
+    session_id: &str,
+    options: &ServeOptions,
+) -> Result<String, AppError> {
+    let (remote_name, root_sub_path) = parse_remote_root(remote_root);
+    let full_relative = format!(
+        "{}/{}",
+        root_sub_path.trim_matches('/'),
+        file_path.trim_start_matches('/')
+    );
+    match find_fuse_local_path(remote_name, full_relative.trim_start_matches('/')) {
+        Some(local_path) => Ok(local_path.to_string_lossy().into_owned()),
+        None => {
+            shared_serve_url(
+                app,
+                vlc,
+                config_path,
+                remote_root,
+                file_path,
+                session_id.to_string(),
+                options,
+            )
+            .await
+        }
+    }
+}
+
+/// Resolve the next queue item's URL and hand it to the VLC thread to preload.
+/// Does nothing unless the queue is in gapless mode (or crossfading) and has a
+/// next item.
+async fn preload_next_in_queue(app: &AppHandle, vlc: &VlcManager) {
+    let crossfade = crossfade_setting(app) > 0;
+    let next = {
+        let queue = vlc.queue.lock().unwrap();
+        // Repeat-one restarts the current item instead
+        if !(queue.gapless || crossfade) || queue.repeat == RepeatMode::One {
+            return;
+        }
+        let Some(index) = queue.step_index(1) else {
+            return;
+        };
+        match queue.items.get(index) {
+            Some(item) => (index, queue.config_path.clone(), item.clone()),
+            None => return,
+        }
+    };
+    let (index, config_path, item) = next;
+    let options = match item.serve_options() {
+        Ok(options) => options,
+        Err(e) => {
+            eprintln!("Gapless preload failed: {}", e);
+            return;
+        }
+    };
+
+    // Album tracks share a folder, so this normally reuses an already-running server
+    let url = match prefetch::cached(app, &item.remote_root, &item.file_path) {
+        Some(path) => path.to_string_lossy().into_owned(),
+        None => match resolve_shared_source(
+            app,
+            vlc,
+            &config_path,
+            &item.remote_root,
+            &item.file_path,
+            QUEUE_PRELOAD_SESSION,
+            &options,
+        )
+        .await
+        {
+            Ok(url) => url,
+            // Not fatal: the queue falls back to a normal open when the track ends
+            Err(e) => {
+                eprintln!("Gapless preload failed: {}", e);
+                return;
+            }
+        },
+    };
 
-        // Wait until rclone's HTTP server is accepting connections
-        wait_for_port(port).await?;
+    // A video item swapped in after audio-only ones needs the window set first
+    if let Some(window) = get_window_handle(app).filter(|_| !item.audio_only) {
+        let _ = vlc.send(VlcCmd::SetWindow(window));
+    }
 
-        let _ = app.emit(
-            "rclone:status",
-            serde_json::json!({ "state": "ready", "message": "Stream ready" }),
-        );
+    let item_id = item.media_id();
+    let _ = vlc.send(VlcCmd::Preload {
+        url,
+        index,
+        item_id,
+        series_key: item.series_key,
+        audio_only: item.audio_only,
+    });
+}
 
-        {
-            let mut guard = vlc.serve_child.lock().unwrap();
-            *guard = Some(child);
+/// Prefetch the next queue item into the temp cache (see `prefetch::start`).
+/// Items that already play from a local file are skipped.
+fn prefetch_next_in_queue(app: &AppHandle, vlc: &VlcManager) {
+    let (config_path, item) = {
+        let queue = vlc.queue.lock().unwrap();
+        if queue.repeat == RepeatMode::One {
+            return;
         }
+        let Some(item) = queue.step_index(1).and_then(|i| queue.items.get(i)) else {
+            return;
+        };
+        (queue.config_path.clone(), item.clone())
+    };
+    // Disc folders are directories and prefetch copies single files
+    if item.disc {
+        return;
+    }
+    let (remote_name, root_sub_path) = parse_remote_root(&item.remote_root);
+    let full_relative = format!(
+        "{}/{}",
+        root_sub_path.trim_matches('/'),
+        item.file_path.trim_start_matches('/')
+    );
+    let local = downloads::completed_path(app, &item.remote_root, &item.file_path)
+        .or_else(|| find_fuse_local_path(remote_name, full_relative.trim_start_matches('/')));
+    if local.is_none() {
+        prefetch::start(app, &config_path, &item.remote_root, &item.file_path);
+    }
+}
 
-        // rclone serve http uses remote_root as its root, so the URL path
-        // is just file_path (relative to remote_root), not full_relative.
-        let encoded = percent_encode_path(file_path.trim_start_matches('/'));
-        format!("http://127.0.0.1:{}/{}", port, encoded)
+/// Called after the VLC thread swapped in a preloaded item: move the queue
+/// index along and notify the UI.
+fn mark_queue_advanced(app: &AppHandle, vlc: &VlcManager, index: usize) {
+    let mut queue = vlc.queue.lock().unwrap();
+    let Some(item) = queue.items.get(index).cloned() else {
+        return;
+    };
+    queue.index = Some(index);
+    discord::now_playing(item.title.clone(), item.audio_only);
+    let _ = app.emit(
+        "player:item-started",
+        serde_json::json!({ "index": index, "item": item }),
+    );
+    emit_queue_changed(app, &queue);
+}
+
+/// Open the queue item at `index` and make it current.
+async fn play_queue_index(app: &AppHandle, vlc: &VlcManager, index: usize) -> Result<(), AppError> {
+    let (config_path, item) = {
+        let queue = vlc.queue.lock().unwrap();
+        let item = queue
+            .items
+            .get(index)
+            .cloned()
+            .ok_or_else(|| AppError::invalid(format!("Queue index {} out of range", index)))?;
+        (queue.config_path.clone(), item)
     };
 
-    // Tell VLC which X11 window to render into (must be sent before Open)
-    #[cfg(target_os = "linux")]
-    if let Some(xid) = xid {
-        let _ = vlc.send(VlcCmd::SetWindow(xid));
-    }
+    open_source(app, vlc, &config_path, &item).await?;
 
-    if !vlc.send(VlcCmd::Open { url, start_ms }) {
-        return Err(
-            "VLC is not available. Make sure libvlc5 is installed (sudo apt install libvlc5)."
-                .to_string(),
-        );
-    }
+    let mut queue = vlc.queue.lock().unwrap();
+    queue.index = Some(index);
+    let _ = app.emit(
+        "player:item-started",
+        serde_json::json!({ "index": index, "item": item }),
+    );
+    emit_queue_changed(app, &queue);
     Ok(())
 }
 
-#[tauri::command]
-pub async fn player_play(vlc: State<'_, VlcManager>) -> Result<(), String> {
-    let _ = vlc.send(VlcCmd::Play);
-    Ok(())
+/// Move `step` items through the queue and open the result.
+/// Returns Ok(false) without touching playback when there is nothing to move to.
+pub(crate) async fn play_queue_step(app: &AppHandle, vlc: &VlcManager, step: isize) -> Result<bool, AppError> {
+    let target = vlc.queue.lock().unwrap().step_index(step);
+    match target {
+        Some(index) => play_queue_index(app, vlc, index).await.map(|_| true),
+        None => Ok(false),
+    }
 }
 
+/// Replace the queue and start playing `start_index` (default 0).
+/// `gapless` preloads each next item so album tracks run back to back.
 #[tauri::command]
-pub async fn player_pause(vlc: State<'_, VlcManager>) -> Result<(), String> {
-    let _ = vlc.send(VlcCmd::Pause);
-    Ok(())
+pub async fn player_queue_set(
+    app: AppHandle,
+    vlc: State<'_, VlcManager>,
+    config_path: String,
+    items: Vec<QueueItem>,
+    start_index: Option<usize>,
+    gapless: Option<bool>,
+) -> Result<(), AppError> {
+    let start = start_index.unwrap_or(0);
+    {
+        let mut queue = vlc.queue.lock().unwrap();
+        *queue = PlayQueue {
+            config_path,
+            items,
+            index: None,
+            gapless: gapless.unwrap_or(false),
+            repeat: queue.repeat,
+        };
+        emit_queue_changed(&app, &queue);
+        if start >= queue.items.len() {
+            return Ok(());
+        }
+    }
+    play_queue_index(&app, &vlc, start).await
 }
 
+/// Skip to the next queue item. Returns false at the end of the queue.
 #[tauri::command]
-pub async fn player_seek(vlc: State<'_, VlcManager>, ms: i64) -> Result<(), String> {
-    let _ = vlc.send(VlcCmd::Seek(ms));
-    Ok(())
+pub async fn player_queue_next(app: AppHandle, vlc: State<'_, VlcManager>) -> Result<bool, AppError> {
+    play_queue_step(&app, &vlc, 1).await
 }
 
-/// vol is 0-100 (maps to VLC's 0-100 normal range)
+/// Go back to the previous queue item. Returns false at the start of the queue.
 #[tauri::command]
-pub async fn player_set_volume(vlc: State<'_, VlcManager>, vol: i32) -> Result<(), String> {
-    let _ = vlc.send(VlcCmd::SetVolume(vol.clamp(0, 100)));
-    Ok(())
+pub async fn player_queue_previous(
+    app: AppHandle,
+    vlc: State<'_, VlcManager>,
+) -> Result<bool, AppError> {
+    play_queue_step(&app, &vlc, -1).await
 }
 
+/// Set the repeat mode ("off", "one" or "all"). Only affects what happens when the
+/// current item ends, so playback isn't interrupted.
 #[tauri::command]
-pub async fn player_stop(vlc: State<'_, VlcManager>) -> Result<(), String> {
-    let _ = vlc.send(VlcCmd::Stop);
-    let mut guard = vlc.serve_child.lock().unwrap();
-    if let Some(mut c) = guard.take() {
-        let _ = c.kill();
+pub async fn player_set_repeat(
+    app: AppHandle,
+    vlc: State<'_, VlcManager>,
+    mode: RepeatMode,
+) -> Result<(), AppError> {
+    let mut queue = vlc.queue.lock().unwrap();
+    if queue.repeat == mode {
+        return Ok(());
     }
+    queue.repeat = mode;
+    emit_queue_changed(&app, &queue);
+    // The VLC thread locks the queue when a track ends, so don't hold it here
+    drop(queue);
+    // A preloaded gapless item may have been picked under the old mode
+    let _ = vlc.send(VlcCmd::DropPreload);
     Ok(())
 }
 
+/// Current queue contents, index and modes, as `{ items, index, gapless, repeat }`.
+#[tauri::command]
+pub async fn player_queue_get(vlc: State<'_, VlcManager>) -> Result<serde_json::Value, AppError> {
+    Ok(vlc.queue.lock().unwrap().snapshot())
+}
+
 /// Start an rclone HTTP server for an epub/pdf file and return its URL.
 /// Falls back to a file:// URL when a FUSE mount is detected.
+/// Sessions under the same remote root share one server, which is reused if
+/// already running and only killed once the last session using it stops.
 #[tauri::command]
 pub async fn start_stream_session(
     app: AppHandle,
@@ -486,14 +3793,17 @@ pub async fn start_stream_session(
     remote_root: String,
     file_path: String,
     session_id: String,
-) -> Result<serde_json::Value, String> {
-    // Kill any previous session with the same id
-    {
-        let mut map = vlc.book_sessions.lock().unwrap();
-        if let Some(mut old) = map.remove(&session_id) {
-            let _ = old.kill();
-        }
-    }
+    bwlimit: Option<String>, // rclone --bwlimit, e.g. "5M"; None is unlimited
+    vfs_cache_mode: Option<String>,     // rclone --vfs-cache-mode; None caches nothing
+    vfs_cache_max_size: Option<String>, // rclone --vfs-cache-max-size, e.g. "20G"
+) -> Result<serde_json::Value, AppError> {
+    let options = ServeOptions::new(
+        bwlimit.as_deref(),
+        vfs_cache_mode.as_deref(),
+        vfs_cache_max_size.as_deref(),
+    )?;
+    // Release any previous session with the same id
+    vlc.book_sessions.lock().unwrap().release(&session_id);
 
     let (remote_name, root_sub_path) = parse_remote_root(&remote_root);
     let full_relative = format!(
@@ -509,96 +3819,155 @@ pub async fn start_stream_session(
         return Ok(serde_json::json!({ "file_url": url }));
     }
 
-    // Fall back: spin up rclone serve http for the remote root
+    let file_url = shared_serve_url(
+        &app,
+        &vlc,
+        &config_path,
+        &remote_root,
+        &file_path,
+        session_id,
+        &options,
+    )
+    .await?;
+    Ok(serde_json::json!({ "file_url": file_url }))
+}
+
+/// Return an HTTP URL for `file_path` from the shared rclone serve http process
+/// for `remote_root`, starting one if none is running, and attach `session_id` to it.
+/// Sessions with different `options` get separate servers.
+/// The process is tracked in the managed `VlcManager`, so it's killed by
+/// `stop_stream_session`, `stop_all_sessions`, or on app exit.
+pub(crate) async fn shared_serve_url(
+    app: &AppHandle,
+    vlc: &VlcManager,
+    config_path: &str,
+    remote_root: &str,
+    file_path: &str,
+    session_id: String,
+    options: &ServeOptions,
+) -> Result<String, AppError> {
+    // rclone serve http uses remote_root as its root, so the URL path
+    // is just file_path (relative to remote_root), not full_relative.
+    let encoded = percent_encode_path(file_path.trim_start_matches('/'));
+    let key: ServeKey = (config_path.to_string(), remote_root.to_string(), options.clone());
+
+    // Reuse a server already running for this root
+    if let Some(port) = vlc.book_sessions.lock().unwrap().reuse(session_id.clone(), key.clone()) {
+        return Ok(format!("http://127.0.0.1:{}/{}", port, encoded));
+    }
+
+    // Otherwise spin up rclone serve http for the remote root
     let port = portpicker::pick_unused_port().ok_or("No available port")?;
-    let rclone = rclone_binary(&app);
 
     let _ = app.emit(
         "rclone:status",
         serde_json::json!({ "state": "starting", "message": "Connecting to remote…" }),
     );
 
-    let child = Command::new(&rclone)
-        .args([
-            "serve", "http",
-            "--config", &config_path,
-            "--addr", &format!("127.0.0.1:{}", port),
-            "--read-only",
-            "--no-checksum",
-            "--allow-origin", "*",
-            &remote_root,
-        ])
-        .spawn()
-        .map_err(|e| format!("Failed to start rclone serve: {}", e))?;
-
-    wait_for_port(port).await?;
+    let mut child = spawn_serve(app, config_path, remote_root, port, options, None)?;
+
+    wait_for_serve(&mut child, port).await?;
 
     let _ = app.emit(
         "rclone:status",
         serde_json::json!({ "state": "ready", "message": "Stream ready" }),
     );
 
-    {
-        let mut map = vlc.book_sessions.lock().unwrap();
-        map.insert(session_id, child);
-    }
+    let port = {
+        let mut servers = vlc.book_sessions.lock().unwrap();
+        // Another session may have started a server for this root while we waited
+        let port = match servers.live_port(&key) {
+            Some(existing) => {
+                let _ = child.kill();
+                existing
+            }
+            None => {
+                servers.insert(key.clone(), child, port);
+                port
+            }
+        };
+        servers.attach(session_id, key);
+        port
+    };
 
-    // rclone serve http uses remote_root as its root, so the URL path
-    // is just file_path (relative to remote_root), not full_relative.
-    let encoded = percent_encode_path(file_path.trim_start_matches('/'));
-    let file_url = format!("http://127.0.0.1:{}/{}", port, encoded);
-    Ok(serde_json::json!({ "file_url": file_url }))
+    Ok(format!("http://127.0.0.1:{}/{}", port, encoded))
+}
+
+/// Detach `session_id` from its shared serve process (killed with its last session).
+pub(crate) fn release_shared_session(vlc: &VlcManager, session_id: &str) {
+    vlc.book_sessions.lock().unwrap().release(session_id);
 }
 
 /// Stop a book (epub/pdf) stream session by its session id.
+/// The shared server is only killed when no other session is using it.
 #[tauri::command]
 pub async fn stop_stream_session(
     vlc: State<'_, VlcManager>,
     session_id: Option<String>,
-) -> Result<(), String> {
+) -> Result<(), AppError> {
     if let Some(sid) = session_id {
-        let mut map = vlc.book_sessions.lock().unwrap();
-        if let Some(mut child) = map.remove(&sid) {
-            let _ = child.kill();
-        }
+        vlc.book_sessions.lock().unwrap().release(&sid);
     }
     Ok(())
 }
 
 #[tauri::command]
-pub async fn stop_all_sessions(vlc: State<'_, VlcManager>) -> Result<(), String> {
+pub async fn stop_all_sessions(app: AppHandle, vlc: State<'_, VlcManager>) -> Result<(), AppError> {
     // Kill VLC + its rclone serve child
     let _ = vlc.send(VlcCmd::Stop);
+    discord::stopped();
+    clear_queue(&app, &vlc);
     if let Ok(mut guard) = vlc.serve_child.lock() {
         if let Some(mut c) = guard.take() {
             let _ = c.kill();
         }
     }
+    transcode::kill_all(&vlc);
     // Kill all book (epub/pdf) rclone serve sessions
-    if let Ok(mut map) = vlc.book_sessions.lock() {
-        for (_, mut c) in map.drain() {
-            let _ = c.kill();
-        }
+    if let Ok(mut servers) = vlc.book_sessions.lock() {
+        servers.kill_all();
     }
     Ok(())
 }
 
 // ── Book download (epub / pdf) ────────────────────────────────────────────────
 
-/// Download an epub or pdf to a per-session temp directory and return a
-/// local file path. Always downloads to temp (even with FUSE mount) because
-/// the asset protocol only allows access to $TEMP/** paths.
+/// Download an epub or pdf into the temp cache (see `temp_cache`) and return a
+/// local file path, reusing an earlier download of the same file if cached.
+/// Always downloads to temp (even with FUSE mount) because the asset protocol
+/// only allows access to $TEMP/** paths.
 ///
-/// Prefer this over `start_stream_session` for books: rclone copyto is a
-/// single download that exits cleanly, whereas rclone serve http keeps an
-/// entire HTTP process alive just to serve one file.
+/// Prefer this over `start_stream_session` for books: the file is fetched once
+/// and the serve session is released as soon as it's done. An interrupted
+/// download resumes from its `.partial` file on the next call.
+///
+/// Emits `book-download-progress` { downloadId, bytes, total, percent } a few
+/// times a second. `download_id` (default: `session_id`) identifies the
+/// download for `cancel_book_download`; downloads with different ids run
+/// independently.
 #[tauri::command]
 pub async fn download_book_to_temp(
     app: AppHandle,
     config_path: String,
     remote_path: String,   // full path, e.g. "gdrive:/Books/Author/book.epub"
     session_id: String,
-) -> Result<String, String> {
+    download_id: Option<String>,
+) -> Result<String, AppError> {
+    let download_id = download_id.unwrap_or_else(|| session_id.clone());
+    let cancel = downloads::start_transfer(&download_id)?;
+    let result = download_book(&app, &config_path, &remote_path, &session_id, &download_id, cancel).await;
+    downloads::end_transfer(&download_id);
+    result
+}
+
+async fn download_book(
+    app: &AppHandle,
+    config_path: &str,
+    remote_path: &str,
+    session_id: &str,
+    download_id: &str,
+    cancel: oneshot::Receiver<()>,
+) -> Result<String, AppError> {
     // Always download to temp - asset protocol only allows $TEMP/** scope
     // FUSE paths outside /tmp won't work with convertFileSrc
     let filename = remote_path
@@ -606,48 +3975,61 @@ pub async fn download_book_to_temp(
         .find(|s| !s.is_empty() && !s.ends_with(':'))
         .unwrap_or("book");
 
-    let temp_dir = std::env::temp_dir()
-        .join("rcloneflix-books")
-        .join(&session_id);
-    std::fs::create_dir_all(&temp_dir)
-        .map_err(|e| format!("Failed to create temp dir: {}", e))?;
+    let stat = downloads::stat_remote(app, config_path, remote_path).await?;
+    if let Some(cached) = temp_cache::lookup(app, remote_path, stat.size, session_id) {
+        eprintln!("Book already cached: {:?}", cached);
+        return Ok(cached.to_string_lossy().into_owned());
+    }
 
+    let temp_dir = temp_cache::cache_dir().join(scan::hash_remote_path(remote_path.to_string()));
     let local_path = temp_dir.join(filename);
-    let rclone = rclone_binary(&app);
+    // Keep `clear_temp_cache` off the partial while it downloads
+    temp_cache::hold(session_id, &downloads::partial_path(&local_path));
 
     eprintln!("Downloading book: {} -> {:?}", remote_path, local_path);
-    
-    let output = TokioCommand::new(&rclone)
-        .args([
-            "copyto",
-            "--config",
-            &config_path,
-            &remote_path,
-            local_path.to_str().unwrap_or(""),
-        ])
-        .output()
-        .await
-        .map_err(|e| format!("rclone copyto failed: {}", e))?;
 
-    if !output.status.success() {
-        let stderr = String::from_utf8_lossy(&output.stderr);
-        eprintln!("rclone copyto error: {}", stderr);
-        return Err(format!("rclone copyto error: {}", stderr));
+    let completed = downloads::resumable_download(
+        app,
+        config_path,
+        remote_path,
+        &stat,
+        &local_path,
+        &ServeOptions::default(),
+        cancel,
+        |stats| {
+            let _ = app.emit("book-download-progress", serde_json::json!({
+                "downloadId": download_id,
+                "bytes": stats.bytes,
+                "total": stats.total,
+                "percent": stats.percent,
+            }));
+        },
+    )
+    .await
+    .inspect_err(|e| eprintln!("Book download error: {}", e))?;
+    if !completed {
+        let _ = std::fs::remove_file(downloads::partial_path(&local_path));
+        return Err(AppError::cancelled("Download cancelled"));
     }
 
+    temp_cache::record(app, remote_path, &local_path, session_id);
     eprintln!("Book download complete: {:?}", local_path);
     Ok(local_path.to_string_lossy().into_owned())
 }
 
-/// Delete the temp directory created by `download_book_to_temp` for this session.
+/// Stop a running `download_book_to_temp` and remove its partial file. Does
+/// nothing if the download already finished.
+#[tauri::command]
+pub fn cancel_book_download(download_id: String) -> Result<(), AppError> {
+    downloads::cancel_transfer(&download_id);
+    Ok(())
+}
+
+/// Release the files `download_book_to_temp` fetched for this session. They stay
+/// in the temp cache for the next open until evicted to make room.
 #[tauri::command]
 pub fn cleanup_book_temp(session_id: String) {
-    let temp_dir = std::env::temp_dir()
-        .join("rcloneflix-books")
-        .join(&session_id);
-    if temp_dir.exists() {
-        let _ = std::fs::remove_dir_all(&temp_dir);
-    }
+    temp_cache::release_session(&session_id);
 }
 
 // ── Legacy media info (ffprobe) ───────────────────────────────────────────────
@@ -659,25 +4041,715 @@ pub struct SubtitleTrack {
     pub title: Option<String>,
 }
 
+/// Video stream of a `MediaInfo`
+#[derive(Debug, Serialize, Clone)]
+pub struct VideoStream {
+    /// ffprobe stream index
+    pub index: u32,
+    pub codec: Option<String>,
+    pub width: Option<u32>,
+    pub height: Option<u32>,
+    pub fps: Option<f64>,
+    /// PQ / HLG transfer or Dolby Vision
+    pub hdr: bool,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct AudioStream {
+    pub index: u32,
+    pub codec: Option<String>,
+    pub channels: Option<u32>,
+    pub language: Option<String>,
+    pub title: Option<String>,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct SubtitleStream {
+    pub index: u32,
+    pub codec: Option<String>,
+    pub language: Option<String>,
+    pub forced: bool,
+}
+
+/// Which prober produced a `MediaInfo`
+#[derive(Debug, Serialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum MediaInfoBackend {
+    Ffprobe,
+    /// ffprobe is missing; only duration and the track list are known, and
+    /// stream indexes are libVLC track ids
+    Libvlc,
+}
+
+/// `get_media_info` result. Fields the prober didn't report are None.
+#[derive(Debug, Serialize, Clone)]
+pub struct MediaInfo {
+    pub backend: MediaInfoBackend,
+    pub duration_s: Option<f64>,
+    /// ffprobe format name, e.g. "matroska,webm"
+    pub container: Option<String>,
+    /// Overall bitrate in bits per second
+    pub bitrate: Option<u64>,
+    pub video: Vec<VideoStream>,
+    pub audio: Vec<AudioStream>,
+    pub subtitles: Vec<SubtitleStream>,
+    /// ffprobe's JSON (or libVLC's track list) as returned, for debugging
+    pub raw: serde_json::Value,
+}
+
+/// ffprobe reports most numbers as strings ("123.456")
+fn probe_number<T: std::str::FromStr>(value: &serde_json::Value) -> Option<T> {
+    match value {
+        serde_json::Value::String(s) => s.parse().ok(),
+        serde_json::Value::Number(n) => n.to_string().parse().ok(),
+        _ => None,
+    }
+}
+
+fn probe_string(value: &serde_json::Value) -> Option<String> {
+    value.as_str().filter(|s| !s.is_empty() && *s != "und").map(|s| s.to_string())
+}
+
+/// "24000/1001" -> 23.976
+fn parse_frame_rate(rate: &str) -> Option<f64> {
+    let (num, den) = rate.split_once('/')?;
+    let (num, den): (f64, f64) = (num.parse().ok()?, den.parse().ok()?);
+    (num > 0.0 && den > 0.0).then(|| num / den)
+}
+
+fn parse_media_info(raw: serde_json::Value) -> MediaInfo {
+    let format = &raw["format"];
+    let mut video = Vec::new();
+    let mut audio = Vec::new();
+    let mut subtitles = Vec::new();
+
+    for stream in raw["streams"].as_array().into_iter().flatten() {
+        let index = stream["index"].as_u64().unwrap_or_default() as u32;
+        let codec = probe_string(&stream["codec_name"]);
+        let language = probe_string(&stream["tags"]["language"]);
+        match stream["codec_type"].as_str() {
+            // Cover art is a one-frame "video" stream
+            Some("video") if stream["disposition"]["attached_pic"].as_u64() != Some(1) => {
+                let transfer = stream["color_transfer"].as_str().unwrap_or_default();
+                let dolby_vision = stream["side_data_list"]
+                    .as_array()
+                    .into_iter()
+                    .flatten()
+                    .any(|d| d["side_data_type"].as_str() == Some("DOVI configuration record"));
+                video.push(VideoStream {
+                    index,
+                    codec,
+                    width: stream["width"].as_u64().map(|w| w as u32),
+                    height: stream["height"].as_u64().map(|h| h as u32),
+                    fps: stream["avg_frame_rate"]
+                        .as_str()
+                        .and_then(parse_frame_rate)
+                        .or_else(|| stream["r_frame_rate"].as_str().and_then(parse_frame_rate)),
+                    hdr: matches!(transfer, "smpte2084" | "arib-std-b67") || dolby_vision,
+                });
+            }
+            Some("audio") => audio.push(AudioStream {
+                index,
+                codec,
+                channels: stream["channels"].as_u64().map(|c| c as u32),
+                language,
+                title: probe_string(&stream["tags"]["title"]),
+            }),
+            Some("subtitle") => subtitles.push(SubtitleStream {
+                index,
+                codec,
+                language,
+                forced: stream["disposition"]["forced"].as_u64() == Some(1)
+                    || stream["tags"]["title"]
+                        .as_str()
+                        .is_some_and(|t| t.to_lowercase().contains("forced")),
+            }),
+            _ => {}
+        }
+    }
+
+    MediaInfo {
+        backend: MediaInfoBackend::Ffprobe,
+        duration_s: probe_number(&format["duration"]),
+        container: probe_string(&format["format_name"]),
+        bitrate: probe_number(&format["bit_rate"]),
+        video,
+        audio,
+        subtitles,
+        raw,
+    }
+}
+
+/// How long libVLC may spend parsing a file for `get_media_info`
+const VLC_PROBE_TIMEOUT_MS: i32 = 10_000;
+
+// Not wrapped by vlc-rs; `libvlc_media_parse_async` skips network inputs
+extern "C" {
+    fn libvlc_media_parse_with_options(
+        p_md: *mut vlc::sys::libvlc_media_t,
+        parse_flag: std::os::raw::c_int,
+        timeout: std::os::raw::c_int,
+    ) -> std::os::raw::c_int;
+    fn libvlc_media_get_parsed_status(p_md: *mut vlc::sys::libvlc_media_t) -> std::os::raw::c_int;
+}
+
+/// Duration and tracks of `file_url` from libVLC's parser, for when ffprobe
+/// isn't installed. Blocks until parsing finishes or times out.
+fn probe_with_vlc(file_url: &str) -> Result<MediaInfo, String> {
+    const PARSE_NETWORK: std::os::raw::c_int = 0x01;
+    const PARSED_STATUS_SKIPPED: std::os::raw::c_int = 1;
+    const PARSED_STATUS_DONE: std::os::raw::c_int = 4;
+
+    let instance = vlc::Instance::new().ok_or("Neither ffprobe nor libVLC is available")?;
+    let media = if file_url.contains("://") {
+        vlc::Media::new_location(&instance, file_url)
+    } else {
+        vlc::Media::new_path(&instance, file_url)
+    }
+    .ok_or_else(|| format!("libVLC could not open {}", file_url))?;
+
+    if unsafe { libvlc_media_parse_with_options(media.raw(), PARSE_NETWORK, VLC_PROBE_TIMEOUT_MS) } != 0 {
+        return Err(format!("libVLC could not parse {}", file_url));
+    }
+    let deadline = Instant::now() + Duration::from_millis(VLC_PROBE_TIMEOUT_MS as u64 + 1000);
+    let status = loop {
+        let status = unsafe { libvlc_media_get_parsed_status(media.raw()) };
+        if status >= PARSED_STATUS_SKIPPED || Instant::now() >= deadline {
+            break status;
+        }
+        thread::sleep(Duration::from_millis(50));
+    };
+    if status != PARSED_STATUS_DONE {
+        return Err(format!("libVLC could not parse {}", file_url));
+    }
+
+    let mut info = MediaInfo {
+        backend: MediaInfoBackend::Libvlc,
+        duration_s: media.duration().filter(|&ms| ms > 0).map(|ms| ms as f64 / 1000.0),
+        container: None,
+        bitrate: None,
+        video: Vec::new(),
+        audio: Vec::new(),
+        subtitles: Vec::new(),
+        raw: collect_tracks(&media).unwrap_or_default(),
+    };
+    for track in media.tracks().unwrap_or_default() {
+        let index = track.id.max(0) as u32;
+        let codec = Some(fourcc_to_string(track.codec)).filter(|c| !c.is_empty());
+        let language = track.language.clone().filter(|l| !l.is_empty());
+        match &track.type_specific_data {
+            vlc::MediaTrackUnion::Video(v) => info.video.push(VideoStream {
+                index,
+                codec,
+                width: Some(v.width),
+                height: Some(v.height),
+                fps: None,
+                hdr: false,
+            }),
+            vlc::MediaTrackUnion::Audio(a) => info.audio.push(AudioStream {
+                index,
+                codec,
+                channels: Some(a.channels),
+                language,
+                title: track.description.clone(),
+            }),
+            vlc::MediaTrackUnion::Subtitle(_) => info.subtitles.push(SubtitleStream {
+                index,
+                codec,
+                language,
+                forced: track
+                    .description
+                    .as_deref()
+                    .is_some_and(|d| d.to_lowercase().contains("forced")),
+            }),
+            _ => {}
+        }
+    }
+    Ok(info)
+}
+
+/// Setting holding the `get_media_info` timeout in seconds
+const PROBE_TIMEOUT_SETTING: &str = "player.probe_timeout_secs";
+const DEFAULT_PROBE_TIMEOUT_SECS: u64 = 15;
+/// ffprobe's network I/O timeout for http inputs, in µs
+const PROBE_RW_TIMEOUT_US: &str = "10000000";
+/// ffprobe's `-analyzeduration` (µs) and `-probesize` (bytes) for http inputs,
+/// so a slow remote isn't read far past the headers
+const PROBE_ANALYZE_DURATION_US: &str = "5000000";
+const PROBE_SIZE_BYTES: &str = "5000000";
+
+/// Run ffprobe with JSON output and the `sections` asked for (e.g.
+/// "-show_streams"), limited to `player.probe_timeout_secs` (default 15) and
+/// with network limits for http inputs. The inner error means ffprobe
+/// couldn't be started at all.
+async fn run_ffprobe(
+    app: &AppHandle,
+    file_url: &str,
+    sections: &[&str],
+) -> Result<std::io::Result<std::process::Output>, AppError> {
+    let mut command = TokioCommand::new(storyboard::bundled_binary(app, "ffprobe"));
+    command.args(["-v", "quiet", "-print_format", "json"]).args(sections);
+    if file_url.starts_with("http://") || file_url.starts_with("https://") {
+        command.args([
+            "-rw_timeout",
+            PROBE_RW_TIMEOUT_US,
+            "-timeout",
+            PROBE_RW_TIMEOUT_US,
+            "-analyzeduration",
+            PROBE_ANALYZE_DURATION_US,
+            "-probesize",
+            PROBE_SIZE_BYTES,
+        ]);
+    }
+    command.arg(file_url).kill_on_drop(true);
+
+    let timeout_secs = store::get_setting(app, PROBE_TIMEOUT_SETTING)
+        .filter(|&secs: &u64| secs > 0)
+        .unwrap_or(DEFAULT_PROBE_TIMEOUT_SECS);
+    // Dropping the timed-out future drops the child, which kills it
+    tokio::time::timeout(Duration::from_secs(timeout_secs), command.output())
+        .await
+        .map_err(|_| AppError::timeout(format!("Probe timed out after {} s", timeout_secs)))
+}
+
+/// Streams and format of `file_url` from ffprobe (bundled, else on PATH). A
+/// file ffprobe can't read gives an empty result rather than an error. Without
+/// ffprobe, libVLC's parser supplies what it can; `backend` says which ran.
+/// A probe that runs past its timeout returns a `timeout` error so the UI can
+/// offer a retry.
 #[tauri::command]
-pub async fn get_media_info(file_url: String) -> Result<serde_json::Value, String> {
-    let output = Command::new("ffprobe")
-        .args([
-            "-v",
-            "quiet",
-            "-print_format",
-            "json",
-            "-show_streams",
-            &file_url,
-        ])
-        .output();
+pub async fn get_media_info(app: AppHandle, file_url: String) -> Result<MediaInfo, AppError> {
+    let output = run_ffprobe(&app, &file_url, &["-show_format", "-show_streams"]).await?;
 
-    match output {
+    let raw = match output {
         Ok(o) if o.status.success() => {
             let stdout = String::from_utf8_lossy(&o.stdout);
             serde_json::from_str(&stdout)
-                .map_err(|e| format!("Failed to parse ffprobe output: {}", e))
+                .map_err(|e| format!("Failed to parse ffprobe output: {}", e))?
+        }
+        Ok(_) => serde_json::json!({ "streams": [] }),
+        Err(e) => {
+            eprintln!("ffprobe unavailable ({}), probing with libVLC", e);
+            return Ok(tokio::task::spawn_blocking(move || probe_with_vlc(&file_url))
+                .await
+                .map_err(|e| format!("Media probe failed: {}", e))??);
+        }
+    };
+    Ok(parse_media_info(raw))
+}
+
+/// Skip segments for the loaded media: those stored for its series, else ones
+/// guessed from its chapter names. None while its chapters haven't been probed.
+fn load_skip_segments(app: &AppHandle, series_key: Option<&str>, item_id: Option<&str>) -> Option<Vec<SkipSegment>> {
+    if let Some(stored) = series_key.and_then(|key| segments::stored_segments(app, key)) {
+        return Some(stored);
+    }
+    let Some(id) = item_id else {
+        return Some(Vec::new());
+    };
+    CHAPTER_CACHE
+        .lock()
+        .unwrap()
+        .as_ref()
+        .and_then(|c| c.get(id))
+        .map(|chapters| segments::segments_from_chapters(chapters))
+}
+
+/// Chapters already probed, by item id
+static CHAPTER_CACHE: Mutex<Option<HashMap<String, Vec<Chapter>>>> = Mutex::new(None);
+
+/// One chapter of an M4B audiobook, MKV, etc.
+#[derive(Debug, Serialize, Clone)]
+pub struct Chapter {
+    pub index: u32,
+    pub title: String,
+    pub start_ms: i64,
+    pub end_ms: i64,
+}
+
+fn parse_chapters(raw: &serde_json::Value) -> Vec<Chapter> {
+    raw["chapters"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .enumerate()
+        .map(|(i, chapter)| {
+            let ms = |key: &str| {
+                probe_number::<f64>(&chapter[key]).map(|s| (s * 1000.0).round() as i64).unwrap_or(0)
+            };
+            Chapter {
+                index: i as u32,
+                title: probe_string(&chapter["tags"]["title"])
+                    .unwrap_or_else(|| format!("Chapter {}", i + 1)),
+                start_ms: ms("start_time"),
+                end_ms: ms("end_time"),
+            }
+        })
+        .collect()
+}
+
+/// Chapters of `file_url` via `ffprobe -show_chapters`; empty when it has none.
+/// With `item_id`, the result is cached so reopening the item doesn't probe again.
+#[tauri::command]
+pub async fn get_media_chapters(
+    app: AppHandle,
+    file_url: String,
+    item_id: Option<String>,
+) -> Result<Vec<Chapter>, AppError> {
+    if let Some(id) = &item_id {
+        if let Some(chapters) = CHAPTER_CACHE.lock().unwrap().as_ref().and_then(|c| c.get(id)) {
+            return Ok(chapters.clone());
         }
-        _ => Ok(serde_json::json!({ "streams": [] })),
+    }
+
+    let output = run_ffprobe(&app, &file_url, &["-show_chapters"])
+        .await?
+        .map_err(|_| AppError::FfmpegMissing)?;
+    // Unreadable right now; not cached so the next open tries again
+    if !output.status.success() {
+        return Ok(Vec::new());
+    }
+    let raw: serde_json::Value = serde_json::from_slice(&output.stdout)
+        .map_err(|e| format!("Failed to parse ffprobe output: {}", e))?;
+    let chapters = parse_chapters(&raw);
+
+    if let Some(id) = item_id {
+        CHAPTER_CACHE
+            .lock()
+            .unwrap()
+            .get_or_insert_with(HashMap::new)
+            .insert(id, chapters.clone());
+    }
+    Ok(chapters)
+}
+
+/// First line of `ffprobe -version`, or an error when no ffprobe is bundled or
+/// on PATH, in which case `get_media_info` falls back to libVLC.
+#[tauri::command]
+pub async fn get_ffprobe_version(app: AppHandle) -> Result<String, AppError> {
+    let output = TokioCommand::new(storyboard::bundled_binary(&app, "ffprobe"))
+        .arg("-version")
+        .output()
+        .await
+        .map_err(|_| AppError::FfmpegMissing)?;
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    Ok(stdout.lines().next().unwrap_or("ffprobe unknown").to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn percent_encode_path_escapes_reserved_and_non_ascii_bytes() {
+        assert_eq!(
+            percent_encode_path("Show #1/Ep? 1 & 2.mkv"),
+            "Show%20%231/Ep%3F%201%20%26%202.mkv"
+        );
+        assert_eq!(percent_encode_path("50% Off.mkv"), "50%25%20Off.mkv");
+        assert_eq!(
+            percent_encode_path("Movie (Director's Cut).mkv"),
+            "Movie%20%28Director%27s%20Cut%29.mkv"
+        );
+        assert_eq!(
+            percent_encode_path("日本/アニメ.mkv"),
+            "%E6%97%A5%E6%9C%AC/%E3%82%A2%E3%83%8B%E3%83%A1.mkv"
+        );
+        assert_eq!(percent_encode_path("Season_1/ep-01.v2~.mkv"), "Season_1/ep-01.v2~.mkv");
+    }
+
+    fn free_port() -> u16 {
+        std::net::TcpListener::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap()
+            .port()
+    }
+
+    fn sleeper() -> Child {
+        Command::new("sleep").arg("30").spawn().expect("spawn sleep")
+    }
+
+    #[tokio::test]
+    async fn failing_serve_binary_fails_fast() {
+        let mut child = Command::new("sh")
+            .args(["-c", "echo 'bad config' >&2; exit 1"])
+            .spawn()
+            .unwrap();
+        let started = Instant::now();
+
+        let err = wait_for_serve(&mut child, free_port()).await.unwrap_err();
+
+        assert!(matches!(err, AppError::RcloneFailed { .. }), "{:?}", err);
+        assert!(started.elapsed() < Duration::from_secs(5));
+        assert!(child.try_wait().unwrap().is_some());
+    }
+
+    #[tokio::test]
+    async fn serve_that_opens_its_port_is_ready() {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let mut child = sleeper();
+
+        wait_for_serve(&mut child, port).await.unwrap();
+
+        assert!(child.try_wait().unwrap().is_none());
+        let _ = child.kill();
+        let _ = child.wait();
+    }
+
+    #[test]
+    fn resume_seek_from_a_stopped_open_never_lands_on_the_next_item() {
+        // Open A (resume at 60 s), Stop, Open B (resume at 30 s), Stop, Open C
+        // (no resume), in quick succession as the VLC loop numbers them
+        let mut generation = 0;
+        generation += 1;
+        let seek_a = PendingSeek::new(60_000, generation);
+        generation += 1; // Stop
+        generation += 1;
+        let seek_b = PendingSeek::new(30_000, generation);
+        generation += 1; // Stop
+        generation += 1; // Open C
+
+        // C starts playing at 0: neither older seek may move it
+        assert_eq!(seek_a.step(generation, Some(0)), ResumeStep::Done);
+        assert_eq!(seek_b.step(generation, Some(0)), ResumeStep::Done);
+        assert_eq!(seek_b.step(generation, None), ResumeStep::Done);
+    }
+
+    #[test]
+    fn resume_seek_waits_for_playback_of_its_own_item() {
+        let seek = PendingSeek::new(60_000, 7);
+        assert_eq!(seek.step(7, None), ResumeStep::Wait);
+        assert_eq!(seek.step(7, Some(0)), ResumeStep::Seek(60_000));
+        // :start-time already got it (close enough to) there
+        assert_eq!(seek.step(7, Some(59_000)), ResumeStep::Done);
+    }
+
+    #[test]
+    fn resume_seek_times_out_if_playback_never_starts() {
+        let mut seek = PendingSeek::new(60_000, 3);
+        seek.since = Instant::now() - RESUME_SEEK_TIMEOUT - Duration::from_secs(1);
+        assert_eq!(seek.step(3, None), ResumeStep::TimedOut);
+        // A late Playing still applies it
+        assert_eq!(seek.step(3, Some(0)), ResumeStep::Seek(60_000));
+    }
+
+    #[test]
+    fn media_info_from_an_mkv_probe() {
+        let raw = serde_json::json!({
+            "streams": [
+                {
+                    "index": 0,
+                    "codec_name": "hevc",
+                    "codec_type": "video",
+                    "width": 3840,
+                    "height": 2160,
+                    "avg_frame_rate": "24000/1001",
+                    "color_transfer": "smpte2084",
+                    "disposition": { "attached_pic": 0 }
+                },
+                {
+                    "index": 1,
+                    "codec_name": "eac3",
+                    "codec_type": "audio",
+                    "channels": 6,
+                    "tags": { "language": "eng", "title": "Surround 5.1" }
+                },
+                {
+                    "index": 2,
+                    "codec_name": "subrip",
+                    "codec_type": "subtitle",
+                    "disposition": { "forced": 1 },
+                    "tags": { "language": "eng" }
+                },
+                {
+                    "index": 3,
+                    "codec_name": "hdmv_pgs_subtitle",
+                    "codec_type": "subtitle",
+                    "tags": { "language": "und", "title": "Forced" }
+                }
+            ],
+            "format": {
+                "format_name": "matroska,webm",
+                "duration": "2712.345000",
+                "bit_rate": "18000000"
+            }
+        });
+        let info = parse_media_info(raw);
+
+        assert_eq!(info.backend, MediaInfoBackend::Ffprobe);
+        assert_eq!(info.container.as_deref(), Some("matroska,webm"));
+        assert_eq!(info.duration_s, Some(2712.345));
+        assert_eq!(info.bitrate, Some(18_000_000));
+
+        assert_eq!(info.video.len(), 1);
+        let video = &info.video[0];
+        assert_eq!(video.codec.as_deref(), Some("hevc"));
+        assert_eq!((video.width, video.height), (Some(3840), Some(2160)));
+        assert!((video.fps.unwrap() - 23.976).abs() < 0.001);
+        assert!(video.hdr);
+
+        assert_eq!(info.audio.len(), 1);
+        assert_eq!(info.audio[0].index, 1);
+        assert_eq!(info.audio[0].channels, Some(6));
+        assert_eq!(info.audio[0].language.as_deref(), Some("eng"));
+
+        assert_eq!(info.subtitles.len(), 2);
+        assert!(info.subtitles[0].forced);
+        assert_eq!(info.subtitles[1].language, None);
+        assert!(info.subtitles[1].forced);
+    }
+
+    #[test]
+    fn cover_art_in_an_mp3_is_not_video() {
+        let raw = serde_json::json!({
+            "streams": [
+                {
+                    "index": 0,
+                    "codec_name": "mp3",
+                    "codec_type": "audio",
+                    "channels": 2
+                },
+                {
+                    "index": 1,
+                    "codec_name": "mjpeg",
+                    "codec_type": "video",
+                    "width": 600,
+                    "height": 600,
+                    "disposition": { "attached_pic": 1 }
+                }
+            ],
+            "format": {
+                "format_name": "mp3",
+                "duration": "215.040000",
+                "bit_rate": "320000"
+            }
+        });
+        let info = parse_media_info(raw);
+
+        assert_eq!(info.container.as_deref(), Some("mp3"));
+        assert_eq!(info.duration_s, Some(215.04));
+        assert!(info.video.is_empty());
+        assert_eq!(info.audio.len(), 1);
+        assert_eq!(info.audio[0].codec.as_deref(), Some("mp3"));
+        assert_eq!(info.audio[0].channels, Some(2));
+        assert!(info.subtitles.is_empty());
+    }
+
+    #[test]
+    fn unreadable_file_has_no_streams() {
+        let info = parse_media_info(serde_json::json!({ "streams": [] }));
+
+        assert_eq!(info.duration_s, None);
+        assert_eq!(info.container, None);
+        assert_eq!(info.bitrate, None);
+        assert!(info.video.is_empty());
+        assert!(info.audio.is_empty());
+        assert!(info.subtitles.is_empty());
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn mount_points_with_escaped_spaces_are_unescaped() {
+        assert_eq!(unescape_mount_field(r"/home/user/My\040Drive"), "/home/user/My Drive");
+        assert_eq!(unescape_mount_field(r"tab\011and\134backslash"), "tab\tand\\backslash");
+        assert_eq!(unescape_mount_field(r"not\09octal"), r"not\09octal");
+
+        let mounts = "proc /proc proc rw,nosuid 0 0\n\
+                      gdrive:Media /home/user/My\\040Drive fuse.rclone rw,nosuid,nodev 0 0\n\
+                      sshfs#host: /mnt/ssh fuse.sshfs rw 0 0\n";
+        let fuse = parse_fuse_mounts(mounts);
+        assert_eq!(fuse.len(), 2);
+        assert_eq!(fuse[0].device, "gdrive:Media");
+        assert_eq!(fuse[0].mount_point, "/home/user/My Drive");
+        assert!(fuse[0].is_rclone());
+        assert!(!fuse[1].is_rclone());
+    }
+
+    fn key(root: &str) -> ServeKey {
+        ("rclone.conf".to_string(), root.to_string(), ServeOptions::default())
+    }
+
+    #[test]
+    fn reattaching_the_same_server_keeps_it_running() {
+        let mut servers = ServeProcesses::default();
+        servers.insert(key("gdrive:Shows/S01"), sleeper(), 8080);
+        servers.attach("queue-preload".into(), key("gdrive:Shows/S01"));
+        servers.attach("queue-preload".into(), key("gdrive:Shows/S01"));
+
+        assert_eq!(servers.live_port(&key("gdrive:Shows/S01")), Some(8080));
+        assert_eq!(servers.servers[&key("gdrive:Shows/S01")].sessions, 1);
+        servers.kill_all();
+    }
+
+    #[test]
+    fn next_episode_in_the_same_folder_reuses_the_live_server() {
+        let mut servers = ServeProcesses::default();
+        servers.insert(key("gdrive:Shows/S01"), sleeper(), 8080);
+        servers.attach("get_stream_url".into(), key("gdrive:Shows/S01"));
+
+        // `shared_serve_url` for E02 under the same session
+        assert_eq!(servers.reuse("get_stream_url".into(), key("gdrive:Shows/S01")), Some(8080));
+        assert_eq!(servers.reuse("get_stream_url".into(), key("gdrive:Shows/S01")), Some(8080));
+        assert_eq!(servers.live_port(&key("gdrive:Shows/S01")), Some(8080));
+        servers.kill_all();
+    }
+
+    #[test]
+    fn moving_a_session_releases_its_old_server() {
+        let mut servers = ServeProcesses::default();
+        servers.insert(key("gdrive:Shows/S01"), sleeper(), 8080);
+        servers.insert(key("gdrive:Shows/S02"), sleeper(), 8081);
+        servers.attach("get_stream_url".into(), key("gdrive:Shows/S01"));
+
+        assert_eq!(servers.reuse("get_stream_url".into(), key("gdrive:Shows/S02")), Some(8081));
+        assert_eq!(servers.live_port(&key("gdrive:Shows/S01")), None);
+        servers.kill_all();
+    }
+
+    #[test]
+    fn back_to_back_preloads_from_one_album_keep_its_server() {
+        let album = key("gdrive:Music/Album");
+        let mut servers = ServeProcesses::default();
+        servers.insert(album.clone(), sleeper(), 8082);
+        // Track 2 was preloaded and swapped in; it still streams under the preload session
+        servers.attach(QUEUE_PRELOAD_SESSION.into(), album.clone());
+        // Preloading track 3 from the same folder
+        assert_eq!(servers.reuse(QUEUE_PRELOAD_SESSION.into(), album.clone()), Some(8082));
+        // And track 4
+        assert_eq!(servers.reuse(QUEUE_PRELOAD_SESSION.into(), album.clone()), Some(8082));
+
+        assert_eq!(servers.live_port(&album), Some(8082));
+        servers.release(QUEUE_PRELOAD_SESSION);
+        assert_eq!(servers.live_port(&album), None);
+    }
+
+    #[test]
+    fn second_preview_in_the_same_folder_gets_a_live_server() {
+        let folder = key("gdrive:Music/Album");
+        let mut servers = ServeProcesses::default();
+        servers.insert(folder.clone(), sleeper(), 8083);
+        servers.attach(PREVIEW_SESSION.into(), folder.clone());
+
+        // Hovering the next track resolves through the same preview session
+        assert_eq!(servers.reuse(PREVIEW_SESSION.into(), folder.clone()), Some(8083));
+        assert_eq!(servers.live_port(&folder), Some(8083));
+        servers.kill_all();
+    }
+
+    #[test]
+    fn file_uri_encodes_unix_and_windows_paths() {
+        assert_eq!(file_uri("/tmp/subs/Ep 1.srt"), "file:///tmp/subs/Ep%201.srt");
+        assert_eq!(file_uri("C:\\Subs\\ep1.srt"), "file:///C%3A/Subs/ep1.srt");
+    }
+
+    #[test]
+    fn path_within_mount_only_resolves_paths_under_the_mounted_folder() {
+        assert_eq!(path_within_mount("", "Movies/a.mkv"), Some("Movies/a.mkv"));
+        assert_eq!(path_within_mount("/Movies/", "Movies/a.mkv"), Some("a.mkv"));
+        assert_eq!(path_within_mount("Movies", "Movies"), Some(""));
+        assert_eq!(path_within_mount("Movies", "MoviesOld/a.mkv"), None);
+        assert_eq!(path_within_mount("Shows", "Movies/a.mkv"), None);
     }
 }
@@ -0,0 +1,355 @@
+use std::collections::HashMap;
+use std::io::SeekFrom;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use tauri::{AppHandle, Emitter};
+use tokio::io::{AsyncBufReadExt, AsyncReadExt, AsyncSeekExt, AsyncWriteExt, BufReader};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::oneshot;
+
+use crate::commands::player::{self, ServeOptions};
+use crate::commands::{downloads, scan, store, temp_cache};
+use crate::error::AppError;
+
+/// Setting: bytes prefetched from the start of the next item; 0 turns
+/// prefetching off
+const HEAD_BYTES_SETTING: &str = "player.prefetch_head_bytes";
+const DEFAULT_HEAD_BYTES: u64 = 100 * 1024 * 1024;
+/// Setting: rclone `--bwlimit` for prefetching, so it leaves bandwidth to the
+/// item that's playing
+const BWLIMIT_SETTING: &str = "player.prefetch_bwlimit";
+const DEFAULT_BWLIMIT: &str = "2M";
+
+/// Counter for temp cache session ids, one per prefetch or proxy, so ending
+/// one never unpins another's file
+static NEXT_SESSION: AtomicU64 = AtomicU64::new(0);
+
+/// The running prefetch: the remote file and how to stop it
+static RUNNING: Mutex<Option<(String, oneshot::Sender<()>)>> = Mutex::new(None);
+
+/// Full size of each remote file with a head in the temp cache, by remote path
+static HEADS: Mutex<Option<HashMap<String, u64>>> = Mutex::new(None);
+
+/// The running head proxy: the URL prefix it answers on and how to stop it
+static PROXY: Mutex<Option<(String, oneshot::Sender<()>)>> = Mutex::new(None);
+
+/// The first bytes of a remote file, on disk, and the file's full size.
+pub(crate) struct WarmHead {
+    path: PathBuf,
+    len: u64,
+    total: u64,
+}
+
+fn new_session() -> String {
+    format!("prefetch-{}", NEXT_SESSION.fetch_add(1, Ordering::Relaxed))
+}
+
+/// "remote:root" + "dir/file.mkv" -> "remote:root/dir/file.mkv", the key the
+/// temp cache files a prefetched item under.
+fn remote_path(remote_root: &str, file_path: &str) -> String {
+    format!(
+        "{}/{}",
+        remote_root.trim_end_matches('/'),
+        file_path.trim_start_matches('/')
+    )
+}
+
+/// Temp cache key of the head of `remote_path`
+fn head_key(remote_path: &str) -> String {
+    format!("{}.head", remote_path)
+}
+
+/// An item prefetched whole, to play instead of streaming it.
+pub(crate) fn cached(app: &AppHandle, remote_root: &str, file_path: &str) -> Option<PathBuf> {
+    temp_cache::touch(app, &remote_path(remote_root, file_path)).filter(|p| p.is_file())
+}
+
+/// The prefetched head of the item, if any of it is still on disk.
+pub(crate) fn warm_head(app: &AppHandle, remote_root: &str, file_path: &str) -> Option<WarmHead> {
+    let remote_path = remote_path(remote_root, file_path);
+    let total = *HEADS.lock().unwrap().as_ref()?.get(&remote_path)?;
+    let path = temp_cache::touch(app, &head_key(&remote_path))?;
+    let len = std::fs::metadata(&path).ok()?.len().min(total);
+    (len > 0).then_some(WarmHead { path, len, total })
+}
+
+/// Start copying the first `HEAD_BYTES_SETTING` bytes of the item into the temp
+/// cache in the background, replacing any other prefetch. Playback starts from
+/// that head and streams the rest (see `serve_head`); an item no larger than
+/// the head is kept whole and plays as a local file.
+pub(crate) fn start(app: &AppHandle, config_path: &str, remote_root: &str, file_path: &str) {
+    let head_bytes = store::get_setting(app, HEAD_BYTES_SETTING).unwrap_or(DEFAULT_HEAD_BYTES);
+    if head_bytes == 0 || cached(app, remote_root, file_path).is_some() {
+        return;
+    }
+    let remote_path = remote_path(remote_root, file_path);
+    let (cancel_tx, cancel_rx) = oneshot::channel();
+    {
+        let mut running = RUNNING.lock().unwrap();
+        if running.as_ref().is_some_and(|(path, _)| *path == remote_path) {
+            return;
+        }
+        if let Some((_, previous)) = running.replace((remote_path.clone(), cancel_tx)) {
+            let _ = previous.send(());
+        }
+    }
+
+    let app = app.clone();
+    let config_path = config_path.to_string();
+    tauri::async_runtime::spawn(async move {
+        let head_bytes = head_bytes.min(temp_cache::limit_bytes(&app) / 2);
+        match prefetch(&app, &config_path, &remote_path, head_bytes, cancel_rx).await {
+            Ok(true) => {
+                let _ = app.emit("player:prefetch-complete", serde_json::json!({ "remote_path": remote_path }));
+            }
+            Ok(false) => {}
+            Err(e) => eprintln!("Prefetch of {} failed: {}", remote_path, e),
+        }
+        let mut running = RUNNING.lock().unwrap();
+        if running.as_ref().is_some_and(|(path, _)| *path == remote_path) {
+            *running = None;
+        }
+    });
+}
+
+/// Returns Ok(false) when cancelled or skipped.
+async fn prefetch(
+    app: &AppHandle,
+    config_path: &str,
+    remote_path: &str,
+    head_bytes: u64,
+    cancel: oneshot::Receiver<()>,
+) -> Result<bool, AppError> {
+    let stat = downloads::stat_remote(app, config_path, remote_path).await?;
+    // The proxy has to know where the file ends
+    let Some(total) = stat.size.filter(|&size| size > 0 && head_bytes > 0) else {
+        return Ok(false);
+    };
+    let filename = remote_path
+        .rsplit('/')
+        .find(|s| !s.is_empty() && !s.ends_with(':'))
+        .unwrap_or("media");
+    let local_path = temp_cache::cache_dir()
+        .join(scan::hash_remote_path(remote_path.to_string()))
+        .join(filename);
+    let mut head_path = local_path.clone().into_os_string();
+    head_path.push(".head");
+    let head_path = PathBuf::from(head_path);
+    let bwlimit = store::get_setting::<String>(app, BWLIMIT_SETTING).unwrap_or_else(|| DEFAULT_BWLIMIT.to_string());
+    let options = ServeOptions::new(Some(&bwlimit), None, None)?;
+
+    // A cancelled prefetch leaves its head short, which the next one resumes
+    // and playback can still start from. It's indexed so it counts against the
+    // cache limit and can be evicted, but held open while it's being written.
+    HEADS.lock().unwrap().get_or_insert_with(HashMap::new).insert(remote_path.to_string(), total);
+    let len = head_bytes.min(total);
+    let session = new_session();
+    temp_cache::hold(&session, &head_path);
+    let result = downloads::download_head(app, config_path, remote_path, &head_path, len, &options, cancel).await;
+    temp_cache::release_session(&session);
+
+    // The whole file fit: keep it as the complete copy, played locally
+    if matches!(result, Ok(true)) && len == total && std::fs::rename(&head_path, &local_path).is_ok() {
+        temp_cache::insert(app, remote_path, &local_path);
+        if let Some(heads) = HEADS.lock().unwrap().as_mut() {
+            heads.remove(remote_path);
+        }
+    } else if head_path.is_file() {
+        temp_cache::insert(app, &head_key(remote_path), &head_path);
+    }
+    Ok(result?)
+}
+
+/// Stop the running prefetch, if any.
+pub(crate) fn cancel() {
+    if let Some((_, cancel)) = RUNNING.lock().unwrap().take() {
+        let _ = cancel.send(());
+    }
+}
+
+/// Serve `head` on a loopback port, with the bytes past it streamed from
+/// `upstream` (the item's rclone serve URL), and return the URL for VLC.
+/// Replaces any running proxy; the head stays held open while it's served.
+pub(crate) async fn serve_head(head: WarmHead, upstream: &str, file_path: &str) -> Result<String, AppError> {
+    stop_proxy();
+    let listener = TcpListener::bind(("127.0.0.1", 0))
+        .await
+        .map_err(|e| format!("Failed to start the prefetch proxy: {}", e))?;
+    let port = listener
+        .local_addr()
+        .map_err(|e| format!("Failed to start the prefetch proxy: {}", e))?
+        .port();
+    let base = format!("http://127.0.0.1:{}/", port);
+    let session = new_session();
+    temp_cache::hold(&session, &head.path);
+    let (stop_tx, mut stop_rx) = oneshot::channel();
+    *PROXY.lock().unwrap() = Some((base.clone(), stop_tx));
+
+    let head = Arc::new(head);
+    let upstream = upstream.to_string();
+    tauri::async_runtime::spawn(async move {
+        loop {
+            tokio::select! {
+                accepted = listener.accept() => match accepted {
+                    Ok((stream, _)) => {
+                        let head = head.clone();
+                        let upstream = upstream.clone();
+                        tauri::async_runtime::spawn(async move {
+                            match proxy_request(stream, &head, &upstream).await {
+                                Err(e) if !matches!(e.kind(), std::io::ErrorKind::BrokenPipe | std::io::ErrorKind::ConnectionReset) => {
+                                    eprintln!("Prefetch proxy request failed: {}", e);
+                                }
+                                _ => {}
+                            }
+                        });
+                    }
+                    Err(e) => {
+                        eprintln!("Prefetch proxy stopped: {}", e);
+                        break;
+                    }
+                },
+                _ = &mut stop_rx => break,
+            }
+        }
+        temp_cache::release_session(&session);
+    });
+
+    Ok(format!("{}{}", base, player::percent_encode_path(file_path.trim_start_matches('/'))))
+}
+
+/// Stop the head proxy, if any. Requests already being answered finish.
+pub(crate) fn stop_proxy() {
+    if let Some((_, stop)) = PROXY.lock().unwrap().take() {
+        let _ = stop.send(());
+    }
+}
+
+/// Stop the head proxy if `url` is one of its URLs. Returns whether it was.
+pub(crate) fn stop_proxy_for(url: &str) -> bool {
+    let mut proxy = PROXY.lock().unwrap();
+    if !proxy.as_ref().is_some_and(|(base, _)| url.starts_with(base.as_str())) {
+        return false;
+    }
+    if let Some((_, stop)) = proxy.take() {
+        let _ = stop.send(());
+    }
+    true
+}
+
+/// "bytes=START-END", "bytes=START-" or "bytes=-SUFFIX" -> the inclusive byte
+/// span of a `total`-byte file, None when unsatisfiable. Only the first range
+/// of a multi-range request is answered.
+fn parse_range(value: &str, total: u64) -> Option<(u64, u64)> {
+    let spec = value.trim().strip_prefix("bytes=")?.split(',').next()?.trim();
+    let (start, end) = spec.split_once('-')?;
+    let last = total.checked_sub(1)?;
+    let (start, end) = match (start.trim(), end.trim()) {
+        ("", suffix) => (total.saturating_sub(suffix.parse::<u64>().ok().filter(|&n| n > 0)?), last),
+        (start, "") => (start.parse().ok()?, last),
+        (start, end) => (start.parse().ok()?, end.parse::<u64>().ok()?.min(last)),
+    };
+    (start <= end).then_some((start, end))
+}
+
+/// Answer one HTTP request on `stream`: the requested range from the head file
+/// as far as it goes, then from `upstream`.
+async fn proxy_request(stream: TcpStream, head: &WarmHead, upstream: &str) -> std::io::Result<()> {
+    let (reader, mut writer) = stream.into_split();
+    let mut reader = BufReader::new(reader);
+    let mut request_line = String::new();
+    reader.read_line(&mut request_line).await?;
+    let mut range = None;
+    loop {
+        let mut line = String::new();
+        if reader.read_line(&mut line).await? == 0 || line.trim().is_empty() {
+            break;
+        }
+        if let Some((name, value)) = line.split_once(':') {
+            if name.trim().eq_ignore_ascii_case("range") {
+                range = Some(value.trim().to_string());
+            }
+        }
+    }
+
+    let span = match &range {
+        Some(value) => parse_range(value, head.total),
+        None => Some((0, head.total - 1)),
+    };
+    let Some((start, end)) = span else {
+        let response = format!(
+            "HTTP/1.1 416 Range Not Satisfiable\r\nContent-Range: bytes */{}\r\nContent-Length: 0\r\nConnection: close\r\n\r\n",
+            head.total
+        );
+        return writer.write_all(response.as_bytes()).await;
+    };
+    let (status, content_range) = match range {
+        Some(_) => ("206 Partial Content", format!("Content-Range: bytes {}-{}/{}\r\n", start, end, head.total)),
+        None => ("200 OK", String::new()),
+    };
+    let response = format!(
+        "HTTP/1.1 {}\r\nContent-Type: application/octet-stream\r\nAccept-Ranges: bytes\r\n{}Content-Length: {}\r\nConnection: close\r\n\r\n",
+        status,
+        content_range,
+        end - start + 1
+    );
+    writer.write_all(response.as_bytes()).await?;
+    if request_line.starts_with("HEAD ") {
+        return Ok(());
+    }
+
+    let mut pos = start;
+    let head_end = head.len.min(end + 1);
+    if pos < head_end {
+        let mut file = tokio::fs::File::open(&head.path).await?;
+        file.seek(SeekFrom::Start(pos)).await?;
+        pos += tokio::io::copy(&mut file.take(head_end - pos), &mut writer).await?;
+        if pos < head_end {
+            return Err(std::io::Error::new(std::io::ErrorKind::UnexpectedEof, "prefetched head is shorter than expected"));
+        }
+    }
+    if pos <= end {
+        let mut resp = tauri_plugin_http::reqwest::Client::new()
+            .get(upstream)
+            .header("Range", format!("bytes={}-{}", pos, end))
+            .send()
+            .await
+            .map_err(std::io::Error::other)?;
+        if resp.status().as_u16() != 206 {
+            return Err(std::io::Error::other(format!("stream answered HTTP {}", resp.status().as_u16())));
+        }
+        while let Some(chunk) = resp.chunk().await.map_err(std::io::Error::other)? {
+            writer.write_all(&chunk).await?;
+        }
+    }
+    writer.flush().await
+}
+
+/// Stop prefetching the next queue item; it streams as usual when it comes up.
+#[tauri::command]
+pub fn player_cancel_prefetch() -> Result<(), AppError> {
+    cancel();
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_range_handles_open_closed_and_suffix_ranges() {
+        assert_eq!(parse_range("bytes=0-", 1000), Some((0, 999)));
+        assert_eq!(parse_range("bytes=100-199", 1000), Some((100, 199)));
+        assert_eq!(parse_range("bytes=900-5000", 1000), Some((900, 999)));
+        assert_eq!(parse_range("bytes=-100", 1000), Some((900, 999)));
+        assert_eq!(parse_range("bytes=0-9, 20-29", 1000), Some((0, 9)));
+    }
+
+    #[test]
+    fn parse_range_rejects_unsatisfiable_ranges() {
+        assert_eq!(parse_range("bytes=1000-", 1000), None);
+        assert_eq!(parse_range("bytes=200-100", 1000), None);
+        assert_eq!(parse_range("items=0-1", 1000), None);
+        assert_eq!(parse_range("bytes=-0", 1000), None);
+    }
+}
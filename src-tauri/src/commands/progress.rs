@@ -0,0 +1,252 @@
+use serde::{Deserialize, Serialize};
+use std::time::{SystemTime, UNIX_EPOCH};
+use tauri::AppHandle;
+use tauri_plugin_store::StoreExt;
+
+use crate::commands::{bookmarks, store};
+use crate::commands::scan::{hash_remote_path, legacy_hash_remote_path};
+use crate::error::AppError;
+
+const STORE_PATH: &str = "rcloneflix-progress.json";
+
+/// Positions before this count as "not started" and don't leave a resume point.
+const NOT_STARTED_MS: i64 = 30_000;
+/// Past this fraction of the duration an item counts as watched: its resume
+/// point is dropped and the player emits `player:watched`.
+pub const DEFAULT_WATCHED_FRACTION: f64 = 0.9;
+/// Setting: the watched fraction (0-1], set with `player_set_watched_threshold`
+pub const WATCHED_THRESHOLD_SETTING: &str = "player.watched_threshold";
+
+/// Resume position for one media item, keyed in the store by its item id
+/// (`hash_remote_path` of the full remote path).
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct WatchProgress {
+    pub position_ms: i64,
+    pub duration_ms: i64,
+    /// Watched past the end threshold; no resume prompt should be shown
+    pub finished: bool,
+    /// Unix seconds
+    pub updated_at: u64,
+}
+
+/// Item id for a file, matching the ids the library scanner assigns.
+pub fn item_id_for(remote_root: &str, file_path: &str) -> String {
+    hash_remote_path(format!(
+        "{}/{}",
+        remote_root.trim_end_matches('/'),
+        file_path.trim_start_matches('/')
+    ))
+}
+
+/// Whether `position_ms` is past `fraction` of a known duration.
+pub fn watched_at(position_ms: i64, duration_ms: i64, fraction: f64) -> bool {
+    duration_ms > 0 && position_ms as f64 >= duration_ms as f64 * fraction
+}
+
+/// The stored watched fraction, or `DEFAULT_WATCHED_FRACTION`.
+pub fn watched_fraction(app: &AppHandle) -> f64 {
+    store::get_setting::<f64>(app, WATCHED_THRESHOLD_SETTING)
+        .filter(|&f| f > 0.0 && f <= 1.0)
+        .unwrap_or(DEFAULT_WATCHED_FRACTION)
+}
+
+fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+/// Store the playback position for an item. Positions in the first 30 seconds
+/// clear the entry; positions past `watched_fraction` of it mark it finished.
+/// The store is only written to disk when `flush` is set, so the 1 Hz tick
+/// stays cheap.
+pub fn record_progress(
+    app: &AppHandle,
+    item_id: &str,
+    position_ms: i64,
+    duration_ms: i64,
+    watched_fraction: f64,
+    flush: bool,
+) -> Result<(), String> {
+    let store = app
+        .store(STORE_PATH)
+        .map_err(|e| format!("Failed to open store: {}", e))?;
+
+    let finished = watched_at(position_ms, duration_ms, watched_fraction);
+    if position_ms < NOT_STARTED_MS && !finished {
+        store.delete(item_id);
+    } else {
+        let progress = WatchProgress {
+            position_ms,
+            duration_ms,
+            finished,
+            updated_at: unix_now(),
+        };
+        store.set(item_id, serde_json::to_value(&progress).unwrap());
+    }
+
+    if flush {
+        store
+            .save()
+            .map_err(|e| format!("Failed to save store: {}", e))?;
+    }
+    Ok(())
+}
+
+/// Where playback should start for an item: the stored position, or 0 when
+/// there is none or the item was finished.
+pub fn resume_position(app: &AppHandle, item_id: &str) -> i64 {
+    load_progress(app, item_id)
+        .filter(|p| !p.finished)
+        .map(|p| p.position_ms)
+        .unwrap_or(0)
+}
+
+fn load_progress(app: &AppHandle, item_id: &str) -> Option<WatchProgress> {
+    let store = app.store(STORE_PATH).ok()?;
+    store
+        .get(item_id)
+        .and_then(|v| serde_json::from_value(v).ok())
+}
+
+/// Record where playback of `media_id` stopped, for players that don't go
+/// through `open_media` (which saves progress on its own). Same rules as the
+/// automatic tracking: the start doesn't count, passing the watched threshold
+/// marks it watched.
+#[tauri::command]
+pub async fn save_resume_position(
+    app: AppHandle,
+    media_id: String,
+    position_ms: i64,
+    duration_ms: i64,
+) -> Result<(), AppError> {
+    Ok(record_progress(&app, &media_id, position_ms, duration_ms, watched_fraction(&app), true)?)
+}
+
+/// Position to resume `media_id` from, or None when it wasn't started or was watched.
+#[tauri::command]
+pub async fn load_resume_position(app: AppHandle, media_id: String) -> Result<Option<i64>, AppError> {
+    Ok(Some(resume_position(&app, &media_id)).filter(|&ms| ms > 0))
+}
+
+#[tauri::command]
+pub async fn clear_resume_position(app: AppHandle, media_id: String) -> Result<(), AppError> {
+    clear_watch_progress(app, media_id).await
+}
+
+#[tauri::command]
+pub async fn get_watch_progress(
+    app: AppHandle,
+    item_id: String,
+) -> Result<Option<WatchProgress>, AppError> {
+    Ok(load_progress(&app, &item_id))
+}
+
+/// All stored progress entries, keyed by item id.
+#[tauri::command]
+pub async fn get_all_watch_progress(
+    app: AppHandle,
+) -> Result<std::collections::HashMap<String, WatchProgress>, AppError> {
+    let store = app
+        .store(STORE_PATH)
+        .map_err(|e| format!("Failed to open store: {}", e))?;
+    Ok(store
+        .entries()
+        .into_iter()
+        .filter_map(|(k, v)| serde_json::from_value(v).ok().map(|p| (k, p)))
+        .collect())
+}
+
+#[tauri::command]
+pub async fn clear_watch_progress(app: AppHandle, item_id: String) -> Result<(), AppError> {
+    let store = app
+        .store(STORE_PATH)
+        .map_err(|e| format!("Failed to open store: {}", e))?;
+    store.delete(&item_id);
+    store
+        .save()
+        .map_err(|e| format!("Failed to save store: {}", e))?;
+    Ok(())
+}
+
+/// Whether playback at `position_ms` counts as having watched the item, past
+/// `threshold_pct` percent of it (default: the threshold the automatic tracking uses).
+#[tauri::command]
+pub fn is_watched(
+    app: AppHandle,
+    position_ms: i64,
+    duration_ms: i64,
+    threshold_pct: Option<f32>,
+) -> Result<bool, AppError> {
+    let fraction = match threshold_pct {
+        Some(pct) if !(pct > 0.0 && pct <= 100.0) => {
+            return Err(AppError::invalid(format!("Watched threshold must be in (0, 100], got {}", pct)));
+        }
+        Some(pct) => f64::from(pct) / 100.0,
+        None => watched_fraction(&app),
+    };
+    Ok(watched_at(position_ms, duration_ms, fraction))
+}
+
+/// Mark `media_id` watched without playing it, keeping its known duration.
+#[tauri::command]
+pub async fn mark_watched(app: AppHandle, media_id: String) -> Result<(), AppError> {
+    let duration_ms = load_progress(&app, &media_id).map(|p| p.duration_ms).unwrap_or(0);
+    let store = app
+        .store(STORE_PATH)
+        .map_err(|e| format!("Failed to open store: {}", e))?;
+    let progress = WatchProgress {
+        position_ms: duration_ms,
+        duration_ms,
+        finished: true,
+        updated_at: unix_now(),
+    };
+    store.set(&media_id, serde_json::to_value(&progress).unwrap());
+    store
+        .save()
+        .map_err(|e| format!("Failed to save store: {}", e))?;
+    Ok(())
+}
+
+/// Forget that `media_id` was watched or started.
+#[tauri::command]
+pub async fn mark_unwatched(app: AppHandle, media_id: String) -> Result<(), AppError> {
+    clear_watch_progress(app, media_id).await
+}
+
+/// Move stored progress and bookmarks from the old djb2 item ids of
+/// `remote_paths` to their current ids. Returns old id -> new id for every
+/// path, so the frontend can rekey its own library. Safe to run again: ids
+/// that already moved are left alone.
+#[tauri::command]
+pub async fn migrate_item_ids(
+    app: AppHandle,
+    remote_paths: Vec<String>,
+) -> Result<std::collections::HashMap<String, String>, AppError> {
+    let ids: std::collections::HashMap<String, String> = remote_paths
+        .into_iter()
+        .map(|path| (legacy_hash_remote_path(&path), hash_remote_path(path)))
+        .collect();
+
+    let store = app
+        .store(STORE_PATH)
+        .map_err(|e| format!("Failed to open store: {}", e))?;
+    let mut moved = 0;
+    for (old_id, new_id) in &ids {
+        if let Some(progress) = store.get(old_id) {
+            if !store.has(new_id) {
+                store.set(new_id, progress);
+            }
+            store.delete(old_id);
+            moved += 1;
+        }
+    }
+    if moved > 0 {
+        store
+            .save()
+            .map_err(|e| format!("Failed to save store: {}", e))?;
+    }
+    bookmarks::rekey(&app, &ids)?;
+    Ok(ids)
+}
@@ -1,12 +1,49 @@
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::path::{Path, PathBuf};
-use std::process::{Child, Command};
-use tauri::{AppHandle, Emitter, Manager};
-use crate::commands::player::{parse_remote_root, percent_encode_path, wait_for_port};
-
-// Global storage for the rclone serve child process (so it doesn't get dropped)
+use std::process::Command;
 use std::sync::Mutex;
-static SERVE_PROCESS: Mutex<Option<Child>> = Mutex::new(None);
+use tauri::{AppHandle, Emitter, Manager, State};
+use crate::commands::player::{shared_serve_url, ServeOptions, VlcManager};
+use crate::error::AppError;
+
+/// Session id under which `get_stream_url` holds its serve process.
+/// Each call moves the session, so the previous folder's server is released.
+const STREAM_URL_SESSION: &str = "get_stream_url";
+
+/// How long `test_remote` waits for rclone before reporting a network problem
+const TEST_REMOTE_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(15);
+
+/// Passwords for encrypted configs by config path, kept in memory for the session
+/// so every rclone process spawned for that config can be given it.
+static CONFIG_PASSWORDS: Mutex<Option<HashMap<String, String>>> = Mutex::new(None);
+
+/// Remember (or with None, keep) the password for an encrypted config.
+pub(crate) fn remember_config_password(config_path: &str, password: Option<String>) {
+    if let Some(password) = password.filter(|p| !p.is_empty()) {
+        CONFIG_PASSWORDS
+            .lock()
+            .unwrap()
+            .get_or_insert_with(HashMap::new)
+            .insert(config_path.to_string(), password);
+    }
+}
+
+/// Environment for an rclone process using `config_path`: the config password if
+/// one was given, and no interactive password prompt, which would hang the
+/// process waiting on stdin.
+pub(crate) fn config_env(config_path: &str) -> Vec<(&'static str, String)> {
+    let mut env = vec![("RCLONE_ASK_PASSWORD", "false".to_string())];
+    let password = CONFIG_PASSWORDS
+        .lock()
+        .unwrap()
+        .as_ref()
+        .and_then(|passwords| passwords.get(config_path).cloned());
+    if let Some(password) = password {
+        env.push(("RCLONE_CONFIG_PASS", password));
+    }
+    env
+}
 
 /// Represents a single rclone remote parsed from the config file
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -48,58 +85,110 @@ fn rclone_binary(app: &AppHandle) -> PathBuf {
     PathBuf::from("rclone")
 }
 
+/// Remotes in a config according to `rclone config dump`.
+async fn dump_remotes(app: &AppHandle, config_path: &str) -> Result<Vec<RcloneRemote>, AppError> {
+    let output = tokio::process::Command::new(rclone_binary(app))
+        .args(["config", "dump", "--config", config_path])
+        .envs(config_env(config_path))
+        .output()
+        .await
+        .map_err(AppError::rclone_spawn)?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        if stderr.contains("password") {
+            return Err(AppError::ConfigPassword);
+        }
+        return Err(AppError::rclone_failed(stderr));
+    }
+
+    // {"remote": {"type": "drive", ...}, ...}, ordered by remote name
+    let dump: serde_json::Map<String, serde_json::Value> = serde_json::from_slice(&output.stdout)
+        .map_err(|e| format!("Failed to parse rclone output: {}", e))?;
+    Ok(dump
+        .into_iter()
+        .filter_map(|(name, fields)| {
+            let remote_type = fields["type"].as_str()?.to_string();
+            Some(RcloneRemote { name, remote_type })
+        })
+        .collect())
+}
+
 /// Parse an rclone config file and return the list of remotes.
-/// The rclone config format is an INI-style file where section names are remote names
-/// and the `type` key gives the remote type.
+/// Reads the config through `rclone config dump`, so quoting, comments, crypt
+/// remotes wrapping other remotes and encrypted configs are handled exactly as
+/// rclone itself sees them. An encrypted config needs `config_password`, which
+/// is then used for every rclone process spawned for this config.
 #[tauri::command]
-pub fn parse_rclone_config(
+pub async fn parse_rclone_config(
+    app: AppHandle,
     config_path: String,
-) -> Result<Vec<RcloneRemote>, String> {
-    let path = Path::new(&config_path);
-    if !path.exists() {
-        return Err(format!("Config file not found: {}", config_path));
+    config_password: Option<String>,
+) -> Result<Vec<RcloneRemote>, AppError> {
+    if !Path::new(&config_path).exists() {
+        return Err(AppError::ConfigNotFound { path: config_path });
     }
+    remember_config_password(&config_path, config_password);
 
-    let content = std::fs::read_to_string(path)
-        .map_err(|e| format!("Failed to read config: {}", e))?;
-
-    let mut remotes = Vec::new();
-    let mut current_section: Option<String> = None;
-    let mut current_type: Option<String> = None;
+    let remotes = dump_remotes(&app, &config_path).await?;
+    if remotes.is_empty() {
+        return Err("No remotes found in the config file. Is this a valid rclone config?".into());
+    }
 
-    for line in content.lines() {
-        let line = line.trim();
+    Ok(remotes)
+}
 
-        if line.starts_with('[') && line.ends_with(']') {
-            // Save previous section if it had a type
-            if let (Some(name), Some(rtype)) = (current_section.take(), current_type.take()) {
-                remotes.push(RcloneRemote {
-                    name,
-                    remote_type: rtype,
-                });
-            }
-            current_section = Some(line[1..line.len() - 1].to_string());
-            current_type = None;
-        } else if line.starts_with("type") {
-            if let Some(value) = line.splitn(2, '=').nth(1) {
-                current_type = Some(value.trim().to_string());
-            }
-        }
+/// Add a remote to the config (created if missing) with `rclone config create`,
+/// for backends that need no browser sign-in such as local, webdav or s3.
+/// Secrets in `options` are obscured by rclone as it writes them. Returns the
+/// config's remotes afterwards.
+#[tauri::command]
+pub async fn create_remote(
+    app: AppHandle,
+    config_path: String,
+    name: String,
+    remote_type: String,
+    options: HashMap<String, String>,
+) -> Result<Vec<RcloneRemote>, AppError> {
+    let name = name.trim();
+    if name.is_empty()
+        || name.starts_with('-')
+        || !name.chars().all(|c| c.is_alphanumeric() || "_-.+@ ".contains(c))
+    {
+        return Err(AppError::invalid(format!("Invalid remote name: {:?}", name)));
     }
-
-    // Don't forget the last section
-    if let (Some(name), Some(rtype)) = (current_section, current_type) {
-        remotes.push(RcloneRemote {
-            name,
-            remote_type: rtype,
-        });
+    let remote_type = remote_type.trim();
+    if remote_type.is_empty() {
+        return Err(AppError::invalid("Remote type must not be empty"));
+    }
+    if Path::new(&config_path).exists()
+        && dump_remotes(&app, &config_path).await?.iter().any(|r| r.name == name)
+    {
+        return Err(AppError::invalid(format!("A remote named {} already exists", name)));
     }
 
-    if remotes.is_empty() {
-        return Err("No remotes found in the config file. Is this a valid rclone config?".to_string());
+    // Sorted so the command line is stable
+    let mut options: Vec<(String, String)> = options.into_iter().collect();
+    options.sort();
+    if let Some((key, _)) = options
+        .iter()
+        .find(|(key, _)| key.is_empty() || !key.chars().all(|c| c.is_ascii_alphanumeric() || c == '_'))
+    {
+        return Err(AppError::invalid(format!("Invalid option name: {:?}", key)));
+    }
+    // One `key=value` argument per option, so a value starting with "-"
+    // isn't taken for a flag
+    let output = tokio::process::Command::new(rclone_binary(&app))
+        .args(["config", "create", "--config", &config_path, "--non-interactive", name, remote_type])
+        .args(options.iter().map(|(key, value)| format!("{}={}", key, value)))
+        .envs(config_env(&config_path))
+        .output()
+        .await
+        .map_err(AppError::rclone_spawn)?;
+    if !output.status.success() {
+        return Err(AppError::rclone_failed(String::from_utf8_lossy(&output.stderr)));
     }
 
-    Ok(remotes)
+    parse_rclone_config(app, config_path, None).await
 }
 
 /// List the contents of a remote path using rclone lsjson
@@ -108,7 +197,9 @@ pub async fn list_remote_path(
     app: AppHandle,
     config_path: String,
     remote_path: String,
-) -> Result<Vec<RcloneListItem>, String> {
+    config_password: Option<String>,
+) -> Result<Vec<RcloneListItem>, AppError> {
+    remember_config_password(&config_path, config_password);
     let rclone = rclone_binary(&app);
 
     let output = Command::new(&rclone)
@@ -119,12 +210,12 @@ pub async fn list_remote_path(
             "--no-modtime",
             &remote_path,
         ])
+        .envs(config_env(&config_path))
         .output()
-        .map_err(|e| format!("Failed to run rclone: {}. Is rclone installed?", e))?;
+        .map_err(AppError::rclone_spawn)?;
 
     if !output.status.success() {
-        let stderr = String::from_utf8_lossy(&output.stderr);
-        return Err(format!("rclone error: {}", stderr));
+        return Err(AppError::rclone_failed(String::from_utf8_lossy(&output.stderr)));
     }
 
     let stdout = String::from_utf8_lossy(&output.stdout);
@@ -160,90 +251,428 @@ pub async fn list_remote_path(
     Ok(result)
 }
 
+/// Why `test_remote` failed, so the UI can say what to fix
+#[derive(Debug, Serialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum RemoteErrorKind {
+    /// Expired token, revoked access or wrong credentials
+    Auth,
+    /// The remote name or the path on it doesn't exist
+    NotFound,
+    /// Unreachable, timed out or a temporary backend failure
+    Network,
+    Other,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct RemoteTestError {
+    pub kind: RemoteErrorKind,
+    pub message: String,
+}
+
+impl RemoteTestError {
+    fn new(kind: RemoteErrorKind, message: impl Into<String>) -> Self {
+        Self { kind, message: message.into() }
+    }
+}
+
+/// Categorise a failed rclone run from its exit code and stderr.
+/// See https://rclone.org/docs/#exit-code for the codes.
+fn classify_rclone_error(code: Option<i32>, stderr: &str) -> RemoteErrorKind {
+    let lower = stderr.to_lowercase();
+    const AUTH: &[&str] = &[
+        "invalid_grant", "unauthorized", "401", "403", "forbidden",
+        "token expired", "couldn't fetch token", "authentication", "access denied",
+        "invalid credentials",
+    ];
+    // "didn't find section" is a remote name missing from the config
+    const NOT_FOUND: &[&str] = &["not found", "didn't find section in config file"];
+    const NETWORK: &[&str] = &[
+        "no such host", "connection refused", "connection reset", "network is unreachable",
+        "i/o timeout", "timeout", "tls handshake", "dial tcp",
+    ];
+    if AUTH.iter().any(|p| lower.contains(p)) {
+        RemoteErrorKind::Auth
+    } else if matches!(code, Some(3) | Some(4)) || NOT_FOUND.iter().any(|p| lower.contains(p)) {
+        RemoteErrorKind::NotFound
+    } else if code == Some(5) || NETWORK.iter().any(|p| lower.contains(p)) {
+        RemoteErrorKind::Network
+    } else {
+        RemoteErrorKind::Other
+    }
+}
+
+/// Check that `remote_path` is reachable with the current credentials before
+/// adding it as a library. Returns the number of top-level directories.
+#[tauri::command]
+pub async fn test_remote(
+    app: AppHandle,
+    config_path: String,
+    remote_path: String,
+) -> Result<usize, RemoteTestError> {
+    let child = tokio::process::Command::new(rclone_binary(&app))
+        .args([
+            "lsd",
+            "--config",
+            &config_path,
+            "--max-depth",
+            "1",
+            // Fail fast instead of retrying the whole listing three times
+            "--retries",
+            "1",
+            "--low-level-retries",
+            "1",
+            &remote_path,
+        ])
+        .envs(config_env(&config_path))
+        .stdout(std::process::Stdio::piped())
+        .stderr(std::process::Stdio::piped())
+        .kill_on_drop(true)
+        .spawn()
+        .map_err(|e| {
+            RemoteTestError::new(
+                RemoteErrorKind::Other,
+                format!("Failed to run rclone: {}. Is rclone installed?", e),
+            )
+        })?;
+
+    let output = tokio::time::timeout(TEST_REMOTE_TIMEOUT, child.wait_with_output())
+        .await
+        .map_err(|_| {
+            RemoteTestError::new(
+                RemoteErrorKind::Network,
+                format!("{} did not respond within {} seconds", remote_path, TEST_REMOTE_TIMEOUT.as_secs()),
+            )
+        })?
+        .map_err(|e| RemoteTestError::new(RemoteErrorKind::Other, format!("rclone failed: {}", e)))?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        let kind = classify_rclone_error(output.status.code(), &stderr);
+        // The last line carries the error itself; earlier ones are retry noise
+        let detail = stderr
+            .lines()
+            .rev()
+            .find(|l| !l.trim().is_empty())
+            .unwrap_or("rclone failed")
+            .trim();
+        let message = match kind {
+            RemoteErrorKind::Auth => format!("Authentication failed; reconnect the remote ({})", detail),
+            RemoteErrorKind::NotFound => format!("{} was not found ({})", remote_path, detail),
+            RemoteErrorKind::Network => format!("Could not reach the remote ({})", detail),
+            RemoteErrorKind::Other => format!("rclone error: {}", detail),
+        };
+        return Err(RemoteTestError::new(kind, message));
+    }
+
+    Ok(String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .filter(|l| !l.trim().is_empty())
+        .count())
+}
+
+/// Storage usage from `rclone about`, in bytes. Backends report only the
+/// fields they know, so any of them may be missing.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct RemoteAbout {
+    pub total: Option<u64>,
+    pub used: Option<u64>,
+    pub free: Option<u64>,
+    pub trashed: Option<u64>,
+}
+
+/// Storage quota of a remote, or None when its backend doesn't support `about`.
+#[tauri::command]
+pub async fn remote_about(
+    app: AppHandle,
+    config_path: String,
+    remote_name: String,
+) -> Result<Option<RemoteAbout>, AppError> {
+    let remote = format!("{}:", remote_name.trim_end_matches(':'));
+    let output = tokio::process::Command::new(rclone_binary(&app))
+        .args(["about", "--json", "--config", &config_path, &remote])
+        .envs(config_env(&config_path))
+        .output()
+        .await
+        .map_err(AppError::rclone_spawn)?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        // e.g. "doesn't support about" / "about not supported"
+        if stderr.contains("support about") || stderr.contains("not supported") {
+            return Ok(None);
+        }
+        return Err(AppError::rclone_failed(stderr));
+    }
+
+    let about: RemoteAbout = serde_json::from_slice(&output.stdout)
+        .map_err(|e| format!("Failed to parse rclone output: {}", e))?;
+    Ok(Some(about))
+}
+
 /// Get rclone version string (also validates rclone is available)
 #[tauri::command]
-pub async fn get_rclone_version(app: AppHandle) -> Result<String, String> {
+pub async fn get_rclone_version(app: AppHandle) -> Result<String, AppError> {
     let rclone = rclone_binary(&app);
 
     let output = Command::new(&rclone)
         .arg("version")
         .output()
-        .map_err(|e| format!("rclone not found: {}", e))?;
+        .map_err(AppError::rclone_spawn)?;
 
     let stdout = String::from_utf8_lossy(&output.stdout);
     let first_line = stdout.lines().next().unwrap_or("rclone unknown").to_string();
     Ok(first_line)
 }
 
-/// Start rclone serve http for a remote path and return the local URL.
-/// This is used for streaming video/audio via libVLC.
-/// Spins up a dedicated rclone serve http process and returns the local URL.
+/// Default and largest `max_bytes` for `read_remote_bytes`
+const DEFAULT_READ_BYTES: usize = 4 * 1024 * 1024;
+const MAX_READ_BYTES: usize = 64 * 1024 * 1024;
+/// Larger reads are written to a temp file instead of returned inline
+const INLINE_READ_BYTES: usize = 512 * 1024;
+
+/// Read a small remote file with `rclone cat`, without starting a serve
+/// process. Fails rather than truncating when it's larger than `max_bytes`.
+pub(crate) async fn cat_remote(
+    app: &AppHandle,
+    config_path: &str,
+    remote_path: &str,
+    max_bytes: usize,
+) -> Result<Vec<u8>, AppError> {
+    // One byte over the cap tells a file of exactly `max_bytes` from a bigger one
+    let count = (max_bytes + 1).to_string();
+    let output = tokio::process::Command::new(rclone_binary(app))
+        .args(["cat", "--count", &count, "--config", config_path, remote_path])
+        .envs(config_env(config_path))
+        .output()
+        .await
+        .map_err(AppError::rclone_spawn)?;
+    if !output.status.success() {
+        return Err(AppError::rclone_failed(String::from_utf8_lossy(&output.stderr)));
+    }
+    if output.stdout.len() > max_bytes {
+        return Err(AppError::invalid(format!(
+            "{} is larger than {} bytes",
+            remote_path, max_bytes
+        )));
+    }
+    Ok(output.stdout)
+}
+
+/// Contents of a file read by `read_remote_bytes`: inline as base64 when
+/// small, otherwise in a temp file the asset protocol can load.
+#[derive(Debug, Serialize, Clone)]
+pub struct RemoteBytes {
+    pub size: usize,
+    pub base64: Option<String>,
+    pub temp_path: Option<String>,
+}
+
+/// Fetch a small file such as cover art, an .nfo or an .opf with `rclone cat`.
+/// `max_bytes` (default 4 MiB, at most 64 MiB) guards against reading a movie.
 #[tauri::command]
-pub async fn get_stream_url(
+pub async fn read_remote_bytes(
     app: AppHandle,
     config_path: String,
     remote_path: String,
-) -> Result<String, String> {
-    let rclone = rclone_binary(&app);
-    
-    // Pick an available port with retry logic
+    max_bytes: Option<usize>,
+) -> Result<RemoteBytes, AppError> {
+    let max_bytes = max_bytes.unwrap_or(DEFAULT_READ_BYTES);
+    if max_bytes == 0 || max_bytes > MAX_READ_BYTES {
+        return Err(AppError::invalid(format!(
+            "max_bytes must be between 1 and {}, got {}",
+            MAX_READ_BYTES, max_bytes
+        )));
+    }
+    let bytes = cat_remote(&app, &config_path, &remote_path, max_bytes).await?;
+    if bytes.len() <= INLINE_READ_BYTES {
+        use base64::Engine;
+        return Ok(RemoteBytes {
+            size: bytes.len(),
+            base64: Some(base64::engine::general_purpose::STANDARD.encode(&bytes)),
+            temp_path: None,
+        });
+    }
+
+    let filename = remote_path
+        .rsplit('/')
+        .find(|s| !s.is_empty() && !s.ends_with(':'))
+        .unwrap_or("file");
+    // Own key and directory, so a prefetch or book download of the same file
+    // isn't overwritten, and indexed so it counts against the cache limit
+    let cache_key = format!("bytes:{}", remote_path);
+    let dir = crate::commands::temp_cache::cache_dir()
+        .join(crate::commands::scan::hash_remote_path(cache_key.clone()));
+    std::fs::create_dir_all(&dir)
+        .map_err(|e| format!("Failed to create {}: {}", dir.display(), e))?;
+    let path = dir.join(filename);
+    std::fs::write(&path, &bytes)
+        .map_err(|e| format!("Failed to write {}: {}", path.display(), e))?;
+    crate::commands::temp_cache::insert(&app, &cache_key, &path);
+    Ok(RemoteBytes {
+        size: bytes.len(),
+        base64: None,
+        temp_path: Some(path.to_string_lossy().into_owned()),
+    })
+}
+
+/// `rclone dedupe` modes that run without prompting
+const DEDUPE_MODES: &[&str] = &["skip", "first", "newest", "oldest", "largest", "smallest", "rename", "list"];
+
+/// What `dedupe_remote` found and did
+#[derive(Debug, Serialize, Clone, Default)]
+pub struct DedupeSummary {
+    /// Names that had more than one file
+    pub duplicate_names: usize,
+    pub deleted: usize,
+    pub renamed: usize,
+    /// rclone's log line for each of the above, without timestamp and level
+    pub actions: Vec<String>,
+}
+
+impl DedupeSummary {
+    /// Count one line of `rclone dedupe -v` output. Returns the message when
+    /// the line was a finding or an action.
+    fn record(&mut self, line: &str) -> Option<String> {
+        let (_, message) = line
+            .split_once("NOTICE:")
+            .or_else(|| line.split_once("INFO  :"))?;
+        let message = message.trim().to_string();
+        if message.contains("duplicate names") {
+            self.duplicate_names += 1;
+        } else if message.ends_with(": Deleted") || message.contains("Skipped delete") {
+            // The second is what --dry-run logs instead
+            self.deleted += 1;
+        } else if message.contains("renamed from") || message.contains("Skipped move") {
+            self.renamed += 1;
+        } else {
+            return None;
+        }
+        self.actions.push(message.clone());
+        Some(message)
+    }
+}
+
+/// Resolve files with the same name in the same folder, which Google Drive
+/// allows and which otherwise show up twice in a scan. `mode` is one of
+/// rclone's non-interactive dedupe modes: "newest", "oldest", "largest",
+/// "smallest" and "first" keep one copy, "rename" gives each a unique name,
+/// "skip" only removes identical copies and "list" changes nothing.
+/// `dry_run` reports what would happen without doing it.
+///
+/// Emits `dedupe-progress` { remotePath, message, duplicateNames } for each
+/// duplicate found or resolved, since big folders take a while.
+#[tauri::command]
+pub async fn dedupe_remote(
+    app: AppHandle,
+    config_path: String,
+    remote_path: String,
+    mode: String,
+    dry_run: Option<bool>,
+) -> Result<DedupeSummary, AppError> {
+    use tokio::io::{AsyncBufReadExt, BufReader};
+
+    let mode = mode.trim().to_lowercase();
+    if !DEDUPE_MODES.contains(&mode.as_str()) {
+        return Err(AppError::invalid(format!(
+            "Unknown dedupe mode {:?}; expected one of {}",
+            mode,
+            DEDUPE_MODES.join(", ")
+        )));
+    }
+    let mut args = vec!["dedupe", "-v", "--dedupe-mode", mode.as_str(), "--config", config_path.as_str()];
+    if dry_run.unwrap_or(false) {
+        args.push("--dry-run");
+    }
+    args.push(&remote_path);
+
+    let mut child = tokio::process::Command::new(rclone_binary(&app))
+        .args(&args)
+        .envs(config_env(&config_path))
+        .stdin(std::process::Stdio::null())
+        .stdout(std::process::Stdio::null())
+        .stderr(std::process::Stdio::piped())
+        .spawn()
+        .map_err(AppError::rclone_spawn)?;
+
+    // rclone logs everything to stderr; keep the errors for the failure message
+    let mut summary = DedupeSummary::default();
+    let mut errors = Vec::new();
+    if let Some(pipe) = child.stderr.take() {
+        let mut lines = BufReader::new(pipe).lines();
+        while let Some(line) = lines
+            .next_line()
+            .await
+            .map_err(|e| format!("Failed to read rclone output: {}", e))?
+        {
+            if let Some(message) = summary.record(&line) {
+                let _ = app.emit("dedupe-progress", serde_json::json!({
+                    "remotePath": remote_path,
+                    "message": message,
+                    "duplicateNames": summary.duplicate_names,
+                }));
+            } else if line.contains("ERROR") {
+                errors.push(line);
+            }
+        }
+    }
+
+    let status = child
+        .wait()
+        .await
+        .map_err(|e| format!("Failed to run rclone: {}", e))?;
+    if !status.success() {
+        if errors.is_empty() {
+            errors.push(format!("rclone dedupe exited with {}", status));
+        }
+        return Err(AppError::rclone_failed(errors.join("\n")));
+    }
+    Ok(summary)
+}
+
+/// Start rclone serve http for a remote file and return a local URL for it.
+/// The server is rooted at the file's parent folder and shared with any other
+/// session under that folder, so asking for the next episode reuses it.
+/// The child process lives in the managed `VlcManager` and is killed by
+/// `stop_all_sessions` or on app exit.
+#[tauri::command]
+pub async fn get_stream_url(
+    app: AppHandle,
+    vlc: State<'_, VlcManager>,
+    config_path: String,
+    remote_path: String, // full path, e.g. "gdrive:/Movies/film.mkv"
+    config_password: Option<String>,
+) -> Result<String, AppError> {
+    remember_config_password(&config_path, config_password);
+    // Split into the served root (parent folder) and the file within it
+    let colon = remote_path
+        .find(':')
+        .ok_or_else(|| AppError::invalid(format!("Not a remote path: {}", remote_path)))?;
+    let split = remote_path.rfind('/').filter(|&pos| pos > colon).unwrap_or(colon);
+    let remote_root = &remote_path[..split.max(colon + 1)];
+    let file_name = &remote_path[split + 1..];
+    if file_name.is_empty() {
+        return Err(AppError::invalid(format!("Not a file path: {}", remote_path)));
+    }
+
+    // Retry a couple of times in case the picked port is grabbed before rclone binds it
     let mut last_error = None;
     for _ in 0..3 {
-        let port = portpicker::pick_unused_port().ok_or("No available port")?;
-        
-        // Parse remote path to get root and sub-path
-        let (remote_name, sub_path) = parse_remote_root(&remote_path);
-        let remote_root = format!("{}:{}", remote_name, 
-            if sub_path.starts_with('/') { &sub_path[1..] } else { &sub_path });
-        
-        let _ = app.emit(
-            "rclone:status",
-            serde_json::json!({ "state": "starting", "message": "Starting stream server…" }),
-        );
-
-        let mut child = Command::new(&rclone)
-            .args([
-                "serve", "http",
-                "--config", &config_path,
-                "--addr", &format!("127.0.0.1:{}", port),
-                "--read-only",
-                "--no-checksum",
-                "--allow-origin", "*",
-                &remote_root,
-            ])
-            .spawn()
-            .map_err(|e| format!("Failed to start rclone serve: {}", e))?;
-
-        // Wait for server to be ready
-        match wait_for_port(port).await {
-            Ok(()) => {
-                // Store child process in global so it doesn't get killed when dropped
-                if let Ok(mut guard) = SERVE_PROCESS.lock() {
-                    // Kill any previous process
-                    if let Some(mut old) = guard.take() {
-                        let _ = old.kill();
-                    }
-                    *guard = Some(child);
-                }
-                
-                let _ = app.emit(
-                    "rclone:status",
-                    serde_json::json!({ "state": "ready", "message": "Stream ready" }),
-                );
-                
-                // Build URL - the file path within the served root
-                let file_name = remote_path.rsplit('/').next().unwrap_or(&remote_path);
-                let encoded = percent_encode_path(file_name);
-                return Ok(format!("http://127.0.0.1:{}/{}", port, encoded));
-            }
-            Err(e) => {
-                let _ = child.kill();
-                last_error = Some(e);
-                // Try again with a different port
-                continue;
-            }
+        match shared_serve_url(
+            &app,
+            &vlc,
+            &config_path,
+            remote_root,
+            file_name,
+            STREAM_URL_SESSION.to_string(),
+            &ServeOptions::default(),
+        )
+        .await
+        {
+            Ok(url) => return Ok(url),
+            Err(e) => last_error = Some(e),
         }
     }
-    
-    Err(last_error.unwrap_or_else(|| "Failed to start stream server".to_string()))
+
+    Err(last_error.unwrap_or_else(|| "Failed to start stream server".into()))
 }
@@ -1,8 +1,13 @@
+use regex::Regex;
 use serde::{Deserialize, Serialize};
-use std::process::Command;
 use std::path::PathBuf;
+use std::process::Stdio;
+use tokio::io::{AsyncBufReadExt, AsyncReadExt, BufReader};
+use tokio::process::Command;
+use std::sync::OnceLock;
 use tauri::{AppHandle, Emitter};
 use tauri::Manager;
+use crate::error::AppError;
 
 /// A discovered file from a remote path
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -12,6 +17,26 @@ pub struct DiscoveredFile {
     pub size: i64,
     pub is_dir: bool,
     pub mime_type: Option<String>,
+    /// Remote paths of sidecar subtitles with the same stem, e.g. "Movie.en.srt"
+    pub subtitles: Vec<String>,
+    /// RFC 3339 modification time; only set when scanning with `track_modtimes`
+    pub mod_time: Option<String>,
+    /// Remote path of a Kodi-style NFO sidecar ("Movie.nfo", or "movie.nfo" in
+    /// the same folder), for `read_nfo`
+    #[serde(default)]
+    pub nfo: Option<String>,
+    /// "dvd" or "bluray" for a disc folder (listed as one `is_dir` entry whose
+    /// size is the whole disc), "iso" for a disc image
+    #[serde(default)]
+    pub disc: Option<String>,
+}
+
+/// A previously scanned file, passed back in so modified files can be detected
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct KnownFile {
+    pub remote_path: String,
+    pub size: i64,
+    pub mod_time: Option<String>,
 }
 
 /// Result of scanning a single library
@@ -19,7 +44,11 @@ pub struct DiscoveredFile {
 pub struct LibraryScanResult {
     pub library_id: String,
     pub new_files: Vec<DiscoveredFile>,
+    /// Known files whose size or modtime changed since the last scan
+    pub changed_files: Vec<DiscoveredFile>,
     pub removed_paths: Vec<String>,
+    /// Media-extension files left out as samples/junk (full remote paths)
+    pub skipped: Vec<String>,
     pub total_found: usize,
     pub errors: Vec<String>,
 }
@@ -31,7 +60,15 @@ pub struct ParsedTitle {
     pub year: Option<u32>,
     pub season: Option<u32>,
     pub episode: Option<u32>,
+    /// Last episode for multi-episode files like "S01E01-E02" (None for single episodes)
+    pub episode_end: Option<u32>,
+    /// Air date ("YYYY-MM-DD") for date-based daily shows
+    pub air_date: Option<String>,
     pub is_episode: bool,
+    /// Normalised resolution, e.g. "1080p" or "2160p" (4K/UHD)
+    pub resolution: Option<String>,
+    /// Source/format tags such as "BluRay", "WEB-DL" or "HDR", space separated
+    pub quality_tag: Option<String>,
 }
 
 fn rclone_binary(app: &AppHandle) -> PathBuf {
@@ -43,8 +80,140 @@ fn rclone_binary(app: &AppHandle) -> PathBuf {
     if sidecar.exists() { sidecar } else { PathBuf::from("rclone") }
 }
 
+/// Extensions scanned when the caller doesn't override them
+const DEFAULT_MEDIA_EXTENSIONS: &[&str] = &[
+    "mkv", "mp4", "avi", "mov", "wmv", "m4v", "ts", "webm", // video
+    "mp3", "flac", "aac", "ogg", "m4a", "wav", "opus",      // audio
+    "epub", "pdf",                                          // books
+    "m4b", "aax",                                           // audiobooks
+    "iso",                                                  // disc images
+];
+
+/// Extensions the junk-size threshold applies to (audio tracks are legitimately small)
+const VIDEO_EXTENSIONS: &[&str] = &["mkv", "mp4", "avi", "mov", "wmv", "m4v", "ts", "webm", "iso"];
+
+/// Folders of a DVD or Blu-ray structure; files under them belong to the disc
+const DISC_DIRS: &[&str] = &["VIDEO_TS", "AUDIO_TS", "BDMV", "CERTIFICATE"];
+
+/// The disc folder a DVD/Blu-ray marker file belongs to, and its kind:
+/// "Movie/VIDEO_TS/VIDEO_TS.IFO" → ("Movie", "dvd"),
+/// "Movie/BDMV/index.bdmv" → ("Movie", "bluray"). The root is "" when the
+/// scanned path is the disc itself.
+fn disc_root(path: &str) -> Option<(&str, &'static str)> {
+    let lower = path.to_lowercase();
+    let (marker, kind) = if lower == "video_ts/video_ts.ifo" || lower.ends_with("/video_ts/video_ts.ifo") {
+        ("video_ts/video_ts.ifo", "dvd")
+    } else if lower == "bdmv/index.bdmv" || lower.ends_with("/bdmv/index.bdmv") {
+        ("bdmv/index.bdmv", "bluray")
+    } else {
+        return None;
+    };
+    let root = &path[..path.len() - marker.len()];
+    Some((root.trim_end_matches('/'), kind))
+}
+
+/// Lowercased root of the known disc folder `path` lies inside, if any.
+fn inside_disc(path: &str, discs: &std::collections::HashMap<String, DiscFolder>) -> Option<String> {
+    let segments: Vec<&str> = path.split('/').collect();
+    segments.iter().enumerate().find_map(|(i, seg)| {
+        if !DISC_DIRS.iter().any(|d| d.eq_ignore_ascii_case(seg)) {
+            return None;
+        }
+        let root = segments[..i].join("/").to_lowercase();
+        discs.contains_key(&root).then_some(root)
+    })
+}
+
+/// A DVD/Blu-ray folder found while scanning, reported as a single item
+struct DiscFolder {
+    root: String,
+    kind: &'static str,
+    size: i64,
+    /// Modtime of the marker file (VIDEO_TS.IFO / index.bdmv)
+    mod_time: Option<String>,
+}
+
+/// Default minimum video size in MB; smaller video files are treated as samples
+const DEFAULT_MIN_VIDEO_SIZE_MB: u64 = 50;
+
+/// Scene-release filler: "sample"/"proof" as a separate word in the name or as a
+/// folder, and tracker promo files like "RARBG.mp4" / "RARBG_DO_NOT_MIRROR.exe".
+/// A release named "Movie.2020.1080p-RARBG.mkv" is not matched.
+fn junk_regex() -> &'static Regex {
+    static RE: OnceLock<Regex> = OnceLock::new();
+    RE.get_or_init(|| {
+        Regex::new(r"(?i)(^|[/\W_])(sample|proof)([/\W_]|$)|(^|/)rarbg[^/]*$").unwrap()
+    })
+}
+
+/// Whether a media file should be skipped as a sample or junk file. `min_video_bytes`
+/// of 0 disables the size check.
+fn is_junk(path: &str, ext: &str, size: i64, min_video_bytes: u64) -> bool {
+    let name_junk = junk_regex().is_match(strip_extension(path));
+    let too_small = min_video_bytes > 0
+        && VIDEO_EXTENSIONS.contains(&ext)
+        && size >= 0 // rclone reports -1 when the backend doesn't know
+        && (size as u64) < min_video_bytes;
+    name_junk || too_small
+}
+
+/// Build the extension set for a scan: `only` replaces the defaults entirely
+/// (e.g. a comic library scanning just cbz/cbr), `extra` adds to whichever base
+/// is in use. Entries are lowercased and a leading '.' is ignored.
+fn media_extensions(
+    extra: Option<Vec<String>>,
+    only: Option<Vec<String>>,
+) -> std::collections::HashSet<String> {
+    let normalize = |e: &str| e.trim().trim_start_matches('.').to_lowercase();
+    let base: std::collections::HashSet<String> = match only {
+        Some(only) if !only.is_empty() => only.iter().map(|e| normalize(e)).collect(),
+        _ => DEFAULT_MEDIA_EXTENSIONS.iter().map(|e| e.to_string()).collect(),
+    };
+    base.into_iter()
+        .chain(extra.unwrap_or_default().iter().map(|e| normalize(e)))
+        .filter(|e| !e.is_empty())
+        .collect()
+}
+
+/// One library to scan in `scan_libraries`; same fields as `scan_library_files`.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct LibrarySpec {
+    pub library_id: String,
+    pub remote_path: String,
+    #[serde(default)]
+    pub known_paths: Vec<String>,
+    #[serde(default)]
+    pub extra_extensions: Option<Vec<String>>,
+    #[serde(default)]
+    pub only_extensions: Option<Vec<String>>,
+    /// List modtimes and report size/modtime changes against `known_files`
+    #[serde(default)]
+    pub track_modtimes: bool,
+    #[serde(default)]
+    pub known_files: Vec<KnownFile>,
+    #[serde(default)]
+    pub min_video_size_mb: Option<u64>,
+    /// rclone filter globs, e.g. "**/Extras/**"; see `filter_args`
+    #[serde(default)]
+    pub include: Vec<String>,
+    #[serde(default)]
+    pub exclude: Vec<String>,
+}
+
+/// How many rclone lsjson processes `scan_libraries` runs at once
+const MAX_CONCURRENT_SCANS: usize = 4;
+
+/// Emit a "listing"/"processing" scan-progress event every this many files
+const PROGRESS_EVERY: usize = 500;
+
 /// Recursively list all files in a remote path using rclone lsjson --recursive
-/// Returns only files (not directories) that look like media
+/// Returns only files (not directories) that look like media.
+/// `extra_extensions` / `only_extensions` adjust which extensions count as media.
+/// Samples and junk files (see `is_junk`) go to `skipped` instead; video files
+/// under `min_video_size_mb` (default 50, 0 disables) count as samples.
+/// With `track_modtimes`, files are listed with modtimes and any entry of
+/// `known_files` whose size or modtime differs is returned in `changed_files`.
+/// `include` / `exclude` are rclone glob patterns applied by lsjson itself.
 #[tauri::command]
 pub async fn scan_library_files(
     app: AppHandle,
@@ -52,8 +221,129 @@ pub async fn scan_library_files(
     remote_path: String,
     library_id: String,
     known_paths: Vec<String>,
+    extra_extensions: Option<Vec<String>>,
+    only_extensions: Option<Vec<String>>,
+    track_modtimes: Option<bool>,
+    known_files: Option<Vec<KnownFile>>,
+    min_video_size_mb: Option<u64>,
+    include: Option<Vec<String>>,
+    exclude: Option<Vec<String>>,
+) -> Result<LibraryScanResult, AppError> {
+    let spec = LibrarySpec {
+        library_id,
+        remote_path,
+        known_paths,
+        extra_extensions,
+        only_extensions,
+        track_modtimes: track_modtimes.unwrap_or(false),
+        known_files: known_files.unwrap_or_default(),
+        min_video_size_mb,
+        include: include.unwrap_or_default(),
+        exclude: exclude.unwrap_or_default(),
+    };
+    Ok(scan_library(&app, &config_path, spec).await?)
+}
+
+/// Scan several libraries concurrently (at most 4 rclone processes at a time).
+/// Results come back in the same order as `libraries`; a library whose rclone
+/// call fails gets a result with the error in `errors` instead of aborting the rest.
+#[tauri::command]
+pub async fn scan_libraries(
+    app: AppHandle,
+    config_path: String,
+    libraries: Vec<LibrarySpec>,
+) -> Result<Vec<LibraryScanResult>, AppError> {
+    let permits = std::sync::Arc::new(tokio::sync::Semaphore::new(MAX_CONCURRENT_SCANS));
+    let mut tasks = Vec::with_capacity(libraries.len());
+
+    for spec in libraries {
+        let app = app.clone();
+        let config_path = config_path.clone();
+        let permits = permits.clone();
+        tasks.push(tauri::async_runtime::spawn(async move {
+            let _permit = permits.acquire_owned().await.ok();
+            let library_id = spec.library_id.clone();
+            scan_library(&app, &config_path, spec)
+                .await
+                .unwrap_or_else(|e| {
+                    let _ = app.emit("scan-progress", serde_json::json!({
+                        "libraryId": library_id,
+                        "stage": "error",
+                        "message": e
+                    }));
+                    LibraryScanResult {
+                        library_id,
+                        new_files: vec![],
+                        changed_files: vec![],
+                        removed_paths: vec![],
+                        skipped: vec![],
+                        total_found: 0,
+                        errors: vec![e],
+                    }
+                })
+        }));
+    }
+
+    let mut results = Vec::with_capacity(tasks.len());
+    for task in tasks {
+        results.push(task.await.map_err(|e| format!("Scan task failed: {}", e))?);
+    }
+    Ok(results)
+}
+
+/// rclone flags for a library's include/exclude globs, following rclone's rules:
+/// with only excludes, matching files are skipped; with only includes, files
+/// matching none of them are skipped. rclone parses `--include` and `--exclude`
+/// in no fixed order when both are given, so in that case they become ordered
+/// `--filter` rules with the excludes first: a file matching both is excluded.
+fn filter_args(include: &[String], exclude: &[String]) -> Vec<String> {
+    let include: Vec<&String> = include.iter().filter(|p| !p.trim().is_empty()).collect();
+    let exclude: Vec<&String> = exclude.iter().filter(|p| !p.trim().is_empty()).collect();
+    let mut args = Vec::new();
+    if !include.is_empty() && !exclude.is_empty() {
+        for pattern in exclude {
+            args.push("--filter".to_string());
+            args.push(format!("- {}", pattern));
+        }
+        for pattern in include {
+            args.push("--filter".to_string());
+            args.push(format!("+ {}", pattern));
+        }
+        args.push("--filter".to_string());
+        args.push("- **".to_string());
+        return args;
+    }
+    for pattern in exclude {
+        args.push("--exclude".to_string());
+        args.push(pattern.clone());
+    }
+    for pattern in include {
+        args.push("--include".to_string());
+        args.push(pattern.clone());
+    }
+    args
+}
+
+async fn scan_library(
+    app: &AppHandle,
+    config_path: &str,
+    spec: LibrarySpec,
 ) -> Result<LibraryScanResult, String> {
-    let rclone = rclone_binary(&app);
+    let LibrarySpec {
+        library_id,
+        remote_path,
+        known_paths,
+        extra_extensions,
+        only_extensions,
+        track_modtimes,
+        known_files,
+        min_video_size_mb,
+        include,
+        exclude,
+    } = spec;
+    let rclone = rclone_binary(app);
+    let min_video_bytes = min_video_size_mb.unwrap_or(DEFAULT_MIN_VIDEO_SIZE_MB) * 1024 * 1024;
+    let extensions = media_extensions(extra_extensions, only_extensions);
 
     // Emit progress event
     let _ = app.emit("scan-progress", serde_json::json!({
@@ -62,24 +352,68 @@ pub async fn scan_library_files(
         "message": format!("Listing files in {}...", remote_path)
     }));
 
-    let output = Command::new(&rclone)
-        .args([
-            "lsjson",
-            "--config", &config_path,
-            "--recursive",
-            "--no-modtime",
-            "--files-only",
-            &remote_path,
-        ])
-        .output()
+    let mut args = vec!["lsjson", "--config", config_path, "--recursive", "--files-only"];
+    // Skipping modtimes makes listing much cheaper on some backends
+    if !track_modtimes {
+        args.push("--no-modtime");
+    }
+    let filters = filter_args(&include, &exclude);
+    args.extend(filters.iter().map(|s| s.as_str()));
+    args.push(&remote_path);
+
+    let mut child = Command::new(&rclone)
+        .args(&args)
+        .envs(crate::commands::rclone::config_env(config_path))
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
         .map_err(|e| format!("Failed to run rclone: {}", e))?;
 
-    if !output.status.success() {
-        let stderr = String::from_utf8_lossy(&output.stderr);
-        return Err(format!("rclone error: {}", stderr));
+    // Drain stderr alongside stdout so a chatty rclone can't fill the pipe and stall
+    let stderr_pipe = child.stderr.take();
+    let stderr_task = tokio::spawn(async move {
+        let mut buf = String::new();
+        if let Some(mut pipe) = stderr_pipe {
+            let _ = pipe.read_to_string(&mut buf).await;
+        }
+        buf
+    });
+
+    // lsjson prints one entry per line, so count them as they arrive to show
+    // movement during long recursive listings
+    let mut stdout = String::new();
+    let mut listed = 0usize;
+    if let Some(pipe) = child.stdout.take() {
+        let mut lines = BufReader::new(pipe).lines();
+        while let Some(line) = lines
+            .next_line()
+            .await
+            .map_err(|e| format!("Failed to read rclone output: {}", e))?
+        {
+            if line.trim_start().starts_with('{') {
+                listed += 1;
+                if listed % PROGRESS_EVERY == 0 {
+                    let _ = app.emit("scan-progress", serde_json::json!({
+                        "libraryId": library_id,
+                        "stage": "listing",
+                        "listed": listed,
+                        "message": format!("Listed {} files in {}...", listed, remote_path)
+                    }));
+                }
+            }
+            stdout.push_str(&line);
+            stdout.push('\n');
+        }
     }
 
-    let stdout = String::from_utf8_lossy(&output.stdout);
+    let status = child
+        .wait()
+        .await
+        .map_err(|e| format!("Failed to run rclone: {}", e))?;
+    let stderr = stderr_task.await.unwrap_or_default();
+    if !status.success() {
+        return Err(format!("rclone error: {}", stderr));
+    }
 
     #[derive(Deserialize)]
     struct RcloneItem {
@@ -93,43 +427,181 @@ pub async fn scan_library_files(
         size: i64,
         #[serde(rename = "MimeType")]
         mime_type: Option<String>,
+        #[serde(rename = "ModTime", default)]
+        mod_time: Option<String>,
     }
 
     let items: Vec<RcloneItem> = serde_json::from_str(&stdout)
         .map_err(|e| format!("Failed to parse rclone output: {}", e))?;
 
     // Build set of known paths for change detection
-    let known_set: std::collections::HashSet<String> = known_paths.into_iter().collect();
+    let known_by_path: std::collections::HashMap<String, KnownFile> = known_files
+        .into_iter()
+        .map(|f| (f.remote_path.clone(), f))
+        .collect();
+    let known_set: std::collections::HashSet<String> = known_paths
+        .into_iter()
+        .chain(known_by_path.keys().cloned())
+        .collect();
 
     let mut new_files = Vec::new();
+    let mut changed_files = Vec::new();
+    let mut skipped = Vec::new();
     let mut found_paths = std::collections::HashSet::new();
 
+    // Collect subtitle sidecars first so they can be attached to their videos
+    let mut sidecars: std::collections::HashMap<String, Vec<String>> =
+        std::collections::HashMap::new();
     for item in &items {
         if item.is_dir { continue; }
+        let keys = subtitle_match_keys(&item.path);
+        if keys.is_empty() { continue; }
+        let full_path = format!("{}/{}", remote_path.trim_end_matches('/'), item.path);
+        for key in keys {
+            sidecars.entry(key).or_default().push(full_path.clone());
+        }
+    }
+
+    // NFO sidecars, by lowercased stem ("Movie.nfo") or folder ("movie.nfo")
+    let mut nfos: std::collections::HashMap<String, String> = std::collections::HashMap::new();
+    let mut folder_nfos: std::collections::HashMap<String, String> = std::collections::HashMap::new();
+    for item in &items {
+        if item.is_dir || !item.name.to_lowercase().ends_with(".nfo") { continue; }
+        let full_path = format!("{}/{}", remote_path.trim_end_matches('/'), item.path);
+        if item.name.eq_ignore_ascii_case("movie.nfo") {
+            folder_nfos.insert(parent_dir(&item.path).to_lowercase(), full_path.clone());
+        }
+        nfos.insert(strip_extension(&item.path).to_lowercase(), full_path);
+    }
+
+    // DVD/Blu-ray folders, by lowercased root; their files are summed into one item
+    let mut discs: std::collections::HashMap<String, DiscFolder> = std::collections::HashMap::new();
+    if VIDEO_EXTENSIONS.iter().any(|e| extensions.contains(*e)) {
+        for item in &items {
+            if let Some((root, kind)) = disc_root(&item.path) {
+                discs.insert(root.to_lowercase(), DiscFolder {
+                    root: root.to_string(),
+                    kind,
+                    size: 0,
+                    mod_time: item.mod_time.clone(),
+                });
+            }
+        }
+    }
+
+    let total_items = items.len();
+    for (processed, item) in items.iter().enumerate() {
+        if processed > 0 && processed % PROGRESS_EVERY == 0 {
+            let _ = app.emit("scan-progress", serde_json::json!({
+                "libraryId": library_id,
+                "stage": "processing",
+                "processed": processed,
+                "total": total_items
+            }));
+        }
+        if item.is_dir { continue; }
+
+        if let Some(root) = inside_disc(&item.path, &discs) {
+            if let Some(disc) = discs.get_mut(&root) {
+                disc.size += item.size.max(0);
+            }
+            continue;
+        }
 
         // Only include media file extensions
         let ext = item.name.rsplit('.').next().unwrap_or("").to_lowercase();
-        let is_media = matches!(
-            ext.as_str(),
-            "mkv" | "mp4" | "avi" | "mov" | "wmv" | "m4v" | "ts" | "webm" |  // video
-            "mp3" | "flac" | "aac" | "ogg" | "m4a" | "wav" | "opus" |          // audio
-            "epub" | "pdf" |                                                       // books
-            "m4b" | "aax"                                                          // audiobooks
-        );
-
-        if !is_media { continue; }
+        if !extensions.contains(ext.as_str()) { continue; }
 
         let full_path = format!("{}/{}", remote_path.trim_end_matches('/'), item.path);
+        // Not added to found_paths, so previously imported junk shows up as removed
+        if is_junk(&item.path, &ext, item.size, min_video_bytes) {
+            skipped.push(full_path);
+            continue;
+        }
+        found_paths.insert(full_path.clone());
+
+        let is_new = !known_set.contains(&full_path);
+        let is_changed = !is_new
+            && track_modtimes
+            && known_by_path
+                .get(&full_path)
+                .is_some_and(|k| k.size != item.size || k.mod_time != item.mod_time);
+        if !is_new && !is_changed { continue; }
+
+        let subtitles = sidecars
+            .get(&strip_extension(&item.path).to_lowercase())
+            .cloned()
+            .unwrap_or_default();
+        let nfo = nfos
+            .get(&strip_extension(&item.path).to_lowercase())
+            .or_else(|| folder_nfos.get(&parent_dir(&item.path).to_lowercase()))
+            .cloned();
+        let file = DiscoveredFile {
+            remote_path: full_path,
+            filename: item.name.clone(),
+            size: item.size,
+            is_dir: false,
+            mime_type: item.mime_type.clone(),
+            subtitles,
+            mod_time: item.mod_time.clone(),
+            nfo,
+            disc: (ext == "iso").then(|| "iso".to_string()),
+        };
+        if is_new {
+            new_files.push(file);
+        } else {
+            changed_files.push(file);
+        }
+    }
+
+    for disc in discs.into_values() {
+        let full_path = if disc.root.is_empty() {
+            remote_path.trim_end_matches('/').to_string()
+        } else {
+            format!("{}/{}", remote_path.trim_end_matches('/'), disc.root)
+        };
+        if is_junk(&disc.root, "", disc.size, min_video_bytes) {
+            skipped.push(full_path);
+            continue;
+        }
         found_paths.insert(full_path.clone());
 
-        if !known_set.contains(&full_path) {
-            new_files.push(DiscoveredFile {
-                remote_path: full_path,
-                filename: item.name.clone(),
-                size: item.size,
-                is_dir: false,
-                mime_type: item.mime_type.clone(),
-            });
+        let is_new = !known_set.contains(&full_path);
+        let is_changed = !is_new
+            && track_modtimes
+            && known_by_path
+                .get(&full_path)
+                .is_some_and(|k| k.size != disc.size || k.mod_time != disc.mod_time);
+        if !is_new && !is_changed { continue; }
+
+        let filename = full_path
+            .rsplit('/')
+            .next()
+            .unwrap_or(&full_path)
+            .rsplit(':')
+            .next()
+            .unwrap_or_default()
+            .to_string();
+        // "Movie (2001).nfo" beside the folder, or "movie.nfo" inside it
+        let nfo = nfos
+            .get(&disc.root.to_lowercase())
+            .or_else(|| folder_nfos.get(&disc.root.to_lowercase()))
+            .cloned();
+        let file = DiscoveredFile {
+            remote_path: full_path,
+            filename,
+            size: disc.size,
+            is_dir: true,
+            mime_type: None,
+            subtitles: Vec::new(),
+            mod_time: disc.mod_time,
+            nfo,
+            disc: Some(disc.kind.to_string()),
+        };
+        if is_new {
+            new_files.push(file);
+        } else {
+            changed_files.push(file);
         }
     }
 
@@ -145,24 +617,74 @@ pub async fn scan_library_files(
         "libraryId": library_id,
         "stage": "complete",
         "newFiles": new_files.len(),
+        "changedFiles": changed_files.len(),
         "removedFiles": removed_paths.len(),
+        "skippedFiles": skipped.len(),
         "totalFound": total_found
     }));
 
     Ok(LibraryScanResult {
         library_id,
         new_files,
+        changed_files,
         removed_paths,
+        skipped,
         total_found,
         errors: vec![],
     })
 }
 
-/// Parse a filename into title, year, season, episode
+/// Path without its final extension ("Show/Ep1.mkv" → "Show/Ep1").
+fn strip_extension(path: &str) -> &str {
+    match path.rfind('.') {
+        Some(pos) if !path[pos..].contains('/') => &path[..pos],
+        _ => path,
+    }
+}
+
+/// Folder part of a relative path ("Show/Ep1.mkv" → "Show"; "" at the top).
+fn parent_dir(path: &str) -> &str {
+    path.rfind('/').map_or("", |pos| &path[..pos])
+}
+
+/// For a subtitle sidecar, the lowercased relative paths (without extension) of the
+/// videos it may belong to. "Movie.en.srt", "Movie.pt-BR.forced.srt" and "Movie.srt"
+/// all include "movie"; the unpeeled stem is kept too, so "Toy.Story.Up.srt" still
+/// matches "Toy.Story.Up.mkv". Returns an empty list for non-subtitle files.
+fn subtitle_match_keys(path: &str) -> Vec<String> {
+    let ext = path.rsplit('.').next().unwrap_or("").to_lowercase();
+    if !matches!(ext.as_str(), "srt" | "ass" | "ssa" | "sub" | "vtt") {
+        return Vec::new();
+    }
+    let mut stem = strip_extension(path);
+    let mut keys = vec![stem.to_lowercase()];
+    // Peel off language / flag suffixes: ".en", ".eng", ".pt-BR", ".forced", ".sdh"
+    for _ in 0..2 {
+        let Some(pos) = stem.rfind('.') else { break };
+        let suffix = stem[pos + 1..].to_lowercase();
+        let is_lang = suffix.len() <= 3 && suffix.len() >= 2 && suffix.bytes().all(|b| b.is_ascii_alphabetic());
+        let is_region = suffix.len() == 5
+            && suffix.as_bytes()[2] == b'-'
+            && suffix.bytes().filter(|b| *b != b'-').all(|b| b.is_ascii_alphabetic());
+        let is_flag = matches!(suffix.as_str(), "forced" | "sdh" | "hi" | "cc");
+        if stem[pos..].contains('/') || !(is_lang || is_region || is_flag) {
+            break;
+        }
+        stem = &stem[..pos];
+        keys.push(stem.to_lowercase());
+    }
+    keys
+}
+
+/// Parse a filename into title, year, season, episode and quality markers
 /// Handles common naming conventions:
 ///   "The.Dark.Knight.2008.mkv"
 ///   "Breaking.Bad.S03E07.mkv"
+///   "Friends.S01E01-E02.mkv" / "Friends.S01E01E02.mkv" (multi-episode)
 ///   "The Wire - 1x01 - The Target.mkv"
+///   "The.Daily.Show.2021.03.14.mkv" (date-based)
+///   "[Group] Show - 105 (1080p).mkv" (absolute numbering, no season)
+///   "Movie.2020.1080p.BluRay.x264.mkv"
 #[tauri::command]
 pub fn parse_media_filename(filename: String) -> ParsedTitle {
     let stem = filename
@@ -173,139 +695,343 @@ pub fn parse_media_filename(filename: String) -> ParsedTitle {
         .rev()
         .collect::<Vec<_>>()
         .join(".");
+    // Drop a leading release-group tag so it doesn't end up in the title
+    let stem = group_tag_regex().replace(&stem, "").into_owned();
+
+    // Quality tokens are never part of the title, so the title always ends before them
+    let quality = find_quality(&stem);
+    let title_end = |end: usize| match &quality {
+        Some(q) if q.start > 0 => q.start.min(end),
+        _ => end,
+    };
+    let resolution = quality.as_ref().and_then(|q| q.resolution.clone());
+    let quality_tag = quality.as_ref().and_then(|q| q.quality_tag.clone());
+
+    // Try each episode pattern in priority order; the first match wins
+    for (kind, re) in episode_patterns() {
+        let Some(caps) = re.captures(&stem) else {
+            continue;
+        };
+        let num = |i: usize| caps.get(i).and_then(|m| m.as_str().parse::<u32>().ok());
+        let marker = caps.get(1).expect("pattern group 1 wraps the episode marker");
+        let title = clean_title(&stem[..title_end(marker.start())]);
 
-    // Try to detect TV episode: S01E01 or 1x01 patterns
-    let season_episode_re = [
-        // SxxExx
-        (r"[Ss](\d{1,2})[Ee](\d{1,2})", true),
-        // NxNN
-        (r"(\d{1,2})[xX](\d{1,2})", true),
-    ];
-
-    for (pattern, _) in &season_episode_re {
-        if let Some(caps) = simple_regex_match(&stem, pattern) {
-            let before_match = &stem[..caps.start];
-            let title = clean_title(before_match);
-            return ParsedTitle {
-                title,
-                year: None,
-                season: caps.group1.parse().ok(),
-                episode: caps.group2.parse().ok(),
-                is_episode: true,
-            };
+        let mut parsed = ParsedTitle {
+            title,
+            year: None,
+            season: None,
+            episode: None,
+            episode_end: None,
+            air_date: None,
+            is_episode: true,
+            resolution: resolution.clone(),
+            quality_tag: quality_tag.clone(),
+        };
+        match kind {
+            EpisodePattern::SeasonEpisode => {
+                parsed.season = num(2);
+                parsed.episode = num(3);
+                parsed.episode_end = num(4);
+            }
+            EpisodePattern::Date => {
+                parsed.year = num(2);
+                parsed.air_date = Some(format!("{}-{}-{}", &caps[2], &caps[3], &caps[4]));
+            }
+            EpisodePattern::Absolute => {
+                parsed.episode = num(2);
+            }
         }
+        return parsed;
     }
 
     // Try to extract year: 4-digit number between 1900-2099
-    if let Some(year_match) = find_year(&stem) {
-        let before_year = &stem[..year_match.start];
-        let title = clean_title(before_year);
+    if let Some(caps) = year_regex().captures(&stem) {
+        let year = caps.get(1).expect("year group");
+        let title = clean_title(&stem[..title_end(year.start())]);
         return ParsedTitle {
             title,
-            year: Some(year_match.year),
+            year: year.as_str().parse().ok(),
             season: None,
             episode: None,
+            episode_end: None,
+            air_date: None,
             is_episode: false,
+            resolution,
+            quality_tag,
         };
     }
 
-    // Fallback: just clean the whole stem
+    // Fallback: just clean the stem up to any quality tokens
     ParsedTitle {
-        title: clean_title(&stem),
+        title: clean_title(&stem[..title_end(stem.len())]),
         year: None,
         season: None,
         episode: None,
+        episode_end: None,
+        air_date: None,
         is_episode: false,
+        resolution,
+        quality_tag,
     }
 }
 
-// ── Simple regex helpers (no regex crate dependency) ──────────────────────────
+/// Parse a full remote path, using the directories where the filename alone
+/// falls short. Episodes named "07 - Title.mkv" or "01.mkv" inside a season
+/// folder get their season from the folder and their title from the show
+/// folder above it:
+///   "TV/Breaking Bad/Season 3/07 - One Minute.mkv"
+///   "TV/The Wire (2002)/S01/01.mkv"
+/// Everything else parses as `parse_media_filename` would.
+#[tauri::command]
+pub fn parse_media_path(full_path: String) -> ParsedTitle {
+    // Drop the "remote:" prefix so it can't end up in a show title
+    let path = match full_path.split_once(':') {
+        Some((remote, rest)) if !remote.contains('/') => rest,
+        _ => full_path.as_str(),
+    };
+    let mut components: Vec<&str> = path.split('/').filter(|c| !c.is_empty()).collect();
+    let Some(filename) = components.pop() else {
+        return parse_media_filename(full_path);
+    };
+    let mut parsed = parse_media_filename(filename.to_string());
 
-struct RegexMatch {
-    start: usize,
-    group1: String,
-    group2: String,
-}
-
-fn simple_regex_match(text: &str, pattern: &str) -> Option<RegexMatch> {
-    // Minimal pattern matching for S01E01 and 1x01 without regex crate
-    let bytes = text.as_bytes();
-
-    if pattern.contains("[Ss]") {
-        // SxxExx pattern
-        for i in 0..bytes.len().saturating_sub(5) {
-            if bytes[i] == b'S' || bytes[i] == b's' {
-                let rest = &text[i+1..];
-                let mut s_end = 0;
-                while s_end < rest.len() && rest.as_bytes()[s_end].is_ascii_digit() { s_end += 1; }
-                if s_end == 0 || s_end > 2 { continue; }
-                let season_str = &rest[..s_end];
-                let rest2 = &rest[s_end..];
-                if rest2.len() < 3 { continue; }
-                if rest2.as_bytes()[0] != b'E' && rest2.as_bytes()[0] != b'e' { continue; }
-                let rest3 = &rest2[1..];
-                let mut e_end = 0;
-                while e_end < rest3.len() && rest3.as_bytes()[e_end].is_ascii_digit() { e_end += 1; }
-                if e_end == 0 || e_end > 2 { continue; }
-                let episode_str = &rest3[..e_end];
-                return Some(RegexMatch {
-                    start: i,
-                    group1: season_str.to_string(),
-                    group2: episode_str.to_string(),
-                });
+    let season_dir = components
+        .iter()
+        .rposition(|dir| season_folder_regex().is_match(dir.trim()));
+    let Some(season_idx) = season_dir else {
+        // "Show/S01E01.mkv": nothing before the marker to take a title from
+        if parsed.title.is_empty() {
+            if let Some(show) = components.last() {
+                let (title, year) = folder_title(show);
+                parsed.title = title;
+                parsed.year = parsed.year.or(year);
             }
         }
-    } else {
-        // NxNN pattern
-        for i in 0..bytes.len().saturating_sub(3) {
-            if bytes[i].is_ascii_digit() {
-                let mut s_end = i;
-                while s_end < bytes.len() && bytes[s_end].is_ascii_digit() { s_end += 1; }
-                if s_end - i > 2 { continue; }
-                if s_end >= bytes.len() { continue; }
-                if bytes[s_end] != b'x' && bytes[s_end] != b'X' { continue; }
-                let e_start = s_end + 1;
-                let mut e_end = e_start;
-                while e_end < bytes.len() && bytes[e_end].is_ascii_digit() { e_end += 1; }
-                if e_end == e_start || e_end - e_start > 2 { continue; }
-                return Some(RegexMatch {
-                    start: i,
-                    group1: text[i..s_end].to_string(),
-                    group2: text[e_start..e_end].to_string(),
-                });
+        return parsed;
+    };
+
+    let filename_had_season = parsed.season.is_some();
+    if !filename_had_season {
+        let caps = season_folder_regex()
+            .captures(components[season_idx].trim())
+            .expect("matched above");
+        parsed.season = match caps.get(1).or_else(|| caps.get(2)) {
+            Some(n) => n.as_str().parse().ok(),
+            // "Specials"
+            None => Some(0),
+        };
+    }
+    if parsed.episode.is_none() {
+        let stem = filename.rsplit_once('.').map_or(filename, |(stem, _)| stem);
+        let stem = group_tag_regex().replace(stem, "");
+        parsed.episode = leading_episode_regex()
+            .captures(&stem)
+            .and_then(|caps| caps[1].parse().ok());
+        // A number that was taken for the year is the episode number here
+        if parsed.episode.is_some() {
+            parsed.year = None;
+        }
+    }
+    parsed.is_episode = parsed.episode.is_some();
+
+    // Without a season marker the filename's "title" is the episode's own name
+    if !filename_had_season || parsed.title.is_empty() {
+        if let Some(show) = season_idx.checked_sub(1).map(|i| components[i]) {
+            let (title, year) = folder_title(show);
+            if !title.is_empty() {
+                parsed.title = title;
+                parsed.year = parsed.year.or(year);
+            }
+        }
+    }
+    parsed
+}
+
+/// Title and year of a show folder such as "Breaking Bad (2008)".
+fn folder_title(name: &str) -> (String, Option<u32>) {
+    let name = group_tag_regex().replace(name, "");
+    match year_regex().captures(&name) {
+        Some(caps) => {
+            let year = caps.get(1).expect("year group");
+            let title = name[..year.start()].trim_end_matches(|c: char| " ._-([".contains(c));
+            // A folder that is only a year ("2008") is still the title
+            if title.is_empty() {
+                (clean_title(&name), None)
+            } else {
+                (clean_title(title), year.as_str().parse().ok())
             }
         }
+        None => (clean_title(&name), None),
     }
-    None
 }
 
-struct YearMatch {
+// ── Filename patterns ─────────────────────────────────────────────────────────
+
+#[derive(Clone, Copy)]
+enum EpisodePattern {
+    /// Groups: 2 = season, 3 = episode, 4 = optional last episode
+    SeasonEpisode,
+    /// Groups: 2 = year, 3 = month, 4 = day
+    Date,
+    /// Groups: 2 = absolute episode number
+    Absolute,
+}
+
+/// Episode patterns in priority order. Group 1 always spans the episode marker,
+/// so the title is everything before it.
+fn episode_patterns() -> &'static [(EpisodePattern, Regex)] {
+    static PATTERNS: OnceLock<Vec<(EpisodePattern, Regex)>> = OnceLock::new();
+    PATTERNS.get_or_init(|| {
+        [
+            // S01E01, S01E01E02, S01E01-E02, S01.E01
+            (
+                EpisodePattern::SeasonEpisode,
+                r"(?i)(?:^|[^a-z0-9])(s(\d{1,2})[ ._]?e(\d{1,3})(?:-?e(\d{1,3}))?)(?:[^0-9]|$)",
+            ),
+            // 1x01, "Show - 1x01"
+            (
+                EpisodePattern::SeasonEpisode,
+                r"(?i)(?:^|[^0-9])((\d{1,2})x(\d{1,3})(?:-?x?(\d{2,3}))?)(?:[^0-9]|$)",
+            ),
+            // Daily shows: 2021.03.14, 2021-03-14
+            (
+                EpisodePattern::Date,
+                r"(?:^|[^0-9])(((?:19|20)\d{2})[._ -](0[1-9]|1[0-2])[._ -](0[1-9]|[12]\d|3[01]))(?:[^0-9]|$)",
+            ),
+            // Anime absolute numbering: "Show - 105", "[Group] Show - 05v2 (1080p)",
+            // "Show - 7 [720p]". Only 1-3 digits after a " - " separator, so titles
+            // ending in a number ("Blade Runner 2049", "Apollo 13") and years
+            // ("Movie - 2012") stay movies; "- 5.1" audio channel tags don't count
+            (
+                EpisodePattern::Absolute,
+                r"(?i)(?:^|\s)(-\s*(\d{1,3})(?:v\d)?)(?:[\s\[(]|\.\D|$)",
+            ),
+        ]
+        .into_iter()
+        .map(|(kind, pattern)| (kind, Regex::new(pattern).expect("valid episode pattern")))
+        .collect()
+    })
+}
+
+/// Season folders: "Season 3", "Season.03", "Series 2", "S03", "Specials".
+/// Group 1 or 2 is the season number; neither for specials (season 0).
+fn season_folder_regex() -> &'static Regex {
+    static RE: OnceLock<Regex> = OnceLock::new();
+    RE.get_or_init(|| {
+        Regex::new(r"(?i)^(?:(?:season|series|staffel|saison)[ ._-]*(\d{1,3})|s(\d{1,3})|specials)$")
+            .expect("valid season folder pattern")
+    })
+}
+
+/// An episode number leading the filename: "07 - Title", "07", "E07", "Ep 7".
+fn leading_episode_regex() -> &'static Regex {
+    static RE: OnceLock<Regex> = OnceLock::new();
+    RE.get_or_init(|| {
+        Regex::new(r"(?i)^\s*(?:e|ep|episode)?[ ._-]*(\d{1,3})(?:[^0-9]|$)")
+            .expect("valid leading episode pattern")
+    })
+}
+
+/// A 4-digit year between 1900 and 2099 not embedded in a longer number.
+fn year_regex() -> &'static Regex {
+    static RE: OnceLock<Regex> = OnceLock::new();
+    RE.get_or_init(|| {
+        Regex::new(r"(?:^|[^0-9])((?:19|20)\d{2})(?:[^0-9]|$)").expect("valid year pattern")
+    })
+}
+
+/// Leading release-group tag, e.g. "[SubsPlease] ".
+fn group_tag_regex() -> &'static Regex {
+    static RE: OnceLock<Regex> = OnceLock::new();
+    RE.get_or_init(|| Regex::new(r"^\s*\[[^\]]*\]\s*").expect("valid group tag pattern"))
+}
+
+struct QualityMatch {
+    /// Byte offset of the first quality token in the stem
     start: usize,
-    year: u32,
-}
-
-fn find_year(text: &str) -> Option<YearMatch> {
-    let bytes = text.as_bytes();
-    let mut i = 0;
-    while i + 4 <= bytes.len() {
-        if bytes[i..i+4].iter().all(|b| b.is_ascii_digit()) {
-            let year: u32 = text[i..i+4].parse().unwrap_or(0);
-            if year >= 1900 && year <= 2099 {
-                // Make sure it's surrounded by non-digit chars or boundaries
-                let before_ok = i == 0 || !bytes[i-1].is_ascii_digit();
-                let after_ok = i + 4 >= bytes.len() || !bytes[i+4].is_ascii_digit();
-                if before_ok && after_ok {
-                    return Some(YearMatch { start: i, year });
-                }
+    resolution: Option<String>,
+    quality_tag: Option<String>,
+}
+
+/// Find resolution and source/format tokens ("1080p", "4K", "BluRay", "WEB-DL", "HDR").
+/// Bare words that also appear in titles (e.g. "Web") are deliberately not matched.
+fn find_quality(text: &str) -> Option<QualityMatch> {
+    // Split into tokens on the usual scene separators, remembering byte offsets
+    let mut tokens: Vec<(usize, &str)> = Vec::new();
+    let mut token_start = None;
+    for (i, c) in text.char_indices() {
+        let is_sep = matches!(c, '.' | '_' | ' ' | '-' | '(' | ')' | '[' | ']');
+        match (is_sep, token_start) {
+            (true, Some(start)) => {
+                tokens.push((start, &text[start..i]));
+                token_start = None;
+            }
+            (false, None) => token_start = Some(i),
+            _ => {}
+        }
+    }
+    if let Some(start) = token_start {
+        tokens.push((start, &text[start..]));
+    }
+
+    let mut first: Option<usize> = None;
+    let mut resolution: Option<String> = None;
+    let mut tags: Vec<&str> = Vec::new();
+
+    for (idx, &(start, token)) in tokens.iter().enumerate() {
+        let lower = token.to_lowercase();
+        let next = tokens.get(idx + 1).map(|(_, t)| t.to_lowercase());
+
+        let res = match lower.as_str() {
+            "4k" | "uhd" => Some("2160p".to_string()),
+            t if t.len() >= 4
+                && (t.ends_with('p') || t.ends_with('i'))
+                && t[..t.len() - 1].bytes().all(|b| b.is_ascii_digit())
+                && matches!(t[..t.len() - 1].len(), 3 | 4) =>
+            {
+                Some(t.to_string())
+            }
+            _ => None,
+        };
+        let tag = match lower.as_str() {
+            "bluray" => Some("BluRay"),
+            "bdrip" => Some("BDRip"),
+            "brrip" => Some("BRRip"),
+            "remux" => Some("REMUX"),
+            "webdl" => Some("WEB-DL"),
+            "web" if next.as_deref() == Some("dl") => Some("WEB-DL"),
+            "webrip" => Some("WEBRip"),
+            "hdtv" => Some("HDTV"),
+            "dvdrip" => Some("DVDRip"),
+            "hdr" | "hdr10" => Some("HDR"),
+            "hdr10+" => Some("HDR10+"),
+            _ => None,
+        };
+
+        if res.is_none() && tag.is_none() {
+            continue;
+        }
+        first.get_or_insert(start);
+        if resolution.is_none() {
+            resolution = res;
+        }
+        if let Some(tag) = tag {
+            if !tags.contains(&tag) {
+                tags.push(tag);
             }
         }
-        i += 1;
     }
-    None
+
+    Some(QualityMatch {
+        start: first?,
+        resolution,
+        quality_tag: if tags.is_empty() { None } else { Some(tags.join(" ")) },
+    })
 }
 
 fn clean_title(raw: &str) -> String {
-    raw
+    // An opening bracket left over from "Title (1995)" or "Title [1080p]"
+    raw.trim_end_matches(|c: char| " ._-([".contains(c))
         .replace('.', " ")
         .replace('_', " ")
         .replace('-', " ")
@@ -324,13 +1050,344 @@ fn clean_title(raw: &str) -> String {
         .to_string()
 }
 
-/// Generate a stable ID for a media item from its remote path
+// ── NFO sidecars ──────────────────────────────────────────────────────────────
+
+/// NFOs are a few KB; anything bigger is not one
+const MAX_NFO_BYTES: usize = 1024 * 1024;
+
+/// Metadata from a Kodi-style NFO file.
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct NfoMetadata {
+    pub title: Option<String>,
+    pub year: Option<u32>,
+    pub plot: Option<String>,
+    pub rating: Option<f32>,
+    /// Provider ids by lowercased type, e.g. "tmdb" -> "603", "imdb" -> "tt0133093"
+    pub unique_ids: std::collections::HashMap<String, String>,
+}
+
+/// Fetch an NFO sidecar with `rclone cat` and parse it. Plain-text NFOs that
+/// only hold an IMDb or TMDB link yield just the id.
+#[tauri::command]
+pub async fn read_nfo(app: AppHandle, config_path: String, nfo_remote_path: String) -> Result<NfoMetadata, AppError> {
+    let bytes = crate::commands::rclone::cat_remote(&app, &config_path, &nfo_remote_path, MAX_NFO_BYTES).await?;
+    Ok(parse_nfo(&String::from_utf8_lossy(&bytes)))
+}
+
+fn parse_nfo(text: &str) -> NfoMetadata {
+    let mut nfo = NfoMetadata {
+        title: xml_tag(text, "title"),
+        year: xml_tag(text, "year").and_then(|y| y.parse().ok()).or_else(|| {
+            // Newer Kodi versions only write <premiered>YYYY-MM-DD</premiered>
+            xml_tag(text, "premiered").and_then(|d| d.get(..4)?.parse().ok())
+        }),
+        plot: xml_tag(text, "plot").or_else(|| xml_tag(text, "outline")),
+        // <rating> is a plain number in old NFOs; newer ones nest
+        // <ratings><rating name="..."><value>
+        rating: xml_tag(text, "value")
+            .or_else(|| xml_tag(text, "rating"))
+            .and_then(|r| r.parse().ok()),
+        unique_ids: std::collections::HashMap::new(),
+    };
+
+    static UNIQUE_ID: OnceLock<Regex> = OnceLock::new();
+    let unique_id = UNIQUE_ID.get_or_init(|| {
+        Regex::new(r#"(?is)<uniqueid[^>]*\btype="([^"]+)"[^>]*>(.*?)</uniqueid>"#).expect("valid uniqueid pattern")
+    });
+    for caps in unique_id.captures_iter(text) {
+        let value = xml_text(&caps[2]);
+        if !value.is_empty() {
+            nfo.unique_ids.insert(caps[1].to_lowercase(), value);
+        }
+    }
+    for (tag, kind) in [("tmdbid", "tmdb"), ("imdbid", "imdb"), ("imdb_id", "imdb")] {
+        if let Some(id) = xml_tag(text, tag) {
+            nfo.unique_ids.entry(kind.to_string()).or_insert(id);
+        }
+    }
+
+    // Links anywhere in the file, which is all some NFOs contain
+    static IMDB_LINK: OnceLock<Regex> = OnceLock::new();
+    static TMDB_LINK: OnceLock<Regex> = OnceLock::new();
+    let imdb = IMDB_LINK.get_or_init(|| Regex::new(r"imdb\.com/title/(tt\d{7,})").expect("valid imdb pattern"));
+    let tmdb = TMDB_LINK.get_or_init(|| {
+        Regex::new(r"themoviedb\.org/(?:movie|tv)/(\d+)").expect("valid tmdb pattern")
+    });
+    if let Some(caps) = imdb.captures(text) {
+        nfo.unique_ids.entry("imdb".to_string()).or_insert_with(|| caps[1].to_string());
+    }
+    if let Some(caps) = tmdb.captures(text) {
+        nfo.unique_ids.entry("tmdb".to_string()).or_insert_with(|| caps[1].to_string());
+    }
+    nfo
+}
+
+/// Elements `parse_nfo` reads with `xml_tag`
+const NFO_TAGS: &[&str] = &[
+    "title", "year", "premiered", "plot", "outline", "value", "rating", "tmdbid", "imdbid", "imdb_id",
+];
+
+/// Text of the first non-empty `<tag>` element; `tag` is one of `NFO_TAGS`.
+fn xml_tag(text: &str, tag: &str) -> Option<String> {
+    static PATTERNS: OnceLock<std::collections::HashMap<&'static str, Regex>> = OnceLock::new();
+    let patterns = PATTERNS.get_or_init(|| {
+        NFO_TAGS
+            .iter()
+            .map(|&tag| {
+                let pattern = format!(r"(?is)<{0}(?:\s[^>]*)?>(.*?)</{0}>", regex::escape(tag));
+                (tag, Regex::new(&pattern).expect("valid NFO tag pattern"))
+            })
+            .collect()
+    });
+    let value = patterns
+        .get(tag)
+        .expect("tag listed in NFO_TAGS")
+        .captures_iter(text)
+        .map(|caps| xml_text(&caps[1]))
+        .find(|value| !value.is_empty());
+    value
+}
+
+/// Unwrap CDATA and decode the predefined XML entities.
+fn xml_text(raw: &str) -> String {
+    let raw = raw.trim();
+    let raw = raw
+        .strip_prefix("<![CDATA[")
+        .and_then(|r| r.strip_suffix("]]>"))
+        .unwrap_or(raw);
+    raw.replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&apos;", "'")
+        .replace("&amp;", "&")
+        .trim()
+        .to_string()
+}
+
+/// Generate a stable ID for a media item from its remote path (32 hex chars)
 #[tauri::command]
 pub fn hash_remote_path(remote_path: String) -> String {
-    // Simple djb2-style hash, no extra crate needed
+    // BLAKE3 truncated to 128 bits: collisions stay negligible even for very large
+    // libraries, where a 64-bit non-cryptographic hash would start merging items
+    let hash = blake3::hash(remote_path.as_bytes());
+    hash.as_bytes()[..16]
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect()
+}
+
+/// The djb2 id `hash_remote_path` gave before ids moved to BLAKE3. Only for
+/// carrying stored progress and bookmarks over to the new ids.
+pub(crate) fn legacy_hash_remote_path(remote_path: &str) -> String {
     let mut hash: u64 = 5381;
     for byte in remote_path.bytes() {
         hash = hash.wrapping_mul(33).wrapping_add(byte as u64);
     }
     format!("{:016x}", hash)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(filename: &str) -> ParsedTitle {
+        parse_media_filename(filename.to_string())
+    }
+
+    #[test]
+    fn scene_tags_are_stripped_from_the_title() {
+        let parsed = parse("Movie.2020.1080p.BluRay.x264-GROUP.mkv");
+        assert_eq!(parsed.title, "Movie");
+        assert_eq!(parsed.year, Some(2020));
+        assert_eq!(parsed.resolution.as_deref(), Some("1080p"));
+        assert_eq!(parsed.quality_tag.as_deref(), Some("BluRay"));
+
+        let parsed = parse("The.Grand.Budapest.Hotel.2160p.UHD.WEB-DL.HDR10.mkv");
+        assert_eq!(parsed.title, "The Grand Budapest Hotel");
+        assert_eq!(parsed.resolution.as_deref(), Some("2160p"));
+        assert_eq!(parsed.quality_tag.as_deref(), Some("WEB-DL HDR"));
+
+        let parsed = parse("Show.S02E03.4K.REMUX.HDR.mkv");
+        assert_eq!(parsed.title, "Show");
+        assert_eq!(parsed.resolution.as_deref(), Some("2160p"));
+        assert_eq!(parsed.quality_tag.as_deref(), Some("REMUX HDR"));
+    }
+
+    #[test]
+    fn clean_title_joins_scene_separators() {
+        assert_eq!(clean_title("the.dark_knight-rises"), "The Dark Knight Rises");
+        assert_eq!(clean_title("Apollo 13 ("), "Apollo 13");
+        assert_eq!(clean_title(""), "");
+    }
+
+    #[test]
+    fn multi_episode_files() {
+        for filename in ["Friends.S01E01-E02.mkv", "Friends.S01E01E02.mkv", "Friends - 1x01-02.mkv"] {
+            let parsed = parse(filename);
+            assert_eq!(parsed.title, "Friends", "{}", filename);
+            assert_eq!(parsed.season, Some(1), "{}", filename);
+            assert_eq!(parsed.episode, Some(1), "{}", filename);
+            assert_eq!(parsed.episode_end, Some(2), "{}", filename);
+        }
+        assert_eq!(parse("Friends.S01E01.mkv").episode_end, None);
+    }
+
+    #[test]
+    fn episode_patterns_table() {
+        // (filename, title, season, episode, air date)
+        let cases: [(&str, &str, Option<u32>, Option<u32>, Option<&str>); 12] = [
+            ("Breaking.Bad.S03E07.mkv", "Breaking Bad", Some(3), Some(7), None),
+            ("breaking_bad_s03e07_720p.mkv", "Breaking Bad", Some(3), Some(7), None),
+            ("Breaking Bad S03 E07.mkv", "Breaking Bad", Some(3), Some(7), None),
+            ("Breaking.Bad.S03.E07.1080p.WEB-DL.mkv", "Breaking Bad", Some(3), Some(7), None),
+            ("Show.S1E5.mkv", "Show", Some(1), Some(5), None),
+            ("Show.S10E100.mkv", "Show", Some(10), Some(100), None),
+            ("The Wire - 1x01 - The Target.mkv", "The Wire", Some(1), Some(1), None),
+            ("the.wire.1x01.mkv", "The Wire", Some(1), Some(1), None),
+            ("The.Daily.Show.2021.03.14.mkv", "The Daily Show", None, None, Some("2021-03-14")),
+            ("The Daily Show 2021-03-14.mkv", "The Daily Show", None, None, Some("2021-03-14")),
+            ("[SubsPlease] Show - 105 (1080p).mkv", "Show", None, Some(105), None),
+            ("[Group] Show - 05v2 (1080p).mkv", "Show", None, Some(5), None),
+        ];
+        for (filename, title, season, episode, air_date) in cases {
+            let parsed = parse(filename);
+            assert_eq!(parsed.title, title, "{}", filename);
+            assert_eq!(parsed.season, season, "{}", filename);
+            assert_eq!(parsed.episode, episode, "{}", filename);
+            assert_eq!(parsed.air_date.as_deref(), air_date, "{}", filename);
+            assert!(parsed.is_episode, "{}", filename);
+        }
+
+        for movie in ["The.Dark.Knight.2008.mkv", "Movie.2020.1080p.BluRay.x264.mkv", "Se7en.1995.mkv"] {
+            assert!(!parse(movie).is_episode, "{}", movie);
+        }
+    }
+
+    #[test]
+    fn subtitle_keys_peel_language_and_flag_suffixes() {
+        assert_eq!(subtitle_match_keys("Movies/Movie.en.srt"), ["movies/movie.en", "movies/movie"]);
+        assert_eq!(
+            subtitle_match_keys("Movie.pt-BR.forced.srt"),
+            ["movie.pt-br.forced", "movie.pt-br", "movie"]
+        );
+        assert_eq!(subtitle_match_keys("Movie.srt"), ["movie"]);
+        assert!(subtitle_match_keys("Movie.mkv").is_empty());
+    }
+
+    #[test]
+    fn subtitle_keys_keep_the_unpeeled_stem() {
+        // "Up" looks like a language code but is part of the title
+        let keys = subtitle_match_keys("Toy.Story.Up.srt");
+        assert_eq!(keys[0], "toy.story.up");
+        assert!(!keys.contains(&"toy".to_string()));
+    }
+
+    #[test]
+    fn hash_remote_path_is_stable_and_distinct() {
+        let id = hash_remote_path("gdrive:Movies/Movie.mkv".to_string());
+        assert_eq!(id.len(), 32);
+        assert!(id.bytes().all(|b| b.is_ascii_hexdigit()));
+        assert_eq!(id, hash_remote_path("gdrive:Movies/Movie.mkv".to_string()));
+
+        let mut seen = std::collections::HashSet::new();
+        for show in 0..200 {
+            for episode in 0..250 {
+                let path = format!("gdrive:TV/Show {}/Season 1/S01E{:03}.mkv", show, episode);
+                assert!(seen.insert(hash_remote_path(path.clone())), "collision at {}", path);
+            }
+        }
+        assert_eq!(seen.len(), 50_000);
+    }
+
+    #[test]
+    fn episode_number_and_season_from_folders() {
+        let parsed = parse_media_path("gdrive:TV/Breaking Bad/Season 3/07 - Episode.mkv".to_string());
+        assert_eq!(parsed.title, "Breaking Bad");
+        assert_eq!(parsed.season, Some(3));
+        assert_eq!(parsed.episode, Some(7));
+        assert!(parsed.is_episode);
+    }
+
+    #[test]
+    fn specials_folder_is_season_zero() {
+        let parsed = parse_media_path("gdrive:TV/Doctor Who (2005)/Specials/01 - The Christmas Invasion.mkv".to_string());
+        assert_eq!(parsed.title, "Doctor Who");
+        assert_eq!(parsed.year, Some(2005));
+        assert_eq!(parsed.season, Some(0));
+        assert_eq!(parsed.episode, Some(1));
+        assert!(parsed.is_episode);
+    }
+
+    #[test]
+    fn movie_in_a_named_folder_keeps_its_own_title() {
+        let parsed = parse_media_path("gdrive:Movies/The Matrix (1999)/The.Matrix.1999.1080p.BluRay.mkv".to_string());
+        assert_eq!(parsed.title, "The Matrix");
+        assert_eq!(parsed.year, Some(1999));
+        assert_eq!(parsed.season, None);
+        assert!(!parsed.is_episode);
+    }
+
+    #[test]
+    fn season_marker_in_the_filename_wins_over_the_folder() {
+        let parsed = parse_media_path("gdrive:TV/The Wire/Season 1/The.Wire.S01E02.mkv".to_string());
+        assert_eq!(parsed.title, "The Wire");
+        assert_eq!(parsed.season, Some(1));
+        assert_eq!(parsed.episode, Some(2));
+    }
+
+    #[test]
+    fn absolute_episode_after_a_group_tag() {
+        let parsed = parse("[Group] Show - 125 [1080p].mkv");
+        assert_eq!(parsed.title, "Show");
+        assert_eq!(parsed.episode, Some(125));
+        assert_eq!(parsed.season, None);
+        assert_eq!(parsed.resolution.as_deref(), Some("1080p"));
+        assert!(parsed.is_episode);
+    }
+
+    #[test]
+    fn single_digit_absolute_episode() {
+        let parsed = parse("Show - 7 [720p].mkv");
+        assert_eq!(parsed.title, "Show");
+        assert_eq!(parsed.episode, Some(7));
+        assert!(parsed.is_episode);
+    }
+
+    #[test]
+    fn titles_ending_in_a_number_stay_movies() {
+        let parsed = parse("Blade Runner 2049.mkv");
+        assert!(!parsed.is_episode);
+        assert_eq!(parsed.episode, None);
+
+        let parsed = parse("Apollo 13 (1995).mkv");
+        assert_eq!(parsed.title, "Apollo 13");
+        assert_eq!(parsed.year, Some(1995));
+        assert_eq!(parsed.episode, None);
+        assert!(!parsed.is_episode);
+    }
+
+    #[test]
+    fn year_after_a_dash_is_not_an_episode() {
+        let parsed = parse("Movie - 2012.mkv");
+        assert_eq!(parsed.title, "Movie");
+        assert_eq!(parsed.year, Some(2012));
+        assert_eq!(parsed.episode, None);
+        assert!(!parsed.is_episode);
+    }
+
+    #[test]
+    fn audio_channel_tags_are_not_episodes() {
+        let parsed = parse("Movie (2010) AAC - 5.1.mkv");
+        assert_eq!(parsed.year, Some(2010));
+        assert_eq!(parsed.episode, None);
+        assert!(!parsed.is_episode);
+    }
+
+    #[test]
+    fn legacy_ids_match_the_old_djb2_scheme() {
+        assert_eq!(legacy_hash_remote_path(""), "0000000000001505");
+        assert_eq!(legacy_hash_remote_path("a"), format!("{:016x}", 5381u64 * 33 + 97));
+        let path = "gdrive:Movies/Movie.mkv";
+        assert_ne!(legacy_hash_remote_path(path), hash_remote_path(path.to_string()));
+    }
+}
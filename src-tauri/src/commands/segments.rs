@@ -0,0 +1,115 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use tauri::{AppHandle, State};
+
+use crate::commands::player::{self, Chapter, VlcManager};
+use crate::commands::store;
+use crate::error::AppError;
+
+/// Setting holding the skip segments of every series, by series key
+const SEGMENTS_SETTING: &str = "skip_segments";
+/// Setting listing the segment kinds the player skips without asking
+pub(crate) const AUTO_SKIP_SETTING: &str = "player.auto_skip_kinds";
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Hash)]
+#[serde(rename_all = "lowercase")]
+pub enum SegmentKind {
+    Intro,
+    Recap,
+    Credits,
+    Preview,
+}
+
+/// A stretch of an episode the UI offers to skip, e.g. the opening titles.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct SkipSegment {
+    pub start_ms: i64,
+    pub end_ms: i64,
+    pub kind: SegmentKind,
+}
+
+impl SkipSegment {
+    pub(crate) fn contains(&self, time_ms: i64) -> bool {
+        self.start_ms <= time_ms && time_ms < self.end_ms
+    }
+}
+
+fn load_all(app: &AppHandle) -> HashMap<String, Vec<SkipSegment>> {
+    store::get_setting(app, SEGMENTS_SETTING).unwrap_or_default()
+}
+
+/// Segments stored for `series_key`; None when none were ever set, so chapter
+/// names can be tried instead.
+pub(crate) fn stored_segments(app: &AppHandle, series_key: &str) -> Option<Vec<SkipSegment>> {
+    load_all(app).remove(series_key)
+}
+
+/// Kinds listed in `AUTO_SKIP_SETTING`.
+pub(crate) fn auto_skip_kinds(app: &AppHandle) -> Vec<SegmentKind> {
+    store::get_setting(app, AUTO_SKIP_SETTING).unwrap_or_default()
+}
+
+/// Guess segments from chapter names, as ripped anime and TV often mark the
+/// opening and ending songs with chapters ("Intro", "Opening", "Credits", "ED").
+pub(crate) fn segments_from_chapters(chapters: &[Chapter]) -> Vec<SkipSegment> {
+    chapters
+        .iter()
+        .filter(|c| c.end_ms > c.start_ms)
+        .filter_map(|c| {
+            Some(SkipSegment {
+                start_ms: c.start_ms,
+                end_ms: c.end_ms,
+                kind: chapter_kind(&c.title)?,
+            })
+        })
+        .collect()
+}
+
+fn chapter_kind(title: &str) -> Option<SegmentKind> {
+    let lower = title.to_lowercase();
+    let words: Vec<&str> = lower
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|w| !w.is_empty())
+        .collect();
+    let has = |candidates: &[&str]| words.iter().any(|w| candidates.contains(w));
+    if has(&["intro", "opening", "op", "titles"]) {
+        Some(SegmentKind::Intro)
+    } else if has(&["credits", "ending", "ed", "outro"]) {
+        Some(SegmentKind::Credits)
+    } else if has(&["recap", "previously"]) {
+        Some(SegmentKind::Recap)
+    } else if has(&["preview", "next"]) {
+        Some(SegmentKind::Preview)
+    } else {
+        None
+    }
+}
+
+/// Replace the skip segments of a series. An empty list is kept (it turns the
+/// chapter-name guess off); it doesn't fall back to chapters.
+#[tauri::command]
+pub async fn set_skip_segments(
+    app: AppHandle,
+    vlc: State<'_, VlcManager>,
+    series_key: String,
+    mut segments: Vec<SkipSegment>,
+) -> Result<(), AppError> {
+    if let Some(bad) = segments.iter().find(|s| s.start_ms < 0 || s.end_ms <= s.start_ms) {
+        return Err(AppError::invalid(format!(
+            "Segment must end after it starts, got {}-{} ms",
+            bad.start_ms, bad.end_ms
+        )));
+    }
+    segments.sort_by_key(|s| s.start_ms);
+    let mut all = load_all(&app);
+    all.insert(series_key, segments);
+    store::set_setting(&app, SEGMENTS_SETTING, serde_json::json!(all))?;
+    player::reload_skip_segments(&vlc);
+    Ok(())
+}
+
+/// Stored skip segments of a series; empty when none were set.
+#[tauri::command]
+pub async fn get_skip_segments(app: AppHandle, series_key: String) -> Result<Vec<SkipSegment>, AppError> {
+    Ok(stored_segments(&app, &series_key).unwrap_or_default())
+}
@@ -1,24 +1,53 @@
+use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
 use tauri::AppHandle;
 use tauri_plugin_store::StoreExt;
 
+use crate::secrets;
+use crate::error::AppError;
+
 const STORE_PATH: &str = "rcloneflix-keys.json";
+/// Generic settings live under this prefix so they can't collide with the
+/// fixed entries (API keys, OAuth tokens) in the same store
+const SETTING_PREFIX: &str = "setting.";
 
 #[derive(Debug, Serialize, Deserialize, Default)]
 pub struct ApiKeys {
     pub tmdb: String,
     pub theporndb: String,
+    #[serde(default)]
+    pub opensubtitles: String,
+}
+
+/// Keys to change in `save_api_keys`; omitted ones keep their stored value
+#[derive(Debug, Deserialize, Default)]
+pub struct ApiKeysUpdate {
+    #[serde(default)]
+    pub tmdb: Option<String>,
+    #[serde(default)]
+    pub theporndb: Option<String>,
+    #[serde(default)]
+    pub opensubtitles: Option<String>,
 }
 
-/// Save API keys to Tauri's encrypted store
+/// Save API keys to the store, encrypted (see `secrets`). Only the keys that
+/// are sent are written, so a form that doesn't know about a key can't wipe it.
 #[tauri::command]
-pub async fn save_api_keys(app: AppHandle, keys: ApiKeys) -> Result<(), String> {
+pub async fn save_api_keys(app: AppHandle, keys: ApiKeysUpdate) -> Result<(), AppError> {
     let store = app
         .store(STORE_PATH)
         .map_err(|e| format!("Failed to open store: {}", e))?;
 
-    store.set("tmdb_key", serde_json::json!(keys.tmdb));
-    store.set("theporndb_key", serde_json::json!(keys.theporndb));
+    let updates = [
+        ("tmdb_key", keys.tmdb),
+        ("theporndb_key", keys.theporndb),
+        ("opensubtitles_key", keys.opensubtitles),
+    ];
+    for (name, value) in updates {
+        if let Some(value) = value {
+            store.set(name, secrets::seal(&app, &serde_json::json!(value))?);
+        }
+    }
 
     store
         .save()
@@ -27,22 +56,122 @@ pub async fn save_api_keys(app: AppHandle, keys: ApiKeys) -> Result<(), String>
     Ok(())
 }
 
-/// Load API keys from Tauri's encrypted store
+/// Load API keys from the store, decrypting them
 #[tauri::command]
-pub async fn load_api_keys(app: AppHandle) -> Result<ApiKeys, String> {
+pub async fn load_api_keys(app: AppHandle) -> Result<ApiKeys, AppError> {
+    let store = app
+        .store(STORE_PATH)
+        .map_err(|e| format!("Failed to open store: {}", e))?;
+
+    let tmdb = match store.get("tmdb_key") {
+        Some(v) => secrets::open(&app, v)?.as_str().unwrap_or_default().to_string(),
+        None => String::new(),
+    };
+
+    let theporndb = match store.get("theporndb_key") {
+        Some(v) => secrets::open(&app, v)?.as_str().unwrap_or_default().to_string(),
+        None => String::new(),
+    };
+
+    let opensubtitles = match store.get("opensubtitles_key") {
+        Some(v) => secrets::open(&app, v)?.as_str().unwrap_or_default().to_string(),
+        None => String::new(),
+    };
+
+    Ok(ApiKeys { tmdb, theporndb, opensubtitles })
+}
+
+/// A stored API key ("tmdb_key", "opensubtitles_key", ...), for native lookups
+/// that keep it out of the webview
+pub(crate) fn api_key(app: &AppHandle, name: &str) -> Result<Option<String>, String> {
     let store = app
         .store(STORE_PATH)
         .map_err(|e| format!("Failed to open store: {}", e))?;
+    match store.get(name) {
+        Some(v) => Ok(secrets::open(app, v)?
+            .as_str()
+            .map(|s| s.trim().to_string())
+            .filter(|s| !s.is_empty())),
+        None => Ok(None),
+    }
+}
+
+fn setting_key(key: &str) -> Result<String, String> {
+    let key = key.trim();
+    if key.is_empty() {
+        return Err("Setting key must not be empty".to_string());
+    }
+    Ok(format!("{}{}", SETTING_PREFIX, key))
+}
+
+/// Read a setting saved with `save_setting`, if present and of the expected shape.
+pub(crate) fn get_setting<T: DeserializeOwned>(app: &AppHandle, key: &str) -> Option<T> {
+    let key = setting_key(key).ok()?;
+    app.store(STORE_PATH)
+        .ok()
+        .and_then(|s| s.get(key))
+        .and_then(|v| serde_json::from_value(v).ok())
+}
+
+/// Write a setting; `null` removes it.
+pub(crate) fn set_setting(app: &AppHandle, key: &str, value: serde_json::Value) -> Result<(), String> {
+    let key = setting_key(key)?;
+    let store = app
+        .store(STORE_PATH)
+        .map_err(|e| format!("Failed to open store: {}", e))?;
+    if value.is_null() {
+        store.delete(key);
+    } else {
+        store.set(key, value);
+    }
+    store
+        .save()
+        .map_err(|e| format!("Failed to save store: {}", e))?;
+    Ok(())
+}
 
-    let tmdb = store
-        .get("tmdb_key")
-        .and_then(|v| v.as_str().map(|s| s.to_string()))
-        .unwrap_or_default();
+/// Save an arbitrary JSON setting under `key`; `null` removes it
+#[tauri::command]
+pub async fn save_setting(app: AppHandle, key: String, value: serde_json::Value) -> Result<(), AppError> {
+    Ok(set_setting(&app, &key, value)?)
+}
 
-    let theporndb = store
-        .get("theporndb_key")
-        .and_then(|v| v.as_str().map(|s| s.to_string()))
-        .unwrap_or_default();
+/// Load a setting saved with `save_setting`; null when unset
+#[tauri::command]
+pub async fn load_setting(app: AppHandle, key: String) -> Result<serde_json::Value, AppError> {
+    setting_key(&key)?;
+    Ok(get_setting(&app, &key).unwrap_or(serde_json::Value::Null))
+}
+
+/// Every setting saved with `save_setting` (bookmarks included), by key, for
+/// the settings backup
+#[tauri::command]
+pub async fn export_settings(app: AppHandle) -> Result<serde_json::Map<String, serde_json::Value>, AppError> {
+    let store = app
+        .store(STORE_PATH)
+        .map_err(|e| format!("Failed to open store: {}", e))?;
+    Ok(store
+        .entries()
+        .into_iter()
+        .filter_map(|(key, value)| Some((key.strip_prefix(SETTING_PREFIX)?.to_string(), value)))
+        .collect())
+}
 
-    Ok(ApiKeys { tmdb, theporndb })
+/// Restore settings from `export_settings`. Settings missing from the backup
+/// keep their current value.
+#[tauri::command]
+pub async fn import_settings(
+    app: AppHandle,
+    settings: serde_json::Map<String, serde_json::Value>,
+) -> Result<(), AppError> {
+    let store = app
+        .store(STORE_PATH)
+        .map_err(|e| format!("Failed to open store: {}", e))?;
+    for (key, value) in settings {
+        store.set(setting_key(&key)?, value);
+    }
+    store
+        .save()
+        .map_err(|e| format!("Failed to save store: {}", e))?;
+    Ok(())
 }
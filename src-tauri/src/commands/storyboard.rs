@@ -0,0 +1,733 @@
+//! Seek-preview storyboards: frames extracted with ffmpeg at a fixed interval and
+//! tiled into one sprite sheet. The frontend maps a hovered time to a tile with
+//! the returned layout.
+//!
+//! Storyboards are cached per media id in the temp directory, so each file is
+//! only processed once. A running generation can be cancelled by media id.
+//!
+//! Seek thumbnails are the per-frame variant for library items: one small JPEG
+//! every N seconds in a per-item directory, generated one item at a time with
+//! progress events, and kept under a size cap with least-recently-used eviction.
+//!
+//! Poster frames stand in for artwork on library items TMDB doesn't know (home
+//! videos): one poster-sized JPEG per item, cached in the temp cache so they
+//! share its size limit.
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::SystemTime;
+use tauri::{AppHandle, Emitter, Manager, State};
+use tokio::io::{AsyncBufReadExt, BufReader};
+use tokio::process::Command as TokioCommand;
+use tokio::sync::oneshot;
+
+use crate::commands::player::{self, VlcManager};
+use crate::commands::progress;
+use crate::commands::temp_cache;
+use crate::error::AppError;
+
+const TILE_WIDTH: u32 = 160;
+const TILE_HEIGHT: u32 = 90;
+const COLUMNS: u32 = 10;
+/// Upper bound on tiles per sheet; long files get a wider interval instead
+const MAX_FRAMES: u32 = 300;
+const LAYOUT_FILE: &str = "storyboard.json";
+const SPRITE_FILE: &str = "sprite.jpg";
+
+/// Written last into a seek-thumbnail directory; its mtime is the LRU timestamp
+const THUMBNAILS_MARKER: &str = "thumbnails.json";
+/// Total size of cached seek thumbnails before the least recently used go
+const MAX_THUMBNAIL_CACHE_BYTES: u64 = 512 * 1024 * 1024;
+/// Session id under which thumbnail jobs hold a serve process
+const THUMBNAIL_SESSION: &str = "seek-thumbnails";
+
+/// Poster frame size (2:3, like TMDB posters) and where in the file it is taken
+const POSTER_WIDTH: u32 = 342;
+const POSTER_HEIGHT: u32 = 513;
+const POSTER_POSITION: f64 = 0.1;
+/// Posters generated at once by `generate_item_thumbnails`
+const POSTER_CONCURRENCY: usize = 3;
+
+/// Cancel senders for running generations, by media id
+static RUNNING: Mutex<Option<HashMap<String, oneshot::Sender<()>>>> = Mutex::new(None);
+/// Held while a seek-thumbnail job runs, so jobs run one at a time
+static THUMBNAIL_JOB: tokio::sync::Mutex<()> = tokio::sync::Mutex::const_new(());
+
+/// `name` ("ffmpeg", "ffprobe") shipped in the app's resources, falling back
+/// to the one on PATH
+pub(crate) fn bundled_binary(app: &AppHandle, name: &str) -> PathBuf {
+    let resource_dir = app
+        .path()
+        .resource_dir()
+        .unwrap_or_else(|_| PathBuf::from("."));
+    let bundled = if cfg!(target_os = "windows") {
+        resource_dir.join(format!("{}.exe", name))
+    } else {
+        resource_dir.join(name)
+    };
+    if bundled.exists() { bundled } else { PathBuf::from(name) }
+}
+
+/// Sprite sheet layout. Tile `i` covers `timestamps_ms[i]` up to the next one and
+/// sits at column `i % cols`, row `i / cols`.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Storyboard {
+    pub sprite_path: String,
+    pub rows: u32,
+    pub cols: u32,
+    pub frame_width: u32,
+    pub frame_height: u32,
+    /// Interval actually used, which is wider than requested for long files
+    pub interval_s: u32,
+    pub timestamps_ms: Vec<i64>,
+}
+
+fn cache_dir(media_id: &str) -> PathBuf {
+    std::env::temp_dir().join("rcloneflix-storyboards").join(media_id)
+}
+
+fn load_cached(dir: &Path) -> Option<Storyboard> {
+    let layout = std::fs::read_to_string(dir.join(LAYOUT_FILE)).ok()?;
+    let storyboard: Storyboard = serde_json::from_str(&layout).ok()?;
+    Path::new(&storyboard.sprite_path).is_file().then_some(storyboard)
+}
+
+async fn probe_duration_s(app: &AppHandle, file_url: &str) -> Result<f64, String> {
+    let output = TokioCommand::new(bundled_binary(app, "ffprobe"))
+        .args([
+            "-v",
+            "error",
+            "-show_entries",
+            "format=duration",
+            "-of",
+            "default=noprint_wrappers=1:nokey=1",
+            file_url,
+        ])
+        .output()
+        .await
+        .map_err(|e| format!("Failed to run ffprobe: {}", e))?;
+    if !output.status.success() {
+        return Err(format!(
+            "ffprobe failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+    String::from_utf8_lossy(&output.stdout)
+        .trim()
+        .parse::<f64>()
+        .ok()
+        .filter(|d| *d > 0.0)
+        .ok_or_else(|| "Could not determine the media duration".to_string())
+}
+
+/// Run ffmpeg into `sprite`, killing it if `cancel` fires first.
+async fn run_ffmpeg(
+    app: &AppHandle,
+    cancel: oneshot::Receiver<()>,
+    file_url: &str,
+    interval_s: u32,
+    rows: u32,
+    sprite: &Path,
+) -> Result<(), AppError> {
+    let filter = format!(
+        "fps=1/{interval},scale={w}:{h}:force_original_aspect_ratio=decrease,\
+         pad={w}:{h}:(ow-iw)/2:(oh-ih)/2,tile={cols}x{rows}",
+        interval = interval_s,
+        w = TILE_WIDTH,
+        h = TILE_HEIGHT,
+        cols = COLUMNS,
+        rows = rows,
+    );
+    let mut child = TokioCommand::new(bundled_binary(app, "ffmpeg"))
+        .args([
+            "-v",
+            "error",
+            // Decoding keyframes only is much faster and close enough for previews
+            "-skip_frame",
+            "nokey",
+            "-i",
+            file_url,
+            "-an",
+            "-sn",
+            "-vf",
+            &filter,
+            "-frames:v",
+            "1",
+            "-q:v",
+            "5",
+            "-y",
+        ])
+        .arg(sprite)
+        .kill_on_drop(true)
+        .spawn()
+        .map_err(|_| AppError::FfmpegMissing)?;
+
+    tokio::select! {
+        status = child.wait() => match status {
+            Ok(s) if s.success() => Ok(()),
+            Ok(s) => Err(format!("ffmpeg exited with {}", s).into()),
+            Err(e) => Err(format!("ffmpeg failed: {}", e).into()),
+        },
+        _ = cancel => {
+            let _ = child.kill().await;
+            Err(AppError::cancelled("Storyboard generation cancelled"))
+        }
+    }
+}
+
+/// Extract a frame every `interval_s` seconds of `file_url` into a sprite sheet
+/// and return its layout. Cached under `media_id` (the item id), so repeated
+/// calls return immediately; the URL can't be the key, as a streamed item's
+/// changes with every serve port.
+#[tauri::command]
+pub async fn generate_storyboard(
+    app: AppHandle,
+    file_url: String,
+    interval_s: u32,
+    media_id: String,
+) -> Result<Storyboard, AppError> {
+    if interval_s == 0 {
+        return Err(AppError::invalid("Interval must be at least 1 second"));
+    }
+    if media_id.is_empty() || !media_id.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_') {
+        return Err(AppError::invalid(format!("Invalid media id: {}", media_id)));
+    }
+    let dir = cache_dir(&media_id);
+    if let Some(cached) = load_cached(&dir) {
+        return Ok(cached);
+    }
+
+    let (cancel_tx, cancel_rx) = oneshot::channel();
+    {
+        let mut running = RUNNING.lock().unwrap();
+        let running = running.get_or_insert_with(HashMap::new);
+        if running.contains_key(&media_id) {
+            return Err("A storyboard is already being generated for this media".into());
+        }
+        running.insert(media_id.clone(), cancel_tx);
+    }
+    let result = build_storyboard(&app, &file_url, interval_s, &dir, cancel_rx).await;
+    if let Some(running) = RUNNING.lock().unwrap().as_mut() {
+        running.remove(&media_id);
+    }
+    result
+}
+
+async fn build_storyboard(
+    app: &AppHandle,
+    file_url: &str,
+    interval_s: u32,
+    dir: &Path,
+    cancel: oneshot::Receiver<()>,
+) -> Result<Storyboard, AppError> {
+    let duration_s = probe_duration_s(app, file_url).await?;
+    let interval_s = interval_s.max((duration_s / MAX_FRAMES as f64).ceil() as u32);
+    let frames = ((duration_s / interval_s as f64).ceil() as u32).max(1);
+    let rows = frames.div_ceil(COLUMNS);
+
+    std::fs::create_dir_all(dir)
+        .map_err(|e| format!("Failed to create {}: {}", dir.display(), e))?;
+    let sprite = dir.join(SPRITE_FILE);
+    if let Err(e) = run_ffmpeg(app, cancel, file_url, interval_s, rows, &sprite).await {
+        let _ = std::fs::remove_file(&sprite);
+        return Err(e);
+    }
+
+    let storyboard = Storyboard {
+        sprite_path: sprite.to_string_lossy().into_owned(),
+        rows,
+        cols: COLUMNS,
+        frame_width: TILE_WIDTH,
+        frame_height: TILE_HEIGHT,
+        interval_s,
+        timestamps_ms: (0..frames).map(|i| i as i64 * interval_s as i64 * 1000).collect(),
+    };
+    let layout = serde_json::to_string(&storyboard).unwrap();
+    std::fs::write(dir.join(LAYOUT_FILE), layout)
+        .map_err(|e| format!("Failed to write storyboard layout: {}", e))?;
+    Ok(storyboard)
+}
+
+fn cancel_running(id: &str) {
+    let sender = RUNNING
+        .lock()
+        .unwrap()
+        .as_mut()
+        .and_then(|running| running.remove(id));
+    if let Some(sender) = sender {
+        let _ = sender.send(());
+    }
+}
+
+/// Stop a running `generate_storyboard` for `media_id`. Nothing is cached for it.
+#[tauri::command]
+pub async fn cancel_storyboard(media_id: String) -> Result<(), AppError> {
+    cancel_running(&media_id);
+    Ok(())
+}
+
+// ── Seek thumbnails ───────────────────────────────────────────────────────────
+
+/// Contents of `THUMBNAILS_MARKER`
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct SeekThumbnails {
+    pub item_id: String,
+    pub interval_s: u32,
+    pub count: u32,
+}
+
+fn thumbnails_root() -> PathBuf {
+    std::env::temp_dir().join("rcloneflix-thumbnails")
+}
+
+fn load_thumbnails(dir: &Path) -> Option<SeekThumbnails> {
+    let marker = std::fs::read_to_string(dir.join(THUMBNAILS_MARKER)).ok()?;
+    serde_json::from_str(&marker).ok()
+}
+
+/// Frame `index` (0-based) as written by ffmpeg's `%05d` pattern, which starts at 1
+fn frame_path(dir: &Path, index: u32) -> PathBuf {
+    dir.join(format!("{:05}.jpg", index + 1))
+}
+
+fn dir_size(dir: &Path) -> u64 {
+    std::fs::read_dir(dir)
+        .map(|entries| {
+            entries
+                .flatten()
+                .filter_map(|e| e.metadata().ok())
+                .map(|m| m.len())
+                .sum()
+        })
+        .unwrap_or(0)
+}
+
+/// Delete the least recently used thumbnail directories until the cache fits in
+/// `MAX_THUMBNAIL_CACHE_BYTES`. Incomplete directories (no marker) are left to
+/// the job that owns them.
+fn evict_thumbnails(keep: &Path) {
+    let Ok(entries) = std::fs::read_dir(thumbnails_root()) else {
+        return;
+    };
+    let mut dirs: Vec<(SystemTime, u64, PathBuf)> = entries
+        .flatten()
+        .map(|e| e.path())
+        .filter(|p| p.is_dir())
+        .filter_map(|p| {
+            let used = std::fs::metadata(p.join(THUMBNAILS_MARKER)).ok()?.modified().ok()?;
+            Some((used, dir_size(&p), p))
+        })
+        .collect();
+    let mut total: u64 = dirs.iter().map(|(_, size, _)| size).sum();
+    dirs.sort_by_key(|(used, _, _)| *used);
+    for (_, size, dir) in dirs {
+        if total <= MAX_THUMBNAIL_CACHE_BYTES {
+            break;
+        }
+        if dir == keep {
+            continue;
+        }
+        if std::fs::remove_dir_all(&dir).is_ok() {
+            total = total.saturating_sub(size);
+        }
+    }
+}
+
+/// Run ffmpeg writing one JPEG per `interval_s` into `dir`, emitting
+/// `thumbnails:progress` from its `-progress` output. Killed if `cancel` fires.
+async fn run_thumbnail_ffmpeg(
+    app: &AppHandle,
+    item_id: &str,
+    source: &str,
+    interval_s: u32,
+    duration_s: Option<f64>,
+    dir: &Path,
+    mut cancel: oneshot::Receiver<()>,
+) -> Result<(), AppError> {
+    let filter = format!("fps=1/{},scale={}:-2", interval_s, TILE_WIDTH);
+    let mut child = TokioCommand::new(bundled_binary(app, "ffmpeg"))
+        .args([
+            "-v", "error",
+            "-skip_frame", "nokey",
+            "-i", source,
+            "-an", "-sn",
+            "-vf", &filter,
+            "-q:v", "5",
+            "-progress", "pipe:1",
+            "-y",
+        ])
+        .arg(dir.join("%05d.jpg"))
+        .stdout(std::process::Stdio::piped())
+        .kill_on_drop(true)
+        .spawn()
+        .map_err(|_| AppError::FfmpegMissing)?;
+
+    let stdout = child.stdout.take().ok_or("Failed to read ffmpeg progress")?;
+    let mut lines = BufReader::new(stdout).lines();
+    loop {
+        tokio::select! {
+            line = lines.next_line() => match line {
+                Ok(Some(line)) => {
+                    // out_time_us is reported as out_time_ms by older ffmpeg; both are µs
+                    let Some(us) = line
+                        .strip_prefix("out_time_us=")
+                        .or_else(|| line.strip_prefix("out_time_ms="))
+                        .and_then(|v| v.trim().parse::<f64>().ok())
+                    else {
+                        continue;
+                    };
+                    let position_s = us / 1_000_000.0;
+                    let fraction = duration_s.map(|d| (position_s / d).clamp(0.0, 1.0));
+                    let _ = app.emit(
+                        "thumbnails:progress",
+                        serde_json::json!({
+                            "item_id": item_id,
+                            "position_ms": (position_s * 1000.0) as i64,
+                            "fraction": fraction,
+                        }),
+                    );
+                }
+                _ => break,
+            },
+            _ = &mut cancel => {
+                let _ = child.kill().await;
+                return Err(AppError::cancelled("Thumbnail generation cancelled"));
+            }
+        }
+    }
+    match child.wait().await {
+        Ok(s) if s.success() => Ok(()),
+        Ok(s) => Err(format!("ffmpeg exited with {}", s).into()),
+        Err(e) => Err(format!("ffmpeg failed: {}", e).into()),
+    }
+}
+
+/// Extract a small JPEG every `interval_s` seconds of a library file for seek-bar
+/// previews. The source is resolved like `open_media` (FUSE mount, else rclone
+/// serve). Jobs run one at a time; items that already have thumbnails return
+/// straight away. Emits `thumbnails:progress` while running.
+#[tauri::command]
+pub async fn generate_seek_thumbnails(
+    app: AppHandle,
+    vlc: State<'_, VlcManager>,
+    config_path: String,
+    remote_root: String,
+    file_path: String,
+    interval_s: u32,
+) -> Result<SeekThumbnails, AppError> {
+    if interval_s == 0 {
+        return Err(AppError::invalid("Interval must be at least 1 second"));
+    }
+    let item_id = progress::item_id_for(&remote_root, &file_path);
+    let dir = thumbnails_root().join(&item_id);
+    if let Some(existing) = load_thumbnails(&dir) {
+        return Ok(existing);
+    }
+
+    let (cancel_tx, cancel_rx) = oneshot::channel();
+    {
+        let mut running = RUNNING.lock().unwrap();
+        let running = running.get_or_insert_with(HashMap::new);
+        if running.contains_key(&item_id) {
+            return Err("Thumbnails are already being generated for this item".into());
+        }
+        running.insert(item_id.clone(), cancel_tx);
+    }
+    let _job = THUMBNAIL_JOB.lock().await;
+    // A job for the same item may have finished while this one waited
+    let result = match load_thumbnails(&dir) {
+        Some(existing) => Ok(existing),
+        None => {
+            let result = build_seek_thumbnails(
+                &app,
+                &vlc,
+                &config_path,
+                &remote_root,
+                &file_path,
+                &item_id,
+                interval_s,
+                &dir,
+                cancel_rx,
+            )
+            .await;
+            player::release_shared_session(&vlc, THUMBNAIL_SESSION);
+            if result.is_err() {
+                let _ = std::fs::remove_dir_all(&dir);
+            }
+            result
+        }
+    };
+    if let Some(running) = RUNNING.lock().unwrap().as_mut() {
+        running.remove(&item_id);
+    }
+    result
+}
+
+#[allow(clippy::too_many_arguments)]
+async fn build_seek_thumbnails(
+    app: &AppHandle,
+    vlc: &VlcManager,
+    config_path: &str,
+    remote_root: &str,
+    file_path: &str,
+    item_id: &str,
+    interval_s: u32,
+    dir: &Path,
+    cancel: oneshot::Receiver<()>,
+) -> Result<SeekThumbnails, AppError> {
+    let source = player::resolve_shared_source(
+        app,
+        vlc,
+        config_path,
+        remote_root,
+        file_path,
+        THUMBNAIL_SESSION,
+        &player::ServeOptions::default(),
+    )
+    .await?;
+    // Only used for progress fractions
+    let duration_s = probe_duration_s(app, &source).await.ok();
+
+    let _ = std::fs::remove_dir_all(dir);
+    std::fs::create_dir_all(dir)
+        .map_err(|e| format!("Failed to create {}: {}", dir.display(), e))?;
+    run_thumbnail_ffmpeg(app, item_id, &source, interval_s, duration_s, dir, cancel).await?;
+
+    let count = std::fs::read_dir(dir)
+        .map(|entries| {
+            entries
+                .flatten()
+                .filter(|e| e.path().extension().is_some_and(|x| x == "jpg"))
+                .count() as u32
+        })
+        .unwrap_or(0);
+    if count == 0 {
+        return Err("ffmpeg produced no thumbnails".into());
+    }
+    let thumbnails = SeekThumbnails {
+        item_id: item_id.to_string(),
+        interval_s,
+        count,
+    };
+    std::fs::write(
+        dir.join(THUMBNAILS_MARKER),
+        serde_json::to_string(&thumbnails).unwrap(),
+    )
+    .map_err(|e| format!("Failed to write thumbnail index: {}", e))?;
+    evict_thumbnails(dir);
+    Ok(thumbnails)
+}
+
+/// Stop a running `generate_seek_thumbnails` for `item_id`.
+#[tauri::command]
+pub async fn cancel_seek_thumbnails(item_id: String) -> Result<(), AppError> {
+    cancel_running(&item_id);
+    Ok(())
+}
+
+/// Path of the cached thumbnail closest to `time_ms`, or None when the item has
+/// no thumbnails yet.
+#[tauri::command]
+pub async fn get_seek_thumbnail(item_id: String, time_ms: i64) -> Result<Option<String>, AppError> {
+    let dir = thumbnails_root().join(&item_id);
+    let Some(thumbnails) = load_thumbnails(&dir) else {
+        return Ok(None);
+    };
+    let interval_ms = thumbnails.interval_s as i64 * 1000;
+    let index = ((time_ms.max(0) + interval_ms / 2) / interval_ms) as u32;
+    let path = frame_path(&dir, index.min(thumbnails.count - 1));
+    // Mark the item as recently used for eviction
+    if let Ok(marker) = std::fs::File::options()
+        .write(true)
+        .open(dir.join(THUMBNAILS_MARKER))
+    {
+        let _ = marker.set_modified(SystemTime::now());
+    }
+    Ok(path.is_file().then(|| path.to_string_lossy().into_owned()))
+}
+
+// ── Poster frames ─────────────────────────────────────────────────────────────
+
+/// One library item for `generate_item_thumbnails`
+#[derive(Debug, Deserialize, Clone)]
+pub struct ThumbnailItem {
+    pub remote_root: String,
+    pub file_path: String,
+}
+
+/// `thumbnail:item` payload and `generate_item_thumbnails` result entry
+#[derive(Debug, Serialize, Clone)]
+pub struct ItemThumbnail {
+    pub item_id: String,
+    pub remote_root: String,
+    pub file_path: String,
+    /// Local JPEG path; None when generation failed
+    pub path: Option<String>,
+    pub error: Option<String>,
+}
+
+/// Inside the temp cache directory, so its sweeps and `clear_temp_cache` also
+/// find posters that dropped out of the index
+fn posters_root() -> PathBuf {
+    temp_cache::cache_dir().join("posters")
+}
+
+/// Temp cache key for an item's poster
+fn poster_key(item_id: &str) -> String {
+    format!("poster:{}", item_id)
+}
+
+async fn build_item_thumbnail(
+    app: &AppHandle,
+    vlc: &VlcManager,
+    config_path: &str,
+    remote_root: &str,
+    file_path: &str,
+    item_id: &str,
+) -> Result<PathBuf, String> {
+    if let Some(cached) = temp_cache::touch(app, &poster_key(item_id)) {
+        return Ok(cached);
+    }
+
+    let session_id = format!("poster:{}", item_id);
+    let source = player::resolve_shared_source(
+        app,
+        vlc,
+        config_path,
+        remote_root,
+        file_path,
+        &session_id,
+        &player::ServeOptions::default(),
+    )
+    .await?;
+    let result = run_poster_ffmpeg(app, &source, item_id).await;
+    player::release_shared_session(vlc, &session_id);
+    let path = result?;
+    temp_cache::insert(app, &poster_key(item_id), &path);
+    Ok(path)
+}
+
+/// Grab one frame at `POSTER_POSITION` of the duration (the start if unknown),
+/// cropped to fill a poster, into the item's cache file.
+async fn run_poster_ffmpeg(app: &AppHandle, source: &str, item_id: &str) -> Result<PathBuf, String> {
+    let offset_s = probe_duration_s(app, source)
+        .await
+        .map(|d| d * POSTER_POSITION)
+        .unwrap_or(0.0);
+    let filter = format!(
+        "scale={w}:{h}:force_original_aspect_ratio=increase,crop={w}:{h}",
+        w = POSTER_WIDTH,
+        h = POSTER_HEIGHT,
+    );
+    let root = posters_root();
+    std::fs::create_dir_all(&root)
+        .map_err(|e| format!("Failed to create {}: {}", root.display(), e))?;
+    let path = root.join(format!("{}.jpg", item_id));
+    // Written under a temporary name so a half-written file is never cached
+    let partial = root.join(format!("{}.partial.jpg", item_id));
+
+    let output = TokioCommand::new(bundled_binary(app, "ffmpeg"))
+        .args([
+            "-v",
+            "error",
+            "-ss",
+            &format!("{:.3}", offset_s),
+            "-i",
+            source,
+            "-an",
+            "-sn",
+            "-frames:v",
+            "1",
+            "-vf",
+            &filter,
+            "-q:v",
+            "3",
+            "-y",
+        ])
+        .arg(&partial)
+        .kill_on_drop(true)
+        .output()
+        .await
+        .map_err(|e| format!("Failed to run ffmpeg: {}", e))?;
+    if !output.status.success() || !partial.is_file() {
+        let _ = std::fs::remove_file(&partial);
+        return Err(format!(
+            "ffmpeg could not extract a frame: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+    std::fs::rename(&partial, &path)
+        .map_err(|e| format!("Failed to save {}: {}", path.display(), e))?;
+    Ok(path)
+}
+
+/// Poster-sized JPEG of a frame 10% into a library file, for items without
+/// artwork. Cached per item (keyed by `hash_remote_path`) in the temp cache, so
+/// later calls return the same file until it is evicted.
+#[tauri::command]
+pub async fn generate_item_thumbnail(
+    app: AppHandle,
+    vlc: State<'_, VlcManager>,
+    config_path: String,
+    remote_root: String,
+    file_path: String,
+) -> Result<String, AppError> {
+    let item_id = progress::item_id_for(&remote_root, &file_path);
+    let path = build_item_thumbnail(&app, &vlc, &config_path, &remote_root, &file_path, &item_id).await?;
+    Ok(path.to_string_lossy().into_owned())
+}
+
+/// `generate_item_thumbnail` for many items, a few at a time. Emits
+/// `thumbnail:item` (an `ItemThumbnail`) as each finishes so the grid fills in
+/// progressively; failures are reported per item and don't stop the batch.
+#[tauri::command]
+pub async fn generate_item_thumbnails(
+    app: AppHandle,
+    config_path: String,
+    items: Vec<ThumbnailItem>,
+) -> Result<Vec<ItemThumbnail>, AppError> {
+    let permits = std::sync::Arc::new(tokio::sync::Semaphore::new(POSTER_CONCURRENCY));
+    let mut tasks = Vec::with_capacity(items.len());
+    for item in items {
+        let app = app.clone();
+        let config_path = config_path.clone();
+        let permits = permits.clone();
+        tasks.push(tauri::async_runtime::spawn(async move {
+            let _permit = permits.acquire_owned().await;
+            let item_id = progress::item_id_for(&item.remote_root, &item.file_path);
+            let result = match app.try_state::<VlcManager>() {
+                Some(vlc) => {
+                    build_item_thumbnail(
+                        &app,
+                        &vlc,
+                        &config_path,
+                        &item.remote_root,
+                        &item.file_path,
+                        &item_id,
+                    )
+                    .await
+                }
+                None => Err("Player is not initialised".to_string()),
+            };
+            let thumbnail = ItemThumbnail {
+                item_id,
+                remote_root: item.remote_root,
+                file_path: item.file_path,
+                path: result.as_ref().ok().map(|p| p.to_string_lossy().into_owned()),
+                error: result.err(),
+            };
+            let _ = app.emit("thumbnail:item", &thumbnail);
+            thumbnail
+        }));
+    }
+
+    let mut results = Vec::with_capacity(tasks.len());
+    for task in tasks {
+        if let Ok(thumbnail) = task.await {
+            results.push(thumbnail);
+        }
+    }
+    Ok(results)
+}
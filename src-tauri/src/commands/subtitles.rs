@@ -0,0 +1,334 @@
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use tauri::AppHandle;
+use tokio::process::Command as TokioCommand;
+use tauri_plugin_store::StoreExt;
+use crate::commands::storyboard;
+use crate::error::AppError;
+
+const STORE_PATH: &str = "rcloneflix-keys.json";
+const STYLE_KEY: &str = "subtitle_style";
+const ENCODING_KEY: &str = "subtitle_encoding";
+
+/// Encodings offered for subtitles, as names libVLC's subsdec accepts.
+const SUPPORTED_ENCODINGS: &[&str] = &[
+    "UTF-8",
+    "UTF-16",
+    "Windows-1250", // Central European
+    "Windows-1251", // Cyrillic
+    "Windows-1252", // Western European
+    "Windows-1253", // Greek
+    "Windows-1254", // Turkish
+    "Windows-1255", // Hebrew
+    "Windows-1256", // Arabic
+    "Windows-1257", // Baltic
+    "Windows-1258", // Vietnamese
+    "ISO-8859-1",
+    "ISO-8859-2",
+    "ISO-8859-5",
+    "ISO-8859-7",
+    "ISO-8859-9",
+    "ISO-8859-15",
+    "KOI8-R",
+    "KOI8-U",
+    "CP949",       // Korean
+    "EUC-KR",
+    "Shift_JIS",
+    "EUC-JP",
+    "GB18030",     // Simplified Chinese
+    "Big5",        // Traditional Chinese
+    "TIS-620",     // Thai
+];
+
+/// Bitmap subtitle codecs; these can't become text without OCR
+const IMAGE_SUBTITLE_CODECS: &[&str] = &[
+    "hdmv_pgs_subtitle",
+    "dvd_subtitle",
+    "dvb_subtitle",
+    "xsub",
+];
+
+/// Whether `codec` (an ffprobe codec name) is a bitmap subtitle format
+pub(crate) fn is_image_codec(codec: &str) -> bool {
+    IMAGE_SUBTITLE_CODECS.contains(&codec)
+}
+
+/// Output extensions that hold text subtitles, with the codec ffmpeg writes for them
+const TEXT_SUBTITLE_FORMATS: &[(&str, &str)] = &[
+    ("srt", "subrip"),
+    ("ass", "ass"),
+    ("ssa", "ass"),
+    ("vtt", "webvtt"),
+];
+
+/// Subtitle rendering overrides. Unset fields keep libVLC's defaults.
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct SubtitleStyle {
+    /// Text size relative to the default, e.g. 2.0 for twice as large (0.1-5.0)
+    pub font_scale: Option<f32>,
+    /// Text color as "#RRGGBB"
+    pub text_color: Option<String>,
+    /// Opacity of the box behind the text, 0 (none) to 255 (solid)
+    pub background_opacity: Option<u8>,
+    /// Outline thickness: "none", "thin", "normal" or "thick"
+    pub outline: Option<String>,
+}
+
+/// Which of the options passed to `player_set_subtitle_style` take effect when,
+/// by field name.
+#[derive(Debug, Serialize, Clone, Default)]
+pub struct SubtitleStyleApplied {
+    /// Per-media options, used from the next opened media on
+    pub next_media: Vec<String>,
+    /// libVLC instance options, used after the app is restarted
+    pub requires_restart: Vec<String>,
+}
+
+fn parse_color(color: &str) -> Option<u32> {
+    let hex = color.trim().trim_start_matches('#');
+    if hex.len() != 6 {
+        return None;
+    }
+    u32::from_str_radix(hex, 16).ok()
+}
+
+fn outline_thickness(outline: &str) -> Option<u32> {
+    // freetype-outline-thickness only accepts these values
+    match outline.to_lowercase().as_str() {
+        "none" => Some(0),
+        "thin" => Some(2),
+        "normal" => Some(4),
+        "thick" => Some(6),
+        _ => None,
+    }
+}
+
+pub(crate) fn load_style(app: &AppHandle) -> SubtitleStyle {
+    app.store(STORE_PATH)
+        .ok()
+        .and_then(|s| s.get(STYLE_KEY))
+        .and_then(|v| serde_json::from_value(v).ok())
+        .unwrap_or_default()
+}
+
+/// Freetype renderer arguments for `vlc::Instance::with_args`. These are module
+/// settings, so they can only be set when the instance is created.
+pub(crate) fn instance_args(app: &AppHandle) -> Vec<String> {
+    let style = load_style(app);
+    let mut args = Vec::new();
+    if let Some(color) = style.text_color.as_deref().and_then(parse_color) {
+        args.push(format!("--freetype-color={}", color));
+    }
+    if let Some(opacity) = style.background_opacity {
+        args.push(format!("--freetype-background-opacity={}", opacity));
+    }
+    if let Some(thickness) = style.outline.as_deref().and_then(outline_thickness) {
+        args.push(format!("--freetype-outline-thickness={}", thickness));
+    }
+    args
+}
+
+fn load_encoding(app: &AppHandle) -> Option<String> {
+    app.store(STORE_PATH)
+        .ok()
+        .and_then(|s| s.get(ENCODING_KEY))
+        .and_then(|v| v.as_str().map(|s| s.to_string()))
+}
+
+/// Store the subtitle encoding; None (or empty) goes back to auto-detection.
+pub(crate) fn save_encoding(app: &AppHandle, encoding: Option<String>) -> Result<(), String> {
+    let encoding = encoding.map(|e| e.trim().to_string()).filter(|e| !e.is_empty());
+    if let Some(e) = &encoding {
+        if !SUPPORTED_ENCODINGS.iter().any(|s| s.eq_ignore_ascii_case(e)) {
+            return Err(format!("Unsupported subtitle encoding: {}", e));
+        }
+    }
+    let store = app
+        .store(STORE_PATH)
+        .map_err(|e| format!("Failed to open store: {}", e))?;
+    match encoding {
+        Some(e) => store.set(ENCODING_KEY, serde_json::json!(e)),
+        None => {
+            store.delete(ENCODING_KEY);
+        }
+    }
+    store
+        .save()
+        .map_err(|e| format!("Failed to save store: {}", e))?;
+    Ok(())
+}
+
+/// Per-media subtitle options (":name=value") attached when media is opened.
+pub(crate) fn media_options(app: &AppHandle) -> Vec<String> {
+    let style = load_style(app);
+    let mut options = Vec::new();
+    if let Some(encoding) = load_encoding(app) {
+        options.push(format!(":subsdec-encoding={}", encoding));
+    }
+    if let Some(scale) = style.font_scale {
+        // sub-text-scale is a percentage in 10..=500
+        options.push(format!(":sub-text-scale={}", (scale * 100.0).round().clamp(10.0, 500.0)));
+    }
+    options
+}
+
+/// Subtitle encodings the UI can offer; an unset encoding means auto-detect.
+#[tauri::command]
+pub async fn player_get_supported_encodings() -> Result<Vec<String>, AppError> {
+    Ok(SUPPORTED_ENCODINGS.iter().map(|e| e.to_string()).collect())
+}
+
+#[tauri::command]
+pub async fn get_subtitle_style(app: AppHandle) -> Result<SubtitleStyle, AppError> {
+    Ok(load_style(&app))
+}
+
+/// Persist subtitle style overrides. The font scale applies from the next opened
+/// media; color, background and outline are renderer settings that need a restart.
+#[tauri::command]
+pub async fn player_set_subtitle_style(
+    app: AppHandle,
+    options: SubtitleStyle,
+) -> Result<SubtitleStyleApplied, AppError> {
+    if let Some(scale) = options.font_scale {
+        if !(0.1..=5.0).contains(&scale) {
+            return Err(AppError::invalid(format!("Font scale must be between 0.1 and 5.0, got {}", scale)));
+        }
+    }
+    if let Some(color) = options.text_color.as_deref() {
+        if parse_color(color).is_none() {
+            return Err(AppError::invalid(format!("Invalid text color '{}', expected #RRGGBB", color)));
+        }
+    }
+    if let Some(outline) = options.outline.as_deref() {
+        if outline_thickness(outline).is_none() {
+            return Err(AppError::invalid(format!(
+                "Invalid outline '{}', expected none, thin, normal or thick",
+                outline
+            )));
+        }
+    }
+
+    let store = app
+        .store(STORE_PATH)
+        .map_err(|e| format!("Failed to open store: {}", e))?;
+    store.set(STYLE_KEY, serde_json::to_value(&options).unwrap());
+    store
+        .save()
+        .map_err(|e| format!("Failed to save store: {}", e))?;
+
+    let mut applied = SubtitleStyleApplied::default();
+    if options.font_scale.is_some() {
+        applied.next_media.push("font_scale".to_string());
+    }
+    if options.text_color.is_some() {
+        applied.requires_restart.push("text_color".to_string());
+    }
+    if options.background_opacity.is_some() {
+        applied.requires_restart.push("background_opacity".to_string());
+    }
+    if options.outline.is_some() {
+        applied.requires_restart.push("outline".to_string());
+    }
+    Ok(applied)
+}
+
+async fn ffmpeg_available(app: &AppHandle) -> bool {
+    TokioCommand::new(storyboard::bundled_binary(app, "ffmpeg"))
+        .arg("-version")
+        .output()
+        .await
+        .is_ok_and(|o| o.status.success())
+}
+
+/// (codec_type, codec_name) of stream `index`, via ffprobe
+async fn probe_stream(app: &AppHandle, file_url: &str, index: u32) -> Result<(String, String), String> {
+    let output = TokioCommand::new(storyboard::bundled_binary(app, "ffprobe"))
+        .args([
+            "-v",
+            "error",
+            "-select_streams",
+            &index.to_string(),
+            "-show_entries",
+            "stream=codec_type,codec_name",
+            "-of",
+            "json",
+            file_url,
+        ])
+        .output()
+        .await
+        .map_err(|e| format!("Failed to run ffprobe: {}", e))?;
+    let info: serde_json::Value = serde_json::from_slice(&output.stdout)
+        .map_err(|e| format!("Failed to parse ffprobe output: {}", e))?;
+    let stream = info["streams"]
+        .get(0)
+        .ok_or_else(|| format!("Stream {} not found", index))?;
+    Ok((
+        stream["codec_type"].as_str().unwrap_or_default().to_string(),
+        stream["codec_name"].as_str().unwrap_or_default().to_string(),
+    ))
+}
+
+/// Extract subtitle stream `stream_index` (absolute ffprobe index, as in
+/// `get_media_info`) of `file_url` to `out_path` and return the path, for use
+/// with `player_add_subtitle`. Text subtitles are copied when `out_path` has
+/// their own format and converted otherwise; bitmap subtitles (PGS, VobSub) can
+/// only be copied to a bitmap-capable container such as .mks or .sup.
+#[tauri::command]
+pub async fn extract_subtitle(
+    app: AppHandle,
+    file_url: String,
+    stream_index: u32,
+    out_path: String,
+) -> Result<String, AppError> {
+    if !ffmpeg_available(&app).await {
+        return Err(AppError::FfmpegMissing);
+    }
+    let (codec_type, codec) = probe_stream(&app, &file_url, stream_index).await?;
+    if codec_type != "subtitle" {
+        return Err(AppError::invalid(format!("Stream {} is not a subtitle stream ({})", stream_index, codec_type)));
+    }
+
+    let ext = Path::new(&out_path)
+        .extension()
+        .and_then(|e| e.to_str())
+        .map(|e| e.to_lowercase())
+        .unwrap_or_default();
+    let text_codec = TEXT_SUBTITLE_FORMATS
+        .iter()
+        .find(|(e, _)| *e == ext)
+        .map(|(_, c)| *c);
+    let image = is_image_codec(&codec);
+    if image && text_codec.is_some() {
+        return Err(AppError::invalid(format!(
+            "Stream {} is a bitmap subtitle ({}) and can't be converted to .{} without OCR; \
+             extract it to .mks or .sup instead",
+            stream_index, codec, ext
+        )));
+    }
+    // Same format (or a bitmap container): copy; otherwise let ffmpeg convert
+    let copy = image || text_codec.is_none() || text_codec == Some(codec.as_str());
+
+    if let Some(dir) = Path::new(&out_path).parent().filter(|d| !d.as_os_str().is_empty()) {
+        std::fs::create_dir_all(dir)
+            .map_err(|e| format!("Failed to create {}: {}", dir.display(), e))?;
+    }
+    let mut command = TokioCommand::new(storyboard::bundled_binary(&app, "ffmpeg"));
+    command.args(["-v", "error", "-y", "-i", &file_url, "-map", &format!("0:{}", stream_index)]);
+    if copy {
+        command.args(["-c", "copy"]);
+    }
+    let output = command
+        .arg(&out_path)
+        .output()
+        .await
+        .map_err(|e| format!("Failed to run ffmpeg: {}", e))?;
+    if !output.status.success() {
+        let _ = std::fs::remove_file(&out_path);
+        return Err(format!(
+            "ffmpeg failed to extract the subtitle: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        ).into());
+    }
+    Ok(out_path)
+}
@@ -0,0 +1,337 @@
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+use tauri::AppHandle;
+use tauri_plugin_store::StoreExt;
+
+use crate::commands::store;
+use crate::error::AppError;
+
+/// Index of cached temp files, keyed by remote path
+const CACHE_STORE_PATH: &str = "rcloneflix-temp-cache.json";
+const ENTRIES_KEY: &str = "entries";
+/// Setting holding the cache size limit in bytes
+const LIMIT_SETTING: &str = "temp_cache.limit_bytes";
+const DEFAULT_LIMIT_BYTES: u64 = 2 * 1024 * 1024 * 1024;
+/// Setting: hours an unused file may sit in the cache before the startup sweep
+/// deletes it; 0 keeps files until evicted for space
+const MAX_AGE_SETTING: &str = "temp_cache.max_age_hours";
+const DEFAULT_MAX_AGE_HOURS: u64 = 24;
+/// Files written to this recently may belong to a running download
+const IN_PROGRESS_SECS: u64 = 60;
+
+/// Serializes read-modify-write cycles on the index
+static INDEX_LOCK: Mutex<()> = Mutex::new(());
+/// Files in use by an open reader or player, by session id. Never evicted.
+static OPEN: Mutex<Option<HashMap<String, HashSet<String>>>> = Mutex::new(None);
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+struct CacheEntry {
+    path: String,
+    size: u64,
+    /// Unix seconds
+    last_access: u64,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct TempCacheStats {
+    pub total_bytes: u64,
+    pub limit_bytes: u64,
+    pub file_count: usize,
+    /// Files currently open and exempt from eviction
+    pub open_count: usize,
+}
+
+/// Directory downloaded books and tracks are cached under. Inside $TEMP so the
+/// asset protocol can serve them.
+pub(crate) fn cache_dir() -> PathBuf {
+    std::env::temp_dir().join("rcloneflix-books")
+}
+
+fn now_secs() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+pub(crate) fn limit_bytes(app: &AppHandle) -> u64 {
+    store::get_setting(app, LIMIT_SETTING).unwrap_or(DEFAULT_LIMIT_BYTES)
+}
+
+fn open_paths() -> HashSet<String> {
+    OPEN.lock()
+        .unwrap()
+        .as_ref()
+        .map(|sessions| sessions.values().flatten().cloned().collect())
+        .unwrap_or_default()
+}
+
+/// Load the index, dropping entries whose file was removed out from under it
+/// and refreshing sizes from disk.
+fn load(app: &AppHandle) -> HashMap<String, CacheEntry> {
+    let mut entries: HashMap<String, CacheEntry> = app
+        .store(CACHE_STORE_PATH)
+        .ok()
+        .and_then(|s| s.get(ENTRIES_KEY))
+        .and_then(|v| serde_json::from_value(v).ok())
+        .unwrap_or_default();
+    entries.retain(|_, entry| match std::fs::metadata(&entry.path) {
+        Ok(meta) if meta.is_file() => {
+            entry.size = meta.len();
+            true
+        }
+        _ => false,
+    });
+    entries
+}
+
+fn save(app: &AppHandle, entries: &HashMap<String, CacheEntry>) -> Result<(), String> {
+    let store = app
+        .store(CACHE_STORE_PATH)
+        .map_err(|e| format!("Failed to open temp cache index: {}", e))?;
+    store.set(ENTRIES_KEY, serde_json::json!(entries));
+    store
+        .save()
+        .map_err(|e| format!("Failed to save temp cache index: {}", e))
+}
+
+fn remove_cached_file(path: &Path) {
+    let _ = std::fs::remove_file(path);
+    // Drop the per-file directory once it's empty
+    if let Some(parent) = path.parent().filter(|p| p.starts_with(cache_dir())) {
+        let _ = std::fs::remove_dir(parent);
+    }
+}
+
+/// Delete least-recently-used files that aren't open until the cache fits `limit`.
+fn evict(entries: &mut HashMap<String, CacheEntry>, limit: u64) {
+    let mut total: u64 = entries.values().map(|e| e.size).sum();
+    if total <= limit {
+        return;
+    }
+    let open = open_paths();
+    let mut candidates: Vec<(String, u64, u64)> = entries
+        .iter()
+        .filter(|(_, e)| !open.contains(&e.path))
+        .map(|(key, e)| (key.clone(), e.last_access, e.size))
+        .collect();
+    candidates.sort_by_key(|(_, last_access, _)| *last_access);
+    for (key, _, size) in candidates {
+        if total <= limit {
+            break;
+        }
+        if let Some(entry) = entries.remove(&key) {
+            eprintln!("Evicting cached file {}", entry.path);
+            remove_cached_file(Path::new(&entry.path));
+            total = total.saturating_sub(size);
+        }
+    }
+}
+
+fn pin(session_id: &str, path: &str) {
+    OPEN.lock()
+        .unwrap()
+        .get_or_insert_with(HashMap::new)
+        .entry(session_id.to_string())
+        .or_default()
+        .insert(path.to_string());
+}
+
+/// Hold `path` open for `session_id` so it's neither evicted nor cleared, like
+/// the partial of a running download that isn't indexed yet.
+pub(crate) fn hold(session_id: &str, path: &Path) {
+    pin(session_id, &path.to_string_lossy());
+}
+
+/// Cached copy of `remote_path` if it's still on disk with `expected_size`
+/// (any size when None). Marks it used and open for `session_id`.
+pub(crate) fn lookup(app: &AppHandle, remote_path: &str, expected_size: Option<u64>, session_id: &str) -> Option<PathBuf> {
+    let _guard = INDEX_LOCK.lock().unwrap();
+    let mut entries = load(app);
+    let entry = entries
+        .get_mut(remote_path)
+        .filter(|e| expected_size.is_none_or(|size| e.size == size))?;
+    entry.last_access = now_secs();
+    let path = entry.path.clone();
+    pin(session_id, &path);
+    let _ = save(app, &entries);
+    Some(PathBuf::from(path))
+}
+
+/// Cached file under `key` if it's still on disk, marked as used. For entries
+/// that aren't tied to a remote file's size, like generated posters. An entry
+/// whose file is gone is dropped so the caller regenerates it.
+pub(crate) fn touch(app: &AppHandle, key: &str) -> Option<PathBuf> {
+    let _guard = INDEX_LOCK.lock().unwrap();
+    let mut entries = load(app);
+    if !entries.get(key).is_some_and(|e| Path::new(&e.path).is_file()) {
+        if entries.remove(key).is_some() {
+            let _ = save(app, &entries);
+        }
+        return None;
+    }
+    let entry = entries.get_mut(key)?;
+    entry.last_access = now_secs();
+    let path = entry.path.clone();
+    let _ = save(app, &entries);
+    Some(PathBuf::from(path))
+}
+
+/// Add a downloaded file to the cache, open for `session_id`, and evict older
+/// files if the cache is now over its limit.
+pub(crate) fn record(app: &AppHandle, remote_path: &str, path: &Path, session_id: &str) {
+    pin(session_id, &path.to_string_lossy());
+    insert(app, remote_path, path);
+}
+
+/// Add a file to the cache under `key` without holding it open, and evict
+/// older files if the cache is now over its limit.
+pub(crate) fn insert(app: &AppHandle, key: &str, path: &Path) {
+    let Ok(meta) = std::fs::metadata(path) else {
+        return;
+    };
+    let path = path.to_string_lossy().into_owned();
+
+    let _guard = INDEX_LOCK.lock().unwrap();
+    let mut entries = load(app);
+    entries.insert(
+        key.to_string(),
+        CacheEntry {
+            path,
+            size: meta.len(),
+            last_access: now_secs(),
+        },
+    );
+    evict(&mut entries, limit_bytes(app));
+    if let Err(e) = save(app, &entries) {
+        eprintln!("{}", e);
+    }
+}
+
+/// Every file under the cache directory with its last modification (Unix
+/// seconds), including `.partial` files and files missing from the index that
+/// a crash left behind.
+fn files_on_disk() -> Vec<(PathBuf, u64)> {
+    let mut files = Vec::new();
+    let mut dirs = vec![cache_dir()];
+    while let Some(dir) = dirs.pop() {
+        let Ok(entries) = std::fs::read_dir(&dir) else {
+            continue;
+        };
+        for entry in entries.flatten() {
+            let Ok(meta) = entry.metadata() else {
+                continue;
+            };
+            if meta.is_dir() {
+                dirs.push(entry.path());
+            } else {
+                let modified = meta
+                    .modified()
+                    .ok()
+                    .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+                    .map(|d| d.as_secs())
+                    .unwrap_or(0);
+                files.push((entry.path(), modified));
+            }
+        }
+    }
+    files
+}
+
+/// Delete cached files not used or written for `min_age_secs` and not open,
+/// and drop them from the index. Returns the number of files deleted.
+fn remove_older_than(app: &AppHandle, min_age_secs: u64) -> usize {
+    let _guard = INDEX_LOCK.lock().unwrap();
+    let mut entries = load(app);
+    let last_access: HashMap<String, u64> = entries
+        .values()
+        .map(|e| (e.path.clone(), e.last_access))
+        .collect();
+    let open = open_paths();
+    let now = now_secs();
+
+    let mut removed = 0;
+    for (path, modified) in files_on_disk() {
+        let key = path.to_string_lossy().into_owned();
+        let last_used = last_access.get(&key).copied().unwrap_or(0).max(modified);
+        if open.contains(&key) || now.saturating_sub(last_used) < min_age_secs {
+            continue;
+        }
+        remove_cached_file(&path);
+        removed += 1;
+    }
+    entries.retain(|_, e| Path::new(&e.path).is_file());
+    if let Err(e) = save(app, &entries) {
+        eprintln!("{}", e);
+    }
+    removed
+}
+
+/// Startup sweep: delete files unused for longer than `MAX_AGE_SETTING`, so
+/// sessions that never released their files (a crash, a killed process)
+/// don't leave them in $TEMP for good.
+pub(crate) fn sweep_expired(app: &AppHandle) {
+    let hours = store::get_setting(app, MAX_AGE_SETTING).unwrap_or(DEFAULT_MAX_AGE_HOURS);
+    if hours == 0 {
+        return;
+    }
+    let removed = remove_older_than(app, hours * 3600);
+    if removed > 0 {
+        eprintln!("Removed {} expired file(s) from the temp cache", removed);
+    }
+}
+
+/// Mark every file opened by `session_id` as no longer in use. The files stay
+/// cached until evicted.
+pub(crate) fn release_session(session_id: &str) {
+    if let Some(sessions) = OPEN.lock().unwrap().as_mut() {
+        sessions.remove(session_id);
+    }
+}
+
+#[tauri::command]
+pub fn get_temp_cache_stats(app: AppHandle) -> Result<TempCacheStats, AppError> {
+    let _guard = INDEX_LOCK.lock().unwrap();
+    let entries = load(&app);
+    let open = open_paths();
+    Ok(TempCacheStats {
+        total_bytes: entries.values().map(|e| e.size).sum(),
+        limit_bytes: limit_bytes(&app),
+        file_count: entries.len(),
+        open_count: entries.values().filter(|e| open.contains(&e.path)).count(),
+    })
+}
+
+/// Set the maximum size of the book/media temp cache and evict down to it.
+#[tauri::command]
+pub fn set_temp_cache_limit(app: AppHandle, bytes: u64) -> Result<(), AppError> {
+    store::set_setting(&app, LIMIT_SETTING, serde_json::json!(bytes))?;
+    let _guard = INDEX_LOCK.lock().unwrap();
+    let mut entries = load(&app);
+    evict(&mut entries, bytes);
+    Ok(save(&app, &entries)?)
+}
+
+/// Set how many hours unused files stay cached between launches; 0 keeps
+/// them until evicted for space.
+#[tauri::command]
+pub fn set_temp_cache_max_age(app: AppHandle, hours: u64) -> Result<(), AppError> {
+    Ok(store::set_setting(&app, MAX_AGE_SETTING, serde_json::json!(hours))?)
+}
+
+/// Clear the temp cache: delete every cached file (books, posters, prefetched
+/// media) that isn't open or still being downloaded. Returns the number of
+/// files deleted.
+#[tauri::command]
+pub fn clear_temp_cache(app: AppHandle) -> Result<usize, AppError> {
+    Ok(remove_older_than(&app, IN_PROGRESS_SECS))
+}
+
+/// `clear_temp_cache` under the name it was first requested as.
+#[tauri::command]
+pub fn cleanup_all_book_temp(app: AppHandle) -> Result<usize, AppError> {
+    clear_temp_cache(app)
+}
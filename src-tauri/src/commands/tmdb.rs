@@ -0,0 +1,236 @@
+use serde::Serialize;
+use std::time::Duration;
+use tauri::AppHandle;
+
+use crate::commands::store;
+use crate::error::AppError;
+
+const API_BASE: &str = "https://api.themoviedb.org/3";
+/// Retries after a 429 before giving up
+const MAX_RATE_LIMIT_RETRIES: u32 = 3;
+/// TMDB accepts at most this many `season/N` entries in one `append_to_response`
+const SEASONS_PER_REQUEST: usize = 20;
+
+/// One hit from `tmdb_search`. Image paths are relative to TMDB's image CDN.
+#[derive(Debug, Serialize, Clone)]
+pub struct TmdbSearchResult {
+    pub id: u64,
+    pub title: String,
+    pub overview: String,
+    pub poster_path: Option<String>,
+    pub backdrop_path: Option<String>,
+    /// Average vote, 0-10
+    pub rating: f64,
+    /// Release (movie) or first air (TV) year
+    pub year: Option<u32>,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct TmdbEpisode {
+    pub episode_number: u32,
+    pub name: String,
+    pub overview: String,
+    pub air_date: Option<String>,
+    pub still_path: Option<String>,
+    pub runtime_minutes: Option<u32>,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct TmdbSeason {
+    pub season_number: u32,
+    pub name: String,
+    pub episodes: Vec<TmdbEpisode>,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct TmdbDetails {
+    pub id: u64,
+    pub title: String,
+    pub overview: String,
+    pub poster_path: Option<String>,
+    pub backdrop_path: Option<String>,
+    pub rating: f64,
+    pub year: Option<u32>,
+    pub genres: Vec<String>,
+    /// Movie runtime; None for TV
+    pub runtime_minutes: Option<u32>,
+    /// Seasons with their episodes; empty for movies
+    pub seasons: Vec<TmdbSeason>,
+}
+
+fn string(value: &serde_json::Value, key: &str) -> String {
+    value[key].as_str().unwrap_or_default().to_string()
+}
+
+fn optional(value: &serde_json::Value, key: &str) -> Option<String> {
+    value[key].as_str().filter(|s| !s.is_empty()).map(|s| s.to_string())
+}
+
+/// Year from a "YYYY-MM-DD" date field
+fn year(value: &serde_json::Value, key: &str) -> Option<u32> {
+    value[key].as_str()?.get(..4)?.parse().ok()
+}
+
+fn search_result(value: &serde_json::Value, is_tv: bool) -> TmdbSearchResult {
+    let (title_key, date_key) = if is_tv { ("name", "first_air_date") } else { ("title", "release_date") };
+    TmdbSearchResult {
+        id: value["id"].as_u64().unwrap_or_default(),
+        title: string(value, title_key),
+        overview: string(value, "overview"),
+        poster_path: optional(value, "poster_path"),
+        backdrop_path: optional(value, "backdrop_path"),
+        rating: value["vote_average"].as_f64().unwrap_or_default(),
+        year: year(value, date_key),
+    }
+}
+
+fn season(value: &serde_json::Value) -> TmdbSeason {
+    let episodes = value["episodes"]
+        .as_array()
+        .map(|eps| {
+            eps.iter()
+                .map(|ep| TmdbEpisode {
+                    episode_number: ep["episode_number"].as_u64().unwrap_or_default() as u32,
+                    name: string(ep, "name"),
+                    overview: string(ep, "overview"),
+                    air_date: optional(ep, "air_date"),
+                    still_path: optional(ep, "still_path"),
+                    runtime_minutes: ep["runtime"].as_u64().map(|m| m as u32),
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+    TmdbSeason {
+        season_number: value["season_number"].as_u64().unwrap_or_default() as u32,
+        name: string(value, "name"),
+        episodes,
+    }
+}
+
+/// GET a TMDB v3 endpoint with the stored key. Accepts both v3 API keys (sent as
+/// `api_key`) and v4 read access tokens (sent as a bearer token). Waits out
+/// 429 responses using `Retry-After`.
+async fn get(app: &AppHandle, path: &str, params: &[(&str, String)]) -> Result<serde_json::Value, String> {
+    let key = store::api_key(app, "tmdb_key")?.ok_or_else(|| "No TMDB API key configured".to_string())?;
+    // v4 tokens are JWTs; v3 keys are 32 hex characters
+    let bearer = key.starts_with("eyJ");
+
+    let client = tauri_plugin_http::reqwest::Client::new();
+    let mut attempt = 0;
+    loop {
+        let mut request = client
+            .get(format!("{}{}", API_BASE, path))
+            .query(params)
+            .timeout(Duration::from_secs(30));
+        request = if bearer {
+            request.bearer_auth(&key)
+        } else {
+            request.query(&[("api_key", key.as_str())])
+        };
+        let resp = request
+            .send()
+            .await
+            .map_err(|e| format!("TMDB request failed: {}", e))?;
+        let status = resp.status().as_u16();
+
+        if status == 429 && attempt < MAX_RATE_LIMIT_RETRIES {
+            attempt += 1;
+            let wait = resp
+                .headers()
+                .get("retry-after")
+                .and_then(|v| v.to_str().ok())
+                .and_then(|v| v.trim().parse::<u64>().ok())
+                .unwrap_or(1)
+                .min(10);
+            tokio::time::sleep(Duration::from_secs(wait)).await;
+            continue;
+        }
+
+        let text = resp
+            .text()
+            .await
+            .map_err(|e| format!("Failed to read TMDB response: {}", e))?;
+        let body: serde_json::Value = serde_json::from_str(&text).unwrap_or_default();
+        if !(200..300).contains(&status) {
+            let message = body["status_message"].as_str().unwrap_or(text.as_str());
+            return Err(format!("TMDB request failed ({}): {}", status, message));
+        }
+        return Ok(body);
+    }
+}
+
+/// Search TMDB for a movie, or a TV show when `is_tv` is set.
+#[tauri::command]
+pub async fn tmdb_search(
+    app: AppHandle,
+    title: String,
+    year: Option<u32>,
+    is_tv: bool,
+) -> Result<Vec<TmdbSearchResult>, AppError> {
+    let title = title.trim();
+    if title.is_empty() {
+        return Err(AppError::invalid("Search title must not be empty"));
+    }
+    let mut params = vec![("query", title.to_string())];
+    if let Some(year) = year {
+        let key = if is_tv { "first_air_date_year" } else { "primary_release_year" };
+        params.push((key, year.to_string()));
+    }
+    let path = if is_tv { "/search/tv" } else { "/search/movie" };
+    let body = get(&app, path, &params).await?;
+    Ok(body["results"]
+        .as_array()
+        .map(|results| results.iter().map(|r| search_result(r, is_tv)).collect())
+        .unwrap_or_default())
+}
+
+/// Full details for a TMDB movie or TV show, including genres and, for TV, every
+/// season's episode list.
+#[tauri::command]
+pub async fn tmdb_details(app: AppHandle, id: u64, is_tv: bool) -> Result<TmdbDetails, AppError> {
+    let path = format!("/{}/{}", if is_tv { "tv" } else { "movie" }, id);
+    let body = get(&app, &path, &[]).await?;
+
+    let base = search_result(&body, is_tv);
+    let genres = body["genres"]
+        .as_array()
+        .map(|g| g.iter().filter_map(|g| g["name"].as_str().map(|s| s.to_string())).collect())
+        .unwrap_or_default();
+
+    let mut seasons = Vec::new();
+    if is_tv {
+        let numbers: Vec<u64> = body["seasons"]
+            .as_array()
+            .map(|s| s.iter().filter_map(|s| s["season_number"].as_u64()).collect())
+            .unwrap_or_default();
+        // Episode lists only come with the season endpoints, so fetch them as
+        // appended responses in as few requests as TMDB allows
+        for chunk in numbers.chunks(SEASONS_PER_REQUEST) {
+            let append = chunk
+                .iter()
+                .map(|n| format!("season/{}", n))
+                .collect::<Vec<_>>()
+                .join(",");
+            let with_seasons = get(&app, &path, &[("append_to_response", append)]).await?;
+            for n in chunk {
+                let key = format!("season/{}", n);
+                if with_seasons[key.as_str()].is_object() {
+                    seasons.push(season(&with_seasons[key.as_str()]));
+                }
+            }
+        }
+    }
+
+    Ok(TmdbDetails {
+        id: base.id,
+        title: base.title,
+        overview: base.overview,
+        poster_path: base.poster_path,
+        backdrop_path: base.backdrop_path,
+        rating: base.rating,
+        year: base.year,
+        genres,
+        runtime_minutes: body["runtime"].as_u64().map(|m| m as u32),
+        seasons,
+    })
+}
@@ -0,0 +1,311 @@
+use serde::{Deserialize, Serialize};
+use tauri::AppHandle;
+use tauri_plugin_store::StoreExt;
+use crate::error::AppError;
+
+const STORE_PATH: &str = "rcloneflix-keys.json";
+const PREFERENCES_KEY: &str = "track_preferences";
+const MEMORY_KEY: &str = "track_memory";
+
+/// Preferred track languages as ISO 639 codes, most preferred first.
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct TrackPreferences {
+    pub preferred_audio_langs: Vec<String>,
+    pub preferred_subtitle_langs: Vec<String>,
+}
+
+/// Track languages last chosen for a series, applied to its other episodes
+/// before the global preferences.
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct TrackMemory {
+    pub audio_lang: Option<String>,
+    pub subtitle_lang: Option<String>,
+    /// The user turned subtitles off for this series
+    #[serde(default)]
+    pub subtitles_off: bool,
+}
+
+/// The parts of a VLC track that language selection looks at.
+pub(crate) struct TrackInfo {
+    pub id: i32,
+    pub language: Option<String>,
+    pub description: Option<String>,
+}
+
+impl TrackInfo {
+    fn is_forced(&self) -> bool {
+        self.description
+            .as_deref()
+            .is_some_and(|d| d.to_lowercase().contains("forced"))
+    }
+}
+
+/// ISO 639-1 code → ISO 639-2 (B and T) codes and English names.
+const LANGUAGE_ALIASES: &[(&str, &[&str])] = &[
+    ("en", &["eng", "english"]),
+    ("ja", &["jpn", "japanese"]),
+    ("fr", &["fre", "fra", "french"]),
+    ("de", &["ger", "deu", "german"]),
+    ("es", &["spa", "spanish"]),
+    ("it", &["ita", "italian"]),
+    ("pt", &["por", "portuguese"]),
+    ("ru", &["rus", "russian"]),
+    ("zh", &["chi", "zho", "chinese"]),
+    ("ko", &["kor", "korean"]),
+    ("nl", &["dut", "nld", "dutch"]),
+    ("sv", &["swe", "swedish"]),
+    ("no", &["nor", "nob", "nno", "norwegian"]),
+    ("da", &["dan", "danish"]),
+    ("fi", &["fin", "finnish"]),
+    ("pl", &["pol", "polish"]),
+    ("cs", &["cze", "ces", "czech"]),
+    ("hu", &["hun", "hungarian"]),
+    ("el", &["gre", "ell", "greek"]),
+    ("tr", &["tur", "turkish"]),
+    ("ar", &["ara", "arabic"]),
+    ("he", &["heb", "hebrew"]),
+    ("hi", &["hin", "hindi"]),
+    ("th", &["tha", "thai"]),
+    ("vi", &["vie", "vietnamese"]),
+    ("id", &["ind", "indonesian"]),
+    ("uk", &["ukr", "ukrainian"]),
+];
+
+/// Normalise a messy language tag ("eng", "en", "English", "en-US", "pt_BR")
+/// to its ISO 639-1 code. Unknown tags come back lowercased; empty ones as None.
+pub(crate) fn normalize_lang(tag: &str) -> Option<String> {
+    let lower = tag.trim().to_lowercase();
+    let primary = lower.split(['-', '_']).next().unwrap_or("").trim();
+    if primary.is_empty() {
+        return None;
+    }
+    for (code, aliases) in LANGUAGE_ALIASES {
+        if primary == *code || aliases.contains(&primary) {
+            return Some(code.to_string());
+        }
+    }
+    Some(primary.to_string())
+}
+
+/// First track matching the preference list, trying each preferred language in
+/// order. Forced subtitle tracks (signs/songs only) are skipped when `skip_forced`.
+pub(crate) fn pick_track(tracks: &[TrackInfo], prefs: &[String], skip_forced: bool) -> Option<i32> {
+    prefs.iter().filter_map(|p| normalize_lang(p)).find_map(|want| {
+        tracks
+            .iter()
+            .filter(|t| !(skip_forced && t.is_forced()))
+            .find(|t| t.language.as_deref().and_then(normalize_lang).as_deref() == Some(&want))
+            .map(|t| t.id)
+    })
+}
+
+pub(crate) fn load_preferences(app: &AppHandle) -> TrackPreferences {
+    app.store(STORE_PATH)
+        .ok()
+        .and_then(|s| s.get(PREFERENCES_KEY))
+        .and_then(|v| serde_json::from_value(v).ok())
+        .unwrap_or_default()
+}
+
+/// Audio and subtitle tracks of a media, split by type.
+fn split_tracks(media: &vlc::Media) -> Option<(Vec<TrackInfo>, Vec<TrackInfo>)> {
+    let tracks = media.tracks()?;
+    let mut audio = Vec::new();
+    let mut subtitles = Vec::new();
+    for t in tracks {
+        let info = TrackInfo {
+            id: t.id,
+            language: t.language.clone(),
+            description: t.description.clone(),
+        };
+        match t.type_specific_data {
+            vlc::MediaTrackUnion::Audio(_) => audio.push(info),
+            vlc::MediaTrackUnion::Subtitle(_) => subtitles.push(info),
+            _ => {}
+        }
+    }
+    Some((audio, subtitles))
+}
+
+/// Select audio and subtitle tracks for freshly parsed media: the series' remembered
+/// languages first, then the global preferences. Tracks are left at VLC's defaults
+/// when nothing matches.
+pub(crate) fn apply_preferences(
+    app: &AppHandle,
+    player: &vlc::MediaPlayer,
+    media: &vlc::Media,
+    series_key: Option<&str>,
+) {
+    let prefs = load_preferences(app);
+    let memory = series_key.and_then(|k| load_memory(app, k)).unwrap_or_default();
+    let Some((audio, subtitles)) = split_tracks(media) else {
+        return;
+    };
+
+    let audio_id = memory
+        .audio_lang
+        .as_ref()
+        .and_then(|lang| pick_track(&audio, std::slice::from_ref(lang), false))
+        .or_else(|| pick_track(&audio, &prefs.preferred_audio_langs, false));
+    let subtitle_id = if memory.subtitles_off {
+        Some(-1)
+    } else {
+        memory
+            .subtitle_lang
+            .as_ref()
+            .and_then(|lang| {
+                let lang = std::slice::from_ref(lang);
+                pick_track(&subtitles, lang, true).or_else(|| pick_track(&subtitles, lang, false))
+            })
+            .or_else(|| pick_track(&subtitles, &prefs.preferred_subtitle_langs, true))
+    };
+
+    // vlc-rs doesn't wrap the track setters
+    if let Some(id) = audio_id {
+        unsafe { vlc::sys::libvlc_audio_set_track(player.raw(), id) };
+    }
+    if let Some(id) = subtitle_id {
+        unsafe { vlc::sys::libvlc_video_set_spu(player.raw(), id) };
+    }
+}
+
+fn load_all_memory(app: &AppHandle) -> std::collections::HashMap<String, TrackMemory> {
+    app.store(STORE_PATH)
+        .ok()
+        .and_then(|s| s.get(MEMORY_KEY))
+        .and_then(|v| serde_json::from_value(v).ok())
+        .unwrap_or_default()
+}
+
+fn load_memory(app: &AppHandle, series_key: &str) -> Option<TrackMemory> {
+    load_all_memory(app).remove(series_key)
+}
+
+fn update_memory(app: &AppHandle, series_key: &str, f: impl FnOnce(&mut TrackMemory)) {
+    let Ok(store) = app.store(STORE_PATH) else {
+        return;
+    };
+    let mut all = load_all_memory(app);
+    f(all.entry(series_key.to_string()).or_default());
+    store.set(MEMORY_KEY, serde_json::to_value(&all).unwrap());
+    let _ = store.save();
+}
+
+/// Language of track `id` of the given type, if VLC reports one.
+fn track_language(tracks: &[TrackInfo], id: i32) -> Option<String> {
+    tracks
+        .iter()
+        .find(|t| t.id == id)
+        .and_then(|t| t.language.as_deref())
+        .and_then(normalize_lang)
+}
+
+/// Remember the language of the audio track the user picked for this series.
+/// Tracks without a language tag can't be matched across files, so they're skipped.
+pub(crate) fn remember_audio(app: &AppHandle, series_key: &str, media: &vlc::Media, id: i32) {
+    let Some(lang) = split_tracks(media).and_then(|(audio, _)| track_language(&audio, id)) else {
+        return;
+    };
+    update_memory(app, series_key, |m| m.audio_lang = Some(lang));
+}
+
+/// Remember the subtitle choice for this series; id -1 means subtitles off.
+pub(crate) fn remember_subtitle(app: &AppHandle, series_key: &str, media: &vlc::Media, id: i32) {
+    if id < 0 {
+        update_memory(app, series_key, |m| m.subtitles_off = true);
+        return;
+    }
+    let Some(lang) = split_tracks(media).and_then(|(_, subs)| track_language(&subs, id)) else {
+        return;
+    };
+    update_memory(app, series_key, |m| {
+        m.subtitle_lang = Some(lang);
+        m.subtitles_off = false;
+    });
+}
+
+#[tauri::command]
+pub async fn get_track_preferences(app: AppHandle) -> Result<TrackPreferences, AppError> {
+    Ok(load_preferences(&app))
+}
+
+#[tauri::command]
+pub async fn set_track_preferences(
+    app: AppHandle,
+    preferences: TrackPreferences,
+) -> Result<(), AppError> {
+    let store = app
+        .store(STORE_PATH)
+        .map_err(|e| format!("Failed to open store: {}", e))?;
+    store.set(PREFERENCES_KEY, serde_json::to_value(&preferences).unwrap());
+    store
+        .save()
+        .map_err(|e| format!("Failed to save store: {}", e))?;
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn get_track_memory(
+    app: AppHandle,
+    series_key: String,
+) -> Result<Option<TrackMemory>, AppError> {
+    Ok(load_memory(&app, &series_key))
+}
+
+#[tauri::command]
+pub async fn clear_track_memory(app: AppHandle, series_key: String) -> Result<(), AppError> {
+    let store = app
+        .store(STORE_PATH)
+        .map_err(|e| format!("Failed to open store: {}", e))?;
+    let mut all = load_all_memory(&app);
+    all.remove(&series_key);
+    store.set(MEMORY_KEY, serde_json::to_value(&all).unwrap());
+    store
+        .save()
+        .map_err(|e| format!("Failed to save store: {}", e))?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn track(id: i32, language: Option<&str>, description: Option<&str>) -> TrackInfo {
+        TrackInfo {
+            id,
+            language: language.map(String::from),
+            description: description.map(String::from),
+        }
+    }
+
+    #[test]
+    fn messy_language_tags_normalize_to_iso_639_1() {
+        assert_eq!(normalize_lang("eng").as_deref(), Some("en"));
+        assert_eq!(normalize_lang("en").as_deref(), Some("en"));
+        assert_eq!(normalize_lang("English").as_deref(), Some("en"));
+        assert_eq!(normalize_lang(" en-US ").as_deref(), Some("en"));
+        assert_eq!(normalize_lang("pt_BR").as_deref(), Some("pt"));
+        assert_eq!(normalize_lang("jpn").as_deref(), Some("ja"));
+        assert_eq!(normalize_lang("klingon").as_deref(), Some("klingon"));
+        assert_eq!(normalize_lang(""), None);
+        assert_eq!(normalize_lang("  "), None);
+    }
+
+    #[test]
+    fn first_preferred_language_with_a_track_wins() {
+        let audio = [track(1, Some("English"), None), track(2, Some("jpn"), None), track(3, None, None)];
+        let prefs = ["ja".to_string(), "en".to_string()];
+        assert_eq!(pick_track(&audio, &prefs, false), Some(2));
+        assert_eq!(pick_track(&audio, &["de".to_string(), "eng".to_string()], false), Some(1));
+        assert_eq!(pick_track(&audio, &["de".to_string()], false), None);
+        assert_eq!(pick_track(&audio, &[], false), None);
+    }
+
+    #[test]
+    fn forced_subtitles_are_skipped_when_asked() {
+        let subtitles = [track(4, Some("en"), Some("English (Forced)")), track(5, Some("eng"), Some("Full"))];
+        let prefs = ["en".to_string()];
+        assert_eq!(pick_track(&subtitles, &prefs, true), Some(5));
+        assert_eq!(pick_track(&subtitles, &prefs, false), Some(4));
+    }
+}
@@ -0,0 +1,482 @@
+//! ffmpeg transcode fallback for files VLC can't decode (old DivX AVIs, odd
+//! audio codecs). The source is resolved like `open_media` and re-encoded to
+//! H.264/AAC HLS in a temp directory; the playlist path can be opened by VLC or,
+//! through the asset protocol, by the webview's `<video>` with an HLS player.
+//!
+//! One session per library item, tracked like the rclone serve processes:
+//! stopped by `stop_transcode_session`, `stop_all_sessions` or on app exit.
+//! Seeking restarts ffmpeg at the new offset.
+//!
+//! A subtitle track can be burned into the picture for outputs that can't
+//! render it (PGS in a `<video>`): bitmap tracks are overlaid straight from the
+//! source, text tracks are extracted to a temp file for ffmpeg's `subtitles`
+//! filter and deleted with the session.
+
+use serde::Serialize;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::process::{Child, Command, Stdio};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, State};
+use tokio::process::Command as TokioCommand;
+use tokio::sync::OnceCell;
+
+use crate::commands::player::{self, VlcManager};
+use crate::commands::{downloads, progress, storyboard, subtitles};
+use crate::error::AppError;
+
+const PLAYLIST_FILE: &str = "index.m3u8";
+/// Seconds per HLS segment; keyframes are forced on the same grid
+const SEGMENT_SECS: u32 = 4;
+/// How long ffmpeg may take to write the first segment
+const FIRST_SEGMENT_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Running transcodes, by session id
+static SESSIONS: Mutex<Option<HashMap<String, TranscodeProcess>>> = Mutex::new(None);
+/// Hardware H.264 encoder this ffmpeg build offers, detected once
+static HW_ENCODER: OnceCell<Option<&'static VideoEncoder>> = OnceCell::const_new();
+
+/// An H.264 encoder and the pixel format it is fed.
+#[derive(Debug)]
+struct VideoEncoder {
+    name: &'static str,
+    pix_fmt: &'static str,
+}
+
+const SOFTWARE_ENCODER: VideoEncoder = VideoEncoder { name: "libx264", pix_fmt: "yuv420p" };
+
+/// Hardware encoders to try, best first. Being compiled into ffmpeg doesn't mean
+/// the GPU is there, so a failed start falls back to `SOFTWARE_ENCODER`.
+#[cfg(target_os = "macos")]
+const HW_ENCODERS: &[VideoEncoder] = &[VideoEncoder { name: "h264_videotoolbox", pix_fmt: "yuv420p" }];
+#[cfg(target_os = "windows")]
+const HW_ENCODERS: &[VideoEncoder] = &[
+    VideoEncoder { name: "h264_nvenc", pix_fmt: "yuv420p" },
+    VideoEncoder { name: "h264_qsv", pix_fmt: "nv12" },
+    VideoEncoder { name: "h264_amf", pix_fmt: "nv12" },
+];
+#[cfg(not(any(target_os = "macos", target_os = "windows")))]
+const HW_ENCODERS: &[VideoEncoder] = &[
+    VideoEncoder { name: "h264_nvenc", pix_fmt: "yuv420p" },
+    VideoEncoder { name: "h264_qsv", pix_fmt: "nv12" },
+];
+
+/// Subtitle track drawn into the video
+#[derive(Debug, Clone)]
+enum BurnIn {
+    /// Bitmap track (PGS, VobSub) overlaid from this source stream index
+    Bitmap(u32),
+    /// Text track extracted to this .ass file
+    Text(PathBuf),
+}
+
+struct TranscodeProcess {
+    child: Child,
+    /// Path or URL ffmpeg reads, kept for restarting at a new offset
+    source: String,
+    dir: PathBuf,
+    burn_in: Option<BurnIn>,
+    /// Burned-in track index and whether it is a bitmap, for reporting
+    burned: Option<(u32, bool)>,
+}
+
+/// Returned by `start_transcode_session` and `seek_transcode_session`.
+#[derive(Debug, Serialize, Clone)]
+pub struct TranscodeSession {
+    pub session_id: String,
+    pub playlist_path: String,
+    /// Where the playlist starts in the source; player times are relative to it
+    pub start_ms: i64,
+    /// ffmpeg video encoder in use, e.g. "h264_nvenc" or "libx264"
+    pub encoder: String,
+    /// Source stream index of the burned-in subtitle track, if any
+    pub burned_subtitle: Option<u32>,
+    /// Set when burning in subtitles: the expected cost, for the UI to show
+    pub notice: Option<String>,
+}
+
+impl TranscodeSession {
+    fn new(session_id: String, start_ms: i64, encoder: &str, burn_in: Option<(u32, bool)>) -> Self {
+        let playlist_path = output_dir(&session_id).join(PLAYLIST_FILE);
+        TranscodeSession {
+            session_id,
+            playlist_path: playlist_path.to_string_lossy().into_owned(),
+            start_ms,
+            encoder: encoder.to_string(),
+            burned_subtitle: burn_in.map(|(index, _)| index),
+            notice: burn_in.map(|(_, bitmap)| burn_in_notice(bitmap)),
+        }
+    }
+}
+
+/// Rough cost of burning in subtitles, for the UI to warn with
+fn burn_in_notice(bitmap: bool) -> String {
+    let cost = if bitmap {
+        "compositing every frame with the subtitle bitmaps"
+    } else {
+        "rendering the subtitle text onto every frame"
+    };
+    format!(
+        "Burning in subtitles re-encodes the whole video, {}; expect it to \
+         start more slowly and run up to about twice as slow as a plain transcode, \
+         and subtitles can't be turned off without restarting it.",
+        cost
+    )
+}
+
+fn output_dir(session_id: &str) -> PathBuf {
+    std::env::temp_dir().join("rcloneflix-transcode").join(session_id)
+}
+
+/// Extracted text subtitle; kept outside `output_dir`, which seeks clear
+fn subtitle_path(session_id: &str) -> PathBuf {
+    std::env::temp_dir()
+        .join("rcloneflix-transcode")
+        .join(format!("{}.ass", session_id))
+}
+
+/// Quote a path for a filtergraph option (`C:` and quotes need escaping)
+fn filter_path(path: &Path) -> String {
+    let path = path
+        .to_string_lossy()
+        .replace('\\', "/")
+        .replace(':', "\\:")
+        .replace('\'', "\\'");
+    format!("'{}'", path)
+}
+
+/// Check `track` against the probed subtitle streams of `source` and prepare
+/// it for burning in. Returns the burn-in and whether the track is a bitmap.
+async fn prepare_burn_in(
+    app: &AppHandle,
+    source: &str,
+    session_id: &str,
+    track: u32,
+) -> Result<(BurnIn, bool), String> {
+    let info = player::get_media_info(app.clone(), source.to_string())
+        .await
+        .map_err(|e| e.to_string())?;
+    if info.backend != player::MediaInfoBackend::Ffprobe {
+        return Err("Burning in subtitles needs ffprobe to identify the track".to_string());
+    }
+    let stream = info
+        .subtitles
+        .iter()
+        .find(|s| s.index == track)
+        .ok_or_else(|| format!("Subtitle track {} does not exist in this file", track))?;
+    let codec = stream.codec.as_deref().unwrap_or_default();
+    if subtitles::is_image_codec(codec) {
+        return Ok((BurnIn::Bitmap(track), true));
+    }
+
+    let path = subtitle_path(session_id);
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)
+            .map_err(|e| format!("Failed to create {}: {}", dir.display(), e))?;
+    }
+    subtitles::extract_subtitle(
+        app.clone(),
+        source.to_string(),
+        track,
+        path.to_string_lossy().into_owned(),
+    )
+    .await?;
+    Ok((BurnIn::Text(path), false))
+}
+
+/// Serve-session id under which a transcode holds its rclone serve process
+fn serve_session(session_id: &str) -> String {
+    format!("transcode:{}", session_id)
+}
+
+async fn hardware_encoder(app: &AppHandle) -> Option<&'static VideoEncoder> {
+    *HW_ENCODER
+        .get_or_init(|| async {
+            let output = TokioCommand::new(storyboard::bundled_binary(app, "ffmpeg"))
+                .args(["-hide_banner", "-encoders"])
+                .output()
+                .await
+                .ok()?;
+            let listed = String::from_utf8_lossy(&output.stdout);
+            HW_ENCODERS.iter().find(|e| {
+                listed
+                    .lines()
+                    .any(|line| line.split_whitespace().nth(1) == Some(e.name))
+            })
+        })
+        .await
+}
+
+fn spawn_ffmpeg(
+    app: &AppHandle,
+    source: &str,
+    start_ms: i64,
+    encoder: &VideoEncoder,
+    dir: &Path,
+    burn_in: Option<&BurnIn>,
+) -> Result<Child, String> {
+    let _ = std::fs::remove_dir_all(dir);
+    std::fs::create_dir_all(dir)
+        .map_err(|e| format!("Failed to create {}: {}", dir.display(), e))?;
+
+    let mut command = Command::new(storyboard::bundled_binary(app, "ffmpeg"));
+    command.args(["-hide_banner", "-loglevel", "error", "-nostdin"]);
+    if start_ms > 0 {
+        command.args(["-ss", &format!("{:.3}", start_ms as f64 / 1000.0)]);
+    }
+    command.args(["-i", source]);
+    match burn_in {
+        Some(BurnIn::Bitmap(index)) => {
+            command.args(["-filter_complex", &format!("[0:v:0][0:{}]overlay[v]", index)]);
+            command.args(["-map", "[v]"]);
+        }
+        Some(BurnIn::Text(path)) => {
+            // The extracted subtitles are timed from the start of the file, but
+            // `-ss` restarts the video at 0: shift it back for the filter only
+            let offset = start_ms as f64 / 1000.0;
+            command.args([
+                "-vf",
+                &format!(
+                    "setpts=PTS+{:.3}/TB,subtitles={},setpts=PTS-STARTPTS",
+                    offset,
+                    filter_path(path)
+                ),
+            ]);
+            command.args(["-map", "0:v:0?"]);
+        }
+        None => {
+            command.args(["-map", "0:v:0?"]);
+        }
+    }
+    command.args(["-map", "0:a:0?"]);
+    command.args(["-c:v", encoder.name, "-pix_fmt", encoder.pix_fmt]);
+    if encoder.name == SOFTWARE_ENCODER.name {
+        command.args(["-preset", "veryfast", "-crf", "21"]);
+    }
+    command.args([
+        "-force_key_frames",
+        &format!("expr:gte(t,n_forced*{})", SEGMENT_SECS),
+        "-c:a",
+        "aac",
+        "-ac",
+        "2",
+        "-b:a",
+        "192k",
+        "-f",
+        "hls",
+        "-hls_time",
+        &SEGMENT_SECS.to_string(),
+        "-hls_list_size",
+        "0",
+        "-hls_playlist_type",
+        "event",
+        "-hls_segment_filename",
+    ]);
+    command
+        .arg(dir.join("segment%05d.ts"))
+        .arg(dir.join(PLAYLIST_FILE))
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .spawn()
+        .map_err(|e| format!("Failed to run ffmpeg: {}", e))
+}
+
+/// Wait until ffmpeg has written the playlist (its first segment is done).
+/// Fails if ffmpeg exits first, e.g. because the hardware encoder has no device.
+async fn wait_for_playlist(child: &mut Child, dir: &Path) -> Result<(), String> {
+    let playlist = dir.join(PLAYLIST_FILE);
+    let deadline = Instant::now() + FIRST_SEGMENT_TIMEOUT;
+    while Instant::now() < deadline {
+        if playlist.is_file() {
+            return Ok(());
+        }
+        if let Ok(Some(status)) = child.try_wait() {
+            return Err(format!("ffmpeg exited with {}", status));
+        }
+        tokio::time::sleep(Duration::from_millis(200)).await;
+    }
+    let _ = child.kill();
+    Err("Timed out waiting for the transcode to start".to_string())
+}
+
+/// Start ffmpeg at `start_ms`, on the hardware encoder if there is one and on
+/// libx264 if that fails to produce a first segment.
+async fn start_ffmpeg(
+    app: &AppHandle,
+    source: &str,
+    start_ms: i64,
+    dir: &Path,
+    burn_in: Option<&BurnIn>,
+) -> Result<(Child, &'static str), String> {
+    if let Some(encoder) = hardware_encoder(app).await {
+        let mut child = spawn_ffmpeg(app, source, start_ms, encoder, dir, burn_in)?;
+        match wait_for_playlist(&mut child, dir).await {
+            Ok(()) => return Ok((child, encoder.name)),
+            Err(e) => {
+                let _ = child.kill();
+                eprintln!("{} transcode failed, using software: {}", encoder.name, e);
+            }
+        }
+    }
+    let mut child = spawn_ffmpeg(app, source, start_ms, &SOFTWARE_ENCODER, dir, burn_in)?;
+    if let Err(e) = wait_for_playlist(&mut child, dir).await {
+        let _ = child.kill();
+        return Err(e);
+    }
+    Ok((child, SOFTWARE_ENCODER.name))
+}
+
+/// Kill a session's ffmpeg and delete its segments and extracted subtitles.
+/// The serve session is left to the caller.
+fn kill_process(mut process: TranscodeProcess) {
+    let _ = process.child.kill();
+    let _ = process.child.wait();
+    let _ = std::fs::remove_dir_all(&process.dir);
+    if let Some(BurnIn::Text(path)) = &process.burn_in {
+        let _ = std::fs::remove_file(path);
+    }
+}
+
+fn take_session(session_id: &str) -> Option<TranscodeProcess> {
+    SESSIONS.lock().unwrap().as_mut()?.remove(session_id)
+}
+
+/// End a transcode for good: kill it and release its serve session, so a
+/// later start resolves its source afresh instead of re-attaching to a
+/// session that is about to go away.
+fn stop_session(vlc: &VlcManager, session_id: &str) {
+    if let Some(process) = take_session(session_id) {
+        kill_process(process);
+    }
+    player::release_shared_session(vlc, &serve_session(session_id));
+}
+
+fn insert_session(session_id: &str, process: TranscodeProcess) {
+    let previous = SESSIONS
+        .lock()
+        .unwrap()
+        .get_or_insert_with(HashMap::new)
+        .insert(session_id.to_string(), process);
+    // A concurrent start for the same item lost the race
+    if let Some(previous) = previous {
+        kill_process(previous);
+    }
+}
+
+/// Stop every transcode (used by `stop_all_sessions` and on app exit).
+pub(crate) fn kill_all(vlc: &VlcManager) {
+    let sessions = SESSIONS.lock().ok().and_then(|mut s| s.take());
+    for (session_id, process) in sessions.into_iter().flatten() {
+        kill_process(process);
+        player::release_shared_session(vlc, &serve_session(&session_id));
+    }
+}
+
+/// Transcode a library file to HLS from `start_ms` (default 0) for playback of
+/// codecs VLC can't handle. The source is resolved like `open_media` (offline
+/// download, FUSE mount, else rclone serve). Replaces any running transcode of
+/// the same file. Opt-in: nothing transcodes unless this is called.
+/// `burn_subtitle_track` (an ffprobe stream index from `get_media_info`) draws
+/// that subtitle track into the video; the result's `notice` describes the cost.
+#[tauri::command]
+pub async fn start_transcode_session(
+    app: AppHandle,
+    vlc: State<'_, VlcManager>,
+    config_path: String,
+    remote_root: String,
+    file_path: String,
+    start_ms: Option<i64>,
+    burn_subtitle_track: Option<u32>,
+) -> Result<TranscodeSession, AppError> {
+    let session_id = progress::item_id_for(&remote_root, &file_path);
+    stop_session(&vlc, &session_id);
+
+    let source = match downloads::completed_path(&app, &remote_root, &file_path) {
+        Some(local) => local.to_string_lossy().into_owned(),
+        None => {
+            player::resolve_shared_source(
+                &app,
+                &vlc,
+                &config_path,
+                &remote_root,
+                &file_path,
+                &serve_session(&session_id),
+                &player::ServeOptions::default(),
+            )
+            .await?
+        }
+    };
+
+    let burn_in = match burn_subtitle_track {
+        Some(track) => match prepare_burn_in(&app, &source, &session_id, track).await {
+            Ok(prepared) => Some(prepared),
+            Err(e) => {
+                player::release_shared_session(&vlc, &serve_session(&session_id));
+                return Err(e.into());
+            }
+        },
+        None => None,
+    };
+    let burned = burn_subtitle_track.zip(burn_in.as_ref().map(|(_, bitmap)| *bitmap));
+    let burn_in = burn_in.map(|(burn_in, _)| burn_in);
+
+    let start_ms = start_ms.unwrap_or(0).max(0);
+    let dir = output_dir(&session_id);
+    let (child, encoder) = match start_ffmpeg(&app, &source, start_ms, &dir, burn_in.as_ref()).await {
+        Ok(started) => started,
+        Err(e) => {
+            player::release_shared_session(&vlc, &serve_session(&session_id));
+            let _ = std::fs::remove_dir_all(&dir);
+            if let Some(BurnIn::Text(path)) = &burn_in {
+                let _ = std::fs::remove_file(path);
+            }
+            return Err(e.into());
+        }
+    };
+    insert_session(&session_id, TranscodeProcess { child, source, dir, burn_in, burned });
+
+    Ok(TranscodeSession::new(session_id, start_ms, encoder, burned))
+}
+
+/// Restart a running transcode at `start_ms`. The playlist path stays the same
+/// but starts over, so the player must reload it.
+#[tauri::command]
+pub async fn seek_transcode_session(
+    app: AppHandle,
+    vlc: State<'_, VlcManager>,
+    session_id: String,
+    start_ms: i64,
+) -> Result<TranscodeSession, AppError> {
+    let mut process = take_session(&session_id)
+        .ok_or_else(|| AppError::invalid(format!("No transcode session {}", session_id)))?;
+    // Only ffmpeg restarts; the extracted subtitles are reused
+    let _ = process.child.kill();
+    let _ = process.child.wait();
+
+    let start_ms = start_ms.max(0);
+    let started = start_ffmpeg(&app, &process.source, start_ms, &process.dir, process.burn_in.as_ref()).await;
+    let (child, encoder) = match started {
+        Ok(started) => started,
+        Err(e) => {
+            kill_process(process);
+            player::release_shared_session(&vlc, &serve_session(&session_id));
+            return Err(e.into());
+        }
+    };
+    process.child = child;
+    let burned = process.burned;
+    insert_session(&session_id, process);
+
+    Ok(TranscodeSession::new(session_id, start_ms, encoder, burned))
+}
+
+/// Stop a transcode, delete its segments and release its serve process.
+#[tauri::command]
+pub async fn stop_transcode_session(
+    vlc: State<'_, VlcManager>,
+    session_id: String,
+) -> Result<(), AppError> {
+    stop_session(&vlc, &session_id);
+    Ok(())
+}
@@ -0,0 +1,137 @@
+//! Error type returned by commands.
+//!
+//! Serializes to a tagged object, `{ "kind": "rclone_missing", "message": "…" }`
+//! plus any variant fields, so the UI can react to the kind and still show the
+//! message. Internal helpers that return `Result<_, String>` convert into
+//! `AppError::Other` with `?`.
+
+use serde::ser::{Serialize, SerializeMap, Serializer};
+use std::fmt;
+
+#[derive(Debug, Clone)]
+pub enum AppError {
+    /// The rclone binary couldn't be started (not bundled and not on PATH)
+    RcloneMissing,
+    /// rclone ran and failed
+    RcloneFailed { stderr: String },
+    /// The rclone config file doesn't exist
+    ConfigNotFound { path: String },
+    /// The config is encrypted and its password is missing or wrong
+    ConfigPassword,
+    /// libVLC couldn't be loaded, so nothing can play. `reason` is what the
+    /// VLC thread reported, when it got that far
+    VlcInit { reason: Option<String> },
+    /// ffmpeg (or ffprobe) couldn't be started
+    FfmpegMissing,
+    /// Something didn't respond in time
+    Timeout { message: String },
+    /// The operation was cancelled by the user
+    Cancelled { message: String },
+    /// A command parameter was rejected
+    InvalidInput { message: String },
+    /// Anything without a more specific kind
+    Other { message: String },
+}
+
+impl AppError {
+    pub fn timeout(message: impl Into<String>) -> Self {
+        AppError::Timeout { message: message.into() }
+    }
+
+    pub fn cancelled(message: impl Into<String>) -> Self {
+        AppError::Cancelled { message: message.into() }
+    }
+
+    pub fn invalid(message: impl Into<String>) -> Self {
+        AppError::InvalidInput { message: message.into() }
+    }
+
+    pub fn rclone_failed(stderr: impl AsRef<str>) -> Self {
+        AppError::RcloneFailed { stderr: stderr.as_ref().trim().to_string() }
+    }
+
+    /// Error for a failed attempt to start rclone
+    pub fn rclone_spawn(e: std::io::Error) -> Self {
+        if e.kind() == std::io::ErrorKind::NotFound {
+            AppError::RcloneMissing
+        } else {
+            AppError::Other { message: format!("Failed to run rclone: {}", e) }
+        }
+    }
+
+    /// Value of the `kind` tag
+    pub fn kind(&self) -> &'static str {
+        match self {
+            AppError::RcloneMissing => "rclone_missing",
+            AppError::RcloneFailed { .. } => "rclone_failed",
+            AppError::ConfigNotFound { .. } => "config_not_found",
+            AppError::ConfigPassword => "config_password",
+            AppError::VlcInit { .. } => "vlc_init",
+            AppError::FfmpegMissing => "ffmpeg_missing",
+            AppError::Timeout { .. } => "timeout",
+            AppError::Cancelled { .. } => "cancelled",
+            AppError::InvalidInput { .. } => "invalid_input",
+            AppError::Other { .. } => "other",
+        }
+    }
+}
+
+impl fmt::Display for AppError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AppError::RcloneMissing => write!(f, "rclone was not found. Is rclone installed?"),
+            AppError::RcloneFailed { stderr } => write!(f, "rclone error: {}", stderr),
+            AppError::ConfigNotFound { path } => write!(f, "Config file not found: {}", path),
+            AppError::ConfigPassword => write!(
+                f,
+                "This rclone config is encrypted; the password is missing or wrong"
+            ),
+            AppError::VlcInit { reason: Some(reason) } => write!(f, "libVLC not available: {}", reason),
+            AppError::VlcInit { reason: None } => write!(
+                f,
+                "VLC is not available. Make sure libvlc5 is installed (sudo apt install libvlc5)."
+            ),
+            AppError::FfmpegMissing => write!(f, "ffmpeg was not found. Install ffmpeg and try again."),
+            AppError::Timeout { message }
+            | AppError::Cancelled { message }
+            | AppError::InvalidInput { message }
+            | AppError::Other { message } => f.write_str(message),
+        }
+    }
+}
+
+impl std::error::Error for AppError {}
+
+impl Serialize for AppError {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut map = serializer.serialize_map(None)?;
+        map.serialize_entry("kind", self.kind())?;
+        map.serialize_entry("message", &self.to_string())?;
+        match self {
+            AppError::RcloneFailed { stderr } => map.serialize_entry("stderr", stderr)?,
+            AppError::ConfigNotFound { path } => map.serialize_entry("path", path)?,
+            AppError::VlcInit { reason: Some(reason) } => map.serialize_entry("reason", reason)?,
+            _ => {}
+        }
+        map.end()
+    }
+}
+
+impl From<String> for AppError {
+    fn from(message: String) -> Self {
+        AppError::Other { message }
+    }
+}
+
+impl From<&str> for AppError {
+    fn from(message: &str) -> Self {
+        AppError::Other { message: message.to_string() }
+    }
+}
+
+/// For helpers that still report errors as strings
+impl From<AppError> for String {
+    fn from(e: AppError) -> Self {
+        e.to_string()
+    }
+}
@@ -1,4 +1,7 @@
 mod commands;
+mod error;
+mod power;
+mod secrets;
 
 use commands::player::VlcManager;
 use tauri::Manager;
@@ -11,6 +14,7 @@ pub fn run() {
         .plugin(tauri_plugin_store::Builder::default().build())
         .plugin(tauri_plugin_shell::init())
         .plugin(tauri_plugin_http::init())
+        .plugin(commands::media_keys::plugin())
         .setup(|app| {
             // Point VLC at bundled plugins when running from an AppImage/deb package.
             // On a plain install the system path is fine; this only overrides when the
@@ -26,34 +30,147 @@ pub fn run() {
 
             let vlc = VlcManager::new(app.handle().clone());
             app.manage(vlc);
+            commands::media_keys::init(app.handle());
+            #[cfg(target_os = "linux")]
+            commands::mpris::init(app.handle());
+            commands::downloads::restore(app.handle());
+            let handle = app.handle().clone();
+            std::thread::spawn(move || commands::temp_cache::sweep_expired(&handle));
+            commands::discord::init(app.handle());
             Ok(())
         })
         .invoke_handler(tauri::generate_handler![
             commands::rclone::parse_rclone_config,
             commands::rclone::list_remote_path,
             commands::rclone::get_rclone_version,
+            commands::rclone::read_remote_bytes,
+            commands::rclone::dedupe_remote,
             commands::rclone::get_stream_url,
+            commands::rclone::test_remote,
+            commands::rclone::remote_about,
+            commands::rclone::create_remote,
             commands::store::save_api_keys,
             commands::store::load_api_keys,
+            commands::store::save_setting,
+            commands::store::load_setting,
+            commands::store::export_settings,
+            commands::store::import_settings,
+            commands::tmdb::tmdb_search,
+            commands::tmdb::tmdb_details,
+            commands::opensubtitles::opensubtitles_search,
+            commands::opensubtitles::opensubtitles_download,
+            commands::player::player_add_subtitle,
+            commands::player::preview_open,
+            commands::player::preview_stop,
+            commands::player::preview_set_volume,
+            commands::storyboard::generate_storyboard,
+            commands::storyboard::cancel_storyboard,
+            commands::storyboard::generate_seek_thumbnails,
+            commands::storyboard::cancel_seek_thumbnails,
+            commands::storyboard::get_seek_thumbnail,
+            commands::storyboard::generate_item_thumbnail,
+            commands::storyboard::generate_item_thumbnails,
             commands::scan::scan_library_files,
+            commands::scan::scan_libraries,
             commands::scan::parse_media_filename,
+            commands::scan::parse_media_path,
+            commands::scan::read_nfo,
             commands::scan::hash_remote_path,
             commands::player::open_media,
             commands::player::start_stream_session,
             commands::player::player_play,
             commands::player::player_pause,
             commands::player::player_seek,
+            commands::player::player_seek_relative,
+            commands::player::player_seek_fraction,
             commands::player::player_set_volume,
+            commands::player::player_set_mute,
+            commands::player::player_set_scale,
+            commands::player::player_set_zoom_rect,
+            commands::player::player_set_subtitle_encoding,
+            commands::player::player_discover_renderers,
+            commands::player::player_set_renderer,
+            commands::player::player_set_audio_track,
+            commands::player::player_set_subtitle_track,
+            commands::player::player_set_audio_keeps_screen_on,
+            commands::player::player_set_normalization,
+            commands::player::player_set_crossfade,
+            commands::player::list_rclone_mounts,
+            commands::player::player_set_sleep_timer,
+            commands::player::player_get_sleep_timer,
+            commands::player::player_set_sleep_timer_keep_on_open,
+            commands::player::player_skip_current_segment,
+            commands::player::player_set_segment_auto_skip,
+            commands::prefetch::player_cancel_prefetch,
+            commands::segments::set_skip_segments,
+            commands::segments::get_skip_segments,
             commands::player::player_stop,
+            commands::player::player_get_status,
+            commands::player::get_player_logs,
+            commands::player::player_set_log_level,
+            commands::player::player_queue_set,
+            commands::player::player_queue_next,
+            commands::player::player_queue_previous,
+            commands::player::player_queue_get,
+            commands::player::player_set_repeat,
+            commands::player::player_set_watched_threshold,
+            commands::player::player_retry_stream,
+            commands::player::player_set_stall_timeout,
             commands::player::stop_stream_session,
             commands::player::stop_all_sessions,
             commands::player::get_media_info,
+            commands::player::get_ffprobe_version,
+            commands::player::get_media_chapters,
+            commands::transcode::start_transcode_session,
+            commands::transcode::seek_transcode_session,
+            commands::transcode::stop_transcode_session,
             commands::player::download_book_to_temp,
+            commands::player::cancel_book_download,
             commands::player::cleanup_book_temp,
+            commands::temp_cache::get_temp_cache_stats,
+            commands::temp_cache::set_temp_cache_limit,
+            commands::temp_cache::set_temp_cache_max_age,
+            commands::temp_cache::clear_temp_cache,
+            commands::temp_cache::cleanup_all_book_temp,
+            commands::downloads::queue_download,
+            commands::downloads::list_downloads,
+            commands::downloads::pause_download,
+            commands::downloads::resume_download,
+            commands::downloads::remove_download,
+            commands::downloads::download_remote_file,
+            commands::downloads::cancel_download,
+            commands::progress::get_watch_progress,
+            commands::progress::get_all_watch_progress,
+            commands::progress::clear_watch_progress,
+            commands::progress::save_resume_position,
+            commands::progress::load_resume_position,
+            commands::progress::clear_resume_position,
+            commands::progress::is_watched,
+            commands::progress::mark_watched,
+            commands::progress::mark_unwatched,
+            commands::progress::migrate_item_ids,
+            commands::bookmarks::add_bookmark,
+            commands::bookmarks::list_bookmarks,
+            commands::bookmarks::delete_bookmark,
+            commands::bookmarks::jump_to_bookmark,
+            commands::media_keys::set_media_keys_enabled,
+            commands::discord::set_discord_presence_enabled,
+            commands::tracks::get_track_preferences,
+            commands::tracks::set_track_preferences,
+            commands::tracks::get_track_memory,
+            commands::tracks::clear_track_memory,
+            commands::subtitles::get_subtitle_style,
+            commands::subtitles::player_set_subtitle_style,
+            commands::subtitles::extract_subtitle,
+            commands::subtitles::player_get_supported_encodings,
             commands::google::start_google_oauth,
+            commands::oauth::start_oauth,
+            commands::oauth::exchange_oauth_code,
             commands::google::save_google_tokens,
             commands::google::load_google_tokens,
             commands::google::clear_google_tokens,
+            commands::google::refresh_google_tokens,
+            commands::google::fetch_google_profile,
         ])
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
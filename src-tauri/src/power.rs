@@ -0,0 +1,240 @@
+//! Screensaver / system-sleep inhibition while media is playing.
+//!
+//! Callers take an `InhibitGuard` from `acquire()` and drop it when playback
+//! pauses or stops. Guards are reference-counted across the whole app, so the
+//! OS-level inhibit is only taken on the first guard and released with the last
+//! one — including when the owning thread exits and drops its guard.
+
+use std::sync::Mutex;
+
+/// What a guard keeps awake.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InhibitKind {
+    /// Keep the screen on and prevent suspend (video)
+    Display,
+    /// Prevent suspend only; the screen may blank (audio-only playback)
+    Sleep,
+}
+
+#[derive(Default)]
+struct Counts {
+    display: usize,
+    sleep: usize,
+    platform: platform::Inhibitor,
+}
+
+static COUNTS: Mutex<Option<Counts>> = Mutex::new(None);
+
+/// Releases its share of the inhibit when dropped.
+pub struct InhibitGuard {
+    kind: InhibitKind,
+}
+
+impl InhibitGuard {
+    pub fn kind(&self) -> InhibitKind {
+        self.kind
+    }
+}
+
+pub fn acquire(kind: InhibitKind) -> InhibitGuard {
+    update(|c| match kind {
+        InhibitKind::Display => c.display += 1,
+        InhibitKind::Sleep => c.sleep += 1,
+    });
+    InhibitGuard { kind }
+}
+
+impl Drop for InhibitGuard {
+    fn drop(&mut self) {
+        let kind = self.kind;
+        update(|c| match kind {
+            InhibitKind::Display => c.display = c.display.saturating_sub(1),
+            InhibitKind::Sleep => c.sleep = c.sleep.saturating_sub(1),
+        });
+    }
+}
+
+fn update(f: impl FnOnce(&mut Counts)) {
+    let Ok(mut guard) = COUNTS.lock() else {
+        return;
+    };
+    let counts = guard.get_or_insert_with(Counts::default);
+    f(counts);
+    let display = counts.display > 0;
+    let sleep = display || counts.sleep > 0;
+    counts.platform.set(display, sleep);
+}
+
+#[cfg(target_os = "linux")]
+mod platform {
+    use zbus::blocking::Connection;
+    use zbus::zvariant::OwnedFd;
+
+    /// org.freedesktop.ScreenSaver for screen blanking, org.freedesktop.login1 for suspend.
+    #[derive(Default)]
+    pub struct Inhibitor {
+        /// Session bus connection + cookie; the inhibit dies with the connection
+        screensaver: Option<(Connection, u32)>,
+        /// logind releases the inhibit when this fd is closed
+        sleep_fd: Option<OwnedFd>,
+    }
+
+    impl Inhibitor {
+        pub fn set(&mut self, display: bool, sleep: bool) {
+            if display && self.screensaver.is_none() {
+                match inhibit_screensaver() {
+                    Ok(s) => self.screensaver = Some(s),
+                    Err(e) => eprintln!("Screensaver inhibit failed: {}", e),
+                }
+            } else if !display {
+                if let Some((conn, cookie)) = self.screensaver.take() {
+                    let _ = conn.call_method(
+                        Some("org.freedesktop.ScreenSaver"),
+                        "/org/freedesktop/ScreenSaver",
+                        Some("org.freedesktop.ScreenSaver"),
+                        "UnInhibit",
+                        &(cookie,),
+                    );
+                }
+            }
+
+            if sleep && self.sleep_fd.is_none() {
+                match inhibit_sleep() {
+                    Ok(fd) => self.sleep_fd = Some(fd),
+                    Err(e) => eprintln!("Sleep inhibit failed: {}", e),
+                }
+            } else if !sleep {
+                self.sleep_fd = None;
+            }
+        }
+    }
+
+    fn inhibit_screensaver() -> zbus::Result<(Connection, u32)> {
+        let conn = Connection::session()?;
+        let reply = conn.call_method(
+            Some("org.freedesktop.ScreenSaver"),
+            "/org/freedesktop/ScreenSaver",
+            Some("org.freedesktop.ScreenSaver"),
+            "Inhibit",
+            &("RcloneFlix", "Playing media"),
+        )?;
+        let cookie: u32 = reply.body().deserialize()?;
+        Ok((conn, cookie))
+    }
+
+    fn inhibit_sleep() -> zbus::Result<OwnedFd> {
+        let conn = Connection::system()?;
+        let reply = conn.call_method(
+            Some("org.freedesktop.login1"),
+            "/org/freedesktop/login1",
+            Some("org.freedesktop.login1.Manager"),
+            "Inhibit",
+            &("sleep", "RcloneFlix", "Playing media", "block"),
+        )?;
+        reply.body().deserialize()
+    }
+}
+
+#[cfg(target_os = "windows")]
+mod platform {
+    use windows_sys::Win32::System::Power::{
+        SetThreadExecutionState, ES_CONTINUOUS, ES_DISPLAY_REQUIRED, ES_SYSTEM_REQUIRED,
+    };
+
+    /// SetThreadExecutionState is per-thread; guards are taken and dropped on the
+    /// VLC thread, so the state set here belongs to that thread.
+    #[derive(Default)]
+    pub struct Inhibitor;
+
+    impl Inhibitor {
+        pub fn set(&mut self, display: bool, sleep: bool) {
+            let mut flags = ES_CONTINUOUS;
+            if sleep {
+                flags |= ES_SYSTEM_REQUIRED;
+            }
+            if display {
+                flags |= ES_DISPLAY_REQUIRED;
+            }
+            unsafe { SetThreadExecutionState(flags) };
+        }
+    }
+}
+
+#[cfg(target_os = "macos")]
+mod platform {
+    use std::ffi::{c_char, c_void, CString};
+
+    type CFStringRef = *const c_void;
+    type IOPMAssertionID = u32;
+    const K_IOPM_ASSERTION_LEVEL_ON: u32 = 255;
+    const K_CF_STRING_ENCODING_UTF8: u32 = 0x0800_0100;
+
+    #[link(name = "CoreFoundation", kind = "framework")]
+    extern "C" {
+        fn CFStringCreateWithCString(
+            alloc: *const c_void,
+            c_str: *const c_char,
+            encoding: u32,
+        ) -> CFStringRef;
+        fn CFRelease(cf: *const c_void);
+    }
+
+    #[link(name = "IOKit", kind = "framework")]
+    extern "C" {
+        fn IOPMAssertionCreateWithName(
+            assertion_type: CFStringRef,
+            level: u32,
+            name: CFStringRef,
+            id: *mut IOPMAssertionID,
+        ) -> i32;
+        fn IOPMAssertionRelease(id: IOPMAssertionID) -> i32;
+    }
+
+    #[derive(Default)]
+    pub struct Inhibitor {
+        display: Option<IOPMAssertionID>,
+        sleep: Option<IOPMAssertionID>,
+    }
+
+    impl Inhibitor {
+        pub fn set(&mut self, display: bool, sleep: bool) {
+            toggle(&mut self.display, display, "PreventUserIdleDisplaySleep");
+            toggle(&mut self.sleep, sleep, "PreventUserIdleSystemSleep");
+        }
+    }
+
+    fn toggle(slot: &mut Option<IOPMAssertionID>, want: bool, assertion_type: &str) {
+        match (want, *slot) {
+            (true, None) => *slot = create(assertion_type),
+            (false, Some(id)) => {
+                unsafe { IOPMAssertionRelease(id) };
+                *slot = None;
+            }
+            _ => {}
+        }
+    }
+
+    fn create(assertion_type: &str) -> Option<IOPMAssertionID> {
+        let kind = CString::new(assertion_type).ok()?;
+        let name = CString::new("RcloneFlix media playback").ok()?;
+        unsafe {
+            let kind = CFStringCreateWithCString(std::ptr::null(), kind.as_ptr(), K_CF_STRING_ENCODING_UTF8);
+            let name = CFStringCreateWithCString(std::ptr::null(), name.as_ptr(), K_CF_STRING_ENCODING_UTF8);
+            let mut id: IOPMAssertionID = 0;
+            let rc = IOPMAssertionCreateWithName(kind, K_IOPM_ASSERTION_LEVEL_ON, name, &mut id);
+            CFRelease(kind);
+            CFRelease(name);
+            (rc == 0).then_some(id)
+        }
+    }
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "windows", target_os = "macos")))]
+mod platform {
+    #[derive(Default)]
+    pub struct Inhibitor;
+
+    impl Inhibitor {
+        pub fn set(&mut self, _display: bool, _sleep: bool) {}
+    }
+}
@@ -0,0 +1,114 @@
+//! Encryption for secrets kept in the settings store (API keys, OAuth tokens).
+//!
+//! Values are sealed with AES-256-GCM under a random key held in the OS keyring
+//! (Secret Service / Keychain / Credential Manager). Where no keyring is
+//! available the key falls back to a user-only file in the app data directory,
+//! which still keeps secrets out of a synced or backed-up store file.
+//!
+//! Sealed values are stored as `{ "enc": "<base64 nonce || ciphertext>" }`.
+//! Anything else is treated as a plaintext value from before encryption, so
+//! existing stores keep loading and get sealed on their next save.
+
+use aes_gcm::aead::{Aead, KeyInit};
+use aes_gcm::{Aes256Gcm, Key, Nonce};
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use rand::RngCore;
+use std::sync::OnceLock;
+use tauri::{AppHandle, Manager};
+
+const KEYRING_SERVICE: &str = "rcloneflix";
+const KEYRING_USER: &str = "store-key";
+const KEY_FILE: &str = "store.key";
+const NONCE_LEN: usize = 12;
+
+static KEY: OnceLock<[u8; 32]> = OnceLock::new();
+
+fn decode_key(encoded: &str) -> Option<[u8; 32]> {
+    STANDARD.decode(encoded.trim()).ok()?.try_into().ok()
+}
+
+fn new_key() -> [u8; 32] {
+    let mut key = [0u8; 32];
+    rand::rngs::OsRng.fill_bytes(&mut key);
+    key
+}
+
+fn keyring_key() -> Option<[u8; 32]> {
+    let entry = keyring::Entry::new(KEYRING_SERVICE, KEYRING_USER).ok()?;
+    match entry.get_password() {
+        Ok(encoded) => decode_key(&encoded),
+        Err(keyring::Error::NoEntry) => {
+            let key = new_key();
+            entry.set_password(&STANDARD.encode(key)).ok()?;
+            Some(key)
+        }
+        Err(_) => None,
+    }
+}
+
+fn file_key(app: &AppHandle) -> Result<[u8; 32], String> {
+    let dir = app
+        .path()
+        .app_data_dir()
+        .map_err(|e| format!("No app data directory: {}", e))?;
+    let path = dir.join(KEY_FILE);
+    if let Some(key) = std::fs::read_to_string(&path).ok().and_then(|s| decode_key(&s)) {
+        return Ok(key);
+    }
+    std::fs::create_dir_all(&dir).map_err(|e| format!("Failed to create {}: {}", dir.display(), e))?;
+    let key = new_key();
+    std::fs::write(&path, STANDARD.encode(key))
+        .map_err(|e| format!("Failed to write {}: {}", path.display(), e))?;
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        let _ = std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o600));
+    }
+    Ok(key)
+}
+
+fn cipher(app: &AppHandle) -> Result<Aes256Gcm, String> {
+    let key = match KEY.get() {
+        Some(key) => *key,
+        None => {
+            let key = match keyring_key() {
+                Some(key) => key,
+                None => file_key(app)?,
+            };
+            *KEY.get_or_init(|| key)
+        }
+    };
+    Ok(Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(&key)))
+}
+
+/// Seal a JSON value for storage.
+pub fn seal(app: &AppHandle, value: &serde_json::Value) -> Result<serde_json::Value, String> {
+    let plaintext = serde_json::to_vec(value).map_err(|e| format!("Serialize error: {}", e))?;
+    let mut nonce = [0u8; NONCE_LEN];
+    rand::rngs::OsRng.fill_bytes(&mut nonce);
+    let ciphertext = cipher(app)?
+        .encrypt(Nonce::from_slice(&nonce), plaintext.as_slice())
+        .map_err(|_| "Encryption failed".to_string())?;
+    let mut sealed = nonce.to_vec();
+    sealed.extend_from_slice(&ciphertext);
+    Ok(serde_json::json!({ "enc": STANDARD.encode(sealed) }))
+}
+
+/// Open a stored value. Plaintext values written before encryption come back as-is.
+pub fn open(app: &AppHandle, stored: serde_json::Value) -> Result<serde_json::Value, String> {
+    let Some(encoded) = stored.get("enc").and_then(|v| v.as_str()) else {
+        return Ok(stored);
+    };
+    let sealed = STANDARD
+        .decode(encoded)
+        .map_err(|e| format!("Corrupt secret: {}", e))?;
+    if sealed.len() < NONCE_LEN {
+        return Err("Corrupt secret: too short".to_string());
+    }
+    let (nonce, ciphertext) = sealed.split_at(NONCE_LEN);
+    let plaintext = cipher(app)?
+        .decrypt(Nonce::from_slice(nonce), ciphertext)
+        .map_err(|_| "Failed to decrypt secret (store key changed?)".to_string())?;
+    serde_json::from_slice(&plaintext).map_err(|e| format!("Parse error: {}", e))
+}
@@ -1,7 +1,7 @@
 import { useEffect, useRef } from "react";
 import { Routes, Route, Navigate } from "react-router-dom";
 import { useAppStore } from "./store/appStore";
-import { scanAllLibraries } from "./lib/scanner";
+import { migrateItemIds, scanAllLibraries } from "./lib/scanner";
 import { SetupPage } from "./pages/SetupPage";
 import { AppShell } from "./components/layout/AppShell";
 import { HomePage } from "./pages/HomePage";
@@ -20,12 +20,16 @@ export default function App() {
 
   // Run a background scan on every launch once setup is complete.
   // The scanner only picks up new/removed files since it passes knownPaths
-  // to rclone, so repeated launches are cheap.
+  // to rclone, so repeated launches are cheap. Item ids saved by older
+  // versions are migrated first; a failed migration is retried next launch.
   const hasAutoScanned = useRef(false);
   useEffect(() => {
     if (setupComplete && !hasAutoScanned.current) {
       hasAutoScanned.current = true;
-      scanAllLibraries().catch(() => {});
+      migrateItemIds()
+        .catch(() => {})
+        .then(() => scanAllLibraries())
+        .catch(() => {});
     }
   }, [setupComplete]);
 
@@ -6,6 +6,7 @@ import {
 } from "lucide-react";
 import { invoke, convertFileSrc } from "@tauri-apps/api/core";
 import { useAppStore, type MediaItem } from "../../store/appStore";
+import { errorMessage } from "../../lib/tauri";
 
 type Props = {
   playlist: MediaItem[];
@@ -51,7 +52,7 @@ export function AudioMiniPlayer({ playlist, playlistIndex: initialIndex, onClose
         return url;
       } catch (e) {
         console.error("Failed to download audio:", e);
-        setError(`Download failed: ${e}`);
+        setError(`Download failed: ${errorMessage(e)}`);
         throw e;
       }
     },
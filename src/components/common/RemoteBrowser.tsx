@@ -1,5 +1,6 @@
 import { useState, useEffect } from "react";
 import { invoke } from "@tauri-apps/api/core";
+import { errorMessage } from "../../lib/tauri";
 import {
   Folder, ChevronRight, X, Check, Loader2, AlertCircle, Home,
 } from "lucide-react";
@@ -60,7 +61,7 @@ export function RemoteBrowser({
       })
       .catch((e) => {
         if (!cancelled) {
-          setError(String(e));
+          setError(errorMessage(e));
           setLoading(false);
         }
       });
@@ -3,6 +3,7 @@ import { motion } from "framer-motion";
 import { Chrome, Loader2, CheckCircle2, AlertCircle, CloudOff, ArrowRight } from "lucide-react";
 import { useAppStore } from "../../store/appStore";
 import { startGoogleSignIn, exchangeOAuthCode, restoreFromDrive, listenOAuthCallback } from "../../lib/sync";
+import { errorMessage } from "../../lib/tauri";
 
 type Props = {
   onNext: () => void;
@@ -42,7 +43,7 @@ export function GoogleSignInStep({ onNext, onSkip, isFirstStep }: Props) {
 
         setState("done");
       } catch (e) {
-        setError(String(e));
+        setError(errorMessage(e));
         setState("error");
       }
     }).then((fn) => { unlisten = fn; });
@@ -56,7 +57,7 @@ export function GoogleSignInStep({ onNext, onSkip, isFirstStep }: Props) {
     try {
       await startGoogleSignIn();
     } catch (e) {
-      setError(String(e));
+      setError(errorMessage(e));
       setState("error");
     }
   };
@@ -1,7 +1,7 @@
 import { useState, useCallback } from "react";
 import { motion, AnimatePresence } from "framer-motion";
 import { Upload, FolderOpen, CheckCircle2, AlertCircle, Loader2, ChevronRight } from "lucide-react";
-import { pickRcloneConfig, parseRcloneConfig } from "../../lib/tauri";
+import { pickRcloneConfig, parseRcloneConfig, isAppError } from "../../lib/tauri";
 import { useAppStore } from "../../store/appStore";
 import type { RcloneRemote } from "../../lib/tauri";
 
@@ -32,7 +32,7 @@ export function RcloneConfigStep({ onNext, onBack }: Props) {
     } catch (e) {
       setState("error");
       setErrorMsg(
-        e instanceof Error
+        isAppError(e) || e instanceof Error
           ? e.message
           : "Could not parse config file. Make sure it's a valid rclone config."
       );
@@ -2,6 +2,7 @@ import { invoke } from "@tauri-apps/api/core";
 import { listen } from "@tauri-apps/api/event";
 import { useAppStore, type MediaItem, type Library } from "../store/appStore";
 import { useToastStore } from "../store/toastStore";
+import { errorMessage } from "./tauri";
 
 type DiscoveredFile = {
   remote_path: string;
@@ -9,6 +10,10 @@ type DiscoveredFile = {
   size: number;
   is_dir: boolean;
   mime_type: string | null;
+  // Kodi-style .nfo sidecar, fetched lazily with readNfo
+  nfo?: string | null;
+  // Disc folders come through as a single is_dir entry
+  disc?: "dvd" | "bluray" | "iso" | null;
 };
 
 type LibraryScanResult = {
@@ -200,6 +205,18 @@ async function fetchOpenLibrary(title: string): Promise<Partial<MediaItem>> {
   } catch { return {}; }
 }
 
+// ─── Item id migration ────────────────────────────────────────────────────────
+
+// Move items saved with the old djb2 ids, and the progress and bookmarks the
+// backend keeps under them, to the current ids. Runs once after the upgrade.
+export async function migrateItemIds() {
+  const { itemIdsMigrationPending, mediaItems, applyItemIdMigration } = useAppStore.getState();
+  if (!itemIdsMigrationPending) return;
+  const remotePaths = Object.values(mediaItems).map((i) => i.remotePath);
+  const ids: Record<string, string> = await invoke("migrate_item_ids", { remotePaths });
+  applyItemIdMigration(ids);
+}
+
 // ─── Main scan ────────────────────────────────────────────────────────────────
 
 export async function scanLibrary(library: Library, apiKeys: { tmdb: string; theporndb: string }) {
@@ -245,7 +262,7 @@ export async function scanLibrary(library: Library, apiKeys: { tmdb: string; the
 
       setScanState({ progress: Math.round((idx / Math.max(total, 1)) * 100), newItemsFound: idx });
 
-      const parsed: ParsedTitle = await invoke("parse_media_filename", { filename: file.filename });
+      const parsed: ParsedTitle = await invoke("parse_media_path", { fullPath: file.remote_path });
       const id: string = await invoke("hash_remote_path", { remotePath: file.remote_path });
 
       const baseItem: MediaItem = {
@@ -254,6 +271,7 @@ export async function scanLibrary(library: Library, apiKeys: { tmdb: string; the
         libraryType: library.type,
         remotePath: file.remote_path,
         filename: file.filename,
+        disc: file.disc ?? undefined,
         title: parsed.title,
         year: parsed.year,
         season: parsed.season,
@@ -318,9 +336,9 @@ export async function scanLibrary(library: Library, apiKeys: { tmdb: string; the
 
     return { newItems: newItems.length, removed: allRemovedPaths.length };
   } catch (e) {
-    const errorMessage = e instanceof Error ? e.message : String(e);
-    setScanState({ status: "error", lastError: errorMessage, currentLibrary: undefined });
-    useToastStore.getState().addToast(`Scan failed: ${errorMessage}`, "error", 6000);
+    const message = errorMessage(e);
+    setScanState({ status: "error", lastError: message, currentLibrary: undefined });
+    useToastStore.getState().addToast(`Scan failed: ${message}`, "error", 6000);
     throw e;
   }
 }
@@ -12,6 +12,7 @@
 import { invoke } from "@tauri-apps/api/core";
 import { listen } from "@tauri-apps/api/event";
 import { useAppStore, type AppConfig, type MediaItem, type WatchProgress } from "../store/appStore";
+import { errorMessage, exportSettings, importSettings } from "./tauri";
 
 // ─── Bundled OAuth client ──────────────────────────────────────────────────────
 // Create a "Desktop app" OAuth 2.0 credential at https://console.cloud.google.com
@@ -31,22 +32,6 @@ const SCOPES = [
   "https://www.googleapis.com/auth/userinfo.profile",
 ].join(" ");
 
-// ─── PKCE helpers ─────────────────────────────────────────────────────────────
-
-function generateCodeVerifier(): string {
-  const array = new Uint8Array(32);
-  crypto.getRandomValues(array);
-  return btoa(String.fromCharCode(...array))
-    .replace(/\+/g, "-").replace(/\//g, "_").replace(/=/g, "");
-}
-
-async function generateCodeChallenge(verifier: string): Promise<string> {
-  const data = new TextEncoder().encode(verifier);
-  const digest = await crypto.subtle.digest("SHA-256", data);
-  return btoa(String.fromCharCode(...new Uint8Array(digest)))
-    .replace(/\+/g, "-").replace(/\//g, "_").replace(/=/g, "");
-}
-
 // ─── Encryption ───────────────────────────────────────────────────────────────
 
 async function deriveKey(googleSub: string): Promise<CryptoKey> {
@@ -92,18 +77,13 @@ async function decryptData(encryptedB64: string, googleSub: string): Promise<str
 // ─── OAuth PKCE Flow ──────────────────────────────────────────────────────────
 
 export async function startGoogleSignIn(): Promise<void> {
-  const verifier = generateCodeVerifier();
-  const challenge = await generateCodeChallenge(verifier);
-
-  sessionStorage.setItem("pkce_verifier", verifier);
-
+  // The backend adds code_challenge / code_challenge_method=S256 to the URL
+  // and hands back the matching verifier for the token exchange
   const params = new URLSearchParams({
     client_id: GOOGLE_CLIENT_ID,
     redirect_uri: "http://localhost:9876/oauth/callback",
     response_type: "code",
     scope: SCOPES,
-    code_challenge: challenge,
-    code_challenge_method: "S256",
     access_type: "offline",
     prompt: "consent",
   });
@@ -111,7 +91,8 @@ export async function startGoogleSignIn(): Promise<void> {
   const authUrl = `https://accounts.google.com/o/oauth2/v2/auth?${params}`;
 
   // Tell Rust to open browser and start local callback server
-  await invoke("start_google_oauth", { authUrl, port: 9876 });
+  const verifier = await invoke<string>("start_google_oauth", { authUrl, port: 9876 });
+  sessionStorage.setItem("pkce_verifier", verifier);
 }
 
 export async function exchangeOAuthCode(code: string): Promise<{
@@ -207,6 +188,8 @@ type BackupData = {
   libraries: AppConfig["libraries"];
   mediaItems: Record<string, MediaItem>;
   watchProgress: Record<string, WatchProgress>;
+  // Native settings store, bookmarks included; missing from older backups
+  settings?: Record<string, unknown>;
   exportedAt: number;
 };
 
@@ -239,6 +222,7 @@ export async function backupToDrive(): Promise<void> {
       libraries: store.libraries,
       mediaItems: store.mediaItems,
       watchProgress: store.watchProgress,
+      settings: await exportSettings(),
       exportedAt: Date.now(),
     };
 
@@ -280,7 +264,7 @@ export async function backupToDrive(): Promise<void> {
 
     store.setSyncState({ syncing: false, lastSyncAt: Date.now() });
   } catch (e) {
-    store.setSyncState({ syncing: false, error: String(e) });
+    store.setSyncState({ syncing: false, error: errorMessage(e) });
     throw e;
   }
 }
@@ -320,11 +304,12 @@ export async function restoreFromDrive(): Promise<BackupData | null> {
       mediaItems: backup.mediaItems,
       watchProgress: backup.watchProgress,
     });
+    if (backup.settings) await importSettings(backup.settings);
 
     store.setSyncState({ syncing: false, lastSyncAt: Date.now() });
     return backup;
   } catch (e) {
-    store.setSyncState({ syncing: false, error: String(e) });
+    store.setSyncState({ syncing: false, error: errorMessage(e) });
     throw e;
   }
 }
@@ -1,6 +1,35 @@
 import { invoke } from "@tauri-apps/api/core";
+import { listen, type UnlistenFn } from "@tauri-apps/api/event";
 import { open } from "@tauri-apps/plugin-dialog";
 
+// Error rejected by every Rust command (see src-tauri/src/error.rs)
+export type AppError = {
+  kind:
+    | "rclone_missing"
+    | "rclone_failed"
+    | "config_not_found"
+    | "config_password"
+    | "vlc_init"
+    | "ffmpeg_missing"
+    | "timeout"
+    | "cancelled"
+    | "invalid_input"
+    | "other";
+  message: string;
+  stderr?: string;
+  path?: string;
+};
+
+export function isAppError(e: unknown): e is AppError {
+  return typeof e === "object" && e !== null && "kind" in e && "message" in e;
+}
+
+// Human-readable text for anything a command or promise rejected with
+export function errorMessage(e: unknown): string {
+  if (isAppError(e) || e instanceof Error) return e.message;
+  return String(e);
+}
+
 export type RcloneRemote = {
   name: string;
   remote_type: string;
@@ -31,34 +60,47 @@ export async function pickRcloneConfig(): Promise<string | null> {
 
 // Parse rclone config and return list of remotes
 export async function parseRcloneConfig(
-  configPath: string
+  configPath: string,
+  configPassword?: string
 ): Promise<RcloneRemote[]> {
-  return invoke<RcloneRemote[]>("parse_rclone_config", { configPath });
+  return invoke<RcloneRemote[]>("parse_rclone_config", {
+    configPath,
+    configPassword,
+  });
 }
 
 // List directory contents via rclone
 export async function listRemotePath(
   configPath: string,
-  remotePath: string
+  remotePath: string,
+  configPassword?: string
 ): Promise<RcloneListItem[]> {
   return invoke<RcloneListItem[]>("list_remote_path", {
     configPath,
     remotePath,
+    configPassword,
   });
 }
 
 // Get a streaming URL for a remote file
 export async function getStreamUrl(
   configPath: string,
-  remotePath: string
+  remotePath: string,
+  configPassword?: string
 ): Promise<string> {
-  return invoke<string>("get_stream_url", { configPath, remotePath });
+  return invoke<string>("get_stream_url", {
+    configPath,
+    remotePath,
+    configPassword,
+  });
 }
 
 // Save API keys to Tauri's encrypted store
+// Keys left out keep their stored value
 export async function saveApiKeys(keys: {
   tmdb?: string;
   theporndb?: string;
+  opensubtitles?: string;
 }): Promise<void> {
   return invoke("save_api_keys", { keys });
 }
@@ -67,11 +109,181 @@ export async function saveApiKeys(keys: {
 export async function loadApiKeys(): Promise<{
   tmdb: string;
   theporndb: string;
+  opensubtitles: string;
 }> {
   return invoke("load_api_keys");
 }
 
+// Every saved setting (bookmarks included), for the settings backup
+export async function exportSettings(): Promise<Record<string, unknown>> {
+  return invoke("export_settings");
+}
+
+// Restore settings from exportSettings; settings not in `settings` are kept
+export async function importSettings(settings: Record<string, unknown>): Promise<void> {
+  return invoke("import_settings", { settings });
+}
+
 // Check if rclone binary is available / get version
 export async function getRcloneVersion(): Promise<string> {
   return invoke<string>("get_rclone_version");
 }
+
+// Active rclone FUSE mounts (Linux only; empty elsewhere)
+export interface RcloneMount {
+  device: string;
+  mount_point: string;
+  fs_type: string;
+}
+
+export async function listRcloneMounts(): Promise<RcloneMount[]> {
+  return invoke<RcloneMount[]>("list_rclone_mounts");
+}
+
+// Whether a position (seconds) counts as watched; the backend owns the threshold
+export async function isWatched(
+  position: number,
+  duration: number,
+  thresholdPct?: number
+): Promise<boolean> {
+  return invoke<boolean>("is_watched", {
+    positionMs: Math.round(position * 1000),
+    durationMs: Math.round(duration * 1000),
+    thresholdPct: thresholdPct ?? null,
+  });
+}
+
+export async function markWatched(mediaId: string): Promise<void> {
+  return invoke("mark_watched", { mediaId });
+}
+
+export async function markUnwatched(mediaId: string): Promise<void> {
+  return invoke("mark_unwatched", { mediaId });
+}
+
+// Bookmarked positions inside a media item
+export interface Bookmark {
+  id: string;
+  item_id: string;
+  time_ms: number;
+  label: string;
+  created_at: number;
+}
+
+export async function addBookmark(
+  itemId: string,
+  timeMs: number,
+  label: string
+): Promise<Bookmark> {
+  return invoke<Bookmark>("add_bookmark", { itemId, timeMs, label });
+}
+
+export async function listBookmarks(itemId: string): Promise<Bookmark[]> {
+  return invoke<Bookmark[]>("list_bookmarks", { itemId });
+}
+
+export async function deleteBookmark(bookmarkId: string): Promise<void> {
+  return invoke("delete_bookmark", { bookmarkId });
+}
+
+// Seeks when the item is playing; otherwise `seeked` is false and the caller
+// opens `item_id` at `time_ms`
+export async function jumpToBookmark(
+  bookmarkId: string
+): Promise<{ item_id: string; time_ms: number; seeked: boolean }> {
+  return invoke("jump_to_bookmark", { bookmarkId });
+}
+
+// Metadata parsed from a Kodi-style .nfo sidecar found during scan
+export interface NfoMetadata {
+  title: string | null;
+  year: number | null;
+  plot: string | null;
+  rating: number | null;
+  unique_ids: Record<string, string>;
+}
+
+export async function readNfo(
+  configPath: string,
+  nfoRemotePath: string
+): Promise<NfoMetadata> {
+  return invoke<NfoMetadata>("read_nfo", { configPath, nfoRemotePath });
+}
+
+// Small remote files (cover art, .nfo, .opf) via `rclone cat`: inline base64
+// when small, otherwise a temp file path
+export interface RemoteBytes {
+  size: number;
+  base64: string | null;
+  temp_path: string | null;
+}
+
+export async function readRemoteBytes(
+  configPath: string,
+  remotePath: string,
+  maxBytes?: number
+): Promise<RemoteBytes> {
+  return invoke<RemoteBytes>("read_remote_bytes", {
+    configPath,
+    remotePath,
+    maxBytes: maxBytes ?? null,
+  });
+}
+
+// Book downloads (download_book_to_temp) report progress under their download
+// id, which defaults to the session id
+export interface BookDownloadProgress {
+  downloadId: string;
+  bytes: number;
+  total: number;
+  percent: number;
+}
+
+export function onBookDownloadProgress(
+  downloadId: string,
+  handler: (progress: BookDownloadProgress) => void
+): Promise<UnlistenFn> {
+  return listen<BookDownloadProgress>("book-download-progress", (ev) => {
+    if (ev.payload.downloadId === downloadId) handler(ev.payload);
+  });
+}
+
+export async function cancelBookDownload(downloadId: string): Promise<void> {
+  return invoke("cancel_book_download", { downloadId });
+}
+
+// Clear the temp cache (books, posters, prefetched media) except files that
+// are open or downloading; returns the count deleted
+export async function clearTempCache(): Promise<number> {
+  return invoke<number>("clear_temp_cache");
+}
+
+// Hours unused temp files survive between launches (0 = until evicted for space)
+export async function setTempCacheMaxAge(hours: number): Promise<void> {
+  return invoke("set_temp_cache_max_age", { hours });
+}
+
+// Resolve same-name duplicates (a Google Drive quirk) with rclone dedupe.
+// Progress arrives as `dedupe-progress` events.
+export type DedupeMode = "skip" | "first" | "newest" | "oldest" | "largest" | "smallest" | "rename" | "list";
+
+export interface DedupeSummary {
+  duplicate_names: number;
+  deleted: number;
+  renamed: number;
+  actions: string[];
+}
+
+export async function dedupeRemote(
+  configPath: string,
+  remotePath: string,
+  mode: DedupeMode,
+  dryRun = false
+): Promise<DedupeSummary> {
+  return invoke<DedupeSummary>("dedupe_remote", { configPath, remotePath, mode, dryRun });
+}
+
+// Mute the VLC player without losing its volume; persisted like the volume
+export async function playerSetMute(muted: boolean): Promise<void> {
+  return invoke("player_set_mute", { muted });
+}
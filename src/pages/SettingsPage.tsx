@@ -11,6 +11,7 @@ import { ScanBar } from "../components/common/ScanBar";
 import { scanAllLibraries } from "../lib/scanner";
 import { backupToDrive, restoreFromDrive, startGoogleSignIn, exchangeOAuthCode, listenOAuthCallback } from "../lib/sync";
 import { RemoteBrowser } from "../components/common/RemoteBrowser";
+import { errorMessage } from "../lib/tauri";
 
 type Section = "api-keys" | "libraries" | "adult" | "sync" | "scan";
 
@@ -75,7 +76,7 @@ export function SettingsPage() {
         });
         setOauthState("idle");
       } catch (e) {
-        setOauthError(String(e));
+        setOauthError(errorMessage(e));
         setOauthState("error");
       }
     }).then((fn) => { unlisten = fn; });
@@ -121,7 +122,7 @@ export function SettingsPage() {
     try {
       await backupToDrive();
       setSyncMsg("✓ Backup complete");
-    } catch (e) { setSyncMsg(`Error: ${String(e)}`); }
+    } catch (e) { setSyncMsg(`Error: ${errorMessage(e)}`); }
     setTimeout(() => setSyncMsg(""), 3000);
   };
 
@@ -130,7 +131,7 @@ export function SettingsPage() {
     try {
       const backup = await restoreFromDrive();
       setSyncMsg(backup ? "✓ Config restored from Drive" : "No backup found");
-    } catch (e) { setSyncMsg(`Error: ${String(e)}`); }
+    } catch (e) { setSyncMsg(`Error: ${errorMessage(e)}`); }
     setTimeout(() => setSyncMsg(""), 4000);
   };
 
@@ -146,7 +147,7 @@ export function SettingsPage() {
     try {
       await startGoogleSignIn();
     } catch (e) {
-      setOauthError(String(e));
+      setOauthError(errorMessage(e));
       setOauthState("error");
     }
   };
@@ -5,6 +5,7 @@ import { ChevronLeft, ChevronRight, ArrowLeft, Type, BookOpen } from "lucide-rea
 import { invoke, convertFileSrc } from "@tauri-apps/api/core";
 import type { MediaItem } from "../../store/appStore";
 import { useAppStore } from "../../store/appStore";
+import { cancelBookDownload, errorMessage, onBookDownloadProgress } from "../../lib/tauri";
 
 // epubjs is loaded dynamically to avoid bundling issues
 // Use the global ePub from the script tag
@@ -51,6 +52,7 @@ export function EpubReaderPage() {
   const [showSettings, setShowSettings] = useState(false);
   const [loading, setLoading] = useState(true);
   const [downloading, setDownloading] = useState(true);
+  const [downloadPercent, setDownloadPercent] = useState<number | null>(null);
   const [error, setError] = useState<string | null>(null);
   const [streamUrl, setStreamUrl] = useState<string | null>(null);
 
@@ -61,6 +63,7 @@ export function EpubReaderPage() {
   useEffect(() => {
     if (!item) return;
 
+    const progress = onBookDownloadProgress(sessionId.current, (p) => setDownloadPercent(p.percent));
     invoke<string>("download_book_to_temp", {
       configPath: rcloneConfigPath,
       remotePath: item.remotePath,
@@ -75,11 +78,13 @@ export function EpubReaderPage() {
       })
       .catch((e) => { 
         console.error("Failed to download EPUB:", e);
-        setError(String(e)); 
+        setError(errorMessage(e)); 
         setDownloading(false); 
       });
 
     return () => {
+      progress.then((unlisten) => unlisten());
+      cancelBookDownload(sessionId.current).catch(() => {});
       invoke("cleanup_book_temp", { sessionId: sessionId.current }).catch(() => {});
     };
   }, [item?.id]);
@@ -302,7 +307,9 @@ export function EpubReaderPage() {
           {(downloading || loading) && (
             <div className="absolute inset-0 flex items-center justify-center">
               <p className="font-body text-sm opacity-50">
-                {downloading ? "Downloading book…" : "Loading book…"}
+                {downloading
+                  ? `Downloading book…${downloadPercent !== null ? ` ${Math.round(downloadPercent)}%` : ""}`
+                  : "Loading book…"}
               </p>
             </div>
           )}
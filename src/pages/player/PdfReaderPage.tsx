@@ -4,6 +4,7 @@ import { ArrowLeft, ChevronLeft, ChevronRight, ZoomIn, ZoomOut, Loader2 } from "
 import { invoke, convertFileSrc } from "@tauri-apps/api/core";
 import type { MediaItem } from "../../store/appStore";
 import { useAppStore } from "../../store/appStore";
+import { cancelBookDownload, onBookDownloadProgress } from "../../lib/tauri";
 import * as pdfjsLib from "pdfjs-dist";
 import type { PDFDocumentProxy, PDFPageProxy } from "pdfjs-dist";
 
@@ -28,6 +29,7 @@ export function PdfReaderPage() {
 
   const [streamUrl, setStreamUrl] = useState<string | null>(null);
   const [downloading, setDownloading] = useState(true);
+  const [downloadPercent, setDownloadPercent] = useState<number | null>(null);
   const [loading, setLoading] = useState(true);
   const [error, setError] = useState<string | null>(null);
   const [currentPage, setCurrentPage] = useState(1);
@@ -39,6 +41,7 @@ export function PdfReaderPage() {
   useEffect(() => {
     if (!item) return;
 
+    const progress = onBookDownloadProgress(sessionId.current, (p) => setDownloadPercent(p.percent));
     invoke<string>("download_book_to_temp", {
       configPath: rcloneConfigPath,
       remotePath: item.remotePath,
@@ -58,6 +61,8 @@ export function PdfReaderPage() {
       });
 
     return () => {
+      progress.then((unlisten) => unlisten());
+      cancelBookDownload(sessionId.current).catch(() => {});
       invoke("cleanup_book_temp", { sessionId: sessionId.current }).catch(() => {});
     };
   }, [item?.id]);
@@ -158,7 +163,9 @@ export function PdfReaderPage() {
           <div className="absolute inset-0 flex items-center justify-center flex-col gap-3">
             <Loader2 size={32} className="text-accent animate-spin" />
             {downloading && (
-              <p className="font-body text-sm text-subtle">Downloading…</p>
+              <p className="font-body text-sm text-subtle">
+                {downloadPercent !== null ? `Downloading… ${Math.round(downloadPercent)}%` : "Downloading…"}
+              </p>
             )}
           </div>
         )}
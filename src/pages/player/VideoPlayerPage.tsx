@@ -8,6 +8,7 @@ import {
 import { invoke } from "@tauri-apps/api/core";
 import { listen } from "@tauri-apps/api/event";
 import { useAppStore, type MediaItem } from "../../store/appStore";
+import { errorMessage, isWatched } from "../../lib/tauri";
 
 type PlayerState = {
   playing: boolean;
@@ -76,10 +77,11 @@ export function VideoPlayerPage() {
       remoteRoot: matchingRoot,
       filePath: relPath,
       startMs,
+      disc: !!item.disc,
     })
       .then(() => setLoading(false))
       .catch((e) => {
-        setPs((s) => ({ ...s, error: String(e), buffering: false }));
+        setPs((s) => ({ ...s, error: errorMessage(e), buffering: false }));
         setLoading(false);
       });
 
@@ -150,13 +152,17 @@ export function VideoPlayerPage() {
       const pos = currentTimeRef.current;
       const dur = durationRef.current;
       if (!dur) return;
-      updateWatchProgress({
-        itemId: item.id,
-        position: pos,
-        duration: dur,
-        completed: dur > 0 && pos / dur > 0.9,
-        lastWatchedAt: Date.now(),
-      });
+      isWatched(pos, dur)
+        .then((completed) =>
+          updateWatchProgress({
+            itemId: item.id,
+            position: pos,
+            duration: dur,
+            completed,
+            lastWatchedAt: Date.now(),
+          })
+        )
+        .catch(() => {});
     }, 10000);
     return () => {
       if (progressTimerRef.current) clearInterval(progressTimerRef.current);
@@ -197,25 +203,36 @@ export function VideoPlayerPage() {
 
   const handleBack = useCallback(() => {
     if (item) {
-      updateWatchProgress({
-        itemId: item.id,
-        position: currentTimeRef.current,
-        duration: durationRef.current,
-        completed:
-          durationRef.current > 0 &&
-          currentTimeRef.current / durationRef.current > 0.9,
-        lastWatchedAt: Date.now(),
-      });
+      const pos = currentTimeRef.current;
+      const dur = durationRef.current;
+      isWatched(pos, dur)
+        .then((completed) =>
+          updateWatchProgress({
+            itemId: item.id,
+            position: pos,
+            duration: dur,
+            completed,
+            lastWatchedAt: Date.now(),
+          })
+        )
+        .catch(() => {});
     }
     navigate(-1);
   }, [item]);
 
   const setVolume = useCallback((vol: number, muted: boolean) => {
-    const effective = muted ? 0 : vol;
-    invoke("player_set_volume", { vol: Math.round(effective * 100) }).catch(() => {});
+    invoke("player_set_volume", { vol: Math.round(vol * 100) }).catch(() => {});
+    invoke("player_set_mute", { muted }).catch(() => {});
     setPs((s) => ({ ...s, volume: vol, muted }));
   }, []);
 
+  // Start the controls at the volume restored from the last session
+  useEffect(() => {
+    invoke<{ volume: number; muted: boolean }>("player_get_status")
+      .then((status) => setPs((s) => ({ ...s, volume: status.volume / 100, muted: status.muted })))
+      .catch(() => {});
+  }, []);
+
   const toggleFullscreen = useCallback(() => {
     const el = containerRef.current;
     if (!el) return;
@@ -247,6 +264,7 @@ export function VideoPlayerPage() {
           setPs((s) => {
             const v = Math.min(1, s.volume + 0.1);
             invoke("player_set_volume", { vol: Math.round(v * 100) }).catch(() => {});
+            invoke("player_set_mute", { muted: false }).catch(() => {});
             return { ...s, volume: v, muted: false };
           });
           break;
@@ -263,9 +281,7 @@ export function VideoPlayerPage() {
         case "KeyM":
           setPs((s) => {
             const muted = !s.muted;
-            invoke("player_set_volume", {
-              vol: muted ? 0 : Math.round(s.volume * 100),
-            }).catch(() => {});
+            invoke("player_set_mute", { muted }).catch(() => {});
             return { ...s, muted };
           });
           break;
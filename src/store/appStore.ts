@@ -52,6 +52,8 @@ export type MediaItem = {
   trackNumber?: number;
   // Books
   author?: string;
+  // DVD/Blu-ray folder or disc image
+  disc?: "dvd" | "bluray" | "iso";
 };
 
 // ─── TV Show grouping ─────────────────────────────────────────────────────────
@@ -146,6 +148,8 @@ export type AppConfig = {
 type AppStore = AppConfig & {
   mediaItems: Record<string, MediaItem>;
   watchProgress: Record<string, WatchProgress>;
+  // Set when items were saved with the old djb2 ids; see migrateItemIds
+  itemIdsMigrationPending?: boolean;
   scanState: ScanState;
   adultSettings: AdultSettings;
   syncState: SyncState;
@@ -175,6 +179,8 @@ type AppStore = AppConfig & {
   updateWatchProgress: (progress: WatchProgress) => void;
   markCompleted: (itemId: string) => void;
   clearProgress: (itemId: string) => void;
+  // Rekey items and progress by old id -> new id and clear itemIdsMigrationPending
+  applyItemIdMigration: (ids: Record<string, string>) => void;
 
   // Scan
   setScanState: (state: Partial<ScanState>) => void;
@@ -257,6 +263,20 @@ export const useAppStore = create<AppStore>()(
         set((s) => ({ watchProgress: { ...s.watchProgress, [itemId]: { ...s.watchProgress[itemId], completed: true } } })),
       clearProgress: (itemId) =>
         set((s) => { const n = { ...s.watchProgress }; delete n[itemId]; return { watchProgress: n }; }),
+      applyItemIdMigration: (ids) =>
+        set((s) => {
+          const mediaItems: Record<string, MediaItem> = {};
+          Object.values(s.mediaItems).forEach((i) => {
+            const id = ids[i.id] ?? i.id;
+            mediaItems[id] = { ...i, id };
+          });
+          const watchProgress: Record<string, WatchProgress> = {};
+          Object.values(s.watchProgress).forEach((p) => {
+            const itemId = ids[p.itemId] ?? p.itemId;
+            watchProgress[itemId] = { ...p, itemId };
+          });
+          return { mediaItems, watchProgress, itemIdsMigrationPending: false };
+        }),
 
       setScanState: (state) => set((s) => ({ scanState: { ...s.scanState, ...state } })),
       resetScanState: () => set({ scanState: defaultScanState }),
@@ -275,7 +295,7 @@ export const useAppStore = create<AppStore>()(
     }),
     {
       name: "rcloneflix-config",
-      version: 2,
+      version: 3,
       migrate: (persistedState: unknown, fromVersion: number) => {
         const state = persistedState as Record<string, unknown>;
         if (fromVersion < 2) {
@@ -287,6 +307,10 @@ export const useAppStore = create<AppStore>()(
               (lib.remotePath ? [lib.remotePath as string] : []),
           }));
         }
+        if (fromVersion < 3) {
+          // Item ids moved from djb2 to BLAKE3; rehashed on the next launch
+          state.itemIdsMigrationPending = true;
+        }
         return state as unknown as AppStore;
       },
       partialize: (s) => ({